@@ -1,6 +1,8 @@
 use std::fmt::Debug;
-use std::hash::Hash;
-//use std::marker::PhantomData;
+use std::hash::{Hash, Hasher};
+use std::cmp::{max, min};
+use std::ops::{Add, Deref};
+use std::marker::PhantomData;
 //use std::num::Zero;
 use std::rc::Rc;
 
@@ -9,6 +11,9 @@ use std::rc::Rc;
 use macros::* ;
 use adapton::engine::* ;
 
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
 pub mod trie {
   pub use trie::*;
 }
@@ -18,6 +23,7 @@ pub mod graph {
 }
 
 #[derive(Clone,Copy,Hash,Eq,PartialEq,Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Dir2 { Left, Right }
 
 trait Invert { fn invert(&self) -> Self; }
@@ -204,6 +210,31 @@ pub fn list_filter_eager<X:'static, Le:'static+ListElim<X>, Li:'static+ListIntro
      })
 }
 
+/// Partitions the list into the elements that satisfy `body`, and
+/// those that do not, in a single pass.  Memoizes recursion for each
+/// name in input, and both outputs keep each input name,
+/// articulating their rest.
+pub fn list_partition<X:'static, Le:'static+ListElim<X>, Li:'static+ListIntro<X>, F:'static>
+  (l:Le, body:Rc<F>) -> (Li, Li)
+ where F:Fn(&X) -> bool
+{
+  Le::elim_arg
+    (l, body,
+     |_,_| (list_nil(), list_nil()),
+     |x, tl, body| {
+       let keep = body.clone() (&x);
+       let (yes, no) = list_partition(tl, body);
+       if keep { (list_cons(x, yes), no) }
+       else    { (yes, list_cons(x, no)) }
+     },
+     |n, tl, body| {
+       let (nm1, nm2, nm3) = name_fork3(n.clone());
+       let (yes, no) = memo!( nm1 =>> list_partition::<X, Le, Li, F>, l:tl ;; body:body );
+       (list_name(n.clone(), list_art(cell(nm2, yes))),
+        list_name(n, list_art(cell(nm3, no))))
+     })
+}
+
 /// Eagerly maps the list.
 /// Uses (eager) memoization for each name in `l`.
 pub fn list_map_eager<X:'static, Le:'static+ListElim<X>, 
@@ -226,6 +257,30 @@ pub fn list_map_eager<X:'static, Le:'static+ListElim<X>,
      })
 }
 
+/// Eagerly maps each element of the list to a list, and concatenates
+/// the results.  Uses (eager) memoization for each name in `l`, and
+/// names the output at the same names, each articulating the rest of
+/// the output.  The names in the lists that `body` returns are
+/// dropped, since `body` may return the same names for many elements.
+pub fn list_flat_map<X:'static, Le:'static+ListElim<X>,
+                     Y:'static, Li:'static+ListIntro<Y>+ListElim<Y>,
+                     F:'static>
+  (l:Le, body:Rc<F>) -> Li
+ where F:Fn(X) -> Li
+{
+  Le::elim_arg
+    (l, body,
+     |_,_| list_nil(),
+     |x, tl, body| {
+       let ys = body.clone() (x);
+       list_append(ys, list_flat_map(tl, body))
+     },
+     |n, tl, body| {
+       let (t,_) = eager!( n.clone() =>> list_flat_map =>> <X, Le, Y, Li, F>, l:tl ;; body:body.clone() );
+       list_name(n, list_art(t))
+     })
+}
+
 /// Eagerly maps the list.  Uses (eager) memoization for each name in
 /// `l`.  Unlike list_map_eager, it allocates a reference cell for
 /// each name, separate from the memoized thunk for the recursive
@@ -252,6 +307,33 @@ pub fn list_map_eager2<X:'static, Le:'static+ListElim<X>,
 }
 
 
+/// Eagerly scans the list: the output holds, for each element `x` of
+/// the input, the running value `body(acc, x)` of the elements up to
+/// and including `x`, starting from `acc`.  Uses (eager) memoization
+/// for each name in `l`, keyed by the running value there, and names
+/// the output at the same names, each articulating the rest of the
+/// output.
+pub fn list_scan<X:'static, Le:'static+ListElim<X>,
+                 Y:'static+Debug+Hash+Eq+Clone, Li:'static+ListIntro<Y>,
+                 F:'static>
+  (l:Le, acc:Y, body:Rc<F>) -> Li
+ where F:Fn(Y, X) -> Y
+{
+  Le::elim_arg
+    (l, (acc, body),
+     |_,_| list_nil(),
+     |x, tl, (acc, body)| {
+       let y = body.clone() (acc, x);
+       list_cons(y.clone(), list_scan(tl, y, body))
+     },
+     |n, tl, (acc, body)| {
+       let (t,_) = eager!( n.clone() =>> list_scan =>> <X, Le, Y, Li, F>, l:tl, acc:acc ;;
+                           body:body.clone() );
+       list_name(n, list_art(t))
+     })
+}
+
+
 /// Eagerly maps the list.
 /// Uses (eager) memoization for each name in `l`.
 pub fn list_reverse<X:'static, Le:'static+ListElim<X>, Li:'static+ListIntro<X>>
@@ -303,6 +385,201 @@ pub fn list_append<X:'static, L:ListIntro<X>+ListElim<X>>(l1:L, l2:L) -> L {
   list_fold(l1, l2, Rc::new(|x,r| list_cons(x,r)))
 }
 
+/// Splits the list into its first `n` elements and the rest.  The
+/// rest is the tail of `l` itself, names and all.  The prefix keeps
+/// the names of `l` that it passes, each re-articulating the prefix
+/// after it, and the split after each name is memoized under that name
+/// paired with the count left to take there.
+pub fn list_split_at<X:'static, L:'static+ListIntro<X>+ListElim<X>>(l:L, n:usize) -> (L, L) {
+  L::elim_arg
+    (l, n,
+     |l, _| (l, L::nil()),
+     |x, tl, n| {
+       if n == 0 { return (L::nil(), L::cons(x, tl)) }
+       let (pre, suf) = list_split_at(tl, n - 1);
+       (L::cons(x, pre), suf)
+     },
+     |nm, tl, n| {
+       if n == 0 { return (L::nil(), L::name(nm, tl)) }
+       let (nm1, nm2) = name_fork(name_pair(nm.clone(), name_of_usize(n)));
+       let (pre, suf) = memo!(nm1 =>> list_split_at::<X,L>, l:tl, n:n);
+       (L::name(nm, L::art(cell(nm2, pre))), suf)
+     })
+}
+
+/// The first `n` elements of the list, as `list_split_at` gives them.
+pub fn list_take<X:'static, L:'static+ListIntro<X>+ListElim<X>>(l:L, n:usize) -> L {
+  list_split_at(l, n).0
+}
+
+/// The list without its first `n` elements: the tail of `l` itself,
+/// names and all.  Performs no memoization.
+pub fn list_drop<X:'static, L:'static+ListIntro<X>+ListElim<X>>(l:L, n:usize) -> L {
+  if n == 0 { return l }
+  L::elim_arg
+    (l, n,
+     |l, _| l,
+     |_, tl, n| list_drop(tl, n - 1),
+     |_, tl, n| list_drop(tl, n))
+}
+
+/// Splits the list just before the name `nm`, into the elements before
+/// it and the rest of `l` from `nm` on; the rest is empty if `l` does
+/// not hold `nm`.  Like `list_split_at`, the prefix keeps the names it
+/// passes, and the split after each is memoized under that name paired
+/// with `nm`.
+pub fn list_split_at_name<X:'static, L:'static+ListIntro<X>+ListElim<X>>(l:L, nm:Name) -> (L, L) {
+  L::elim_arg
+    (l, nm,
+     |l, _| (l, L::nil()),
+     |x, tl, nm| {
+       let (pre, suf) = list_split_at_name(tl, nm);
+       (L::cons(x, pre), suf)
+     },
+     |n, tl, nm| {
+       if n == nm { return (L::nil(), L::name(n, tl)) }
+       let (n1, n2) = name_fork(name_pair(n.clone(), nm.clone()));
+       let (pre, suf) = memo!(n1 =>> list_split_at_name::<X,L>, l:tl, nm:nm);
+       (L::name(n, L::art(cell(n2, pre))), suf)
+     })
+}
+
+/// Inserts `x` into the sorted list `l`, just before the first element
+/// that is not less than `x`, and shares the rest of `l` from there
+/// on.  Like `list_split_at_name`, the output keeps the names before
+/// the insertion point, and the insertion after each is memoized under
+/// that name paired with `x`, and articulated; so, re-inserting into
+/// an edited list rebuilds only the regions of the output before the
+/// insertion point whose input has changed, and inserting different
+/// elements into the same list builds a distinct output for each.
+pub fn list_insert_sorted<X:'static+Ord+Debug+Hash+Clone, L:'static+ListIntro<X>+ListElim<X>>
+  (l:L, x:X) -> L
+{
+  L::elim_arg
+    (l, x,
+     |l, x| L::cons(x, l),
+     |y, tl, x| {
+       if x <= y { L::cons(x, L::cons(y, tl)) }
+       else { L::cons(y, list_insert_sorted(tl, x)) }
+     },
+     |n, tl, x| {
+       let (n1, n2) = name_fork(name_pair(n.clone(), name_of_usize(my_hash(&x) as usize)));
+       let rest = memo!(n1 =>> list_insert_sorted::<X,L>, l:tl, x:x);
+       L::name(n, L::art(cell(n2, rest)))
+     })
+}
+
+/// Removes the elements of `l` whose key, under `key`, has already
+/// occurred earlier in the list, keeping the first occurrence of each
+/// key.  The keys seen so far are threaded through the list as a
+/// `trie::Set`; at each name in `l`, the rest of the output is
+/// memoized on this set, and articulated.
+pub fn list_dedup_by_key<X:'static, K:Debug+Hash+Eq+Clone+'static,
+                         L:'static+ListIntro<X>+ListElim<X>, F:'static>
+  (l:L, key:Rc<F>) -> L
+ where F:Fn(&X) -> K
+{
+  list_dedup_rec(l, SetIntro::empty(), key)
+}
+
+fn list_dedup_rec<X:'static, K:Debug+Hash+Eq+Clone+'static,
+                  L:'static+ListIntro<X>+ListElim<X>, F:'static>
+  (l:L, seen:trie::Set<K>, key:Rc<F>) -> L
+ where F:Fn(&X) -> K
+{
+  L::elim_arg
+    (l, (seen, key),
+     |_, _| L::nil(),
+     |x, tl, (seen, key)| {
+       let k = key(&x);
+       if SetElim::is_mem(&seen, &k) { list_dedup_rec(tl, seen, key) }
+       else {
+         let seen = SetIntro::add(seen, k);
+         L::cons(x, list_dedup_rec(tl, seen, key))
+       }
+     },
+     |n, tl, (seen, key)| {
+       let (n1, n2) = name_fork(n.clone());
+       let rest = memo!(n1 =>> list_dedup_rec::<X,K,L,F>, l:tl, seen:seen ;; key:key);
+       L::name(n, L::art(cell(n2, rest)))
+     })
+}
+
+/// Splits the list into chunks of `n` consecutive elements, the last
+/// of which may be shorter.  Each name in `l` also ends a chunk, so
+/// that an edit to `l` moves the chunk boundaries only as far as the
+/// next name.  The names of `l` occur between the chunks, each
+/// articulating the chunks after it, which are memoized under it.
+pub fn list_chunks<X:'static, L:'static+ListIntro<X>+ListElim<X>, Lo:'static+ListIntro<L>>
+  (l:L, n:usize) -> Lo
+{
+  assert!(n > 0);
+  L::elim_arg
+    (l, n,
+     |_, _| Lo::nil(),
+     |x, tl, n| {
+       let (chunk, rest) = list_chunk(tl, n, n - 1);
+       Lo::cons(L::cons(x, chunk), rest)
+     },
+     |nm, tl, n| {
+       let (nm1, nm2) = name_fork(nm.clone());
+       let rest = memo!(nm1 =>> list_chunks::<X,L,Lo>, l:tl, n:n);
+       Lo::name(nm, Lo::art(cell(nm2, rest)))
+     })
+}
+
+/// The next (at most) `k` elements of the chunk, up to the next name,
+/// and the chunks after them.
+fn list_chunk<X:'static, L:'static+ListIntro<X>+ListElim<X>, Lo:'static+ListIntro<L>>
+  (l:L, n:usize, k:usize) -> (L, Lo)
+{
+  if k == 0 { return (L::nil(), list_chunks(l, n)) }
+  L::elim_arg
+    (l, (n, k),
+     |_, _| (L::nil(), Lo::nil()),
+     |x, tl, (n, k)| {
+       let (chunk, rest) = list_chunk(tl, n, k - 1);
+       (L::cons(x, chunk), rest)
+     },
+     |nm, tl, (n, _)| (L::nil(), list_chunks(L::name(nm, tl), n)))
+}
+
+/// The sliding windows of the list: for each element of `l` with at
+/// least `n - 1` elements after it, the list of this element and
+/// those after it.  The windows hold no names; the names of `l` occur
+/// before the windows that begin after them, each articulating the
+/// windows after it, which are memoized under it.
+pub fn list_windows<X:'static, L:'static+ListIntro<X>+ListElim<X>, Lo:'static+ListIntro<L>>
+  (l:L, n:usize) -> Lo
+{
+  assert!(n > 0);
+  L::elim_arg
+    (l, n,
+     |_, _| Lo::nil(),
+     |x, tl, n| {
+       match list_window(tl.clone(), n - 1) {
+         None => Lo::nil(),
+         Some(window) => Lo::cons(L::cons(x, window), list_windows(tl, n)),
+       }
+     },
+     |nm, tl, n| {
+       let (nm1, nm2) = name_fork(nm.clone());
+       let rest = memo!(nm1 =>> list_windows::<X,L,Lo>, l:tl, n:n);
+       Lo::name(nm, Lo::art(cell(nm2, rest)))
+     })
+}
+
+/// The first `n` elements of the list, without its names, or `None`
+/// if it has fewer.
+fn list_window<X:'static, L:'static+ListIntro<X>+ListElim<X>>(l:L, n:usize) -> Option<L> {
+  if n == 0 { return Some(L::nil()) }
+  L::elim_arg
+    (l, n,
+     |_, _| None,
+     |x, tl, n| list_window(tl, n - 1).map(|window| L::cons(x, window)),
+     |_, tl, n| list_window(tl, n))
+}
+
 
 /// Rose Trees: A tree with arbitrary branching at each node.
 /// See also, Definition 2 (page 2) of
@@ -439,6 +716,31 @@ pub trait MapIntro<Dom,Cod>
   //let (map, _) = self.extend(d,move|_|{(None,None)});
   //map
   //}
+
+  /// Binds `d` to `f` of its binding in `map`, if any: a
+  /// read-modify-write of the value of `d` (e.g., of a nested tree or
+  /// list) as one operation, whose call to `f` is memoized at `nm`,
+  /// on `d` and its binding.  The memo does not compare closures; so,
+  /// callers use a distinct `nm` for each distinct `f`.  By default,
+  /// the update occurs in a namespace named by a fork of `nm`.
+  fn update_with<F:'static> (map:Self, nm:Name, d:Dom, f:Rc<F>) -> Self
+    where F:Fn(Option<Cod>) -> Cod,
+          Self:MapElim<Dom,Cod>,
+          Dom:Debug+Hash+Eq+Clone+'static,
+          Cod:Debug+Hash+Eq+Clone+'static
+  {
+    let (nm1, nm2) = name_fork(nm);
+    let found = Self::find(&map, &d);
+    let c = memo!(nm1 =>> map_update_with_cod::<Dom,Cod,F>, d:d.clone(), found:found ;; f:f);
+    ns(nm2, || Self::update(map, d, c))
+  }
+}
+
+/// The new value of `d` for `MapIntro::update_with`, given its binding.
+fn map_update_with_cod<Dom, Cod, F:'static> (_d:Dom, found:Option<Cod>, f:Rc<F>) -> Cod
+  where F:Fn(Option<Cod>) -> Cod
+{
+  f(found)
 }
 
 pub trait MapElim<Dom,Cod>
@@ -451,10 +753,67 @@ pub trait MapElim<Dom,Cod>
         where F:Fn(Dom, Cod, Res) -> Res+'static,
               Res:Hash+Debug+Eq+Clone+'static;
   fn append(Self, other:Self) -> Self;
+
+  /// Merges `other` into `map`: a key bound in just one of the maps
+  /// keeps its value, and a key `d` bound to `c1` in `map` and to `c2`
+  /// in `other` is bound to `f(&d, c1, c2)`.  By default, derived from
+  /// `fold` over `other`, with `find` and `update` on `map`.
+  fn merge_with<F:'static> (map:Self, other:Self, f:Rc<F>) -> Self
+    where F:Fn(&Dom, Cod, Cod) -> Cod,
+          Self:MapIntro<Dom,Cod>
+  {
+    ns(name_of_str("merge_with"), || {
+      Self::fold(other, map, Rc::new(move |d, c2, map:Self| {
+        let c = match Self::find(&map, &d) {
+          Some(c1) => f(&d, c1, c2),
+          None => c2,
+        };
+        MapIntro::update(map, d, c)
+      }))
+    })
+  }
+
+  /// Tests if `d` has a binding in the map. Derived from `find`.
+  fn contains_key (map:&Self, d:&Dom) -> bool {
+    Self::find(map, d).is_some()
+  }
+
+  /// The number of bindings in the map. Derived from `fold`, in its
+  /// own namespace, so that it is memoized apart from other folds.
+  fn len (map:&Self) -> usize {
+    ns(name_of_str("map_len"), || {
+      Self::fold(map.clone(), 0, Rc::new(|_, _, n| n + 1))
+    })
+  }
+
+  /// The keys of the map, in the reverse of the order in which `fold`
+  /// visits them. Derived from `fold`, like `len`.
+  fn keys (map:&Self) -> List<Dom>
+    where Dom:'static+Debug+Hash+Eq+Clone
+  {
+    ns(name_of_str("map_keys"), || {
+      Self::fold(map.clone(), List::nil(), Rc::new(|d, _, l| List::cons(d, l)))
+    })
+  }
+
+  /// The values of the map, in the same order as `keys` gives the
+  /// keys. Derived from `fold`, like `len`.
+  fn values (map:&Self) -> List<Cod>
+    where Cod:'static+Debug+Hash+Eq+Clone
+  {
+    ns(name_of_str("map_values"), || {
+      Self::fold(map.clone(), List::nil(), Rc::new(|_, c, l| List::cons(c, l)))
+    })
+  }
 }
 
 pub fn map_empty<Dom,Cod,M:MapIntro<Dom,Cod>>() -> M { M::empty() }
 pub fn map_update<Dom,Cod,M:MapIntro<Dom,Cod>>(map:M, d:Dom, c:Cod) -> M { M::update(map, d, c) }
+pub fn map_update_with<Dom,Cod,M,F>(map:M, nm:Name, d:Dom, f:Rc<F>) -> M
+    where M:MapIntro<Dom,Cod>+MapElim<Dom,Cod>,
+          F:Fn(Option<Cod>) -> Cod+'static,
+          Dom:Debug+Hash+Eq+Clone+'static,
+          Cod:Debug+Hash+Eq+Clone+'static { M::update_with(map, nm, d, f) }
 pub fn map_find<Dom,Cod,M:MapElim<Dom,Cod>>(map:&M, d:&Dom) -> Option<Cod> { M::find(map, d) }
 pub fn map_fold<Dom,Cod,M:MapElim<Dom,Cod>,F,Res>(map:M, r:Res, f:Rc<F>) -> Res
     where F:Fn(Dom,Cod, Res) -> Res+'static,
@@ -489,8 +848,18 @@ impl<Elm,Map:MapIntro<Elm,()>+MapElim<Elm,()>> SetIntro<Elm> for Map {
        })) ; 
     return out
   }
-  fn diff (_set:Self, _other:Self) -> Self {
-    unimplemented!() // Should be very similar to inter, above.
+  fn diff (set:Self, other:Self) -> Self {
+    let (out, _) =
+    Map::fold
+      (set, (Self::empty(), other),
+       Rc::new(|x, (), (out, other)|{
+         let out = match Map::find(&other, &x) {
+           Some (ref _unit) => out,
+           None => Self::add(out, x)
+         };
+         (out, other)
+       })) ;
+    return out
   }
 }
 
@@ -624,6 +993,39 @@ pub fn tree_fold_up
      )
 }
 
+/// Like `tree_fold_up`, except that it folds within namespaces of the
+/// given `Namer`, rather than the caller's namespace: It memoizes at
+/// the names of the tree in one namespace, and runs `bin` and `name`
+/// in two others, where they may allocate at these same names
+/// (e.g., see `bin_arts_niltest`).  Two folds over the same tree, in
+/// the same namespace, do not collide when given distinct namers.
+pub fn tree_fold_up_in
+  < Lev:Level, Leaf, T:TreeElim<Lev,Leaf>
+  , Res:Hash+Debug+Eq+Clone+'static
+  , NilF:'static
+  , LeafF:'static
+  , BinF:'static
+  , NameF:'static
+  >
+  (namer:Namer,
+   tree:T,
+   nil:Rc<NilF>,
+   leaf:Rc<LeafF>,
+   bin:Rc<BinF>,
+   name:Rc<NameF>) -> Res
+  where  NilF:Fn() -> Res
+  ,     LeafF:Fn(Leaf                ) -> Res
+  ,      BinF:Fn(Lev,       Res, Res ) -> Res
+  ,     NameF:Fn(Name, Lev, Res, Res ) -> Res
+{
+  let (fold_nr, bin_nr, name_nr) = namer.fork3();
+  fold_nr.ns(||
+    tree_fold_up
+      (tree, nil, leaf,
+       Rc::new(move |lev,l,r|   bin_nr.ns(|| bin(lev, l, r))),
+       Rc::new(move |n,lev,l,r| name_nr.ns(|| name(n, lev, l, r)))))
+}
+
 /// Like `tree_fold_up`, except that names from `name` nodes are passed down, to the next `nil` and `leaf` cases.
 /// The name from a `name` constructor associates to its right subtree, which is consistent with a left-to-right, in-order traversal of the tree.
 /// The recursive argument `nm` provides the name for the left subtree, if any.
@@ -673,17 +1075,146 @@ pub fn tree_fold_up_nm_dn
 }
 
 
+/// Folds `leaf` over the elements of the list, and `name` over its
+/// names, in sequence: for `Dir2::Left`, from the head of the list to
+/// its end, and for `Dir2::Right`, from its end to its head.  Like
+/// `tree_fold_seq`, memoizes the fold of the rest of the list at (a
+/// fork of) each name, so that distinct folds over one list should
+/// occur in distinct namespaces.
+pub fn list_fold_seq
+  < X:'static, L:ListElim<X>+'static
+  , Res:Hash+Debug+Eq+Clone+'static
+  , LeafC:'static
+  , NameC:'static
+  >
+  (list:L, dir:Dir2, res:Res,
+   leaf:Rc<LeafC>,
+   name:Rc<NameC>) -> Res
+  where LeafC:Fn(X,    Res) -> Res
+  ,     NameC:Fn(Name, Res) -> Res
+{
+  L::elim_arg
+    (list, (res,(leaf,name)),
+     |_,  (res,_)| res,
+     |x, tl, (res,(leaf,name))| match dir {
+       Dir2::Left => {
+         let res = leaf(x, res);
+         list_fold_seq(tl, dir, res, leaf, name)
+       }
+       Dir2::Right => {
+         let res = list_fold_seq(tl, dir, res, leaf.clone(), name);
+         leaf(x, res)
+       }
+     },
+     |n, tl, (res,(leaf,name))| {
+       let (n1, _) = name_fork(n.clone());
+       match dir {
+         Dir2::Left => {
+           let res = name(n, res);
+           memo!(n1 =>> list_fold_seq, list:tl, dir:dir, res:res ;; leaf:leaf, name:name)
+         }
+         Dir2::Right => {
+           let res = memo!(n1 =>> list_fold_seq, list:tl, dir:dir, res:res ;;
+                           leaf:leaf, name:name.clone());
+           name(n, res)
+         }
+       }
+     })
+}
+
+/// Collections that fold over their elements and names in sequence,
+/// so that algorithms over them may be written once, rather than for
+/// the fold of each collection (`list_fold_seq`, `tree_fold_seq` and
+/// `trie::trie_fold_seq_dir`).  These folds memoize at the names of
+/// the collection, so folds of distinct collections that share names
+/// should occur in distinct namespaces.
+pub trait Foldable<X> : Sized {
+  /// Folds `leaf` over the elements, and `name` over the names, in
+  /// the order of the collection for `Dir2::Left`, and in the reverse
+  /// order for `Dir2::Right`.
+  fn fold_seq<Res,LeafC,NameC>
+    (Self, Dir2, Res, Rc<LeafC>, Rc<NameC>) -> Res
+    where Res:Hash+Debug+Eq+Clone+'static
+    ,     LeafC:Fn(X,    Res) -> Res+'static
+    ,     NameC:Fn(Name, Res) -> Res+'static
+    ;
+
+  /// Folds `body` over the elements, in the given direction, without
+  /// the names.  Derived from `fold_seq`.
+  fn fold_elms<Res,F> (c:Self, dir:Dir2, res:Res, body:Rc<F>) -> Res
+    where Res:Hash+Debug+Eq+Clone+'static
+    ,     F:Fn(X, Res) -> Res+'static
+  {
+    Self::fold_seq(c, dir, res, body, Rc::new(|_, res| res))
+  }
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> Foldable<X> for List<X> {
+  fn fold_seq<Res,LeafC,NameC>
+    (l:Self, dir:Dir2, res:Res, leaf:Rc<LeafC>, name:Rc<NameC>) -> Res
+    where Res:Hash+Debug+Eq+Clone+'static
+    ,     LeafC:Fn(X,    Res) -> Res+'static
+    ,     NameC:Fn(Name, Res) -> Res+'static
+  {
+    list_fold_seq(l, dir, res, leaf, name)
+  }
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> Foldable<X> for Tree<X> {
+  fn fold_seq<Res,LeafC,NameC>
+    (t:Self, dir:Dir2, res:Res, leaf:Rc<LeafC>, name:Rc<NameC>) -> Res
+    where Res:Hash+Debug+Eq+Clone+'static
+    ,     LeafC:Fn(X,    Res) -> Res+'static
+    ,     NameC:Fn(Name, Res) -> Res+'static
+  {
+    tree_fold_seq(t, dir, res, leaf,
+                  Rc::new(|_, res| res),
+                  Rc::new(move |n, _, res| name(n, res)))
+  }
+}
+
+
 pub fn tree_of_list
   < Lev:Level, X:'static+Hash+Clone+Debug
   , T:TreeIntro<Lev,X>+'static
   , L:ListElim<X>+ListIntro<X>+'static
   >
   (dir_list:Dir2, list:L) -> T {
+    tree_of_list_with(dir_list, list, Rc::new(level_of_elm::<Lev,X>), Rc::new(level_of_name::<Lev>))
+  }
+
+/// The level that `tree_of_list` gives the element `x`: one more than
+/// `Level::new` of `x` (for `usize`, the trailing zeros of its hash).
+pub fn level_of_elm<Lev:Level, X:Hash> (x:&X) -> Lev {
+  Lev::inc( &Lev::new(x) )
+}
+
+/// The level that `tree_of_list` gives the name `nm`, which is above
+/// the level of every element.
+pub fn level_of_name<Lev:Level> (nm:&Name) -> Lev {
+  Lev::inc( &Lev::add( &Lev::bits() , &Lev::new(nm) ) )
+}
+
+/// Like `tree_of_list`, except that `lev_elm` and `lev_name` give the
+/// levels of the elements and names of the list, rather than
+/// `level_of_elm` and `level_of_name`.  The levels determine the
+/// shape of the tree: higher levels are placed nearer to the root.
+pub fn tree_of_list_with
+  < Lev:Level, X:'static+Hash+Clone+Debug
+  , T:TreeIntro<Lev,X>+'static
+  , L:ListElim<X>+ListIntro<X>+'static
+  , E:'static, N:'static
+  >
+  (dir_list:Dir2, list:L, lev_elm:Rc<E>, lev_name:Rc<N>) -> T
+  where E:Fn(&X) -> Lev
+  ,     N:Fn(&Name) -> Lev
+{
     let tnil = T::nil();
-    let (tree, list) = tree_of_list_rec::<Lev,X,T,L>(dir_list, list, tnil, Lev::zero(), Lev::max_val());
+    let (tree, list) = tree_of_list_rec_with::<Lev,X,T,L,E,N>
+      (dir_list, list, tnil, Lev::zero(), Lev::max_val(), lev_elm, lev_name);
     assert!(L::is_empty(&list));
     tree
-  }
+}
 
 pub fn tree_of_list_rec
   < Lev:Level, X:'static+Hash+Clone+Debug
@@ -691,41 +1222,58 @@ pub fn tree_of_list_rec
   , L:ListElim<X>+ListIntro<X>+'static
   >
   (dir_list:Dir2, list:L, tree:T, tree_lev:Lev, parent_lev:Lev) -> (T, L)
+{
+  tree_of_list_rec_with(dir_list, list, tree, tree_lev, parent_lev,
+                        Rc::new(level_of_elm::<Lev,X>), Rc::new(level_of_name::<Lev>))
+}
+
+fn tree_of_list_rec_with
+  < Lev:Level, X:'static+Hash+Clone+Debug
+  , T:TreeIntro<Lev,X>+'static
+  , L:ListElim<X>+ListIntro<X>+'static
+  , E:'static, N:'static
+  >
+  (dir_list:Dir2, list:L, tree:T, tree_lev:Lev, parent_lev:Lev,
+   lev_elm:Rc<E>, lev_name:Rc<N>) -> (T, L)
+  where E:Fn(&X) -> Lev
+  ,     N:Fn(&Name) -> Lev
 {
   L::elim_arg (
-    list, (dir_list, tree, tree_lev, parent_lev),
+    list, (dir_list, tree, tree_lev, parent_lev, lev_elm, lev_name),
     
     /* Nil */
-    |nil,(_dir_list, tree, _, _)| (tree, nil),
+    |nil,(_dir_list, tree, _, _, _, _)| (tree, nil),
     
     /* Cons */
-    |hd, rest, (dir_list, tree, tree_lev, parent_lev)| {      
-      let lev_hd = Lev::inc ( &Lev::new(&hd) ) ;
+    |hd, rest, (dir_list, tree, tree_lev, parent_lev, lev_elm, lev_name)| {
+      let lev_hd = lev_elm ( &hd ) ;
       if Lev::lte ( &tree_lev , &lev_hd ) && Lev::lte ( &lev_hd , &parent_lev ) {
         let leaf = T::leaf(hd) ;
         let (tree2, rest2) = {
-          tree_of_list_rec( dir_list.clone(), rest, leaf, Lev::zero(), lev_hd.clone() )
+          tree_of_list_rec_with( dir_list.clone(), rest, leaf, Lev::zero(), lev_hd.clone(),
+                                 lev_elm.clone(), lev_name.clone() )
         };
         let tree3 = match dir_list.clone() {
           Dir2::Left  => T::bin ( lev_hd.clone(), tree,  tree2 ),
           Dir2::Right => T::bin ( lev_hd.clone(), tree2, tree  ),
         } ;
-        tree_of_list_rec( dir_list, rest2, tree3, lev_hd, parent_lev )
+        tree_of_list_rec_with( dir_list, rest2, tree3, lev_hd, parent_lev, lev_elm, lev_name )
       }
       else {
         (tree, L::cons(hd,rest))
       }},
     
     /* Name */
-    |nm:Name, rest, (dir_list, tree, tree_lev, parent_lev)|{
-      let lev_nm = Lev::inc( &Lev::add( &Lev::bits() , &Lev::new(&nm) ) ) ;
+    |nm:Name, rest, (dir_list, tree, tree_lev, parent_lev, lev_elm, lev_name)|{
+      let lev_nm = lev_name( &nm ) ;
       if Lev::lte ( &tree_lev , &lev_nm ) && Lev::lte ( &lev_nm ,  &parent_lev ) {
         let nil = T::nil() ;
         let (nm1, nm2) = name_fork(nm.clone());
         let (_, (tree2, rest)) =
-          eager!(nm1 =>> tree_of_list_rec,
+          eager!(nm1 =>> tree_of_list_rec_with =>> <Lev, X, T, L, E, N>,
                  dir_list:dir_list.clone(), list:rest,
-                 tree:nil, tree_lev:Lev::zero(), parent_lev:lev_nm.clone() ) ;
+                 tree:nil, tree_lev:Lev::zero(), parent_lev:lev_nm.clone()
+                 ;; lev_elm:lev_elm.clone(), lev_name:lev_name.clone() ) ;
         let tree3 = match dir_list.clone() {
           Dir2::Left  => T::name ( nm.clone(), lev_nm.clone(), tree,  tree2 ),
           Dir2::Right => T::name ( nm.clone(), lev_nm.clone(), tree2, tree  ),
@@ -733,9 +1281,10 @@ pub fn tree_of_list_rec
         let art = cell(nm, tree3) ;
         let tree3 = T::art( art ) ;
         let (_, (tree, rest)) =
-          eager!(nm2 =>> tree_of_list_rec,
+          eager!(nm2 =>> tree_of_list_rec_with =>> <Lev, X, T, L, E, N>,
                  dir_list:dir_list.clone(), list:rest,
-                 tree:tree3, tree_lev:lev_nm, parent_lev:parent_lev ) ;
+                 tree:tree3, tree_lev:lev_nm, parent_lev:parent_lev
+                 ;; lev_elm:lev_elm, lev_name:lev_name ) ;
         (tree, rest)
       }
       else {
@@ -744,7 +1293,6 @@ pub fn tree_of_list_rec
     )
 }
 
-
 // /// Just like ListT, except with an additional constructor: `tree`
 // pub trait TreeListT<X,T:TreeT<X>> : ListT<X> {    
 //   fn tree (T::Tree, Dir2, Self) -> Self ;
@@ -845,15 +1393,44 @@ pub fn filter_tree_of_tree
   >
   (tree:Te, pred:Box<Fn(&X) -> bool>) -> Ti
 {
-  tree_fold_up
-    (tree,
+  tree_fold_up_in
+    (Namer::of_str("filter_tree_of_tree"),
+     tree,
      Rc::new(|| Ti::nil()),
      Rc::new(move |x|
              {  let fx = pred(&x);
                 if !fx { Ti::nil()   }
                 else   { Ti::leaf(x) } }),
-     Rc::new(|lev,l,r|   ns(name_unit(),|| bin_arts_niltest(None, lev, l, r))),
-     Rc::new(|n,lev,l,r| ns(name_unit(),|| bin_arts_niltest(Some(n), lev, l, r)))
+     Rc::new(|lev,l,r|   bin_arts_niltest(None, lev, l, r)),
+     Rc::new(|n,lev,l,r| bin_arts_niltest(Some(n), lev, l, r))
+     )
+}
+
+/// Maps the leaf elements of a tree using `body`, and keeps those for
+/// which it returns `Some`.  Like `filter_tree_of_tree`, collapses the
+/// binary nodes whose subtrees are emptied, along with their names
+/// and levels, and articulates the subtrees of the named nodes that
+/// remain; the output is memoized at the names of the input (see
+/// `tree_fold_up`).
+pub fn tree_filter_map
+  < Lev:Level, X:Hash+Clone+'static, Y:Hash+Clone+'static
+  , Te:TreeElim<Lev,X>+'static
+  , Ti:TreeIntro<Lev,Y>+TreeElim<Lev,Y>+'static
+  , F:'static
+  >
+  (tree:Te, body:Rc<F>) -> Ti
+  where F:Fn(X) -> Option<Y>
+{
+  tree_fold_up_in
+    (Namer::of_str("tree_filter_map"),
+     tree,
+     Rc::new(|| Ti::nil()),
+     Rc::new(move |x| match body(x) {
+       None    => Ti::nil(),
+       Some(y) => Ti::leaf(y),
+     }),
+     Rc::new(|lev,l,r|   bin_arts_niltest(None, lev, l, r)),
+     Rc::new(|n,lev,l,r| bin_arts_niltest(Some(n), lev, l, r))
      )
 }
 
@@ -877,6 +1454,70 @@ pub fn monoid_of_tree
      )
 }
 
+/// The balanced tree of the list, as `tree_of_list` builds it.  The
+/// tree is built by a thunk in a namespace of its own for each list,
+/// named by the first name of the list (or, for a list that does not
+/// begin with a name, by its hash), so that the reductions over lists
+/// below share the tree of each list (rather than each building it),
+/// and, after an edit to the list, rebuild it incrementally.
+fn list_tree<X:'static+Hash+Clone+Debug+Eq, L:'static+ListElim<X>+ListIntro<X>> (l:L) -> Tree<X> {
+  let nm = L::elim(&l, |_| None, |_, _| None, |nm, _| Some(nm.clone()))
+    .unwrap_or_else(|| name_of_usize(my_hash(&l) as usize));
+  ns(name_of_str("list_tree"), || ns(nm, || {
+    memo!(name_of_str("tree_of_list") =>> tree_of_list::<usize,X,Tree<X>,L>,
+          dir_list:Dir2::Left, list:l)
+  }))
+}
+
+/// Reduces the list with the monoid of `id_elm` and `bin_op`, via the
+/// balanced tree of the list (see `monoid_of_tree`).  The reduction is
+/// memoized by the names of the tree, not by `bin_op`: reductions with
+/// different operations should occur in different namespaces.
+pub fn monoid_of_list
+  < X:Debug+Eq+Hash+Clone+'static
+  , L:ListElim<X>+ListIntro<X>+'static
+  >
+  (l:L, id_elm:X, bin_op:Rc<Fn(X,X) -> X>) -> X
+{
+  let tree = list_tree(l);
+  ns(name_of_str("monoid_of_list"), || monoid_of_tree(tree, id_elm, bin_op))
+}
+
+/// The greatest element of the list, if any, via its balanced tree.
+pub fn list_max<X:Ord+Debug+Hash+Clone+'static, L:ListElim<X>+ListIntro<X>+'static>
+  (l:L) -> Option<X>
+{
+  let tree = list_tree(l);
+  ns(name_of_str("list_max"), || {
+    tree_fold_up(tree,
+                 Rc::new(|| None),
+                 Rc::new(|x| Some(x)),
+                 Rc::new(|_:usize, l, r| max(l, r)),
+                 Rc::new(|_, _, l, r| max(l, r)))
+  })
+}
+
+/// The least element of the list, if any, via its balanced tree.
+pub fn list_min<X:Ord+Debug+Hash+Clone+'static, L:ListElim<X>+ListIntro<X>+'static>
+  (l:L) -> Option<X>
+{
+  fn min_op<X:Ord> (l:Option<X>, r:Option<X>) -> Option<X> {
+    match (l, r) {
+      (None, r) => r,
+      (l, None) => l,
+      (Some(l), Some(r)) => Some(min(l, r)),
+    }
+  }
+  let tree = list_tree(l);
+  ns(name_of_str("list_min"), || {
+    tree_fold_up(tree,
+                 Rc::new(|| None),
+                 Rc::new(|x| Some(x)),
+                 Rc::new(|_:usize, l, r| min_op(l, r)),
+                 Rc::new(|_, _, l, r| min_op(l, r)))
+  })
+}
+
 /// Produces a tree with the same structure as its input, but without
 /// any articulations.  Useful for `println`-style debugging, and for
 /// equality comparisons across distinct engine implementations (e.g.,
@@ -916,6 +1557,158 @@ pub fn prune_tree_of_tree
      )
 }
 
+/// Scans the tree: produces a tree with the same structure as its
+/// input, where each leaf `x` becomes the running value `body(acc, x)`
+/// of the leaves up to and including `x`, in left-to-right order,
+/// starting from `acc`.  Memoizes the scan of both subtrees of each
+/// named node, and articulates them in the output.
+pub fn tree_scan
+  < Lev:Level, X:'static
+  , Te:TreeElim<Lev,X>+'static
+  , Y:Debug+Hash+Eq+Clone+'static
+  , Ti:TreeIntro<Lev,Y>+'static
+  , F:'static
+  >
+  (tree:Te, acc:Y, body:Rc<F>) -> Ti
+  where F:Fn(Y, X) -> Y
+{
+  tree_scan_rec::<Lev,X,Te,Y,Ti,F>(tree, acc, body).0
+}
+
+fn tree_scan_rec
+  < Lev:Level, X:'static
+  , Te:TreeElim<Lev,X>+'static
+  , Y:Debug+Hash+Eq+Clone+'static
+  , Ti:TreeIntro<Lev,Y>+'static
+  , F:'static
+  >
+  (tree:Te, acc:Y, body:Rc<F>) -> (Ti, Y)
+  where F:Fn(Y, X) -> Y
+{
+  Te::elim_arg
+    (tree, (acc, body),
+     |(acc, _)| (Ti::nil(), acc),
+     |x, (acc, body)| {
+       let y = body(acc, x);
+       (Ti::leaf(y.clone()), y)
+     },
+     |lev, l, r, (acc, body)| {
+       let (l, acc) = tree_scan_rec::<Lev,X,Te,Y,Ti,F>(l, acc, body.clone());
+       let (r, acc) = tree_scan_rec::<Lev,X,Te,Y,Ti,F>(r, acc, body);
+       (Ti::bin(lev, l, r), acc)
+     },
+     |n, lev, l, r, (acc, body)| {
+       let (n1, n2, n3, n4) = name_fork4(n.clone());
+       let (l, acc) = memo!(n1 =>> tree_scan_rec::<Lev,X,Te,Y,Ti,F>, tree:l, acc:acc ;;
+                            body:body.clone());
+       let (r, acc) = memo!(n3 =>> tree_scan_rec::<Lev,X,Te,Y,Ti,F>, tree:r, acc:acc ;;
+                            body:body);
+       (Ti::name(n, lev, Ti::art(cell(n2, l)), Ti::art(cell(n4, r))), acc)
+     })
+}
+
+/// Maps `body` over the leaves of the tree, and keeps its levels,
+/// names and articulations in place (unlike going through a list,
+/// which rebuilds the tree), so that the output aligns with the input
+/// for the memoized consumers of both.  Memoizes the map of both
+/// subtrees of each named node, and of each articulated named node;
+/// articulations of unnamed nodes are dropped.  The memoized maps
+/// are named by forks of the names of the tree, and so should occur
+/// in a namespace apart from the one that builds the tree.
+pub fn tree_map<X:'static+Debug+Hash+PartialEq+Eq+Clone,
+                Y:'static+Debug+Hash+PartialEq+Eq+Clone, F:'static>
+  (tree:Tree<X>, body:Rc<F>) -> Tree<Y>
+  where F:Fn(X) -> Y
+{
+  match tree {
+    Tree::Nil => Tree::Nil,
+    Tree::Leaf(x) => Tree::Leaf(body(x)),
+    Tree::Bin(lev, l, r) => Tree::bin(lev, tree_map(*l, body.clone()), tree_map(*r, body)),
+    Tree::Name(nm, lev, l, r) => tree_map_name(nm, lev, *l, *r, body),
+    Tree::Art(a) => match force(&a) {
+      Tree::Name(nm, lev, l, r) => {
+        let (_, _, nm3) = name_fork3(nm.clone());
+        let (art, _) = eager!(nm3 =>> tree_map_name =>> <X, Y, F>,
+                              nm:nm, lev:lev, l:*l, r:*r ;; body:body);
+        Tree::art(art)
+      }
+      tree => tree_map(tree, body),
+    },
+  }
+}
+
+fn tree_map_name<X:'static+Debug+Hash+PartialEq+Eq+Clone,
+                 Y:'static+Debug+Hash+PartialEq+Eq+Clone, F:'static>
+  (nm:Name, lev:usize, l:Tree<X>, r:Tree<X>, body:Rc<F>) -> Tree<Y>
+  where F:Fn(X) -> Y
+{
+  let (nm1, nm2, _) = name_fork3(nm.clone());
+  let l = tree_map_sub(nm1, l, body.clone());
+  let r = tree_map_sub(nm2, r, body);
+  Tree::name(nm, lev, l, r)
+}
+
+/// Maps a subtree of a named node, memoized at `nm`; the output is
+/// articulated if the subtree was.
+fn tree_map_sub<X:'static+Debug+Hash+PartialEq+Eq+Clone,
+                Y:'static+Debug+Hash+PartialEq+Eq+Clone, F:'static>
+  (nm:Name, tree:Tree<X>, body:Rc<F>) -> Tree<Y>
+  where F:Fn(X) -> Y
+{
+  let is_art = match tree { Tree::Art(_) => true, _ => false };
+  let (art, res) = eager!(nm =>> tree_map =>> <X, Y, F>, tree:tree ;; body:body);
+  match res {
+    Tree::Art(a) => Tree::Art(a),
+    res => if is_art { Tree::art(art) } else { res },
+  }
+}
+
+/// Shape statistics of a `Tree`, from `tree_stats`.
+#[derive(Debug,PartialEq,Eq,Hash,Clone,Copy,Default)]
+pub struct TreeStats {
+  /// The number of leaves (elements).
+  pub size: usize,
+  /// The most leaf, binary and named nodes on a path from the root;
+  /// articulations do not count.
+  pub height: usize,
+  /// The number of named nodes.
+  pub name_count: usize,
+  /// The number of articulations, which `tree_stats` forces.
+  pub art_count: usize,
+}
+
+/// Measures the shape of the tree; useful to find which names (and
+/// hashes of names) lead `tree_of_list` to produce skewed trees.
+/// Memoizes the statistics of the subtrees at each named node.
+pub fn tree_stats<X:'static+Debug+Hash+PartialEq+Eq+Clone> (tree:&Tree<X>) -> TreeStats {
+  ns(name_of_str("tree_stats"), || tree_stats_rec(tree.clone()))
+}
+
+fn tree_stats_rec<X:'static+Debug+Hash+PartialEq+Eq+Clone> (tree:Tree<X>) -> TreeStats {
+  match tree {
+    Tree::Nil => TreeStats::default(),
+    Tree::Leaf(_) => TreeStats{ size:1, height:1, name_count:0, art_count:0 },
+    Tree::Bin(_, l, r) => tree_stats_node(*l, *r),
+    Tree::Name(nm, _, l, r) => {
+      let stats = memo!(nm =>> tree_stats_node::<X>, l:*l, r:*r);
+      TreeStats{ name_count: stats.name_count + 1, .. stats }
+    }
+    Tree::Art(ref a) => {
+      let stats = tree_stats_rec(force(a));
+      TreeStats{ art_count: stats.art_count + 1, .. stats }
+    }
+  }
+}
+
+/// The statistics of a (binary or named) node over `l` and `r`.
+fn tree_stats_node<X:'static+Debug+Hash+PartialEq+Eq+Clone> (l:Tree<X>, r:Tree<X>) -> TreeStats {
+  let (l, r) = (tree_stats_rec(l), tree_stats_rec(r));
+  TreeStats{ size: l.size + r.size,
+             height: 1 + l.height.max(r.height),
+             name_count: l.name_count + r.name_count,
+             art_count: l.art_count + r.art_count }
+}
+
 /// Calls `vec_of_list` with the given `demand`
 pub fn list_demand<X:Clone,L:ListElim<X>+'static>
  (list:L, demand:usize) -> Vec<NameElse<X>>
@@ -966,6 +1759,39 @@ pub fn list_of_vec<X:'static+Clone,L:ListIntro<X>>
   return l
 }
 
+/// Constructs a linked list of the elements of `v`, with a name (and
+/// an articulation) before every `k`th element, starting with the
+/// first; the `i`th name is the pair of `nm` and `i`.  Larger `k`
+/// gives coarser-grained incremental reuse, at a lower cost in names
+/// and articulations.  As with `list_of_vec`, the articulations are
+/// named by the names of the list, so the list should be consumed in
+/// a different namespace than the one that builds it.
+pub fn list_of_vec_named<X:'static+Clone,L:ListIntro<X>>
+  (v:Vec<X>, k:usize, nm:Name) -> L
+{
+  assert!(k > 0);
+  let mut l = L::nil();
+  for (i, x) in v.into_iter().enumerate().rev() {
+    l = L::cons(x, l);
+    if i % k == 0 {
+      let nm = name_pair(nm.clone(), name_of_usize(i / k));
+      l = L::name(nm.clone(), L::art(cell(nm, l)))
+    }
+  }
+  l
+}
+
+/// Forces the entire list, and gathers its elements, without its
+/// names; the inverse of `list_of_vec_named`.
+pub fn vec_of_list_elms<X:Clone,L:ListElim<X>+'static>
+  (list:L) -> Vec<X>
+{
+  vec_of_list(list, None).into_iter().filter_map(|x| match x {
+    NameElse::Else(x) => Some(x),
+    NameElse::Name(_) => None,
+  }).collect()
+}
+
 // pub fn rev_list_of_vec<X:Clone,L:ListT<X>> (v:Vec<X>) -> L::List {
 //     let mut l = L::nil(st);
 //     for x in v.iter() { l = L::cons(st,x.clone(), l) }
@@ -1258,6 +2084,117 @@ pub fn list_merge_wrapper<X:'static+Ord+Clone+Debug,L:ListIntro<X>+ListElim<X>+'
   ns(name_of_str("merge"), || list_merge(n1, l1, n2, l2))
 }
 
+/// Sorts a list whose elements are `Ord`.  Builds a tree from the list
+/// with `tree_of_list`, whose names come from the list's names, and
+/// sorts the tree's leaves with `mergesort_list_of_tree`, which
+/// memoizes the merge at each of those names.  So after an edit to the
+/// input, re-sorting repeats only the merges of the runs that hold the
+/// edit, and reuses the rest.
+pub fn list_mergesort<X:'static+Ord+Hash+Debug+Clone>(list:List<X>) -> List<X> {
+  let tree = ns(name_of_str("tree_of_list"),
+                || tree_of_list::<_,_,Tree<_>,_>(Dir2::Left, list));
+  ns(name_of_str("mergesort"),
+     || mergesort_list_of_tree::<_,_,_,List<_>>(tree))
+}
+
+/// A point in the plane, for `quickhull`.  Ordered by `x`, then `y`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,PartialOrd,Ord)]
+pub struct Point {
+  pub x: isize,
+  pub y: isize,
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`: positive when
+/// `c` is left of the line from `a` to `b`, and negative when right.
+fn point_cross (a:&Point, b:&Point, c:&Point) -> isize {
+  (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn name_of_line (a:&Point, b:&Point) -> Name {
+  name_pair(name_pair(name_of_isize(a.x), name_of_isize(a.y)),
+            name_pair(name_of_isize(b.x), name_of_isize(b.y)))
+}
+
+/// The convex hull of `points`, as its corners in counterclockwise
+/// order, from the least point.  Quickhull splits the points by the
+/// line between the least and greatest points, and on each side finds
+/// the point furthest from the line, which is a corner, and recurses
+/// on the lines to it.  Each recursive call is memoized under a name
+/// for its line, and filters its points in a namespace of that name
+/// with `list_filter_eager`, so an edit to `points` re-filters only
+/// under the names of `points` that hold the edit.
+pub fn quickhull (points:List<Point>) -> List<Point> {
+  let ends = list_fold(points.clone(), None, Rc::new(|p:Point, ends:Option<(Point,Point)>| {
+    match ends {
+      None => Some((p, p)),
+      Some((lo, hi)) => Some((lo.min(p), hi.max(p))),
+    }}));
+  match ends {
+    None => List::nil(),
+    Some((lo, hi)) => {
+      if lo == hi { return List::singleton(lo) }
+      ns(name_of_str("quickhull"), || {
+        let lower = memo!(name_of_line(&lo, &hi) =>> quickhull_rec,
+                          a:lo, b:hi, points:points.clone());
+        let upper = memo!(name_of_line(&hi, &lo) =>> quickhull_rec,
+                          a:hi, b:lo, points:points);
+        List::cons(lo, list_append(lower, List::cons(hi, upper)))
+      })
+    }
+  }
+}
+
+/// The corners of the hull of `points` right of the line from `a` to
+/// `b`, in order from `a` to `b`.
+fn quickhull_rec (a:Point, b:Point, points:List<Point>) -> List<Point> {
+  ns(name_of_line(&a, &b), || {
+    let right : List<Point> =
+      list_filter_eager(points, Rc::new(move |c:&Point| point_cross(&a, &b, c) < 0));
+    let far = list_fold(right.clone(), None, Rc::new(move |c:Point, far:Option<(isize,Point)>| {
+      let cross = point_cross(&a, &b, &c);
+      match far {
+        Some((far_cross, _)) if far_cross <= cross => far,
+        _ => Some((cross, c)),
+      }}));
+    match far {
+      None => List::nil(),
+      Some((_, c)) => {
+        let before = memo!(name_of_line(&a, &c) =>> quickhull_rec,
+                           a:a, b:c, points:right.clone());
+        let after = memo!(name_of_line(&c, &b) =>> quickhull_rec,
+                          a:c, b:b, points:right);
+        list_append(before, List::cons(c, after))
+      }
+    }
+  })
+}
+
+#[test]
+pub fn test_quickhull () {
+  fn doit() -> Vec<Point> {
+    let mut l : List<Point> = List::nil();
+    for i in 0..100 {
+      if i % 10 == 0 { l = list_name_art_op(Some(name_of_usize(i)), l) }
+      let (x, y) = ((i * 37) % 7, (i * 53) % 7);
+      l = List::cons(Point{ x:x as isize - 3, y:y as isize - 3 }, l);
+    }
+    for &(x, y) in [(-8, 0), (0, 9), (8, 1), (1, -9), (0, 0)].iter() {
+      l = List::cons(Point{ x:x, y:y }, l);
+    }
+    vec_of_list(quickhull(l), None).into_iter().filter_map(|x| match x {
+      NameElse::Else(x) => Some(x),
+      NameElse::Name(_) => None,
+    }).collect()
+  }
+  manage::init_naive();
+  let o1 = doit();
+  let corners = vec![(-8, 0), (1, -9), (8, 1), (0, 9)];
+  assert_eq!(o1, corners.into_iter().map(|(x, y)| Point{ x:x, y:y }).collect::<Vec<_>>());
+  manage::init_dcg();
+  let o2 = doit();
+  assert_eq!(o1, o2);
+}
+
 // /// Returns the length of the longest run of non-name list elements.
 // /// Useful for tests that assert that lists have well-spaced names.
 // pub fn list_max_run<X,L:ListElim<X>+'static>
@@ -1315,6 +2252,28 @@ pub fn test_mergesort2 () {
   assert_eq!(o1, o2);
 }
 
+#[test]
+pub fn test_list_mergesort () {
+  fn doit() -> Vec<usize> {
+    let mut l : List<usize> = List::nil();
+    for i in 0..100 {
+      if i % 10 == 0 { l = list_name_art_op(Some(name_of_usize(i)), l) }
+      l = List::cons((i * 7919) % 100, l);
+    }
+    let s = list_mergesort(l);
+    vec_of_list(s, None).into_iter().filter_map(|x| match x {
+      NameElse::Else(x) => Some(x),
+      NameElse::Name(_) => None,
+    }).collect()
+  }
+  manage::init_naive();
+  let o1 = doit();
+  assert_eq!(o1, (0..100).collect::<Vec<_>>());
+  manage::init_dcg();
+  let o2 = doit();
+  assert_eq!(o1, o2);
+}
+
 // pub fn tree_append
 //   < X:Clone+Hash+Eq+Debug
 //   , T:TreeT<X> >
@@ -1539,7 +2498,14 @@ pub enum List<X> {
   Art(Art<List<X>>),
 }
 
+/// With the `serde` feature, trees serialize with their names, and
+/// the contents of their articulations, which deserialize into new
+/// articulations (see `put`).
 #[derive(Debug,PartialEq,Eq,Hash,Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+  serialize = "X: 'static+Debug+Hash+Eq+Clone+Serialize",
+  deserialize = "X: Debug+Eq+Clone+Deserialize<'de>")))]
 pub enum Tree<X> {
   Nil,
   Leaf(X),
@@ -1549,6 +2515,7 @@ pub enum Tree<X> {
 }
 
 #[derive(Debug,Clone,PartialEq,Eq,Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum NameElse<X> {
   Name(Name),
   Else(X),
@@ -1615,6 +2582,29 @@ impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> ListElim<X> for List<X>
   }
 }
 
+/// With the `serde` feature, lists serialize as a sequence of their
+/// names and elements (see `vec_of_list`), forcing their
+/// articulations.
+#[cfg(feature = "serde")]
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone+Serialize> Serialize for List<X> {
+  fn serialize<S:Serializer>(&self, serializer:S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(vec_of_list(self.clone(), None))
+  }
+}
+
+/// Deserializes a sequence of names and elements into a list.  The
+/// list has no articulations; its names are not followed by arts.
+#[cfg(feature = "serde")]
+impl<'de, X:'static+Debug+Hash+PartialEq+Eq+Clone+Deserialize<'de>> Deserialize<'de> for List<X> {
+  fn deserialize<D:Deserializer<'de>>(deserializer:D) -> Result<Self, D::Error> {
+    let elms : Vec<NameElse<X>> = Deserialize::deserialize(deserializer)?;
+    Ok(elms.into_iter().rev().fold(List::nil(), |l, elm| match elm {
+      NameElse::Name(nm) => List::name(nm, l),
+      NameElse::Else(x) => List::cons(x, l),
+    }))
+  }
+}
+
 impl<Dom:Debug+Hash+PartialEq+Eq+Clone+'static,
      Cod:Debug+Hash+PartialEq+Eq+Clone+'static> 
   MapIntro<Dom,Cod> 
@@ -2058,203 +3048,1576 @@ impl <Leaf:Debug+Hash+PartialEq+Eq+Clone+'static>
   //      )}  
 }
 
-/// Random Access Zipper (RAZ).
-/// Purely functional sequences with global access (via a balanced
-/// tree structure) and simple local edits (via a zipper structure).
-///
-/// See also: [RAZ in OCaml](http://github.com/cuplv/raz.ocaml.git)
-pub mod raz {
-  use std::rc::Rc;
-  use std::fmt::Debug;
-  use std::hash::Hash;
+/// Meldable priority queues, as leftist heaps.  Besides the nodes of
+/// the heap, a heap may hold names and articulations, like the other
+/// collections here: `meld` memoizes its work at each name it meets,
+/// under the first of the name's forks, and names its output with the
+/// second, so that re-melding after an edit to one of the inputs reuses the
+/// work done under the names that the edit leaves alone.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub enum Heap<X> {
+  Nil,
+  /// The rank (the length of the rightmost path), the least element,
+  /// and the left and right sub-heaps, where the left's rank is at
+  /// least the right's.
+  Node(usize, X, Box<Heap<X>>, Box<Heap<X>>),
+  Name(Name, Box<Heap<X>>),
+  Art(Art<Heap<X>>),
+}
 
-  use macros::* ;
-  use adapton::engine::* ; 
+impl<X:'static+Ord+Hash+Debug+Clone> Heap<X> {
+  /// The heap with no elements.
+  pub fn empty () -> Self { Heap::Nil }
+  /// Names the heap `h`.
+  pub fn name (nm:Name, h:Self) -> Self { Heap::Name(nm, Box::new(h)) }
+  /// A heap held in an articulation.
+  pub fn art (art:Art<Self>) -> Self { Heap::Art(art) }
 
-  /// Punctuation: Information that is interposed between each run of
-  /// elements, and sometimes before and after them.  The RAZ uses
-  /// user-chosen levels to probabilistically balance the Tree form.
-  /// The RAZ uses the Name to identify cached computations in
-  /// Adapton.
-  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
-  pub struct Punc{ pub level:usize, pub name:Name }
-  
-  /// A binary tree with vector leaves (aka, a "rope"), balanced
-  /// probabilistically.
-  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
-  pub enum Tree<X> { 
-    /// Invariant: Nil only appears in certain edge positions
-    Nil, 
-    /// Invariant: Each element run in a Leaf is interposed between two `Punc`s
-    Leaf(Vec<X>),
-    /// (Non-trivial) Inductive case: Two balanced sub-trees, interposed with punctuation.
-    /// Invariant: For balance, every Bin node in left/right subtrees has a equal-or-lower level.
-    Bin(Box<Tree<X>>,Punc,Box<Tree<X>>),
-    /// (Trivial) Inductive case: An Adapton articulation.
-    Art(Art<Tree<X>>),
+  /// Tests if the heap has no elements.
+  pub fn is_empty (h:&Self) -> bool { Self::find_min(h).is_none() }
+
+  /// Adds `x` to the heap `h`.
+  pub fn insert (h:Self, x:X) -> Self {
+    Self::meld(h, Heap::Node(1, x, Box::new(Heap::Nil), Box::new(Heap::Nil)))
   }
-  
-  /// Runs of contiguous element sequences, interposed with
-  /// punctuation information of type `Punc`.
-  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
-  enum Elms<X> {
-    /// Base case: No more Cons cells, all remaining elements are formed into a sequence of Trees.
-    Trees(Vec<Tree<X>>), 
-    /// (Non-trivial) Inductive case: Run of elements, followed by more elements.
-    Cons(Vec<X>,Option<ConsTl<X>>),
-    /// (Trivial) Inductive case: an Adapton articulation.
-    Art(Art<Elms<X>>),
+
+  /// The least element of the heap, if any.
+  pub fn find_min (h:&Self) -> Option<X> {
+    match *h {
+      Heap::Nil => None,
+      Heap::Node(_, ref x, _, _) => Some(x.clone()),
+      Heap::Name(_, ref h) => Self::find_min(h),
+      Heap::Art(ref a) => Self::find_min(&force(a)),
+    }
   }
-  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
-  struct ConsTl<X> {
-    /// Invariant: Punctuation separates each run of elements.
-    punc:Punc,
-    /// Inductive structure: (possibly empty) sequence of more element runs.
-    elms:Box<Elms<X>>,
+
+  /// Removes the least element of the heap, if any.  Keeps the names
+  /// above it.
+  pub fn delete_min (h:Self) -> Self {
+    match h {
+      Heap::Nil => Heap::Nil,
+      Heap::Node(_, _, l, r) => Self::meld(*l, *r),
+      Heap::Name(nm, h) => Heap::name(nm, Self::delete_min(*h)),
+      Heap::Art(ref a) => Self::delete_min(force(a)),
+    }
   }
 
-  /// The Zipper form consists of element sequences before (to the
-  /// left of) and after (to the right of) a distinguished punctuation
-  /// point in the sequence.
-  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
-  pub struct Zip<X> {
-    left:  Elms<X>,
-    curs:  Punc,
-    right: Elms<X>
+  /// Merges the heaps `h1` and `h2`, along their rightmost paths.
+  pub fn meld (h1:Self, h2:Self) -> Self {
+    heap_meld(h1, h2)
   }
+}
 
-  /// Distinguishes left (`L`) and right (`R`) traversal orders for various operations involving trees.
-  #[derive(PartialEq,Eq,Hash,Debug,Clone,Copy)]
-  pub enum Dir { L, R }
-  
-  /// An O(1)-sized change to the zipper: a single element insertion,
-  /// replacement, removal; or, a local cursor movement by one element
-  /// in either direction.
-  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
-  pub enum Edit<X> {
-    Insert(Dir,X,Option<Punc>),
-    Replace(Dir,X),
-    Remove(Dir),
-    Move(Dir),
+fn heap_rank<X:'static+Ord+Hash+Debug+Clone> (h:&Heap<X>) -> usize {
+  match *h {
+    Heap::Nil => 0,
+    Heap::Node(rank, _, _, _) => rank,
+    Heap::Name(_, ref h) => heap_rank(h),
+    Heap::Art(ref a) => heap_rank(&force(a)),
   }
+}
 
-  /// A zipper that consists of zero elements (and exactly one punctuation `p`).
-  pub fn zip_empty<X>(p:Punc) -> Zip<X> {
-    return Zip{left:Elms::Cons(vec![], None),
-               curs:p,
-               right:Elms::Cons(vec![], None)}
+/// A node holding `x`, over the heaps `a` and `b`, with the heap of
+/// greater rank on the left.
+fn heap_node<X:'static+Ord+Hash+Debug+Clone> (x:X, a:Heap<X>, b:Heap<X>) -> Heap<X> {
+  let (rank_a, rank_b) = (heap_rank(&a), heap_rank(&b));
+  if rank_a >= rank_b { Heap::Node(rank_b + 1, x, Box::new(a), Box::new(b)) }
+  else                { Heap::Node(rank_a + 1, x, Box::new(b), Box::new(a)) }
+}
+
+fn heap_meld<X:'static+Ord+Hash+Debug+Clone> (h1:Heap<X>, h2:Heap<X>) -> Heap<X> {
+  match (h1, h2) {
+    (Heap::Art(ref a), h2) => heap_meld(force(a), h2),
+    (h1, Heap::Art(ref a)) => heap_meld(h1, force(a)),
+    (Heap::Nil, h2) => h2,
+    (h1, Heap::Nil) => h1,
+    (Heap::Name(nm, h1), h2) => {
+      let (nm1, nm2) = name_fork(nm);
+      let (art, _) = eager!(nm1 =>> heap_meld::<X>, h1:*h1, h2:h2);
+      Heap::name(nm2, Heap::art(art))
+    }
+    (h1, Heap::Name(nm, h2)) => {
+      let (nm1, nm2) = name_fork(nm);
+      let (art, _) = eager!(nm1 =>> heap_meld::<X>, h1:h1, h2:*h2);
+      Heap::name(nm2, Heap::art(art))
+    }
+    (Heap::Node(r1, x1, l1, rt1), Heap::Node(r2, x2, l2, rt2)) => {
+      if x1 <= x2 {
+        heap_node(x1, *l1, heap_meld(*rt1, Heap::Node(r2, x2, l2, rt2)))
+      } else {
+        heap_node(x2, *l2, heap_meld(Heap::Node(r1, x1, l1, rt1), *rt2))
+      }
+    }
   }
+}
 
-  /// A tree that consists of zero elements (and exactly one punctuation `p`).
-  pub fn tree_empty<X>(p:Punc) -> Tree<X> {
-    return Tree::Bin(Box::new(Tree::Nil), p, Box::new(Tree::Nil))
+/// Monoids that summarize sequences of `X`, for `MeasuredSeq`.
+pub trait Measure<X> : Debug+Hash+PartialEq+Eq+Clone+'static {
+  /// The measure of the empty sequence.
+  fn zero () -> Self ;
+  /// The measure of the sequence holding just `x`.
+  fn of_elm (x:&X) -> Self ;
+  /// The measure of one sequence followed by another.  Associative,
+  /// with `zero` as its unit.
+  fn plus (&Self, &Self) -> Self ;
+}
+
+/// Measures the length of a sequence.
+#[derive(Debug,Hash,PartialEq,Eq,Clone,Copy)]
+pub struct Size(pub usize);
+
+impl<X> Measure<X> for Size {
+  fn zero () -> Self { Size(0) }
+  fn of_elm (_:&X) -> Self { Size(1) }
+  fn plus (a:&Self, b:&Self) -> Self { Size(a.0 + b.0) }
+}
+
+/// Measures the length, sum and greatest element of a sequence.
+#[derive(Debug,Hash,PartialEq,Eq,Clone)]
+pub struct Stats<X> {
+  pub size: usize,
+  pub sum: X,
+  pub max: Option<X>,
+}
+
+impl<X:Add<Output=X>+Ord+Default+Hash+Debug+Clone+'static> Measure<X> for Stats<X> {
+  fn zero () -> Self { Stats{ size:0, sum:X::default(), max:None } }
+  fn of_elm (x:&X) -> Self { Stats{ size:1, sum:x.clone(), max:Some(x.clone()) } }
+  fn plus (a:&Self, b:&Self) -> Self {
+    Stats{ size:a.size + b.size,
+           sum:a.sum.clone() + b.sum.clone(),
+           max:match (a.max.clone(), b.max.clone()) {
+             (Some(x), Some(y)) => Some(if x < y { y } else { x }),
+             (x, None) => x,
+             (None, y) => y,
+           }}
   }
+}
 
-  /// Transform the zipper, inserting element `x` in the given
-  /// direction `d`. The optional punctuation `p` follows the inserted
-  /// element, in the given direction.
-  pub fn zip_insert<X:'static+Eq+Clone+Hash+Debug>(z:Zip<X>, d:Dir, x:X, p:Option<Punc>) -> Zip<X> {
-    match &d {
-      &Dir::L => {
-        match p {
-          Some(p) => { // Punctuate the insertion with the given p; creates new run of elements, consisting only of element x
-            let a = cell(p.name.clone(), z.left);
-            Zip{left:Elms::Cons(vec![x], Some(ConsTl{punc:p, elms:Box::new(Elms::Art(a))})), ..z}
-          }
-          None => {
-            match z.left { // Mutate the current run of elements, by pushing element x onto it
-              Elms::Cons(mut xs,tl) => { xs.push(x); Zip{left:Elms::Cons(xs, tl), ..z} },
-              _ => panic!("bad arguments: need punctuation")
-            }
-          }
-        }
-      }
-      &Dir::R => {
-        // TODO-Now
-        panic!("symmetric to above")
-      }
+/// A sequence of `X`s held in a tree whose inner nodes each cache the
+/// measure `M` of their leaves.  So the measure of the whole sequence
+/// is a lookup, and `split_at_measure` follows a single path down the
+/// tree.  `of_tree` builds a sequence with `tree_fold_up`, memoizing
+/// the subtree under each of the input's names; rebuilding after an
+/// edit to the input thus re-measures only the subtrees that hold the
+/// edit, rather than folding over the whole input as `monoid_of_tree`
+/// does.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub enum MeasuredSeq<X, M> {
+  Nil,
+  Leaf(X),
+  Bin(M, Box<MeasuredSeq<X,M>>, Box<MeasuredSeq<X,M>>),
+  Name(Name, M, Box<MeasuredSeq<X,M>>, Box<MeasuredSeq<X,M>>),
+  /// An articulated sequence, with its measure.
+  Art(M, Art<MeasuredSeq<X,M>>),
+}
+
+impl<X:'static+Hash+Debug+Eq+Clone, M:Measure<X>> MeasuredSeq<X,M> {
+  /// The measure of the sequence.  Forces no articulations.
+  pub fn measure (s:&Self) -> M {
+    match *s {
+      MeasuredSeq::Nil => M::zero(),
+      MeasuredSeq::Leaf(ref x) => M::of_elm(x),
+      MeasuredSeq::Bin(ref m, _, _) |
+      MeasuredSeq::Name(_, ref m, _, _) |
+      MeasuredSeq::Art(ref m, _) => m.clone(),
     }
   }
-    
-  /// Perform the given edit, in the given direction, at the current focus of the given zipper.
-  /// The zipper is taken because its head vectors may be mutated, e.g., to insert or remove elements.
-  pub fn zip_edit<X:'static+Eq+Clone+Hash+Debug>(z:Zip<X>, edit:Edit<X>) -> Zip<X> {
-    match edit {
-      Edit::Insert(d,x,p) => { zip_insert(z, d, x, p) },
-      _ => unimplemented!() // TODO-Later
+
+  /// The sequence `l` followed by `r`.
+  pub fn append (l:Self, r:Self) -> Self {
+    match (l, r) {
+      (MeasuredSeq::Nil, r) => r,
+      (l, MeasuredSeq::Nil) => l,
+      (l, r) => {
+        let m = M::plus(&Self::measure(&l), &Self::measure(&r));
+        MeasuredSeq::Bin(m, Box::new(l), Box::new(r))
+      }
     }
   }
 
-  fn cons_trampoline<X:Eq+Clone+Hash+Debug+'static>
-    (dir:Dir, parent_lev:usize, tr_lev:usize, tr:Tree<X>, es:Elms<X>) 
-     -> (Tree<X>, Elms<X>)
-  {
-    match es {
-      Elms::Trees(trs) => { (tr, Elms::Trees(trs)) }
-      Elms::Cons(v, None) => { if v.len() > 0 { panic!("bad") } else { (tr, Elms::Cons(v, None)) }},
-      Elms::Art(a) => cons_trampoline(dir, parent_lev, tr_lev, tr, force(&a)),
-      Elms::Cons(v, Some(ConsTl{punc:p, elms:es})) => {
-        if tr_lev <= p.level && p.level <= parent_lev {
-          let (n1, n2) = name_fork(p.name.clone());
-          let (_, (rtr, es)) = eager!(n1 =>> cons_trampoline, dir:dir, parent_lev:p.level, tr_lev:0, tr:Tree::Leaf(vec![]), es:*es);
-          let tr = match &dir {
-            & Dir::L => Tree::Bin(Box::new(tr),  p.clone(), Box::new(rtr)),
-            & Dir::R => Tree::Bin(Box::new(rtr), p.clone(), Box::new(tr)),
-          };
-          let tr = Tree::Art(cell(p.name.clone(), tr));
-          let (_, (tr, es)) = eager!(n2 =>> cons_trampoline, dir:dir, parent_lev:parent_lev, tr_lev:p.level, tr:tr, es:es);
-          (tr, es)
-        }
-        else {
-          (tr, Elms::Cons(v, Some(ConsTl{punc:p, elms:es})))
+  /// The sequence of the leaves of `tree`, in order.  At each of the
+  /// tree's names, articulates the subsequence in a cell of that name.
+  pub fn of_tree<Lev:Level, T:TreeElim<Lev,X>> (tree:T) -> Self {
+    ns(name_of_str("measured_seq"), || {
+      tree_fold_up(tree,
+                   Rc::new(|| MeasuredSeq::Nil),
+                   Rc::new(|x| MeasuredSeq::Leaf(x)),
+                   Rc::new(|_, l, r| Self::append(l, r)),
+                   Rc::new(|n:Name, _, l, r| {
+                     let m = M::plus(&Self::measure(&l), &Self::measure(&r));
+                     let s = MeasuredSeq::Name(n.clone(), m.clone(), Box::new(l), Box::new(r));
+                     MeasuredSeq::Art(m, cell(n, s))
+                   }))
+    })
+  }
+
+  /// The sequence of the elements of `list`, via `tree_of_list`.
+  pub fn of_list (list:List<X>) -> Self {
+    let tree = ns(name_of_str("tree_of_list"),
+                  || tree_of_list::<_,_,Tree<_>,_>(Dir2::Left, list));
+    Self::of_tree(tree)
+  }
+
+  /// Splits the sequence at the first element `x` for which `pred`
+  /// holds of the measure of the prefix ending with `x`, returning the
+  /// elements before `x`, `x` itself, and those after; or the whole
+  /// sequence, `None` and `Nil` if there is no such element.  `pred`
+  /// should be monotone: once it holds of a prefix, it should hold of
+  /// every longer prefix.  Takes time logarithmic in the length of a
+  /// balanced sequence, and keeps the names off the path to `x`.
+  pub fn split_at_measure<F:Fn(&M) -> bool> (s:Self, pred:&F) -> (Self, Option<X>, Self) {
+    Self::split_rec(s, &M::zero(), pred)
+  }
+
+  fn split_rec<F:Fn(&M) -> bool> (s:Self, acc:&M, pred:&F) -> (Self, Option<X>, Self) {
+    match s {
+      MeasuredSeq::Nil => (MeasuredSeq::Nil, None, MeasuredSeq::Nil),
+      MeasuredSeq::Leaf(x) => {
+        if pred(&M::plus(acc, &M::of_elm(&x))) { (MeasuredSeq::Nil, Some(x), MeasuredSeq::Nil) }
+        else { (MeasuredSeq::Leaf(x), None, MeasuredSeq::Nil) }
+      }
+      MeasuredSeq::Bin(_, l, r) |
+      MeasuredSeq::Name(_, _, l, r) => {
+        let acc_l = M::plus(acc, &Self::measure(&l));
+        if pred(&acc_l) {
+          let (ll, x, lr) = Self::split_rec(*l, acc, pred);
+          (ll, x, Self::append(lr, *r))
+        } else {
+          let (rl, x, rr) = Self::split_rec(*r, &acc_l, pred);
+          (Self::append(*l, rl), x, rr)
         }
       }
+      MeasuredSeq::Art(_, ref a) => Self::split_rec(force(a), acc, pred),
     }
-  } 
+  }
 
-  /// Compute a vector of trees, building any leading Cons cells into Tree structure
-  fn trees_of_elms<X:Eq+Clone+Hash+Debug+'static>(dir:Dir, es:Elms<X>) -> Vec<Tree<X>> {
-    match cons_trampoline(dir, usize::max_value(), 0, Tree::Nil, es) {
-      (tr, Elms::Trees(mut trs)) => { trs.push(tr); return trs }
-      _ => unreachable!(),
+  /// The elements of the sequence, in order.
+  pub fn elems (s:&Self) -> Vec<X> {
+    fn push<X:'static+Hash+Debug+Eq+Clone, M:Measure<X>>
+      (s:&MeasuredSeq<X,M>, out:&mut Vec<X>) {
+      match *s {
+        MeasuredSeq::Nil => (),
+        MeasuredSeq::Leaf(ref x) => out.push(x.clone()),
+        MeasuredSeq::Bin(_, ref l, ref r) |
+        MeasuredSeq::Name(_, _, ref l, ref r) => { push(l, out); push(r, out) }
+        MeasuredSeq::Art(_, ref a) => push(&force(a), out),
+      }
     }
+    let mut out = Vec::new();
+    push(s, &mut out);
+    out
   }
+}
 
-  /// Appends the sequences of two trees
-  pub fn tree_append<X>(tr1:Tree<X>, tr2:Tree<X>) -> Tree<X> {
-    panic!("TODO")    
-  }
+/// Ordered finite maps, as treaps: search trees by key that are also
+/// heaps by a priority hashed from the key.  So the shape of the tree
+/// depends only on its keys, not on the order they were added in, and
+/// the same entries make the same tree, as memoization likes.  Unlike
+/// the trie maps, an `OrdMap` answers queries about the order of its
+/// keys: `min_key`, `max_key` and `range`.  Like them, it may hold
+/// names and articulations; each operation rebuilds only the path to
+/// the keys it affects, and keeps the names off that path.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub enum OrdMap<K,V> {
+  Nil,
+  /// The priority, key and value of the root, and the maps of the
+  /// lesser and greater keys.
+  Node(u64, K, V, Box<OrdMap<K,V>>, Box<OrdMap<K,V>>),
+  Name(Name, Box<OrdMap<K,V>>),
+  Art(Art<OrdMap<K,V>>),
+}
 
-  /// Unfocuses the zipper into a tree form
-  pub fn tree_of_zip<X:Eq+Clone+Hash+Debug+'static>(z:Zip<X>) -> Tree<X> {
-    let mut tree = tree_empty(z.curs);
-    let ltrees = trees_of_elms(Dir::L, z.left);
-    for t in ltrees.iter() { // TODO: Take iterator; Reverse iteration here
-      tree = tree_append(t.clone(), tree); // TODO Avoid clone
+impl<K:'static+Ord+Hash+Debug+Clone,
+     V:'static+Hash+Debug+Eq+Clone> OrdMap<K,V> {
+  /// Names the map `m`.
+  pub fn name (nm:Name, m:Self) -> Self { OrdMap::Name(nm, Box::new(m)) }
+  /// A map held in an articulation.
+  pub fn art (art:Art<Self>) -> Self { OrdMap::Art(art) }
+
+  /// The least key of the map, if any.
+  pub fn min_key (m:&Self) -> Option<K> {
+    match *m {
+      OrdMap::Nil => None,
+      OrdMap::Node(_, ref k, _, ref l, _) => Self::min_key(l).or_else(|| Some(k.clone())),
+      OrdMap::Name(_, ref m) => Self::min_key(m),
+      OrdMap::Art(ref a) => Self::min_key(&force(a)),
     }
-    let rtrees = trees_of_elms(Dir::R, z.right);
-    for t in rtrees.iter() {
-      tree = tree_append(tree, t.clone()); // TODO Avoid clone
+  }
+
+  /// The greatest key of the map, if any.
+  pub fn max_key (m:&Self) -> Option<K> {
+    match *m {
+      OrdMap::Nil => None,
+      OrdMap::Node(_, ref k, _, _, ref r) => Self::max_key(r).or_else(|| Some(k.clone())),
+      OrdMap::Name(_, ref m) => Self::max_key(m),
+      OrdMap::Art(ref a) => Self::max_key(&force(a)),
     }
-    return tree
   }
 
-  fn merge_elms<X:Ord+Hash+Eq+Debug+Clone+'static>
-  (es1:Elms<X>, es2:Elms<X>) -> Elms<X> {
-    let (mut v1, tl1, 
-         mut v2, tl2) = match (es1, es2) {
-      (Elms::Cons(v1, tl1), 
-       Elms::Cons(v2, tl2)) => (v1.clone(), tl1, v2.clone(), tl2), // TODO Avoid these clones.
-      (_, _)                => panic!("illegal argument(s)")
+  /// The entries of `m` with keys from `lo`, inclusive, to `hi`,
+  /// exclusive.
+  pub fn range (m:Self, lo:&K, hi:&K) -> Self {
+    let (_, v, above) = ordmap_split(m, lo);
+    let from_lo = match v {
+      None => above,
+      Some(v) => ordmap_join(ordmap_leaf(lo.clone(), v), above),
     };
-    let mut merged = Vec::new();
-    loop {
-      match (v1.pop(), v2.pop()) {
-        (None, None)     => { return Elms::Cons(Vec::new(), None) },
-        (None, Some(e2)) => { // First vector "wins" race to empty.
-          v2.push(e2); let es2 = Elms::Cons(v2, tl2);
-          match tl1 { 
-            None => return es2,
+    let (range, _, _) = ordmap_split(from_lo, hi);
+    range
+  }
+}
+
+/// Strips the names and forces the articulations at the root of `m`.
+fn ordmap_expose<K:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (m:OrdMap<K,V>) -> OrdMap<K,V>
+{
+  match m {
+    OrdMap::Name(_, m) => ordmap_expose(*m),
+    OrdMap::Art(ref a) => ordmap_expose(force(a)),
+    m => m,
+  }
+}
+
+fn ordmap_leaf<K:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (k:K, v:V) -> OrdMap<K,V>
+{
+  OrdMap::Node(my_hash(&k), k, v, Box::new(OrdMap::Nil), Box::new(OrdMap::Nil))
+}
+
+/// Splits `m` into the entries with keys less than `k`, the value at
+/// `k`, if any, and the entries with greater keys.
+fn ordmap_split<K:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (m:OrdMap<K,V>, k:&K) -> (OrdMap<K,V>, Option<V>, OrdMap<K,V>)
+{
+  match ordmap_expose(m) {
+    OrdMap::Node(p, k2, v2, l, r) => {
+      if *k < k2 {
+        let (ll, v, lr) = ordmap_split(*l, k);
+        (ll, v, OrdMap::Node(p, k2, v2, Box::new(lr), r))
+      } else if k2 < *k {
+        let (rl, v, rr) = ordmap_split(*r, k);
+        (OrdMap::Node(p, k2, v2, l, Box::new(rl)), v, rr)
+      } else {
+        (*l, Some(v2), *r)
+      }
+    }
+    _ => (OrdMap::Nil, None, OrdMap::Nil),
+  }
+}
+
+/// Joins `l` and `r`, all of whose keys are less than those of `r`.
+fn ordmap_join<K:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (l:OrdMap<K,V>, r:OrdMap<K,V>) -> OrdMap<K,V>
+{
+  match (ordmap_expose(l), ordmap_expose(r)) {
+    (OrdMap::Nil, r) => r,
+    (l, OrdMap::Nil) => l,
+    (OrdMap::Node(pl, kl, vl, ll, lr), OrdMap::Node(pr, kr, vr, rl, rr)) => {
+      // Ties between priorities go to the lesser key, on the left.
+      if pl >= pr {
+        let r = OrdMap::Node(pr, kr, vr, rl, rr);
+        OrdMap::Node(pl, kl, vl, ll, Box::new(ordmap_join(*lr, r)))
+      } else {
+        let l = OrdMap::Node(pl, kl, vl, ll, lr);
+        OrdMap::Node(pr, kr, vr, Box::new(ordmap_join(l, *rl)), rr)
+      }
+    }
+    _ => unreachable!(),
+  }
+}
+
+impl<K:'static+Ord+Hash+Debug+Clone,
+     V:'static+Hash+Debug+Eq+Clone>
+  MapIntro<K,V>
+  for OrdMap<K,V>
+{
+  fn empty () -> Self { OrdMap::Nil }
+
+  fn update (map:Self, k:K, v:V) -> Self {
+    let (l, _, r) = ordmap_split(map, &k);
+    ordmap_join(ordmap_join(l, ordmap_leaf(k, v)), r)
+  }
+}
+
+impl<K:'static+Ord+Hash+Debug+Clone,
+     V:'static+Hash+Debug+Eq+Clone>
+  MapElim<K,V>
+  for OrdMap<K,V>
+{
+  fn find (map:&Self, k:&K) -> Option<V> {
+    match *map {
+      OrdMap::Nil => None,
+      OrdMap::Node(_, ref k2, ref v, ref l, ref r) => {
+        if *k < *k2 { Self::find(l, k) }
+        else if *k2 < *k { Self::find(r, k) }
+        else { Some(v.clone()) }
+      }
+      OrdMap::Name(_, ref m) => Self::find(m, k),
+      OrdMap::Art(ref a) => Self::find(&force(a), k),
+    }
+  }
+
+  fn remove (map:Self, k:&K) -> (Self, Option<V>) {
+    let (l, v, r) = ordmap_split(map, k);
+    (ordmap_join(l, r), v)
+  }
+
+  /// Folds over the entries in order of their keys.
+  fn fold<Res,F> (map:Self, res:Res, f:Rc<F>) -> Res
+    where F:Fn(K, V, Res) -> Res+'static,
+          Res:Hash+Debug+Eq+Clone+'static
+  {
+    match ordmap_expose(map) {
+      OrdMap::Node(_, k, v, l, r) => {
+        let res = Self::fold(*l, res, f.clone());
+        let res = f(k, v, res);
+        Self::fold(*r, res, f)
+      }
+      _ => res,
+    }
+  }
+
+  /// Adds the entries of `other` to `map`, replacing those with the
+  /// same keys.
+  fn append (map:Self, other:Self) -> Self {
+    Self::fold(other, map, Rc::new(|k, v, map| Self::update(map, k, v)))
+  }
+}
+
+/// Maps from half-open intervals `[lo, hi)` to values, answering which
+/// intervals hold a point.  An `IntervalMap` is a treap ordered by the
+/// intervals, like `OrdMap`, in which each node also caches the
+/// greatest `hi` of its subtree, so that a query skips the subtrees
+/// that end before its point.  The same interval may be inserted more
+/// than once.  Like the other collections here, it may hold names and
+/// articulations: `query_point` memoizes the query of each named
+/// subtree, and an insertion keeps the names off the path it rebuilds.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub enum IntervalMap<T,V> {
+  Nil,
+  /// The priority, interval and value of the root, the greatest `hi`
+  /// of the subtree, and the maps of lesser and greater intervals.
+  Node(u64, (T,T), V, T, Box<IntervalMap<T,V>>, Box<IntervalMap<T,V>>),
+  Name(Name, Box<IntervalMap<T,V>>),
+  Art(Art<IntervalMap<T,V>>),
+}
+
+impl<T:'static+Ord+Hash+Debug+Clone,
+     V:'static+Hash+Debug+Eq+Clone> IntervalMap<T,V> {
+  /// The map with no intervals.
+  pub fn empty () -> Self { IntervalMap::Nil }
+  /// Names the map `m`.
+  pub fn name (nm:Name, m:Self) -> Self { IntervalMap::Name(nm, Box::new(m)) }
+  /// A map held in an articulation.
+  pub fn art (art:Art<Self>) -> Self { IntervalMap::Art(art) }
+
+  /// Maps the interval `[lo, hi)` to `v`, alongside any other values
+  /// that the map holds for it.
+  pub fn insert (m:Self, interval:(T,T), v:V) -> Self {
+    let (l, r) = interval_split(m, &interval);
+    let p = my_hash(&(&interval, &v));
+    let leaf = interval_node(p, interval, v, IntervalMap::Nil, IntervalMap::Nil);
+    interval_join(interval_join(l, leaf), r)
+  }
+
+  /// The values of the intervals that hold `t`, in order of their
+  /// intervals.  Each point is queried in a namespace of its own, so
+  /// that queries at different points do not overwrite each other.
+  pub fn query_point (m:Self, t:T) -> List<V> {
+    let nm = name_pair(name_of_str("query_point"), name_of_usize(my_hash(&t) as usize));
+    ns(nm, || interval_query(m, t, List::nil()))
+  }
+}
+
+/// Strips the names and forces the articulations at the root of `m`.
+fn interval_expose<T:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (m:IntervalMap<T,V>) -> IntervalMap<T,V>
+{
+  match m {
+    IntervalMap::Name(_, m) => interval_expose(*m),
+    IntervalMap::Art(ref a) => interval_expose(force(a)),
+    m => m,
+  }
+}
+
+fn interval_max_hi<T:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (m:&IntervalMap<T,V>) -> Option<T>
+{
+  match *m {
+    IntervalMap::Nil => None,
+    IntervalMap::Node(_, _, _, ref max_hi, _, _) => Some(max_hi.clone()),
+    IntervalMap::Name(_, ref m) => interval_max_hi(m),
+    IntervalMap::Art(ref a) => interval_max_hi(&force(a)),
+  }
+}
+
+fn interval_node<T:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (p:u64, interval:(T,T), v:V, l:IntervalMap<T,V>, r:IntervalMap<T,V>) -> IntervalMap<T,V>
+{
+  let max_hi = vec![interval_max_hi(&l), interval_max_hi(&r)].into_iter()
+    .fold(interval.1.clone(), |max_hi, hi| match hi {
+      Some(ref hi) if max_hi < *hi => hi.clone(),
+      _ => max_hi,
+    });
+  IntervalMap::Node(p, interval, v, max_hi, Box::new(l), Box::new(r))
+}
+
+/// Splits `m` into the entries with intervals less than `interval`,
+/// and the rest.
+fn interval_split<T:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (m:IntervalMap<T,V>, interval:&(T,T)) -> (IntervalMap<T,V>, IntervalMap<T,V>)
+{
+  match interval_expose(m) {
+    IntervalMap::Node(p, interval2, v2, _, l, r) => {
+      if interval2 < *interval {
+        let (rl, rr) = interval_split(*r, interval);
+        (interval_node(p, interval2, v2, *l, rl), rr)
+      } else {
+        let (ll, lr) = interval_split(*l, interval);
+        (ll, interval_node(p, interval2, v2, lr, *r))
+      }
+    }
+    _ => (IntervalMap::Nil, IntervalMap::Nil),
+  }
+}
+
+/// Joins `l` and `r`, all of whose intervals are no greater than those
+/// of `r`.
+fn interval_join<T:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (l:IntervalMap<T,V>, r:IntervalMap<T,V>) -> IntervalMap<T,V>
+{
+  match (interval_expose(l), interval_expose(r)) {
+    (IntervalMap::Nil, r) => r,
+    (l, IntervalMap::Nil) => l,
+    (IntervalMap::Node(pl, il, vl, _, ll, lr), IntervalMap::Node(pr, ir, vr, _, rl, rr)) => {
+      if pl >= pr {
+        let r = interval_node(pr, ir, vr, *rl, *rr);
+        interval_node(pl, il, vl, *ll, interval_join(*lr, r))
+      } else {
+        let l = interval_node(pl, il, vl, *ll, *lr);
+        interval_node(pr, ir, vr, interval_join(l, *rl), *rr)
+      }
+    }
+    _ => unreachable!(),
+  }
+}
+
+/// The values of the intervals of `m` that hold `t`, followed by
+/// `rest`.  Walks the map from right to left; as `list_reverse` does,
+/// at each name it places `rest` in a cell, and memoizes the query of
+/// the named subtree.
+fn interval_query<T:'static+Ord+Hash+Debug+Clone, V:'static+Hash+Debug+Eq+Clone>
+  (m:IntervalMap<T,V>, t:T, rest:List<V>) -> List<V>
+{
+  match m {
+    IntervalMap::Nil => rest,
+    IntervalMap::Node(_, (lo, hi), v, max_hi, l, r) => {
+      if max_hi <= t { return rest }
+      let rest = if lo <= t { interval_query(*r, t.clone(), rest) } else { rest };
+      let rest = if lo <= t && t < hi { List::cons(v, rest) } else { rest };
+      interval_query(*l, t, rest)
+    }
+    IntervalMap::Name(nm, m) => {
+      let (nm1, nm2) = name_fork(nm.clone());
+      let rest = List::art(cell(nm1, rest));
+      let (art, _) = eager!(nm2 =>> interval_query::<T,V>, m:*m, t:t, rest:rest);
+      List::name(nm, List::art(art))
+    }
+    IntervalMap::Art(ref a) => interval_query(force(a), t, rest),
+  }
+}
+
+/// The most elements that `ChunkedList::cons` places in one chunk.
+pub const CHUNK_LEN : usize = 32;
+
+/// Unrolled lists: lists whose cons cells each hold a chunk of
+/// elements, rather than a single element.  Chunked lists are
+/// `ListIntro` and `ListElim`, so the list functions here apply to
+/// them; though, for large lists, building them with `of_vec` and
+/// consuming them with `fold` places one name and one articulation
+/// per chunk, and visits each chunk at once, rather than paying for
+/// a cons cell per element.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub enum ChunkedList<X> {
+  Nil,
+  /// A non-empty chunk of elements, in list order, and the rest of
+  /// the list.
+  Chunk(SharedChunk<X>, Box<ChunkedList<X>>),
+  Name(Name, Box<ChunkedList<X>>),
+  Art(Art<ChunkedList<X>>),
+}
+
+/// The elements of a chunk of a `ChunkedList`: a suffix of a vector
+/// that is shared, so that taking the tail of a chunk does not copy
+/// the rest of it.  Derefs to, compares as and hashes as the slice of
+/// elements it holds.
+#[derive(Clone)]
+pub struct SharedChunk<X> {
+  elems: Rc<Vec<X>>,
+  start: usize,
+}
+
+impl<X> SharedChunk<X> {
+  pub fn new (xs:Vec<X>) -> Self { SharedChunk{ elems:Rc::new(xs), start:0 } }
+  /// The chunk without its first element.
+  fn tail (&self) -> Self { SharedChunk{ elems:self.elems.clone(), start:self.start + 1 } }
+}
+
+impl<X> Deref for SharedChunk<X> {
+  type Target = [X];
+  fn deref (&self) -> &[X] { &self.elems[self.start..] }
+}
+
+impl<X:Debug> Debug for SharedChunk<X> {
+  fn fmt (&self, f:&mut ::std::fmt::Formatter) -> ::std::fmt::Result { (**self).fmt(f) }
+}
+
+impl<X:PartialEq> PartialEq for SharedChunk<X> {
+  fn eq (&self, other:&Self) -> bool { **self == **other }
+}
+
+impl<X:Eq> Eq for SharedChunk<X> { }
+
+impl<X:Hash> Hash for SharedChunk<X> {
+  fn hash<H:Hasher> (&self, state:&mut H) { (**self).hash(state) }
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> ChunkedList<X> {
+  /// Prepends the chunk `xs` onto `tl`; is the identity on `tl` when
+  /// `xs` is empty.
+  pub fn chunk (xs:Vec<X>, tl:Self) -> Self {
+    if xs.is_empty() { tl } else { ChunkedList::Chunk(SharedChunk::new(xs), Box::new(tl)) }
+  }
+
+  /// The elements of `xs`, in chunks of `chunk_len` elements, where
+  /// the `i`th chunk is named by the pair of `nm` and `i`, and held in
+  /// an articulation.
+  pub fn of_vec (xs:Vec<X>, chunk_len:usize, nm:Name) -> Self {
+    assert!(chunk_len > 0);
+    let chunks : Vec<Vec<X>> = xs.chunks(chunk_len).map(|c| c.to_vec()).collect();
+    chunks.into_iter().enumerate().rev().fold(ChunkedList::Nil, |tl, (i, chunk)| {
+      let nm = name_pair(nm.clone(), name_of_usize(i));
+      Self::name_art(Some(nm), Self::chunk(chunk, tl))
+    })
+  }
+
+  /// Folds `body` over the elements of the list, from left to right,
+  /// a chunk at a time.  Memoizes the fold of the rest of the list at
+  /// each name.
+  pub fn fold<Res,F> (l:Self, res:Res, body:Rc<F>) -> Res
+    where F:Fn(X, Res) -> Res+'static,
+          Res:Hash+Debug+Eq+Clone+'static
+  {
+    chunked_list_fold(l, res, body)
+  }
+}
+
+fn chunked_list_fold<X:'static+Debug+Hash+PartialEq+Eq+Clone,
+                     Res:Hash+Debug+Eq+Clone+'static, F:'static>
+  (l:ChunkedList<X>, res:Res, body:Rc<F>) -> Res
+  where F:Fn(X, Res) -> Res
+{
+  match l {
+    ChunkedList::Nil => res,
+    ChunkedList::Chunk(xs, tl) => {
+      let res = xs.iter().fold(res, |res, x| body(x.clone(), res));
+      chunked_list_fold(*tl, res, body)
+    }
+    ChunkedList::Name(nm, tl) => {
+      let (nm, _) = name_fork(nm);
+      memo!(nm =>> chunked_list_fold::<X,Res,F>, l:*tl, res:res ;; body:body)
+    }
+    ChunkedList::Art(ref a) => chunked_list_fold(force(a), res, body),
+  }
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> ListIntro<X> for ChunkedList<X>
+{
+  fn nil  ()                 -> Self { ChunkedList::Nil }
+  /// Adds `hd` to the front of the first chunk of `tl`, when it has
+  /// fewer than `CHUNK_LEN` elements; otherwise, begins a new chunk.
+  fn cons (hd:X, tl:Self)    -> Self {
+    match tl {
+      ChunkedList::Chunk(xs, tl) => {
+        if xs.len() < CHUNK_LEN {
+          let mut ys = Vec::with_capacity(xs.len() + 1);
+          ys.push(hd);
+          ys.extend(xs.iter().cloned());
+          ChunkedList::Chunk(SharedChunk::new(ys), tl)
+        } else {
+          Self::chunk(vec![hd], ChunkedList::Chunk(xs, tl))
+        }
+      }
+      tl => Self::chunk(vec![hd], tl),
+    }
+  }
+  fn name (nm:Name, tl:Self) -> Self { ChunkedList::Name(nm, Box::new(tl)) }
+  fn art  (art:Art<Self>)    -> Self { ChunkedList::Art(art) }
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> ListElim<X> for ChunkedList<X>
+{
+  fn elim<Res,NilF,ConsF,NameF>
+    (list:&Self, nilf:NilF, consf:ConsF, namef:NameF) -> Res
+    where NilF:FnOnce(       &Self) -> Res
+    ,    ConsF:FnOnce(&X,    &Self) -> Res
+    ,    NameF:FnOnce(&Name, &Self) -> Res
+  {
+    match *list {
+      ChunkedList::Nil => nilf(&ChunkedList::Nil),
+      ChunkedList::Chunk(ref xs, ref tl) => {
+        if xs.is_empty() { return Self::elim(tl, nilf, consf, namef) }
+        let rest = if xs.len() == 1 { (**tl).clone() }
+                   else { ChunkedList::Chunk(xs.tail(), tl.clone()) };
+        consf(&xs[0], &rest)
+      }
+      ChunkedList::Name(ref nm, ref tl) => namef(nm, tl),
+      ChunkedList::Art(ref art) => {
+        let list = force(art);
+        Self::elim(&list, nilf, consf, namef)
+      }
+    }
+  }
+
+  fn elim_arg<Arg,Res,NilF,ConsF,NameF>
+    (list:Self, arg:Arg, nilf:NilF, consf:ConsF, namef:NameF) -> Res
+    where NilF:FnOnce(      Self, Arg) -> Res
+    ,    ConsF:FnOnce(X,    Self, Arg) -> Res
+    ,    NameF:FnOnce(Name, Self, Arg) -> Res
+  {
+    match list {
+      ChunkedList::Nil => nilf(ChunkedList::Nil, arg),
+      ChunkedList::Chunk(xs, tl) => {
+        if xs.is_empty() { return Self::elim_arg(*tl, arg, nilf, consf, namef) }
+        let hd = xs[0].clone();
+        let rest = if xs.len() == 1 { *tl } else { ChunkedList::Chunk(xs.tail(), tl) };
+        consf(hd, rest, arg)
+      }
+      ChunkedList::Name(nm, tl) => namef(nm, *tl, arg),
+      ChunkedList::Art(ref art) => {
+        let list = force(art);
+        Self::elim_arg(list, arg, nilf, consf, namef)
+      }
+    }
+  }
+}
+
+/// Lazy streams: each cell holds an element, and a named thunk that
+/// produces the next cell on demand, so that a stream may be infinite.
+/// `map` and `filter` name the tails of their outputs by forks of the
+/// names of the tails in their input, and so, under the DCG, reuse the
+/// cells that they have already produced; to map or filter one stream
+/// more than once, do so in distinct namespaces (see `ns`).
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub enum Stream<X> {
+  Nil,
+  /// An element, and the name of the thunk that produces the rest.
+  Cons(X, Name, Art<Stream<X>>),
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> Stream<X> {
+  /// The stream with no elements.
+  pub fn nil () -> Self { Stream::Nil }
+  /// The stream of `hd`, then the stream produced by `tl`, named `nm`.
+  pub fn cons (hd:X, nm:Name, tl:Art<Self>) -> Self { Stream::Cons(hd, nm, tl) }
+
+  /// The stream of elements that `step` produces from `seed`, until
+  /// it returns `None`.  Produces the first element now, and each
+  /// other element in a thunk, where the `i`th thunk is named by the
+  /// pair of `nm` and `i`.
+  pub fn unfold<S:'static+Debug+Hash+Eq+Clone, F:'static>
+    (nm:Name, seed:S, step:Rc<F>) -> Self
+    where F:Fn(S) -> Option<(X, S)>
+  {
+    stream_unfold(nm, 0, seed, step)
+  }
+
+  /// The first `n` elements of the stream, as a list.  Forces only the
+  /// thunks for these elements.
+  pub fn take (s:Self, n:usize) -> List<X> {
+    let mut s = s;
+    let mut xs = vec![];
+    while xs.len() < n {
+      match s {
+        Stream::Nil => break,
+        Stream::Cons(x, _, tl) => {
+          xs.push(x);
+          if xs.len() < n { s = force(&tl) } else { break }
+        }
+      }
+    }
+    xs.into_iter().rev().fold(List::Nil, |l, x| List::cons(x, l))
+  }
+
+  /// Lazily maps `body` over the stream.
+  pub fn map<Y:'static+Debug+Hash+PartialEq+Eq+Clone, F:'static>
+    (s:Self, body:Rc<F>) -> Stream<Y>
+    where F:Fn(X) -> Y
+  {
+    stream_map(s, body)
+  }
+
+  /// Lazily filters the stream; to produce each cell of the output,
+  /// forces the input until the next element that satisfies `body`.
+  pub fn filter<F:'static> (s:Self, body:Rc<F>) -> Self
+    where F:Fn(&X) -> bool
+  {
+    stream_filter(s, body)
+  }
+}
+
+fn stream_unfold<X:'static+Debug+Hash+PartialEq+Eq+Clone,
+                 S:'static+Debug+Hash+Eq+Clone, F:'static>
+  (nm:Name, i:usize, seed:S, step:Rc<F>) -> Stream<X>
+  where F:Fn(S) -> Option<(X, S)>
+{
+  match step(seed) {
+    None => Stream::Nil,
+    Some((x, seed)) => {
+      let tl_nm = name_pair(nm.clone(), name_of_usize(i));
+      let tl = thunk!(tl_nm.clone() =>> stream_unfold =>> <X, S, F>,
+                      nm:nm, i:i + 1, seed:seed ;; step:step);
+      Stream::Cons(x, tl_nm, tl)
+    }
+  }
+}
+
+fn stream_map<X:'static+Debug+Hash+PartialEq+Eq+Clone,
+              Y:'static+Debug+Hash+PartialEq+Eq+Clone, F:'static>
+  (s:Stream<X>, body:Rc<F>) -> Stream<Y>
+  where F:Fn(X) -> Y
+{
+  match s {
+    Stream::Nil => Stream::Nil,
+    Stream::Cons(x, nm, tl) => {
+      let (nm, _) = name_fork(nm);
+      let y = body(x);
+      let tl = thunk!(nm.clone() =>> stream_map_art =>> <X, Y, F>, s:tl ;; body:body);
+      Stream::Cons(y, nm, tl)
+    }
+  }
+}
+
+fn stream_map_art<X:'static+Debug+Hash+PartialEq+Eq+Clone,
+                  Y:'static+Debug+Hash+PartialEq+Eq+Clone, F:'static>
+  (s:Art<Stream<X>>, body:Rc<F>) -> Stream<Y>
+  where F:Fn(X) -> Y
+{
+  stream_map(force(&s), body)
+}
+
+fn stream_filter<X:'static+Debug+Hash+PartialEq+Eq+Clone, F:'static>
+  (s:Stream<X>, body:Rc<F>) -> Stream<X>
+  where F:Fn(&X) -> bool
+{
+  let mut s = s;
+  loop {
+    match s {
+      Stream::Nil => return Stream::Nil,
+      Stream::Cons(x, nm, tl) => {
+        if body(&x) {
+          let (_, nm) = name_fork(nm);
+          let tl = thunk!(nm.clone() =>> stream_filter_art =>> <X, F>, s:tl ;; body:body);
+          return Stream::Cons(x, nm, tl)
+        }
+        s = force(&tl)
+      }
+    }
+  }
+}
+
+fn stream_filter_art<X:'static+Debug+Hash+PartialEq+Eq+Clone, F:'static>
+  (s:Art<Stream<X>>, body:Rc<F>) -> Stream<X>
+  where F:Fn(&X) -> bool
+{
+  stream_filter(force(&s), body)
+}
+
+/// Two-dimensional grids, for image- or spreadsheet-like data: each
+/// row consists of chunks of (up to) `chunk_len` cells, and each chunk
+/// is named and held in its own articulation.  `set` replaces one
+/// chunk, and shares the others; the reductions over rows and regions
+/// memoize the reduction of each chunk that they cover entirely, at
+/// (a fork of) the name of the chunk.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub struct Grid<X> {
+  width: usize,
+  chunk_len: usize,
+  rows: Vec<Rc<Vec<(Name, Art<Vec<X>>)>>>,
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> Grid<X> {
+  /// The grid of `rows`, which must have equal lengths.  The `j`th
+  /// chunk of the `y`th row is named by the pair of `nm` and the pair
+  /// of `y` and `j`.
+  pub fn of_rows (rows:Vec<Vec<X>>, chunk_len:usize, nm:Name) -> Self {
+    assert!(chunk_len > 0);
+    let width = rows.first().map(|r| r.len()).unwrap_or(0);
+    let rows = rows.into_iter().enumerate().map(|(y, row)| {
+      assert_eq!(row.len(), width);
+      let chunks = row.chunks(chunk_len).enumerate().map(|(j, chunk)| {
+        let nm = name_pair(nm.clone(), name_pair(name_of_usize(y), name_of_usize(j)));
+        (nm.clone(), cell(nm, chunk.to_vec()))
+      }).collect();
+      Rc::new(chunks)
+    }).collect();
+    Grid { width: width, chunk_len: chunk_len, rows: rows }
+  }
+
+  /// The number of columns.
+  pub fn width  (g:&Self) -> usize { g.width }
+  /// The number of rows.
+  pub fn height (g:&Self) -> usize { g.rows.len() }
+
+  /// The cell at column `x` of row `y`, if any.
+  pub fn get (g:&Self, x:usize, y:usize) -> Option<X> {
+    if x >= g.width || y >= g.rows.len() { return None }
+    let (_, ref chunk) = g.rows[y][x / g.chunk_len];
+    Some(force(chunk)[x % g.chunk_len].clone())
+  }
+
+  /// Sets the cell at column `x` of row `y` to `v`; names the new
+  /// chunk that holds it `nm`.  To change the grid in place under the
+  /// DCG, give the name of the chunk from `of_rows`.
+  pub fn set (nm:Name, g:Self, x:usize, y:usize, v:X) -> Self {
+    assert!(x < g.width && y < g.rows.len());
+    let mut g = g;
+    let j = x / g.chunk_len;
+    let mut chunk = force(&g.rows[y][j].1);
+    chunk[x % g.chunk_len] = v;
+    let mut row = (*g.rows[y]).clone();
+    row[j] = (nm.clone(), cell(nm, chunk));
+    g.rows[y] = Rc::new(row);
+    g
+  }
+
+  /// Reduces row `y` with the monoid of `id_elm` and `bin_op`.
+  pub fn row_monoid (g:&Self, y:usize, id_elm:X, bin_op:Rc<Fn(X,X) -> X>) -> X {
+    Self::region_monoid(g, (0, y), (g.width, y + 1), id_elm, bin_op)
+  }
+
+  /// Reduces the cells with columns from `lo.0` up to (and excluding)
+  /// `hi.0`, and rows from `lo.1` up to `hi.1`, with the monoid of
+  /// `id_elm` and `bin_op`, row by row.  As with `monoid_of_list`, the
+  /// reductions of the chunks are memoized by name, not by `bin_op`:
+  /// reductions with different operations should occur in different
+  /// namespaces.
+  pub fn region_monoid (g:&Self, lo:(usize, usize), hi:(usize, usize),
+                        id_elm:X, bin_op:Rc<Fn(X,X) -> X>) -> X {
+    let (x1, y1) = (min(hi.0, g.width), min(hi.1, g.rows.len()));
+    let mut res = id_elm.clone();
+    for row in g.rows.iter().take(y1).skip(lo.1) {
+      for (j, &(ref nm, ref chunk)) in row.iter().enumerate() {
+        let (c0, c1) = (j * g.chunk_len, min((j + 1) * g.chunk_len, g.width));
+        if c1 <= lo.0 || c0 >= x1 { continue }
+        let r = if lo.0 <= c0 && c1 <= x1 {
+          let (nm, _) = name_fork(nm.clone());
+          memo!(nm =>> grid_chunk_monoid::<X>, chunk:chunk.clone(), id_elm:id_elm.clone() ;;
+                bin_op:bin_op.clone())
+        } else {
+          let xs = force(chunk);
+          let (a, b) = (max(lo.0, c0) - c0, min(x1, c1) - c0);
+          xs[a..b].iter().fold(id_elm.clone(), |r, x| bin_op(r, x.clone()))
+        };
+        res = bin_op(res, r)
+      }
+    }
+    res
+  }
+}
+
+fn grid_chunk_monoid<X:'static+Debug+Hash+PartialEq+Eq+Clone>
+  (chunk:Art<Vec<X>>, id_elm:X, bin_op:Rc<Fn(X,X) -> X>) -> X
+{
+  force(&chunk).into_iter().fold(id_elm, |r, x| bin_op(r, x))
+}
+
+/// Quantile sketches: counts of values in `2^depth` buckets of `width`
+/// values each (the last bucket also counts the values beyond them),
+/// held in a binary tree over the bits of the bucket numbers, with a
+/// total count at each node.  `insert` articulates each node that it
+/// rebuilds, with names from the name of the insertion (as with the
+/// names and articulations of the inputs of tries), and shares the
+/// other nodes.  Sketches of the same `width` and `depth` `merge`, so
+/// `of_list` reduces a list of values to its sketch incrementally, by
+/// merging the sketches of the subtrees of the balanced tree of the
+/// list (see `monoid_of_list`).
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub struct QuantileSketch {
+  width: usize,
+  depth: usize,
+  counts: SketchCounts,
+}
+
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+enum SketchCounts {
+  Nil,
+  Bucket(usize),
+  Bin(usize, Box<SketchCounts>, Box<SketchCounts>),
+  /// An articulated node, and its total count.
+  Art(usize, Art<SketchCounts>),
+}
+
+impl SketchCounts {
+  fn count (&self) -> usize {
+    match *self {
+      SketchCounts::Nil => 0,
+      SketchCounts::Bucket(n) | SketchCounts::Bin(n, _, _) | SketchCounts::Art(n, _) => n,
+    }
+  }
+  fn expose (self) -> Self {
+    match self { SketchCounts::Art(_, a) => force(&a), c => c }
+  }
+}
+
+impl QuantileSketch {
+  /// The sketch with no values.
+  pub fn empty (width:usize, depth:usize) -> Self {
+    assert!(width > 0 && depth < 64);
+    QuantileSketch { width: width, depth: depth, counts: SketchCounts::Nil }
+  }
+
+  /// The number of values in the sketch.
+  pub fn count (s:&Self) -> usize { s.counts.count() }
+
+  fn bucket (s:&Self, x:usize) -> usize {
+    min(x / s.width, (1 << s.depth) - 1)
+  }
+
+  /// Adds `x` to the sketch.  The node at depth `i` of the path to the
+  /// bucket of `x` is articulated with the pair of `nm` and `i`; so,
+  /// under the DCG, re-inserting at the same name replaces the path
+  /// of the earlier insertion.
+  pub fn insert (nm:Name, s:Self, x:usize) -> Self {
+    let b = Self::bucket(&s, x);
+    let counts = sketch_insert(&nm, 0, s.depth, b, s.counts.clone());
+    QuantileSketch { counts: counts, .. s }
+  }
+
+  /// The sketch of the values of both sketches, which must have the
+  /// same `width` and `depth`.
+  pub fn merge (a:Self, b:Self) -> Self {
+    assert_eq!((a.width, a.depth), (b.width, b.depth));
+    let counts = sketch_merge(a.counts.clone(), b.counts);
+    QuantileSketch { counts: counts, .. a }
+  }
+
+  /// The sketch of the values of the list; see `monoid_of_list`.
+  pub fn of_list<L:ListElim<usize>+ListIntro<usize>+'static>
+    (width:usize, depth:usize, l:L) -> Self
+  {
+    let empty = Self::empty(width, depth);
+    ns(name_of_str("quantile_sketch"), || {
+      let e = empty.clone();
+      let sketches : List<Self> = list_map_eager(l, Rc::new(move |x| {
+        let counts = sketch_path(e.depth, Self::bucket(&e, x));
+        QuantileSketch { counts: counts, .. e.clone() }
+      }));
+      monoid_of_list(sketches, empty, Rc::new(Self::merge))
+    })
+  }
+
+  /// The least value of the bucket that holds the value of rank
+  /// `ceil(q * count)` (counting from one), for `q` in `[0, 1]`; or,
+  /// `None` if the sketch is empty.  Exact up to the `width` of the
+  /// buckets.
+  pub fn quantile (s:&Self, q:f64) -> Option<usize> {
+    let count = Self::count(s);
+    if count == 0 { return None }
+    let mut rank = max(1, min(count, (q * count as f64).ceil() as usize));
+    let mut b = 0;
+    let mut counts = s.counts.clone().expose();
+    for _ in 0..s.depth {
+      counts = match counts {
+        SketchCounts::Bin(_, l, r) => {
+          b = b << 1;
+          if rank <= l.count() { l.expose() }
+          else { rank -= l.count(); b |= 1; r.expose() }
+        }
+        _ => unreachable!(),
+      }
+    }
+    Some(b * s.width)
+  }
+
+  /// The median of the sketch; see `quantile`.
+  pub fn median (s:&Self) -> Option<usize> { Self::quantile(s, 0.5) }
+}
+
+/// The counts of one value, in bucket `b`, without articulations.
+fn sketch_path (depth:usize, b:usize) -> SketchCounts {
+  (0..depth).fold(SketchCounts::Bucket(1), |c, i| {
+    if b >> i & 1 == 0 { SketchCounts::Bin(1, Box::new(c), Box::new(SketchCounts::Nil)) }
+    else { SketchCounts::Bin(1, Box::new(SketchCounts::Nil), Box::new(c)) }
+  })
+}
+
+fn sketch_insert (nm:&Name, i:usize, depth:usize, b:usize, c:SketchCounts) -> SketchCounts {
+  let c = match c.expose() {
+    SketchCounts::Nil if i == depth => SketchCounts::Bucket(1),
+    SketchCounts::Bucket(n) => SketchCounts::Bucket(n + 1),
+    SketchCounts::Nil => {
+      let c = SketchCounts::Bin(0, Box::new(SketchCounts::Nil), Box::new(SketchCounts::Nil));
+      return sketch_insert(nm, i, depth, b, c)
+    }
+    SketchCounts::Bin(n, l, r) => {
+      if b >> (depth - 1 - i) & 1 == 0 {
+        SketchCounts::Bin(n + 1, Box::new(sketch_insert(nm, i + 1, depth, b, *l)), r)
+      } else {
+        SketchCounts::Bin(n + 1, l, Box::new(sketch_insert(nm, i + 1, depth, b, *r)))
+      }
+    }
+    SketchCounts::Art(_, _) => unreachable!(),
+  };
+  SketchCounts::Art(c.count(), cell(name_pair(nm.clone(), name_of_usize(i)), c))
+}
+
+fn sketch_merge (a:SketchCounts, b:SketchCounts) -> SketchCounts {
+  match (a, b) {
+    (SketchCounts::Nil, c) | (c, SketchCounts::Nil) => c,
+    (a, b) => match (a.expose(), b.expose()) {
+      (SketchCounts::Bucket(m), SketchCounts::Bucket(n)) => SketchCounts::Bucket(m + n),
+      (SketchCounts::Bin(m, l1, r1), SketchCounts::Bin(n, l2, r2)) =>
+        SketchCounts::Bin(m + n,
+                          Box::new(sketch_merge(*l1, *l2)),
+                          Box::new(sketch_merge(*r1, *r2))),
+      _ => unreachable!(),
+    }
+  }
+}
+
+/// Count-min sketches: approximate counts of hashable values, in
+/// `depth` rows of `width` counters, where each row hashes a value to
+/// one of its counters.  The estimated count of a value is the least
+/// of its counters, which never undercounts it.  Each row consists of
+/// chunks of (up to) `chunk_len` counters, each named and held in its
+/// own articulation; `add` replaces one chunk in each row, and shares
+/// the others, so a computation that `estimate`s counts depends only
+/// on the chunks that hold them.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub struct CountMinSketch {
+  width: usize,
+  chunk_len: usize,
+  rows: Vec<Rc<Vec<Art<Vec<usize>>>>>,
+}
+
+impl CountMinSketch {
+  /// The sketch with all counters zero.  The `j`th chunk of the `i`th
+  /// row is named by the pair of `nm` and the pair of `i` and `j`.
+  pub fn empty (nm:Name, depth:usize, width:usize, chunk_len:usize) -> Self {
+    assert!(depth > 0 && width > 0 && chunk_len > 0);
+    let rows = (0..depth).map(|i| {
+      let chunks = (0..(width + chunk_len - 1) / chunk_len).map(|j| {
+        let nm = name_pair(nm.clone(), name_pair(name_of_usize(i), name_of_usize(j)));
+        cell(nm, vec![0; min(chunk_len, width - j * chunk_len)])
+      }).collect();
+      Rc::new(chunks)
+    }).collect();
+    CountMinSketch { width: width, chunk_len: chunk_len, rows: rows }
+  }
+
+  /// The counter of `x` in row `i`.
+  fn index<X:Hash> (s:&Self, i:usize, x:&X) -> usize {
+    (my_hash(&(i, x)) % (s.width as u64)) as usize
+  }
+
+  /// Adds `count` occurrences of `x`.  The new chunk of the `i`th row
+  /// is named by the pair of `nm` and `i`, so each add should have a
+  /// name of its own (as each insertion into a trie does).
+  pub fn add<X:Hash> (nm:Name, s:Self, x:&X, count:usize) -> Self {
+    let mut s = s;
+    for i in 0..s.rows.len() {
+      let k = Self::index(&s, i, x);
+      let j = k / s.chunk_len;
+      let mut chunk = force(&s.rows[i][j]);
+      chunk[k % s.chunk_len] += count;
+      let mut row = (*s.rows[i]).clone();
+      row[j] = cell(name_pair(nm.clone(), name_of_usize(i)), chunk);
+      s.rows[i] = Rc::new(row);
+    }
+    s
+  }
+
+  /// The estimated number of occurrences of `x`: at least as many as
+  /// were added, and (with high probability, for a wide and deep
+  /// enough sketch) not many more.
+  pub fn estimate<X:Hash> (s:&Self, x:&X) -> usize {
+    (0..s.rows.len()).map(|i| {
+      let k = Self::index(s, i, x);
+      force(&s.rows[i][k / s.chunk_len])[k % s.chunk_len]
+    }).min().unwrap_or(0)
+  }
+}
+
+/// Bloom filters: approximate sets of hashable values, as `bits` bits,
+/// where each value sets `hashes` of them.  `maybe_contains` has no
+/// false negatives, and few false positives while few of the bits are
+/// set, so it can pre-filter lookups that are expensive to memoize.
+/// The bits consist of chunks of (up to) `chunk_len` words, each named
+/// and held in its own articulation; `add` replaces only the chunks
+/// whose bits it sets.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub struct BloomSet<X> {
+  bits: usize,
+  hashes: usize,
+  chunk_len: usize,
+  chunks: Rc<Vec<Art<Vec<u64>>>>,
+  elms: PhantomData<X>,
+}
+
+impl<X:Hash> BloomSet<X> {
+  /// The filter with no bits set.  The `j`th chunk is named by the
+  /// pair of `nm` and `j`.
+  pub fn empty (nm:Name, bits:usize, hashes:usize, chunk_len:usize) -> Self {
+    Self::of_words(nm, bits, hashes, chunk_len, vec![0; (bits + 63) / 64])
+  }
+
+  fn of_words (nm:Name, bits:usize, hashes:usize, chunk_len:usize, words:Vec<u64>) -> Self {
+    assert!(bits > 0 && hashes > 0 && chunk_len > 0);
+    let chunks = words.chunks(chunk_len).enumerate().map(|(j, chunk)| {
+      cell(name_pair(nm.clone(), name_of_usize(j)), chunk.to_vec())
+    }).collect();
+    BloomSet { bits: bits, hashes: hashes, chunk_len: chunk_len,
+               chunks: Rc::new(chunks), elms: PhantomData }
+  }
+
+  /// The filter of the elements of `set`, folded as a `SetElim`.  The
+  /// chunks are named as those of `empty`.
+  pub fn of_set<S:SetElim<X>> (nm:Name, set:S, bits:usize, hashes:usize, chunk_len:usize) -> Self {
+    let words = ns(name_of_str("bloom_of_set"), || {
+      S::fold(set, vec![0u64; (bits + 63) / 64], move |x, mut words: Vec<u64>| {
+        for i in 0..hashes {
+          let b = bloom_bit(bits, i, &x);
+          words[b / 64] |= 1 << (b % 64);
+        }
+        words
+      })
+    });
+    Self::of_words(nm, bits, hashes, chunk_len, words)
+  }
+
+  /// Adds `x`.  The new version of the `j`th chunk is named by the
+  /// pair of `nm` and `j`, so each add should have a name of its own.
+  pub fn add (nm:Name, s:Self, x:&X) -> Self {
+    let mut s = s;
+    let mut chunks = (*s.chunks).clone();
+    for i in 0..s.hashes {
+      let b = bloom_bit(s.bits, i, x);
+      let (j, k) = ((b / 64) / s.chunk_len, (b / 64) % s.chunk_len);
+      let mut chunk = force(&chunks[j]);
+      if chunk[k] & (1 << (b % 64)) == 0 {
+        chunk[k] |= 1 << (b % 64);
+        chunks[j] = cell(name_pair(nm.clone(), name_of_usize(j)), chunk);
+      }
+    }
+    s.chunks = Rc::new(chunks);
+    s
+  }
+
+  /// Whether `x` may have been added: `false` only if it has not.
+  pub fn maybe_contains (s:&Self, x:&X) -> bool {
+    (0..s.hashes).all(|i| {
+      let b = bloom_bit(s.bits, i, x);
+      force(&s.chunks[(b / 64) / s.chunk_len])[(b / 64) % s.chunk_len] & (1 << (b % 64)) != 0
+    })
+  }
+}
+
+/// The `i`th bit of `x`, of `bits` bits.
+fn bloom_bit<X:Hash> (bits:usize, i:usize, x:&X) -> usize {
+  (my_hash(&(i, x)) % (bits as u64)) as usize
+}
+
+/// Indexed lists: sequences with positional access (`nth` and
+/// `update_at`) in time logarithmic in their length (in expectation).
+/// As in a skip list, each element has a tower whose height is
+/// determined by its name (see `Level`); the list is split at its
+/// tallest tower (after its first element), and each half is split
+/// likewise, so the shape of the list depends only on its names, not
+/// on the history of its edits.  Each binary node is articulated in a
+/// cell named by the name of its splitting element.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub enum IndexedList<X> {
+  Nil,
+  Leaf(Name, X),
+  /// The name of the first element of the right subtree, the number
+  /// of elements, and the subtrees.
+  Bin(Name, usize, Box<IndexedList<X>>, Box<IndexedList<X>>),
+  /// An articulated subtree, and its number of elements.
+  Art(usize, Art<IndexedList<X>>),
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> IndexedList<X> {
+  /// The list of `xs`, where the `i`th element is named by the pair of
+  /// `nm` and `i`.
+  pub fn of_vec (xs:Vec<X>, nm:Name) -> Self {
+    let elms : Vec<(usize, Name, X)> = xs.into_iter().enumerate().map(|(i, x)| {
+      let nm = name_pair(nm.clone(), name_of_usize(i));
+      (<usize as Level>::new(&nm), nm, x)
+    }).collect();
+    indexed_list_of_elms(&elms)
+  }
+
+  /// The number of elements of the list.
+  pub fn len (l:&Self) -> usize {
+    match *l {
+      IndexedList::Nil => 0,
+      IndexedList::Leaf(_, _) => 1,
+      IndexedList::Bin(_, n, _, _) | IndexedList::Art(n, _) => n,
+    }
+  }
+
+  /// The `i`th element of the list, counting from zero, if any.
+  pub fn nth (l:&Self, i:usize) -> Option<X> {
+    match *l {
+      IndexedList::Nil => None,
+      IndexedList::Leaf(_, ref x) => if i == 0 { Some(x.clone()) } else { None },
+      IndexedList::Bin(_, _, ref l, ref r) => {
+        let n = Self::len(l);
+        if i < n { Self::nth(l, i) } else { Self::nth(r, i - n) }
+      }
+      IndexedList::Art(_, ref a) => Self::nth(&force(a), i),
+    }
+  }
+
+  /// Replaces the `i`th element of the list with `x`; keeps its name,
+  /// and re-sets the cells of the nodes above it (under the DCG, this
+  /// is an edit of the list, in place).  Panics if `i` is out of
+  /// bounds.
+  pub fn update_at (l:Self, i:usize, x:X) -> Self {
+    assert!(i < Self::len(&l));
+    match l {
+      IndexedList::Nil => unreachable!(),
+      IndexedList::Leaf(nm, _) => IndexedList::Leaf(nm, x),
+      IndexedList::Bin(nm, n, l, r) => {
+        let ln = Self::len(&l);
+        if i < ln { IndexedList::Bin(nm, n, Box::new(Self::update_at(*l, i, x)), r) }
+        else { IndexedList::Bin(nm, n, l, Box::new(Self::update_at(*r, i - ln, x))) }
+      }
+      IndexedList::Art(n, a) => {
+        let t = Self::update_at(force(&a), i, x);
+        let nm = match t { IndexedList::Bin(ref nm, _, _, _) => nm.clone(), _ => unreachable!() };
+        IndexedList::Art(n, cell(nm, t))
+      }
+    }
+  }
+}
+
+fn indexed_list_of_elms<X:'static+Debug+Hash+PartialEq+Eq+Clone>
+  (elms:&[(usize, Name, X)]) -> IndexedList<X>
+{
+  match elms.len() {
+    0 => IndexedList::Nil,
+    1 => IndexedList::Leaf(elms[0].1.clone(), elms[0].2.clone()),
+    n => {
+      let p = (1..n).fold(1, |p, k| if elms[k].0 > elms[p].0 { k } else { p });
+      let nm = elms[p].1.clone();
+      let l = indexed_list_of_elms(&elms[..p]);
+      let r = indexed_list_of_elms(&elms[p..]);
+      let bin = IndexedList::Bin(nm.clone(), n, Box::new(l), Box::new(r));
+      IndexedList::Art(n, cell(nm, bin))
+    }
+  }
+}
+
+/// Random Access Zipper (RAZ).
+/// Purely functional sequences with global access (via a balanced
+/// tree structure) and simple local edits (via a zipper structure).
+///
+/// See also: [RAZ in OCaml](http://github.com/cuplv/raz.ocaml.git)
+pub mod raz {
+  use std::rc::Rc;
+  use std::fmt::Debug;
+  use std::hash::Hash;
+
+  use macros::* ;
+  use adapton::engine::* ; 
+
+  /// Punctuation: Information that is interposed between each run of
+  /// elements, and sometimes before and after them.  The RAZ uses
+  /// user-chosen levels to probabilistically balance the Tree form.
+  /// The RAZ uses the Name to identify cached computations in
+  /// Adapton.
+  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
+  pub struct Punc{ pub level:usize, pub name:Name }
+  
+  /// A binary tree with vector leaves (aka, a "rope"), balanced
+  /// probabilistically.
+  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
+  pub enum Tree<X> { 
+    /// Invariant: Nil only appears in certain edge positions
+    Nil, 
+    /// Invariant: Each element run in a Leaf is interposed between two `Punc`s
+    Leaf(Vec<X>),
+    /// (Non-trivial) Inductive case: Two balanced sub-trees, interposed with punctuation.
+    /// Invariant: For balance, every Bin node in left/right subtrees has a equal-or-lower level.
+    Bin(Box<Tree<X>>,Punc,Box<Tree<X>>),
+    /// (Trivial) Inductive case: An Adapton articulation.
+    Art(Art<Tree<X>>),
+  }
+  
+  /// Runs of contiguous element sequences, interposed with
+  /// punctuation information of type `Punc`.
+  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
+  enum Elms<X> {
+    /// Base case: No more Cons cells, all remaining elements are formed into a sequence of Trees.
+    Trees(Vec<Tree<X>>), 
+    /// (Non-trivial) Inductive case: Run of elements, followed by more elements.
+    Cons(Vec<X>,Option<ConsTl<X>>),
+    /// (Trivial) Inductive case: an Adapton articulation.
+    Art(Art<Elms<X>>),
+  }
+  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
+  struct ConsTl<X> {
+    /// Invariant: Punctuation separates each run of elements.
+    punc:Punc,
+    /// Inductive structure: (possibly empty) sequence of more element runs.
+    elms:Box<Elms<X>>,
+  }
+
+  /// The Zipper form consists of element sequences before (to the
+  /// left of) and after (to the right of) a distinguished punctuation
+  /// point in the sequence.
+  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
+  pub struct Zip<X> {
+    left:  Elms<X>,
+    curs:  Punc,
+    right: Elms<X>
+  }
+
+  /// Distinguishes left (`L`) and right (`R`) traversal orders for various operations involving trees.
+  #[derive(PartialEq,Eq,Hash,Debug,Clone,Copy)]
+  pub enum Dir { L, R }
+  
+  /// An O(1)-sized change to the zipper: a single element insertion,
+  /// replacement, removal; or, a local cursor movement by one element
+  /// in either direction.
+  #[derive(PartialEq,Eq,Hash,Debug,Clone)]
+  pub enum Edit<X> {
+    Insert(Dir,X,Option<Punc>),
+    Replace(Dir,X),
+    Remove(Dir),
+    Move(Dir),
+  }
+
+  /// A zipper that consists of zero elements (and exactly one punctuation `p`).
+  pub fn zip_empty<X>(p:Punc) -> Zip<X> {
+    return Zip{left:Elms::Cons(vec![], None),
+               curs:p,
+               right:Elms::Cons(vec![], None)}
+  }
+
+  /// A tree that consists of zero elements (and exactly one punctuation `p`).
+  pub fn tree_empty<X>(p:Punc) -> Tree<X> {
+    return Tree::Bin(Box::new(Tree::Nil), p, Box::new(Tree::Nil))
+  }
+
+  /// Transform the zipper, inserting element `x` in the given
+  /// direction `d`. The optional punctuation `p` follows the inserted
+  /// element, in the given direction.
+  pub fn zip_insert<X:'static+Eq+Clone+Hash+Debug>(z:Zip<X>, d:Dir, x:X, p:Option<Punc>) -> Zip<X> {
+    match &d {
+      &Dir::L => {
+        match p {
+          Some(p) => { // Punctuate the insertion with the given p; creates new run of elements, consisting only of element x
+            let a = cell(p.name.clone(), z.left);
+            Zip{left:Elms::Cons(vec![x], Some(ConsTl{punc:p, elms:Box::new(Elms::Art(a))})), ..z}
+          }
+          None => {
+            match z.left { // Mutate the current run of elements, by pushing element x onto it
+              Elms::Cons(mut xs,tl) => { xs.push(x); Zip{left:Elms::Cons(xs, tl), ..z} },
+              _ => panic!("bad arguments: need punctuation")
+            }
+          }
+        }
+      }
+      &Dir::R => {
+        // TODO-Now
+        panic!("symmetric to above")
+      }
+    }
+  }
+    
+  /// Perform the given edit, in the given direction, at the current focus of the given zipper.
+  /// The zipper is taken because its head vectors may be mutated, e.g., to insert or remove elements.
+  pub fn zip_edit<X:'static+Eq+Clone+Hash+Debug>(z:Zip<X>, edit:Edit<X>) -> Zip<X> {
+    match edit {
+      Edit::Insert(d,x,p) => { zip_insert(z, d, x, p) },
+      _ => unimplemented!() // TODO-Later
+    }
+  }
+
+  fn cons_trampoline<X:Eq+Clone+Hash+Debug+'static>
+    (dir:Dir, parent_lev:usize, tr_lev:usize, tr:Tree<X>, es:Elms<X>) 
+     -> (Tree<X>, Elms<X>)
+  {
+    match es {
+      Elms::Trees(trs) => { (tr, Elms::Trees(trs)) }
+      Elms::Cons(v, None) => { if v.len() > 0 { panic!("bad") } else { (tr, Elms::Cons(v, None)) }},
+      Elms::Art(a) => cons_trampoline(dir, parent_lev, tr_lev, tr, force(&a)),
+      Elms::Cons(v, Some(ConsTl{punc:p, elms:es})) => {
+        if tr_lev <= p.level && p.level <= parent_lev {
+          let (n1, n2) = name_fork(p.name.clone());
+          let (_, (rtr, es)) = eager!(n1 =>> cons_trampoline, dir:dir, parent_lev:p.level, tr_lev:0, tr:Tree::Leaf(vec![]), es:*es);
+          let tr = match &dir {
+            & Dir::L => Tree::Bin(Box::new(tr),  p.clone(), Box::new(rtr)),
+            & Dir::R => Tree::Bin(Box::new(rtr), p.clone(), Box::new(tr)),
+          };
+          let tr = Tree::Art(cell(p.name.clone(), tr));
+          let (_, (tr, es)) = eager!(n2 =>> cons_trampoline, dir:dir, parent_lev:parent_lev, tr_lev:p.level, tr:tr, es:es);
+          (tr, es)
+        }
+        else {
+          (tr, Elms::Cons(v, Some(ConsTl{punc:p, elms:es})))
+        }
+      }
+    }
+  } 
+
+  /// Compute a vector of trees, building any leading Cons cells into Tree structure
+  fn trees_of_elms<X:Eq+Clone+Hash+Debug+'static>(dir:Dir, es:Elms<X>) -> Vec<Tree<X>> {
+    match cons_trampoline(dir, usize::max_value(), 0, Tree::Nil, es) {
+      (tr, Elms::Trees(mut trs)) => { trs.push(tr); return trs }
+      _ => unreachable!(),
+    }
+  }
+
+  /// Appends the sequences of two trees
+  pub fn tree_append<X>(tr1:Tree<X>, tr2:Tree<X>) -> Tree<X> {
+    panic!("TODO")    
+  }
+
+  /// Unfocuses the zipper into a tree form
+  pub fn tree_of_zip<X:Eq+Clone+Hash+Debug+'static>(z:Zip<X>) -> Tree<X> {
+    let mut tree = tree_empty(z.curs);
+    let ltrees = trees_of_elms(Dir::L, z.left);
+    for t in ltrees.iter() { // TODO: Take iterator; Reverse iteration here
+      tree = tree_append(t.clone(), tree); // TODO Avoid clone
+    }
+    let rtrees = trees_of_elms(Dir::R, z.right);
+    for t in rtrees.iter() {
+      tree = tree_append(tree, t.clone()); // TODO Avoid clone
+    }
+    return tree
+  }
+
+  fn merge_elms<X:Ord+Hash+Eq+Debug+Clone+'static>
+  (es1:Elms<X>, es2:Elms<X>) -> Elms<X> {
+    let (mut v1, tl1, 
+         mut v2, tl2) = match (es1, es2) {
+      (Elms::Cons(v1, tl1), 
+       Elms::Cons(v2, tl2)) => (v1.clone(), tl1, v2.clone(), tl2), // TODO Avoid these clones.
+      (_, _)                => panic!("illegal argument(s)")
+    };
+    let mut merged = Vec::new();
+    loop {
+      match (v1.pop(), v2.pop()) {
+        (None, None)     => { return Elms::Cons(Vec::new(), None) },
+        (None, Some(e2)) => { // First vector "wins" race to empty.
+          v2.push(e2); let es2 = Elms::Cons(v2, tl2);
+          match tl1 { 
+            None => return es2,
             Some(tl1) => { // since v1 is out of elements, use its punctuation (aka, tl1.punc)
               let rest = thunk!(tl1.punc.name.clone() =>> merge_elms, es1:*(tl1.elms), es2:es2);
               return Elms::Cons(merged, Some(ConsTl{punc:tl1.punc, elms:Box::new(Elms::Art(rest))}))
@@ -2532,272 +4895,248 @@ pub mod raz {
 // }
 
 
-// /// Lists with a focus; suitable to implement `ListEdit`.
-// #[derive(Debug,Hash,PartialEq,Eq,Clone)]
-// pub struct ListZipper<X,T:TreeT<X>,L:TreeListT<X,T>> {
-//     /// Elements to the left of the focus, nearest to furthest.
-//     pub left: L::List,
-//     /// Elements to the right of the focus, nearest to furthest.
-//     pub right: L::List,
+/// Lists with a focus, for making local edits.  The focus sits
+/// between the elements to its left and those to its right, each held
+/// nearest first.  A name placed in the zipper articulates the rest of
+/// its side in a cell of the same name, and an edit re-sets only the
+/// cells between the focus and the edit, so that re-running a fold over
+/// `unfocus` after a local edit reuses the work done under the
+/// untouched names.
+#[derive(Debug,Hash,PartialEq,Eq,Clone)]
+pub struct ListZipper<X> {
+  /// Elements to the left of the focus, nearest to furthest.
+  pub left: List<X>,
+  /// Elements to the right of the focus, nearest to furthest.
+  pub right: List<X>,
+}
 
-//     /// Todo-Later: This phantom field should *not* be needed, but is, due to rustc being confused (?).
-//     /// It complains that the parameter T is not used, though it is (by the type parameter L's trait bound).
-//     phantom:PhantomData<T::Tree>,
-// }
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> ListZipper<X> {
+  /// A zipper with no elements.
+  pub fn empty () -> Self {
+    ListZipper{ left:List::nil(), right:List::nil() }
+  }
 
-// macro_rules! zipper {
-//     { $left:expr , $right:expr } => {
-//         {
-//             ListZipper{ left:$left, right:$right, phantom:PhantomData}
-//         }
-//     };
-// }
+  /// A zipper over `list`, focused before its first element.
+  pub fn of_list (list:List<X>) -> Self {
+    ListZipper{ left:List::nil(), right:list }
+  }
 
-// /// Implement `ListEdit` for `ListZipper` generically with respect to
-// /// adapton implementation `A`, list element type `X`, and list
-// /// implementation `L`.
-// impl<A:Adapton
-//     ,X:Debug+Hash+PartialEq+Eq+Clone
-//     ,T:TreeT<X>
-//     ,L:TreeListT<X,T>
-//     >
-//     ListEdit<X,T>
-//     for
-//     ListZipper<X,T,L>
-// {
-//     type State=ListZipper<X,T,L>;
+  /// Inserts `x` immediately to the left of the focus.
+  pub fn insert_left (z:Self, x:X) -> Self {
+    ListZipper{ left:List::cons(x, z.left), right:z.right }
+  }
 
-//     // XXX
-//     // type Tree=T;
-    
-//   fn clr_names (zip:Self::State, dir:Dir2) -> Self::State {
-//     panic!("clr_names generally re-associates names");
-//         // match dir {
-//         //     Dir2::Left => L::elim_arg
-//         //         (st, zip.left, zip.right,
-//         //          |st,right| zipper!{L::nil(st), right},
-//         //          |st,x,left,right| zipper!{L::cons(st,x,left), right},
-//         //          |st,nm,left,right| {
-//         //              let right = L::name(st,nm,right);
-//         //              Self::clr_names(st, zipper!{left, right}, dir)}
-//         //          ),
-//         //     Dir2::Right => L::elim_arg
-//         //         (st, zip.right, zip.left,
-//         //          |st,left| zipper!{left, L::nil(st)},
-//         //          |st,x,right,left| zipper!{left, L::cons(st,x,right)},
-//         //          |st,nm,right,left| {
-//         //              let left = L::name(st,nm,left);
-//         //              Self::clr_names(st, zipper!{left, right}, dir)}
-//         //          ),
-//         // }
-//     }
+  /// Inserts `x` immediately to the right of the focus.
+  pub fn insert_right (z:Self, x:X) -> Self {
+    ListZipper{ left:z.left, right:List::cons(x, z.right) }
+  }
 
-//   fn print_all (zip:Self::State) {
-//     println!("zip-left:  {}", L::get_string(st, zip.left));
-//     println!("zip-right: {}", L::get_string(st, zip.right));
-//   }
-  
-//     fn ins_tree (zip:Self::State, ins_dir:Dir2, tree:T::Tree, tree_dir:Dir2) -> Self::State {
-//         match ins_dir {
-//             Dir2::Left =>
-//                 zipper!{L::tree(st, tree, tree_dir, zip.left),
-//                         zip.right},
-//             Dir2::Right =>
-//                 zipper!{zip.left,
-//                         L::tree(st, tree, tree_dir, zip.right)},
-//         }
-//     }
-    
-//     fn ins_name (zip:Self::State, dir:Dir2, name:Name) -> Self::State {
-//         match dir {
-//             Dir2::Left =>
-//                 zipper!{L::name(st, name, zip.left),
-//                            zip.right},
-//             Dir2::Right =>
-//                 zipper!{zip.left,
-//                            L::name(st, name, zip.right)},
-//         }
-//     }
+  /// Places the name `nm` immediately to the left of the focus.
+  pub fn name_left (z:Self, nm:Name) -> Self {
+    ListZipper{ left:List::name_art(Some(nm), z.left), right:z.right }
+  }
 
-//     fn ins_cell (zip:Self::State, dir:Dir2, name:Name) -> Self::State {
-//         match dir {
-//             Dir2::Left => {
-//                 let art = st.cell(name, zip.left) ;
-//                 let art = st.read_only(art);
-//                 zipper!{L::art(st, art),
-//                            zip.right}},                
-//             Dir2::Right => {
-//                 let art = st.cell(name, zip.right) ;
-//                 let art = st.read_only(art);
-//                 zipper!{zip.left,
-//                            L::art(st, art)}},
-//         }
-//     }
+  /// Places the name `nm` immediately to the right of the focus.
+  pub fn name_right (z:Self, nm:Name) -> Self {
+    ListZipper{ left:z.left, right:List::name_art(Some(nm), z.right) }
+  }
 
-//     fn rem_name  (zip:Self::State, dir:Dir2) -> (Self::State, Option<Name>) {
-//         match dir {
-//             Dir2::Left => L::elim_arg
-//                 (st, zip.left, zip.right,
-//                  |st,right|         (zipper!{L::nil(st), right}, None ),
-//                  |_,x,left,right|   (zipper!{left,       right}, None ),
-//                  |st,nm,left,right| (zipper!{left, right},       Some(nm))
-//                  ),
-//             Dir2::Right => L::elim_arg
-//                 (st, zip.right, zip.left,
-//                  |st,left|          (zipper!{left, L::nil(st)}, None ),
-//                  |_,x,right,left|   (zipper!{left, right},      None ),
-//                  |st,nm,right,left| (zipper!{left, right},      Some(nm))
-//                  ),
-//         }
-//     }
-    
-//     fn empty (st: &mut A) -> Self::State {
-//         let nil1 = L::nil(st);
-//         let nil2 = nil1.clone();
-//         zipper!{nil1, nil2}
-//     }
-    
-//     fn insert (zip:Self::State, dir:Dir2, x:X) -> Self::State {
-//         match dir {
-//             Dir2::Left =>
-//                 zipper!{L::cons(st, x, zip.left),
-//                            zip.right},
-//             Dir2::Right =>
-//                 zipper!{zip.left,
-//                            L::cons(st, x, zip.right)},
-//         }
-//     }
+  /// Removes the element nearest the focus on side `dir`, or returns
+  /// `None` if that side has no elements.  The names passed over stay
+  /// where they are.
+  pub fn delete (z:Self, dir:Dir2) -> (Self, Option<X>) {
+    match dir {
+      Dir2::Left => {
+        let (left, x) = list_delete(z.left);
+        (ListZipper{ left:left, right:z.right }, x)
+      }
+      Dir2::Right => {
+        let (right, x) = list_delete(z.right);
+        (ListZipper{ left:z.left, right:right }, x)
+      }
+    }
+  }
 
-//     fn remove  (zip:Self::State, dir:Dir2) -> (Self::State, Option<X>) {
-//         match dir {
-//             Dir2::Left => L::elim_arg
-//                 (st, zip.left, zip.right,
-//                  |st,right|         (zipper!{L::nil(st), right}, None   ),
-//                  |_,x,left,right|   (zipper!{left,       right}, Some(x)),
-//                  |st,nm,left,right| {let zip = zipper!{left, right};
-//                                      Self::remove (st, zip, Dir2::Left)}
-//                  ),
-//             Dir2::Right => L::elim_arg
-//                 (st, zip.right, zip.left,
-//                  |st,left|          (zipper!{left, L::nil(st)}, None   ),
-//                  |_,x,right,left|   (zipper!{left, right},      Some(x)),
-//                  |st,nm,right,left| {let zip = zipper!{left, right};
-//                                      Self::remove (st, zip, Dir2::Right)}
-//                  ),
-//         }
-//     }
+  /// Moves the focus left past one element, carrying the names before
+  /// it along.  Returns `false` if no element is left of the focus.
+  pub fn move_left (z:Self) -> (Self, bool) {
+    let (left, right, moved) = list_shift(z.left, z.right);
+    (ListZipper{ left:left, right:right }, moved)
+  }
 
+  /// Moves the focus right past one element, carrying the names before
+  /// it along.  Returns `false` if no element is right of the focus.
+  pub fn move_right (z:Self) -> (Self, bool) {
+    let (right, left, moved) = list_shift(z.right, z.left);
+    (ListZipper{ left:left, right:right }, moved)
+  }
 
-//   // XXX rename 'move'
-//     fn goto (zip:Self::State, dir:Dir2) -> (Self::State, bool) {
-//       match dir {
-//         Dir2::Left => L::elim_arg
-//           (st, zip.left, zip.right,
-//            /* Nil */  |st,right|          (zipper!{L::nil(st), right}          , false ),
-//            /* Cons */ |st,elm,left,right| (zipper!{left, L::cons(st,elm,right)}, true  ),
-//            /* Name */ |st,_,left,right| {let zip = zipper!{left, right};
-//                                           Self::goto (st, zip, Dir2::Left)}
-//            ),
-//         Dir2::Right => L::elim_arg
-//           (st, zip.right, zip.left,
-//            /* Nil */ |st,left|           (zipper!{left,              L::nil(st)}, false ),
-//            /* Cons */ |st,x,right,left|  (zipper!{L::cons(st,x,left),right}     , true  ),
-//            /* Name */ |st,_,right,left| {let zip = zipper!{left, right};
-//                                           Self::goto (st, zip, Dir2::Right)}
-//            ),
-//       }
-//     }
+  /// Moves the focus to just after the name `nm`, or returns `None` if
+  /// the zipper does not hold `nm`.
+  pub fn goto_name (z:Self, nm:&Name) -> Option<Self> {
+    let mut z = z;
+    loop {
+      let (z2, moved) = Self::move_left(z);
+      z = z2;
+      if !moved { break }
+    }
+    goto_name_rec(z.left, z.right, nm)
+  }
 
-//     fn shift (zip:Self::State, dir:Dir2) -> (Self::State, bool) {
-//       match dir {
-//         Dir2::Left => L::elim_arg
-//           (st, zip.left, zip.right,
-//            /* Nil */  |st,right|          (zipper!{L::nil(st), right}          , false ),
-//            /* Cons */ |st,elm,left,right| (zipper!{left, L::cons(st,elm,right)}, true  ),
-//            /* Name */ |st,nm,left,right| {let zip = zipper!{left, L::name(st,nm,right)};
-//                                           Self::shift (st, zip, Dir2::Left)}
-//            ),
-//         Dir2::Right => L::elim_arg
-//           (st, zip.right, zip.left,
-//            /* Nil */ |st,left|           (zipper!{left,              L::nil(st)}, false ),
-//            /* Cons */ |st,x,right,left|  (zipper!{L::cons(st,x,left),right}     , true  ),
-//            /* Name */ |st,nm,right,left| {let zip = zipper!{L::name(st,nm,left), right};
-//                                           Self::shift (st, zip, Dir2::Right)}
-//            ),
-//       }
-//     }
+  /// The elements of the zipper as a list, in order.  The left side is
+  /// reversed onto the right with `list_reverse`, which memoizes at
+  /// each of its names.
+  pub fn unfocus (z:Self) -> List<X> {
+    ns(name_of_str("unfocus"),
+       move || list_reverse::<X,List<X>,List<X>>(z.left, z.right))
+  }
+}
 
-//     fn observe (zip:Self::State, dir:Dir2) -> (Self::State,Option<X>) {
-//         match dir {
-//             Dir2::Left => L::elim_arg
-//                 (st, zip.left, zip.right,
-//                  |st,right|         (zipper!{L::nil(st), right}, None),
-//                  |st,x,left,right| {let x2 = x.clone();
-//                                     (zipper!{L::cons(st,x,left), right}, Some(x2))},
-//                  |st,nm,left,right|{let zip = zipper!{left,right};
-//                                     Self::observe (st, zip, Dir2::Left)}
-//                  ),
-//             Dir2::Right => L::elim_arg
-//                 (st, zip.right, zip.left,
-//                  |st,left|         (zipper!{left, L::nil(st)}, None),
-//                  |st,x,right,left| {let x2 = x.clone();
-//                                     (zipper!{left, L::cons(st,x,right)}, Some(x2))},
-//                  |st,nm,right,left|{let zip = zipper!{left,right};
-//                                     Self::observe (st, zip, Dir2::Right)}
-//                  ),
-//         }
-//     }
+/// Forces the articulations at the head of `list`.
+fn list_force_arts<X:'static+Debug+Hash+PartialEq+Eq+Clone>(list:List<X>) -> List<X> {
+  match list {
+    List::Art(ref a) => return list_force_arts(force(a)),
+    _ => (),
+  };
+  list
+}
 
-//     fn replace (zip:Self::State, dir:Dir2, y:X) -> (Self::State, X, bool) {
-//         match dir {
-//             Dir2::Left => L::elim_arg
-//                 (st, zip.left, (zip.right, y),
-//                  |st,(right,y)|        (zipper!{L::nil(st),         right}, y, false),
-//                  |st,x,left,(right,y)| (zipper!{L::cons(st,y,left), right}, x, true ),
-//                  |st,nm,left,(right,y)|{let zip = zipper!{left,right};
-//                                         Self::replace (st, zip, Dir2::Left, y)}
-//                  ),
-//             Dir2::Right => L::elim_arg
-//                 (st, zip.right, (zip.left,y),
-//                  |st,(left,y)|         (zipper!{left, L::nil(st)},          y, false),
-//                  |st,x,right,(left,y)| (zipper!{left, L::cons(st,y,right)}, x, true ),
-//                  |st,nm,right,(left,y)|{let zip = zipper!{left,right};
-//                                         Self::replace (st, zip, Dir2::Right, y)}
-//                  ),
-//         }
-//     }
+/// Removes the first element of `list`, re-articulating the names
+/// before it.
+fn list_delete<X:'static+Debug+Hash+PartialEq+Eq+Clone>(list:List<X>) -> (List<X>, Option<X>) {
+  List::elim_arg(list, (),
+                 |list, _| (list, None),
+                 |x, tl, _| (tl, Some(x)),
+                 |nm, tl, _| {
+                   let (tl, x) = list_delete(tl);
+                   (List::name_art(Some(nm), tl), x)
+                 })
+}
 
-//     fn clear_side
-//         (zip:Self::State, dir:Dir2) -> Self::State {
-//             let emp = L::nil(st) ;
-//             match dir {
-//                 Dir2::Right => zipper!{zip.left, emp},
-//                 Dir2::Left  => zipper!{emp, zip.right},
-//             }
-//         }
-        
-//     fn get_list<N:ListT<X>>
-//         (zip:Self::State, dir:Dir2) -> N::List
-//     {
-//         let tree = Self::get_tree(st, zip, dir);
-//         list_of_tree::<X,N,T>(st, tree)
-//     }
+/// Moves the first element of `from` onto `to`, along with the names
+/// before it.  Each name's cell is forced on `from` before the name
+/// re-sets it on `to`.
+fn list_shift<X:'static+Debug+Hash+PartialEq+Eq+Clone>
+  (from:List<X>, to:List<X>) -> (List<X>, List<X>, bool)
+{
+  List::elim_arg(from, to,
+                 |from, to| (from, to, false),
+                 |x, from, to| (from, List::cons(x, to), true),
+                 |nm, from, to| list_shift(list_force_arts(from), List::name_art(Some(nm), to)))
+}
 
-//     /// Creates a tree whose leaves hold the contents of the zipper, in order.
-//     /// When `dir=Left`,  the tree's leaves are ordered from left-to-right, i.e., as (rev left) @ right.
-//     /// When `dir=Right`, the tree's leaves are ordered from right-to-left, i.e., as (rev right) @ left.
-//     fn get_tree(zip:Self::State, dir:Dir2) -> T::Tree
-//     {
-//         match dir {
-//             Dir2::Left  => {
-//                 let left  = tree_of_treelist::<X,T,L>(st, Dir2::Right, zip.left);
-//                 let right = tree_of_treelist::<X,T,L>(st, Dir2::Left,  zip.right);
-//                 tree_append::<X,T>(st, left, right)}
-            
-//             Dir2::Right => {
-//                 let right = tree_of_treelist::<X,T,L>(st, Dir2::Right, zip.right);
-//                 let left  = tree_of_treelist::<X,T,L>(st, Dir2::Left,  zip.left);
-//                 tree_append::<X,T>(st, right, left)}
-//         }
-//     }
-// }
+fn goto_name_rec<X:'static+Debug+Hash+PartialEq+Eq+Clone>
+  (left:List<X>, right:List<X>, nm:&Name) -> Option<ListZipper<X>>
+{
+  List::elim_arg(right, left,
+                 |_, _| None,
+                 |x, right, left| goto_name_rec(List::cons(x, left), right, nm),
+                 |n, right, left| {
+                   // Force the cell named `n` before re-setting it.
+                   let found = n == *nm;
+                   let right = list_force_arts(right);
+                   let left = List::name_art(Some(n), left);
+                   if found { Some(ListZipper{ left:left, right:right }) }
+                   else { goto_name_rec(left, right, nm) }
+                 })
+}
+
+/// Trees with a focus on one subtree, for making local edits.  The
+/// zipper holds the path from the focus up to the root, as the nodes
+/// passed on the way down: `ascend` rebuilds a node only if the
+/// subtree under it has changed, and then keeps the level and name of
+/// the node, as well as its articulation, if it had one; the cell of a
+/// named node is re-set at the name of the node (as `tree_of_list`
+/// names it), so that folds over the tree reuse the work done under
+/// the untouched names.
+#[derive(Debug,Hash,PartialEq,Eq,Clone)]
+pub struct TreeZipper<X> {
+  /// The subtree at the focus.
+  pub focus: Tree<X>,
+  path: Vec<TreeZipperCtx<X>>,
+}
+
+/// A node on the path of a `TreeZipper`: the node as it was, its name
+/// and level, the direction of the step down from it, and its subtree
+/// in that direction, and in the other.
+#[derive(Debug,Hash,PartialEq,Eq,Clone)]
+struct TreeZipperCtx<X> {
+  node: Tree<X>,
+  name: Option<Name>,
+  lev: usize,
+  dir: Dir2,
+  child: Tree<X>,
+  other: Tree<X>,
+}
+
+impl<X:'static+Debug+Hash+PartialEq+Eq+Clone> TreeZipper<X> {
+  /// A zipper over `tree`, focused on its root.
+  pub fn of_tree (tree:Tree<X>) -> Self {
+    TreeZipper{ focus:tree, path:vec![] }
+  }
+
+  /// The number of nodes above the focus.
+  pub fn depth (z:&Self) -> usize { z.path.len() }
+
+  /// Moves the focus down to the subtree in direction `dir`, forcing
+  /// the articulations above it.  Returns `false` if the focus is not
+  /// a binary or named node.
+  pub fn descend (z:Self, dir:Dir2) -> (Self, bool) {
+    let mut z = z;
+    let mut tree = z.focus.clone();
+    while let Tree::Art(a) = tree { tree = force(&a) }
+    let (name, lev, l, r) = match tree {
+      Tree::Bin(lev, l, r) => (None, lev, *l, *r),
+      Tree::Name(nm, lev, l, r) => (Some(nm), lev, *l, *r),
+      _ => return (z, false),
+    };
+    let (child, other) = match dir { Dir2::Left => (l, r), Dir2::Right => (r, l) };
+    let node = z.focus;
+    z.path.push(TreeZipperCtx{ node:node, name:name, lev:lev, dir:dir,
+                               child:child.clone(), other:other });
+    z.focus = child;
+    (z, true)
+  }
+
+  /// Moves the focus up to the node above it, rebuilding the node if
+  /// the focus has changed.  Returns `false` at the root.
+  pub fn ascend (z:Self) -> (Self, bool) {
+    let mut z = z;
+    let ctx = match z.path.pop() {
+      None => return (z, false),
+      Some(ctx) => ctx,
+    };
+    z.focus = if z.focus == ctx.child { ctx.node } else {
+      let (l, r) = match ctx.dir {
+        Dir2::Left => (z.focus, ctx.other),
+        Dir2::Right => (ctx.other, z.focus),
+      };
+      match ctx.name {
+        None => Tree::bin(ctx.lev, l, r),
+        Some(nm) => {
+          let tree = Tree::name(nm.clone(), ctx.lev, l, r);
+          match ctx.node {
+            Tree::Art(_) => Tree::art(cell(nm, tree)),
+            _ => tree,
+          }
+        }
+      }
+    };
+    (z, true)
+  }
+
+  /// Replaces the subtree at the focus with `tree`.
+  pub fn replace (z:Self, tree:Tree<X>) -> Self {
+    TreeZipper{ focus:tree, path:z.path }
+  }
+
+  /// The tree of the zipper, with the focus moved up to its root.
+  pub fn unfocus (z:Self) -> Tree<X> {
+    let mut z = z;
+    loop {
+      let (z2, moved) = Self::ascend(z);
+      z = z2;
+      if !moved { return z.focus }
+    }
+  }
+}
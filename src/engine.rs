@@ -25,8 +25,9 @@
 //!     namespace concept is analogous to a directory in the UNIX
 //!     filesystem.
 
+use std::any::{Any,type_name};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap,HashMap,HashSet};
 use std::env;
 use std::fmt::Debug;
 use std::fmt::{Formatter,Result};
@@ -36,14 +37,27 @@ use std::hash::{Hash,Hasher};
 use std::collections::hash_map::DefaultHasher;
 use std::mem::replace;
 use std::mem::transmute;
+use std::mem::{size_of,size_of_val};
 use std::num::Zero;
 use std::ops::Add;
 use std::rc::Rc;
 use std::fmt::Write;
+use std::time::{Duration,Instant};
+use std::panic;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool,Ordering};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context,Poll,Wake,Waker};
+use std::thread;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 
 use macros::*;
 
-thread_local!(static GLOBALS: RefCell<Globals> = RefCell::new(Globals{engine:Engine::Naive}));
+thread_local!(static GLOBALS: RefCell<Globals> = RefCell::new(Globals{engine:Engine::Naive,
+                                                                    handle:None}));
 thread_local!(static ROOT_NAME: Name = Name{ hash:0, symbol: Rc::new(NameSym::Root) });
 
 struct TraceSt { stack:Vec<Box<Vec<reflect::trace::Trace>>>, }
@@ -51,6 +65,10 @@ struct TraceSt { stack:Vec<Box<Vec<reflect::trace::Trace>>>, }
 /// When this option is set to some, the engine will record a trace of its DCG effects.
 thread_local!(static TRACES: RefCell<Option<TraceSt>> = RefCell::new( None ));
 
+/// When this option is set to some, the engine records the operations
+/// that the program performs on it; see `replay`.
+thread_local!(static RECORDING: RefCell<Option<Vec<replay::Step>>> = RefCell::new( None ));
+
 
 /// Reflects the DCG engine, including both the effects of the
 /// programs running in it, and the internal effects of the engine
@@ -155,6 +173,56 @@ pub mod reflect {
     })
   }
 
+  /// Render the DCG's current structure (see `dcg_reflect_now`) in
+  /// the DOT language of GraphViz.  Each node is labeled with its
+  /// location; thunks (boxes) are also labeled with their program
+  /// point, and cells (ellipses) with their value.  Edges are solid
+  /// for `Force` effects and dashed for `Alloc` effects; dirty edges,
+  /// and thunks with dirty outgoing edges, are drawn in red.  Nodes
+  /// and edges are listed in a deterministic order, by location.
+  /// Returns an empty graph if the engine is `Naive`.
+  pub fn dcg_to_dot() -> String {
+    let mut out = String::from("digraph dcg {\n");
+    if let Some(dcg) = dcg_reflect_now() {
+      let mut nodes : Vec<(String, &Node)> =
+        dcg.nodes().map(|(l,nd)| (string_of_loc(l), nd)).collect();
+      nodes.sort_by(|a,b| a.0.cmp(&b.0));
+      for &(ref id, nd) in nodes.iter() {
+        match *nd {
+          Node::Comp(ref nd) => {
+            let dirty = nd.succs.iter().any(|s| s.dirty);
+            writeln!(out, "  \"{}\" [shape=box,label=\"{}\\n{}\"{}];",
+                     dot_escape(id), dot_escape(id), dot_escape(nd.prog_pt.symbol),
+                     if dirty { ",color=red" } else { "" }).unwrap();
+            for s in nd.succs.iter() {
+              let style = match s.effect { Effect::Force => "solid", Effect::Alloc => "dashed" };
+              writeln!(out, "  \"{}\" -> \"{}\" [style={}{}];",
+                       dot_escape(id), dot_escape(&string_of_loc(&s.loc)), style,
+                       if s.dirty { ",color=red" } else { "" }).unwrap();
+            }
+          }
+          Node::Ref(ref nd) => {
+            writeln!(out, "  \"{}\" [shape=ellipse,label=\"{}\\n{}\"];",
+                     dot_escape(id), dot_escape(id),
+                     dot_escape(&format!("{:?}", nd.value))).unwrap();
+          }
+          Node::Pure(ref nd) => {
+            writeln!(out, "  \"{}\" [shape=plaintext,label=\"{}\\n{}\"];",
+                     dot_escape(id), dot_escape(id),
+                     dot_escape(&format!("{:?}", nd.value))).unwrap();
+          }
+        }
+      }
+    }
+    out.push_str("}\n");
+    out
+  }
+
+  /// Escape a string for use within a double-quoted DOT identifier.
+  fn dot_escape (s:&str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+  }
+
   /// Begin recording (reflections of) DCG effects.  See `dcg_reflect_end()`.
   pub fn dcg_reflect_begin() {
     TRACES.with(|tr| { 
@@ -260,6 +328,29 @@ macro_rules! dcg_effect {
   }}
 }
 
+/// With the `tracing` feature, enters a (trace-level) span, with the
+/// name and path of `loc`, and a program point (or `""`) as fields,
+/// until the end of the current block.  These spans cover the
+/// engine's operations: `force`, `eval` (re-evaluation of a thunk),
+/// `alloc` (of a cell or thunk), `dirty` (from a changed node) and
+/// `clean` (of a cached result), so that existing `tracing` tooling
+/// (e.g., flamegraphs, and the Chrome trace viewer) can explain where
+/// incremental work goes.  Without this feature, it does nothing, and
+/// does not evaluate its arguments.
+#[cfg(feature = "tracing")]
+macro_rules! engine_span {
+  ( $op:expr, $loc:expr, $prog_pt:expr ) => {
+    let _span = ::tracing::trace_span!(
+      target:"adapton::engine", $op,
+      name = ?($loc).id, path = ?($loc).path, prog_pt = $prog_pt
+    ).entered() ;
+  }
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! engine_span {
+  ( $op:expr, $loc:expr, $prog_pt:expr ) => { }
+}
+
 macro_rules! current_loc {
   ( $st:expr ) => {{ 
     match ($st).stack.last() { 
@@ -275,6 +366,7 @@ macro_rules! current_loc {
 /// changing is occurring, relative to other (unaffected) parts of
 /// data structures or computations.
 #[derive(PartialEq,Eq,Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Name {
   hash : u64, // hash of symbol
   symbol : Rc<NameSym>,
@@ -348,10 +440,48 @@ pub struct Flags {
   /// Deprecated: At certain points in the Engine's code, write state changes as graph-movie output
   /// TODO: To be replaced with DCG reflection, and reflection-to-filesystem logic.
   pub gmlog_dcg : bool,
+  /// How changes to cells propagate, unless overridden for a cell (see `Propagation`)
+  pub propagation : Propagation,
+  /// Which cached results to evict, beyond their bounds (see `Eviction`)
+  pub eviction : Eviction,
+  /// Debug mode: Report the `Art`s that `collect_garbage` could not collect (see `LiveArt`)
+  pub track_arts : bool,
+}
+
+/// How the changes that the outer layer makes to cells propagate to
+/// the thunks that depend on them.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Propagation {
+  /// Dirty the dependent thunks; re-evaluate them (as needed) when
+  /// they are next demanded, by `force`.  This is the default.
+  Lazy,
+  /// Dirty the dependent thunks, then immediately demand the
+  /// outermost ones (those not observed by other thunks), cleaning
+  /// or re-evaluating them, before `set` returns.  Suited to
+  /// interactive applications, e.g., with a small frontier of thunks
+  /// that feed callbacks registered by `observe`.
+  Eager,
+}
+
+/// Which cached results to evict, when the thunks of a program point
+/// cache more results than its bound permits (see
+/// `manage::bound_cached_results`).
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Eviction {
+  /// Evict the result that was least recently cached or found by
+  /// `force`.  This is the default.
+  Lru,
+  /// Evict the result that was least recently cached, unless `force`
+  /// found it since it was last considered for eviction; in that
+  /// case, give it a second chance, as though it were freshly cached.
+  /// Cheaper than `Lru` for each cache hit, but less precise.
+  SecondChance,
 }
 
 struct Globals {
   engine: Engine,
+  /// The handle whose engine is in use, if any (see `EngineHandle::run`)
+  handle: Option<Rc<RefCell<Option<Engine>>>>,
 }
 
 /// The engine API works in two modes: `Naive` and `DCG`. A `Naive` engine is stateless, whereas the `DCG` is stateful.
@@ -361,6 +491,151 @@ pub enum Engine {
   Naive
 }
 
+/// A handle on an engine: Clones of a handle share its engine, and
+/// `EngineHandle::run` performs a computation in it, without
+/// disturbing the engine that the thread uses otherwise (see
+/// `manage::init_dcg`, etc.).  Hence, a program (or test) may hold
+/// several independent engines, and alternate among them.
+#[derive(Debug,Clone)]
+pub struct EngineHandle {
+  /// The engine, or `None` while `run` has it in use
+  engine: Rc<RefCell<Option<Engine>>>,
+}
+
+impl EngineHandle {
+  /// A handle on the given engine, not (yet) in use; see `run`.
+  pub fn new (engine:Engine) -> EngineHandle {
+    EngineHandle{ engine: Rc::new(RefCell::new(Some(engine))) }
+  }
+  /// A handle on a fresh DCG-based engine.
+  pub fn new_dcg () -> EngineHandle { EngineHandle::new(Engine::DCG(RefCell::new(DCG::new()))) }
+  /// A handle on a naive engine.
+  pub fn new_naive () -> EngineHandle { EngineHandle::new(Engine::Naive) }
+
+  /// Performs the given computation with this handle's engine in use,
+  /// as the thread's current engine; afterward (even if the
+  /// computation panics), restores the engine that was in use before.
+  /// The computation may use the global operations (`cell`, `force`,
+  /// the `memo!` macro, etc.), or the same operations of this handle;
+  /// it should not replace the engine in use (e.g., by `init_dcg`).
+  /// When this engine is already in use, `run` merely performs the
+  /// computation; so, these operations may occur within thunks.
+  ///
+  /// Panics if the engine in use is forcing a thunk, or otherwise
+  /// computing, and this engine is not in use: The thread cannot switch
+  /// engines within such a computation.  Likewise, panics if this
+  /// engine is in use by an enclosing `run`, but another (nested)
+  /// `run` switched from it.
+  pub fn run<T,F> (&self, body:F) -> T
+    where F:FnOnce(&EngineHandle) -> T {
+      if self.is_in_use() { return body(self) }
+      struct Restore { handle:Rc<RefCell<Option<Engine>>>,
+                       outer:Option<(Engine, Option<Rc<RefCell<Option<Engine>>>>)> }
+      impl Drop for Restore {
+        fn drop (&mut self) {
+          if let Some((engine, handle)) = self.outer.take() {
+            let engine = GLOBALS.with(|g| {
+              let mut g = g.borrow_mut();
+              g.handle = handle;
+              replace(&mut g.engine, engine)
+            });
+            *self.handle.borrow_mut() = Some(engine)
+          }
+        }
+      }
+      let outer = GLOBALS.with(|g| {
+        let mut g = match g.try_borrow_mut() {
+          Ok(g) => g,
+          Err(_) => panic!("EngineHandle::run: cannot switch engines within a computation \
+                            of the engine in use (e.g., within a thunk)"),
+        };
+        let engine = match self.engine.borrow_mut().take() {
+          Some(engine) => engine,
+          None => panic!("EngineHandle::run: this engine is in use by an enclosing run, \
+                          but another engine is in use now"),
+        };
+        (replace(&mut g.engine, engine), replace(&mut g.handle, Some(self.engine.clone())))
+      });
+      let _restore = Restore{ handle:self.engine.clone(), outer:Some(outer) };
+      body(self)
+    }
+
+  /// True iff this handle's engine is the thread's current engine.
+  pub fn is_in_use (&self) -> bool {
+    GLOBALS.with(|g| {
+      match g.borrow().handle {
+        Some(ref h) => Rc::ptr_eq(h, &self.engine),
+        None => false,
+      }
+    })
+  }
+
+  /// Like `ns`, in this engine.
+  pub fn ns<T,F> (&self, n:Name, body:F) -> T
+    where F:FnOnce() -> T {
+      self.run(|_| ns(n, body))
+    }
+
+  /// Like `cell`, in this engine.
+  pub fn cell<T:Hash+Eq+Debug+Clone+'static> (&self, n:Name, val:T) -> Art<T> {
+    self.run(|_| cell(n, val))
+  }
+
+  /// Like `set`, in this engine.
+  pub fn set<T:Eq+Debug+Clone> (&self, a:&Art<T>, val:T) {
+    self.run(|_| set(a, val))
+  }
+
+  /// Like `thunk`, in this engine.
+  pub fn thunk<Arg:Hash+Eq+Debug+Clone+'static,
+               Spurious:Clone+'static,
+               Res:Hash+Eq+Debug+Clone+'static>
+    (&self,
+     id:ArtIdChoice,
+     prog_pt:ProgPt,
+     fn_box:Rc<Box< Fn(Arg, Spurious) -> Res >>,
+     arg:Arg, spurious:Spurious)
+     -> Art<Res>
+  {
+    self.run(|_| thunk(id, prog_pt, fn_box, arg, spurious))
+  }
+
+  /// Like `force`, in this engine.
+  pub fn force<T:Hash+Eq+Debug+Clone+'static> (&self, a:&Art<T>) -> T {
+    self.run(|_| force(a))
+  }
+
+  /// Like `stats`, for this engine.
+  pub fn stats (&self) -> Cnt {
+    self.run(|_| stats())
+  }
+}
+
+/// The kinds of engines, for `with_engine`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum EngineKind {
+  /// A fresh DCG-based engine (see `EngineHandle::new_dcg`)
+  DCG,
+  /// The naive engine (see `EngineHandle::new_naive`)
+  Naive,
+}
+
+/// Performs the given computation with a fresh, temporary engine of
+/// the given kind; afterward (even if the computation panics),
+/// restores the engine that was in use before, with its state intact.
+/// Use this to compare the results of the naive and DCG-based engines
+/// in one test, regardless of the order of `init_dcg` and `init_naive`
+/// calls elsewhere.  Like `EngineHandle::run`, panics within a thunk
+/// (or other computation) of the engine in use.
+pub fn with_engine<T,F> (kind:EngineKind, body:F) -> T
+  where F:FnOnce() -> T {
+    let engine = match kind {
+      EngineKind::DCG   => EngineHandle::new_dcg(),
+      EngineKind::Naive => EngineHandle::new_naive(),
+    } ;
+    engine.run(|_| body())
+  }
+
 /// *(DCG) Demanded Computation Graph*: The cache of past computation.
 ///
 /// The DCG consists of private state (a memo table of DCG nodes, a
@@ -375,6 +650,71 @@ pub struct DCG {
   cnt   : Cnt,
   dcg_count : usize,
   dcg_hash  : u64,  
+  observers : Observers,
+  /// Overrides of `flags.propagation`, for particular cells.
+  propagation : HashMap<Rc<Loc>, Propagation>,
+  bounded : BoundedResults,
+  /// The token that `loc_produce` polls, if any (see `with_cancel`)
+  cancel : Option<CancelToken>,
+  /// The async thunks whose bodies are suspended at an await point,
+  /// each as a `Suspended<Arg,T>` (see `thunk_async`)
+  suspended : HashMap<Rc<Loc>, Box<Any>>,
+}
+
+/// The bounds on cached results (see `manage::bound_cached_results`):
+/// for each `ProgPt` with a bound, its thunks with cached results, in
+/// the order in which to consider evicting them.
+#[derive(Debug)]
+struct BoundedResults {
+  capacity : HashMap<ProgPt, usize>,
+  queues   : HashMap<ProgPt, BTreeMap<usize, Rc<Loc>>>,
+  entries  : HashMap<Rc<Loc>, BoundedResult>,
+  tick     : usize,
+}
+
+#[derive(Debug)]
+struct BoundedResult {
+  prog_pt    : ProgPt,
+  tick       : usize, // The entry's key in the queue of its prog_pt
+  referenced : bool,  // Found by force since last queued (for Eviction::SecondChance)
+}
+
+/// The callbacks registered by `observe`, and the notifications for
+/// them that are pending, until the outer layer regains control.
+struct Observers {
+  table   : HashMap<Rc<Loc>, Vec<Rc<AnyObserver>>>,
+  pending : Vec<Box<Fn()>>,
+}
+
+impl Debug for Observers {
+  fn fmt(&self, f:&mut Formatter) -> Result {
+    write!(f, "Observers{{locs:{}, pending:{}}}", self.table.len(), self.pending.len())
+  }
+}
+
+/// A callback registered by `observe`, with the last value passed to
+/// it, if any.
+struct Observer<T> {
+  last     : RefCell<Option<T>>,
+  callback : Box<Fn(&T)>,
+}
+
+/// An `Observer<T>`, for some type `T`.
+trait AnyObserver {
+  /// Calls back with the new value, unless it is equal to the last
+  /// one that this observer saw, or (lacking that) the old value.
+  /// Does nothing if the values are not of type `T`.
+  fn notify (&self, old:Option<&Any>, new:&Any) ;
+}
+
+impl<T:Eq+Clone+'static> AnyObserver for Observer<T> {
+  fn notify (&self, old:Option<&Any>, new:&Any) {
+    let new = match new.downcast_ref::<T>() { None => return, Some(new) => new.clone() } ;
+    let old = old.and_then(|old| old.downcast_ref::<T>().cloned()) ;
+    let prev = self.last.borrow_mut().take().or(old);
+    if prev.as_ref() != Some(&new) { (self.callback)(&new) }
+    *self.last.borrow_mut() = Some(new)
+  }
 }
 
 impl reflect::Reflect<reflect::DCG> for DCG {
@@ -408,6 +748,7 @@ impl Clone for     DCG { fn clone(&self) -> Self { unimplemented!() } }
 /// For a general semantics of symbols, see Chapter 31 of PFPL 2nd
 /// Edition. Harper 2016: http://www.cs.cmu.edu/~rwh/pfpl
 #[derive(Hash,PartialEq,Eq,Clone,Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 enum NameSym {
   Root,           // Unit value for name symbols
   Hash64,        // Hashes (for structural names); hash stored in name struct
@@ -470,6 +811,22 @@ trait GraphNode : Debug + reflect::Reflect<reflect::Node> {
   fn succs_mut<'r>   (self:&'r mut Self) -> &'r mut Vec<Succ> ;
   fn succs<'r>       (self:&'r Self) -> &'r Vec<Succ> ;
   fn hash_seeded     (self:&Self, u64) -> u64 ;
+  fn res_clear       (self:&mut Self) -> bool ;
+  fn demander        (self:&Self) -> Option<fn(&RefCell<DCG>, &Rc<Loc>)> ;
+  fn prog_pt<'r>     (self:&'r Self) -> Option<&'r ProgPt> ;
+  fn mem_use         (self:&Self) -> MemoryUse ;
+  fn locs_each       (self:&Self, &mut FnMut(&Rc<Loc>)) ;
+  /// The type of the node's value (see `PersistTypes`)
+  #[cfg(feature = "persist")]
+  fn res_type        (self:&Self) -> &'static str ;
+  /// The node's value: a cell's, or a thunk's cached result, if any
+  fn res_clone       (self:&Self) -> Option<Box<Any>> ;
+  /// The hash of a thunk's argument
+  #[cfg(feature = "persist")]
+  fn arg_hash        (self:&Self) -> Option<u64> ;
+  /// The dependency of an (observe) edge that observed the node's value
+  #[cfg(feature = "persist")]
+  fn observed_dep    (self:&Self) -> Rc<Box<DCGDep>> ;
 }
 
 #[derive(Debug,Clone)]
@@ -655,6 +1012,10 @@ pub struct Cnt {
   pub create : usize, // Add trait performs sum
   /// Number of DCG nodes evaluated
   pub eval   : usize, // Add trait performs sum
+  /// Number of forces of DCG nodes that found a cached result (memo hits)
+  pub hit    : usize, // Add trait performs sum
+  /// Number of cached results evicted, beyond their bounds (see `manage::bound_cached_results`)
+  pub evict  : usize, // Add trait performs sum
   /// Number of DCG nodes marked as dirty
   pub dirty  : usize, // Add trait performs sum
   /// Number of DCG nodes reverted from dirty to clean
@@ -669,6 +1030,8 @@ impl Add for Cnt {
     Cnt {
       create : self.create + rhs.create,
       eval   : self.eval + rhs.eval,
+      hit    : self.hit + rhs.hit,
+      evict  : self.evict + rhs.evict,
       dirty  : self.dirty + rhs.dirty,
       clean  : self.clean + rhs.clean,
       stack  : if self.stack > rhs.stack { self.stack } else { rhs.stack }
@@ -682,6 +1045,8 @@ impl<'a> Add for &'a Cnt {
     Cnt {
       create : self.create + rhs.create,
       eval   : self.eval + rhs.eval,
+      hit    : self.hit + rhs.hit,
+      evict  : self.evict + rhs.evict,
       dirty  : self.dirty + rhs.dirty,
       clean  : self.clean + rhs.clean,
       stack  : if self.stack > rhs.stack { self.stack } else { rhs.stack }
@@ -694,6 +1059,8 @@ impl Zero for Cnt {
     Cnt {
       create : 0 as usize,
       eval   : 0 as usize,
+      hit    : 0 as usize,
+      evict  : 0 as usize,
       dirty  : 0 as usize,
       clean  : 0 as usize,
       stack  : 0 as usize,
@@ -723,6 +1090,10 @@ trait Producer<Res> : Debug {
   fn copy(self:&Self) -> Box<Producer<Res>>;
   fn eq(self:&Self, other:&Producer<Res>) -> bool;
   fn prog_pt<'r>(self:&'r Self) -> &'r ProgPt;
+  fn arg_hash(self:&Self) -> u64;
+  /// True for the producer of a thunk restored by `load`, which lacks
+  /// the thunk's code (see `Restored`).
+  fn is_restored(self:&Self) -> bool { false }
 }
 // Consume a value of type Arg.
 trait Consumer<Arg> : Debug {
@@ -754,7 +1125,7 @@ impl<Arg:Hash+Debug,Spurious,Res>
   fn hash<H>(&self, state: &mut H) where H: Hasher { (&self.prog_pt,&self.arg).hash(state) }
 }
 
-impl<Arg:'static+PartialEq+Eq+Clone+Debug,Spurious:'static+Clone,Res:'static+Debug+Hash> 
+impl<Arg:'static+PartialEq+Eq+Clone+Debug+Hash,Spurious:'static+Clone,Res:'static+Debug+Hash> 
   Producer<Res> for 
   App<Arg,Spurious,Res>
 {
@@ -774,8 +1145,11 @@ impl<Arg:'static+PartialEq+Eq+Clone+Debug,Spurious:'static+Clone,Res:'static+Deb
   fn prog_pt<'r>(self:&'r Self) -> &'r ProgPt {
     & self.prog_pt
   }
+  fn arg_hash(self:&Self) -> u64 {
+    my_hash(&self.arg)
+  }
   fn eq (&self, other:&Producer<Res>) -> bool {
-    if &self.prog_pt == other.prog_pt() {
+    if &self.prog_pt == other.prog_pt() && !other.is_restored() {
       let other = Box::new(other) ;
       // This is safe if the prog_pt implies unique Arg and Res types.
       let other : &Box<App<Arg,Spurious,Res>> = unsafe { transmute::<_,_>( other ) } ;
@@ -793,6 +1167,34 @@ impl<Arg:Clone+PartialEq+Eq+Debug,Spurious,Res>
   fn get_arg(self:&mut Self) -> Arg   { self.arg.clone() }
 }
 
+/// The producer of a thunk restored by `load`, in place of its code,
+/// until the program allocates the thunk again (see `thunk`): its
+/// program point, and the hash of its argument.  Until then, the
+/// thunk keeps its restored result while its edges are clean, and
+/// otherwise, it makes its predecessors re-evaluate (see
+/// `ProducerDep`), which allocate it again.
+#[cfg(feature = "persist")]
+#[derive(Debug,Clone)]
+struct Restored {
+  prog_pt  : ProgPt,
+  arg_hash : u64,
+}
+
+#[cfg(feature = "persist")]
+impl<Res> Producer<Res> for Restored {
+  fn produce(self:&Self) -> Res {
+    panic!("internal error: the restored thunk {:?} re-evaluated before its allocation",
+           self.prog_pt)
+  }
+  fn copy(self:&Self) -> Box<Producer<Res>> { Box::new(self.clone()) }
+  fn eq(self:&Self, other:&Producer<Res>) -> bool {
+    other.is_restored() && &self.prog_pt == other.prog_pt() && self.arg_hash == other.arg_hash()
+  }
+  fn prog_pt<'r>(self:&'r Self) -> &'r ProgPt { &self.prog_pt }
+  fn arg_hash(self:&Self) -> u64 { self.arg_hash }
+  fn is_restored(self:&Self) -> bool { true }
+}
+
 // ----------- Location resolution:
 
 fn lookup_abs<'r>(st:&'r mut DCG, loc:&Rc<Loc>) -> &'r mut Box<GraphNode> {
@@ -811,7 +1213,7 @@ fn res_node_of_loc<'r,Res> (st:&'r mut DCG, loc:&Rc<Loc>) -> &'r mut Box<Node<Re
 
 // ---------- Node implementation:
 
-impl <Res:Debug+Hash> GraphNode for Node<Res> {
+impl <Res:'static+Debug+Hash+Eq+Clone> GraphNode for Node<Res> {
   fn preds_alloc(self:&Self) -> Vec<Rc<Loc>> {
     match *self { Node::Mut(ref nd) => nd.preds.iter().filter_map(|&(ref effect,ref loc)| if effect == &Effect::Allocate { Some(loc.clone()) } else { None } ).collect::<Vec<_>>(),
                   Node::Comp(ref nd) => nd.preds.iter().filter_map(|&(ref effect,ref loc)| if effect == &Effect::Allocate { Some(loc.clone()) } else { None } ).collect::<Vec<_>>(),
@@ -856,6 +1258,85 @@ impl <Res:Debug+Hash> GraphNode for Node<Res> {
     self.hash(&mut hasher);
     hasher.finish()
   }
+  fn res_clear(self:&mut Self) -> bool {
+    match *self { Node::Comp(ref mut n) => n.res.take().is_some(),
+                  _ => false,
+    }
+  }
+  fn demander(self:&Self) -> Option<fn(&RefCell<DCG>, &Rc<Loc>)> {
+    match *self { Node::Comp(_) => Some(demand_loc::<Res>),
+                  _ => None,
+    }
+  }
+  fn prog_pt<'r>(self:&'r Self) -> Option<&'r ProgPt> {
+    match *self { Node::Comp(ref n) => Some(n.producer.prog_pt()),
+                  _ => None,
+    }
+  }
+  fn mem_use(self:&Self) -> MemoryUse {
+    // The table entry, its (reference-counted) location and this node:
+    let bytes = size_of::<(Rc<Loc>, Box<GraphNode>)>() + size_of::<Rc<Loc>>() * 2
+      + size_of::<Loc>() + size_of::<Node<Res>>() ;
+    let preds_bytes = |preds:&Vec<(Effect,Rc<Loc>)>| {
+      preds.capacity() * size_of::<(Effect,Rc<Loc>)>()
+    } ;
+    match *self {
+      Node::Comp(ref n) => {
+        let succs_bytes : usize = n.succs.capacity() * size_of::<Succ>() +
+          n.succs.iter().map(|s| size_of::<Rc<Loc>>() * 2 + size_of_val(&**s.dep)).sum::<usize>() ;
+        MemoryUse{ cells:0, thunks:1,
+                   cached:if n.res.is_some() { 1 } else { 0 },
+                   edges:n.succs.len(),
+                   bytes:bytes + preds_bytes(&n.preds) + succs_bytes + size_of_val(&*n.producer) }
+      },
+      Node::Mut(ref n) =>
+        MemoryUse{ cells:1, thunks:0, cached:0, edges:0, bytes:bytes + preds_bytes(&n.preds) },
+      _ => MemoryUse{ cells:1, thunks:0, cached:0, edges:0, bytes:bytes },
+    }
+  }
+  fn locs_each(self:&Self, f:&mut FnMut(&Rc<Loc>)) {
+    match *self { Node::Comp(ref n) => {
+                    for &(_, ref loc) in n.preds.iter() { f(loc) } ;
+                    for succ in n.succs.iter() { f(&succ.loc) } },
+                  Node::Mut(ref n) => for &(_, ref loc) in n.preds.iter() { f(loc) },
+                  _ => (),
+    }
+  }
+  #[cfg(feature = "persist")]
+  fn res_type(self:&Self) -> &'static str { type_name::<Res>() }
+  fn res_clone(self:&Self) -> Option<Box<Any>> {
+    match *self { Node::Comp(ref n) => n.res.clone().map(|r| Box::new(r) as Box<Any>),
+                  Node::Mut(ref n) => Some(Box::new(n.val.clone())),
+                  _ => None,
+    }
+  }
+  #[cfg(feature = "persist")]
+  fn arg_hash(self:&Self) -> Option<u64> {
+    match *self { Node::Comp(ref n) => Some(n.producer.arg_hash()),
+                  _ => None,
+    }
+  }
+  #[cfg(feature = "persist")]
+  fn observed_dep(self:&Self) -> Rc<Box<DCGDep>> {
+    let res = match *self {
+      Node::Comp(ref n) => n.res.clone().expect("observed thunk has a result"),
+      Node::Mut(ref n) => n.val.clone(),
+      _ => panic!("undefined"),
+    } ;
+    Rc::new(Box::new(ProducerDep{res:res}))
+  }
+}
+
+/// Demands the thunk at `loc`, for its effects on the DCG (cleaning
+/// or re-evaluation), rather than its result; see `GraphNode::demander`.
+fn demand_loc<Res:'static+Debug+Hash+Eq+Clone> (g:&RefCell<DCG>, loc:&Rc<Loc>) {
+  // A thunk restored by `load` cannot re-evaluate until allocated again (see `Restored`):
+  let restored = match **res_node_of_loc::<Res>(&mut *g.borrow_mut(), loc) {
+    Node::Comp(ref nd) => nd.producer.is_restored(),
+    _ => false,
+  } ;
+  if restored { return }
+  let _ = <DCG as Adapton>::force::<Res>(g, &AbsArt::Loc(loc.clone()));
 }
 
 trait ShapeShifter {
@@ -902,6 +1383,10 @@ impl<Res:Hash> Hash for CompNode<Res> {
 /// `Node::Comp`.
 fn loc_produce<Res:'static+Debug+PartialEq+Eq+Clone+Hash>(g:&RefCell<DCG>, loc:&Rc<Loc>) -> Res
 {
+  // Poll for cancellation (see `with_cancel`) between evaluations:
+  let cancelled = match g.borrow().cancel { Some(ref t) => t.is_cancelled(), None => false } ;
+  if cancelled { panic::resume_unwind(Box::new(Cancelled)) } ;
+  engine_span!("eval", loc, prog_pt_symbol(&mut *g.borrow_mut(), loc)) ;
   let (producer, prev_path) = {
     let st : &mut DCG = &mut *g.borrow_mut() ;
     let succs : Vec<Succ> = {
@@ -931,7 +1416,9 @@ fn loc_produce<Res:'static+Debug+PartialEq+Eq+Clone+Hash>(g:&RefCell<DCG>, loc:&
   /// engine library.  That's why we end the mutable borrow of `g`
   /// above, before making this call.  We re-borrow `g` below, when
   /// the call is complete.
+  let mut guard = ProduceGuard{ g:g, loc:loc.clone(), prev_path:prev_path.clone(), armed:true } ;
   let res = producer.produce() ;
+  guard.armed = false ;
   // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
   let st = &mut * g.borrow_mut() ;
   st.path = prev_path ;
@@ -948,34 +1435,185 @@ fn loc_produce<Res:'static+Debug+PartialEq+Eq+Clone+Hash>(g:&RefCell<DCG>, loc:&
     let succ_node = lookup_abs( st, &succ.loc );
     succ_node.preds_insert( succ.effect.clone(), loc );
   } ;
-  {
+  let (old_res, prog_pt) = {
     let node : &mut Node<Res> = res_node_of_loc( st, loc ) ;
     match *node {
       Node::Comp(ref mut node) => {
         replace(&mut node.succs, frame.succs) ;
-        replace(&mut node.res, Some(res.clone()))
+        (replace(&mut node.res, Some(res.clone())), node.producer.prog_pt().clone())
       },
       _ => panic!("internal error"),
     }
   } ;
+  bounded_insert(st, &prog_pt, loc);
+  if is_observed(st, loc) {
+    notify_observers(st, loc, old_res.map(|old| Box::new(old) as Box<Any>))
+  }
   res
 }
 
+/// Records that the thunk at `loc` (for `prog_pt`) cached its result,
+/// if the cached results for `prog_pt` are bounded; evicts others, as
+/// needed, to keep them within their bound.
+fn bounded_insert (st:&mut DCG, prog_pt:&ProgPt, loc:&Rc<Loc>) {
+  let capacity = match st.bounded.capacity.get(prog_pt) { None => return, Some(c) => *c } ;
+  st.bounded.tick += 1 ;
+  let tick = st.bounded.tick ;
+  let entry = BoundedResult{prog_pt:prog_pt.clone(), tick:tick, referenced:false} ;
+  let old_entry = st.bounded.entries.insert(loc.clone(), entry) ;
+  {
+    let queue = st.bounded.queues.entry(prog_pt.clone()).or_insert_with(BTreeMap::new) ;
+    match old_entry { None => (), Some(old) => { queue.remove(&old.tick); } } ;
+    queue.insert(tick, loc.clone()) ;
+  }
+  bounded_evict(st, prog_pt, capacity)
+}
+
+/// Records that `force` found the cached result of the thunk at
+/// `loc`, for the eviction policy (see `Eviction`).
+fn bounded_touch (st:&mut DCG, loc:&Rc<Loc>) {
+  let policy = st.flags.eviction ;
+  let bounded = &mut st.bounded ;
+  match bounded.entries.get_mut(loc) {
+    None => (),
+    Some(entry) => match policy {
+      Eviction::SecondChance => entry.referenced = true,
+      Eviction::Lru => {
+        bounded.tick += 1 ;
+        let queue = bounded.queues.get_mut(&entry.prog_pt).unwrap() ;
+        queue.remove(&entry.tick) ;
+        queue.insert(bounded.tick, loc.clone()) ;
+        entry.tick = bounded.tick ;
+      }
+    }
+  }
+}
+
+/// Evicts cached results of the thunks for `prog_pt` until at most
+/// `capacity` of them remain.  Evicting a result does not remove its
+/// node, nor the node's edges, so that changes still propagate through
+/// it; the node merely re-evaluates when next demanded.
+fn bounded_evict (st:&mut DCG, prog_pt:&ProgPt, capacity:usize) {
+  loop {
+    let loc = {
+      let queue = match st.bounded.queues.get_mut(prog_pt) { None => return, Some(q) => q } ;
+      if queue.len() <= capacity { return } ;
+      let tick = *queue.keys().next().unwrap() ;
+      queue.remove(&tick).unwrap()
+    } ;
+    let second_chance = st.flags.eviction == Eviction::SecondChance &&
+      replace(&mut st.bounded.entries.get_mut(&loc).unwrap().referenced, false) ;
+    if second_chance {
+      st.bounded.tick += 1 ;
+      let tick = st.bounded.tick ;
+      st.bounded.entries.get_mut(&loc).unwrap().tick = tick ;
+      st.bounded.queues.get_mut(prog_pt).unwrap().insert(tick, loc) ;
+    } else {
+      st.bounded.entries.remove(&loc) ;
+      if lookup_abs(st, &loc).res_clear() { st.cnt.evict += 1 } ;
+    }
+  }
+}
+
+/// Restores the DCG when unwinding (e.g., for `Cancelled`) out of the
+/// producer of the thunk at `loc`: Pops its frame, and discards its
+/// cached result, so that it re-evaluates when next demanded (its
+/// predecessors' edges to it remain dirty, or it was never cached).
+struct ProduceGuard<'a> {
+  g         : &'a RefCell<DCG>,
+  loc       : Rc<Loc>,
+  prev_path : Rc<Path>,
+  armed     : bool,
+}
+
+impl<'a> Drop for ProduceGuard<'a> {
+  fn drop (&mut self) {
+    if !self.armed { return } ;
+    match self.g.try_borrow_mut() {
+      Err(_) => (), // Case: Unwinding from within the engine; its state is lost anyway.
+      Ok(mut st) => {
+        let st = &mut *st ;
+        let frame = st.stack.pop() ;
+        assert!( frame.map_or(false, |f| f.loc == self.loc) ) ;
+        st.path = self.prev_path.clone() ;
+        let _ = lookup_abs(st, &self.loc).res_clear() ;
+      }
+    }
+  }
+}
+
+/// True iff some callbacks observe the node at `loc` (see `observe`).
+fn is_observed (st:&DCG, loc:&Rc<Loc>) -> bool {
+  st.observers.table.contains_key(loc)
+}
+
+/// Queues notifications for the observers of the node at `loc` (see
+/// `observe`) of its new value, given its old one, if any.  The outer
+/// layer runs them; see `run_notifications`.
+fn notify_observers (st:&mut DCG, loc:&Rc<Loc>, old:Option<Box<Any>>) {
+  let observers = match st.observers.table.get(loc) {
+    None => return,
+    Some(obs) => obs.clone(),
+  } ;
+  let new : Rc<Any> = match lookup_abs(st, loc).res_clone() {
+    None => return,
+    Some(new) => Rc::from(new),
+  } ;
+  let old : Option<Rc<Any>> = old.map(Rc::from) ;
+  for o in observers {
+    let (old, new) = (old.clone(), new.clone());
+    st.observers.pending.push(Box::new(move || o.notify(old.as_ref().map(|old| &**old), &*new)))
+  }
+}
+
+/// Runs the pending notifications of observers, unless the DCG is
+/// busy evaluating (in which case, the outer layer runs them later).
+/// The observers' callbacks may use the engine; so, callers release
+/// their borrow of `GLOBALS` first.
+fn run_notifications () {
+  loop {
+    let pending = GLOBALS.with(|g| {
+      match g.borrow().engine {
+        Engine::DCG(ref dcg) => {
+          let st = &mut *dcg.borrow_mut();
+          if st.stack.is_empty() { replace(&mut st.observers.pending, Vec::new()) }
+          else { Vec::new() }
+        },
+        Engine::Naive => Vec::new(),
+      }
+    }) ;
+    if pending.is_empty() { return }
+    for n in pending { n() }
+  }
+}
+
 fn clean_comp<Res:'static+Sized+Debug+PartialEq+Clone+Eq+Hash>
   (g:&RefCell<DCG>,
    this_dep:&ProducerDep<Res>,
    loc:&Rc<Loc>, cache:Res, succs:Vec<Succ>) -> DCGRes
 {
-  for succ in succs.iter() {
+  engine_span!("clean", loc, prog_pt_symbol(&mut *g.borrow_mut(), loc)) ;
+  // Edges with the same effect and target share a dirty bit, but not
+  // necessarily a dependency (e.g., see `force_map`); so, clean them
+  // as a group, in the order of their first edges.
+  let mut groups : Vec<Vec<&Succ>> = Vec::new() ;
+  {
+    let mut group_of : HashMap<(&Effect, &Rc<Loc>), usize> = HashMap::new() ;
+    for succ in succs.iter() {
+      let i = *group_of.entry((&succ.effect, &succ.loc)).or_insert(groups.len()) ;
+      if i == groups.len() { groups.push(vec![succ]) } else { groups[i].push(succ) }
+    }
+  }
+  for group in groups.iter() {
+    let succ = group[0] ;
     let dirty = {
       let mut st = &mut *g.borrow_mut();
       get_succ_mut(st, loc, succ.effect.clone(), &succ.loc).dirty
     } ;
     if dirty {
       dcg_effect_begin!(reflect::trace::Effect::CleanRec, Some(loc), succ);
-      let succ_dep = & succ.dep ;
-      let res = succ_dep.clean(g, &succ.loc) ;
-      if res.changed {        
+      let changed = group.iter().any(|s| s.dep.clean(g, &s.loc).changed) ;
+      if changed {        
         dcg_effect_begin!(reflect::trace::Effect::CleanEval, Some(loc), succ);
         let result : Res = loc_produce( g, loc ) ;
         dcg_effect_end!();
@@ -1011,6 +1649,13 @@ impl <Res:'static+Sized+Debug+PartialEq+Eq+Clone+Hash>
       let st = &mut *g.borrow_mut();
       let node : &mut Node<Res> = res_node_of_loc(st, loc) ;
       match *node {
+        Node::Comp(ref mut nd) if nd.producer.is_restored() => {
+          // Case: Restored by `load`, and not yet allocated again;
+          // lacking its code, it cannot re-evaluate; so, if any of its
+          // edges are dirty, its predecessor must re-evaluate instead.
+          if nd.succs.iter().any(|succ| succ.dirty) { nd.res = None } ;
+          return DCGRes{changed:nd.res.as_ref() != Some(&self.res)}
+        },
         Node::Comp(ref nd) => {
           match nd.res {
             Some(ref res) => Some((res.clone(), nd.succs.clone ())),
@@ -1051,6 +1696,52 @@ impl <Res:'static+Sized+Debug+PartialEq+Eq+Clone+Hash>
   }
 }
 
+/// The dependency of an edge recorded by `force_map`: a projection
+/// of the target's value, and the projected value, to compare against
+/// future projections.
+struct MapDep<T,U> { map:Rc<Fn(&T) -> U>, res:U }
+
+impl<T,U:Debug> Debug for MapDep<T,U> {
+  fn fmt(&self, f:&mut Formatter) -> Result {
+    write!(f, "MapDep{{res:{:?}}}", self.res)
+  }
+}
+
+impl<T:'static+Debug+Eq+Clone+Hash, U:Debug+Eq> DCGDep for MapDep<T,U> {
+  fn clean(self:&Self, g:&RefCell<DCG>, loc:&Rc<Loc>) -> DCGRes {
+    let val : T = loc_value(g, loc) ;
+    DCGRes{changed:(self.map)(&val) != self.res}
+  }
+}
+
+/// The up-to-date value of the node at `loc`, cleaning or
+/// re-evaluating it as needed, like `force`, but without recording an
+/// edge to it.
+fn loc_value<T:'static+Debug+Eq+Clone+Hash> (g:&RefCell<DCG>, loc:&Rc<Loc>) -> T {
+  let cached = {
+    let st = &mut *g.borrow_mut();
+    let node : &mut Node<T> = res_node_of_loc(st, loc) ;
+    match *node {
+      Node::Pure(ref nd) => return nd.val.clone(),
+      Node::Mut(ref nd)  => return nd.val.clone(),
+      Node::Comp(ref nd) => nd.res.clone(),
+      _ => panic!("undefined")
+    }
+  } ;
+  match cached {
+    None => loc_produce(g, loc),
+    Some(res) => {
+      let _ = ProducerDep{res:res}.clean(g, loc) ;
+      let st = &mut *g.borrow_mut();
+      let node : &mut Node<T> = res_node_of_loc(st, loc) ;
+      match *node {
+        Node::Comp(ref nd) => match nd.res { Some(ref res) => res.clone(), None => unreachable!() },
+        _ => unreachable!(),
+      }
+    }
+  }
+}
+
 // ---------- Node implementation:
 
 fn revoke_succs<'x> (st:&mut DCG, src:&Rc<Loc>, succs:&Vec<Succ>) {
@@ -1062,11 +1753,29 @@ fn revoke_succs<'x> (st:&mut DCG, src:&Rc<Loc>, succs:&Vec<Succ>) {
   }
 }
 
+/// The location in the table that equals `loc`, if any, or else
+/// `loc`: The `Art`s of a node share its location, so that
+/// `collect_garbage` can count them.
+fn canonical_loc(st:&DCG, loc:Rc<Loc>) -> Rc<Loc> {
+  match st.table.get_key_value(&loc) { Some((key, _)) => key.clone(), None => loc }
+}
+
 fn loc_of_id(path:Rc<Path>,id:Rc<ArtId>) -> Rc<Loc> {
   let hash = my_hash(&(&path,&id));
   Rc::new(Loc{path:path,id:id,hash:hash})
 }
 
+/// The location of the given reflected location (see `Loc::reflect`).
+fn loc_of_reflect(loc:&reflect::Loc) -> Rc<Loc> {
+  let path = loc.path.iter()
+    .fold(Rc::new(Path::Empty), |p, n| Rc::new(Path::Child(p, n.clone())));
+  let id = match *loc.name.symbol {
+    NameSym::Hash64 => ArtId::Structural(loc.name.hash),
+    _ => ArtId::Nominal(loc.name.clone()),
+  } ;
+  loc_of_id(path, Rc::new(id))
+}
+
 fn get_succ<'r>(st:&'r DCG, src_loc:&Rc<Loc>, eff:Effect, tgt_loc:&Rc<Loc>) -> &'r Succ {
   let nd = st.table.get(src_loc);
   let nd = match nd {
@@ -1094,6 +1803,12 @@ fn get_succ_mut<'r>(st:&'r mut DCG, src_loc:&Rc<Loc>, eff:Effect, tgt_loc:&Rc<Lo
   panic!("tgt_loc is dangling in src_node.dem_succs")
 }
 
+/// The symbol of the program point of the thunk at `loc`, or `""`.
+#[cfg(feature = "tracing")]
+fn prog_pt_symbol (st:&mut DCG, loc:&Rc<Loc>) -> &'static str {
+  lookup_abs(st, loc).prog_pt().map_or("", |p| p.symbol)
+}
+
 fn dirty_pred_observers(st:&mut DCG, loc:&Rc<Loc>) {
   let pred_locs : Vec<Rc<Loc>> = lookup_abs( st, loc ).preds_obs() ;
   let mut dirty_edge_count = 0;
@@ -1116,6 +1831,7 @@ fn dirty_pred_observers(st:&mut DCG, loc:&Rc<Loc>) {
 }
 
 fn dirty_alloc(st:&mut DCG, loc:&Rc<Loc>) {
+  engine_span!("dirty", loc, prog_pt_symbol(st, loc)) ;
   dirty_pred_observers(st, loc);
   let pred_locs : Vec<Rc<Loc>> = lookup_abs(st, loc).preds_alloc() ;
   for pred_loc in pred_locs {
@@ -1137,29 +1853,131 @@ fn dirty_alloc(st:&mut DCG, loc:&Rc<Loc>) {
   }
 }
 
-/// Returns true if changed, false if unchanged.
-fn set_<T:Eq+Debug> (st:&mut DCG, cell:AbsArt<T,Loc>, val:T) {
+/// Dirties the node at `loc` and its transitive observers, like
+/// `dirty_pred_observers`, but also clears their cached results, so
+/// that each re-evaluates when next forced, even if what it observes
+/// compares equal to what it observed before.  Unlike
+/// `dirty_pred_observers`, does not stop at edges that are already
+/// dirty, since their sources may still hold cached results.
+fn invalidate_(st:&mut DCG, loc:&Rc<Loc>) {
+  engine_span!("dirty", loc, prog_pt_symbol(st, loc)) ;
+  let mut visited : HashSet<Rc<Loc>> = HashSet::new();
+  let mut todo = vec![loc.clone()];
+  visited.insert(loc.clone());
+  while let Some(loc) = todo.pop() {
+    let _ = lookup_abs(st, &loc).res_clear();
+    let pred_locs : Vec<Rc<Loc>> = lookup_abs(st, &loc).preds_obs() ;
+    for pred_loc in pred_locs {
+      let was_dirty = {
+        let succ = get_succ_mut(st, &pred_loc, Effect::Observe, &loc) ;
+        dcg_effect!(reflect::trace::Effect::Dirty, Some(&pred_loc), succ);
+        replace(&mut succ.dirty, true)
+      } ;
+      if !was_dirty { st.cnt.dirty += 1 } ;
+      if visited.insert(pred_loc.clone()) { todo.push(pred_loc) }
+    }
+  }
+}
+
+/// For eager propagation (see `Propagation`) of a change to the cell
+/// at `loc`: The thunks that (transitively) observe or allocate the
+/// cell, which no other thunk observes, and which are dirty.
+fn dirty_roots(st:&mut DCG, loc:&Rc<Loc>) -> Vec<(Rc<Loc>, fn(&RefCell<DCG>, &Rc<Loc>))> {
+  let mut roots = Vec::new();
+  let mut visited : HashSet<Rc<Loc>> = HashSet::new();
+  let mut todo = vec![loc.clone()];
+  visited.insert(loc.clone());
+  while let Some(loc) = todo.pop() {
+    let node = lookup_abs(st, &loc);
+    let preds_obs = node.preds_obs();
+    match node.demander() {
+      Some(demand) if preds_obs.is_empty() && node.succs().iter().any(|s| s.dirty) =>
+        roots.push((loc.clone(), demand)),
+      _ => (),
+    }
+    for pred_loc in preds_obs.into_iter().chain(node.preds_alloc().into_iter()) {
+      if visited.insert(pred_loc.clone()) { todo.push(pred_loc) }
+    }
+  }
+  roots
+}
+
+/// Propagates a change to the cell at `loc` by the outer layer,
+/// eagerly, if its propagation policy (see `Propagation`) says to.
+fn propagate(g:&RefCell<DCG>, loc:&Rc<Loc>) {
+  let roots = {
+    let st = &mut *g.borrow_mut();
+    let policy = match st.propagation.get(loc) {
+      Some(p) => *p,
+      None => st.flags.propagation,
+    } ;
+    if policy == Propagation::Lazy || !st.stack.is_empty() { return }
+    dirty_roots(st, loc)
+  } ;
+  for (root, demand) in roots { demand(g, &root) }
+}
+
+/// True iff the node is a thunk with a dirty outgoing edge.
+fn is_dirty (node:&Box<GraphNode>) -> bool {
+  node.demander().is_some() && node.succs().iter().any(|s| s.dirty)
+}
+
+/// For `propagate_with_budget`: The dirty thunks, ordered so that each
+/// follows the dirty thunks that it depends upon (its successors).
+fn dirty_bottom_up (st:&DCG) -> Vec<(Rc<Loc>, fn(&RefCell<DCG>, &Rc<Loc>))> {
+  let dirty : HashSet<Rc<Loc>> =
+    st.table.iter().filter(|&(_, node)| is_dirty(node)).map(|(loc, _)| loc.clone()).collect();
+  let mut order = Vec::new();
+  let mut visited : HashSet<Rc<Loc>> = HashSet::new();
+  for root in dirty.iter() {
+    if !visited.insert(root.clone()) { continue } ;
+    // Depth-first, with the index of the next successor to visit:
+    let mut todo : Vec<(Rc<Loc>, usize)> = vec![(root.clone(), 0)];
+    while let Some((loc, i)) = todo.pop() {
+      let succ = st.table[&loc].succs().get(i).map(|s| s.loc.clone()) ;
+      match succ {
+        None => order.push(loc),
+        Some(succ) => {
+          todo.push((loc, i + 1));
+          if dirty.contains(&succ) && visited.insert(succ.clone()) { todo.push((succ, 0)) }
+        }
+      }
+    }
+  } ;
+  order.into_iter().map(|loc| {
+    let demand = st.table[&loc].demander().unwrap() ;
+    (loc, demand)
+  }).collect()
+}
+
+/// Sets the cell, and dirties its dependents, unless the new value is
+/// equal to the current one and `cutoff` holds; returns whether it
+/// dirtied them.
+fn set_<T:Eq+Debug> (st:&mut DCG, cell:AbsArt<T,Loc>, val:T, cutoff:bool) -> bool {
   if let AbsArt::Loc(ref loc) = cell { 
+    let old = if is_observed(st, loc) { lookup_abs(st, loc).res_clone() } else { None } ;
     let changed : bool = {
       let node = res_node_of_loc( st, loc ) ;
       match **node {
         Node::Mut(ref mut nd) => {
-          if nd.val == val {
+          if cutoff && nd.val == val {
             false
           } else {
-            replace(&mut nd.val, val) ;
+            nd.val = val ;
             true
           }},
         _ => unreachable!(),
       }} ;
     if changed {
+      notify_observers(st, loc, old);
       /// TODO: Dirtying isn't quite necessary for *all* allocations.
       /// Only those that allocated a different value than the present
       /// one--- we should check this, but we do not (we are *too*
       /// conservative at present).
       dirty_alloc(st, loc);
     }
-    else { }
+    else { } ;
+    changed
   }
   else { panic!("{:?} is not a cell", cell) }
 }
@@ -1234,11 +2052,22 @@ impl Adapton for DCG {
         check_dcg_is_wf               : { match env::var("ADAPTON_CHECK_DCG")  { Ok(_) => true,  _ => false } },
         write_dcg                     : { match env::var("ADAPTON_WRITE_DCG")  { Ok(_) => true,  _ => false } },
         gmlog_dcg                     : { match env::var("ADAPTON_GMLOG_DCG")  { Ok(_) => true,  _ => false } },
+        propagation                   : { match env::var("ADAPTON_EAGER")      { Ok(_) => Propagation::Eager,
+                                                                                  _ => Propagation::Lazy } },
+        eviction                      : { match env::var("ADAPTON_SECOND_CHANCE") { Ok(_) => Eviction::SecondChance,
+                                                                                     _ => Eviction::Lru } },
+        track_arts                    : { match env::var("ADAPTON_TRACK_ARTS") { Ok(_) => true,  _ => false } },
       },
       table : table,
       stack : stack,
       path  : path,
       cnt   : Cnt::zero (),
+      observers : Observers{ table:HashMap::new(), pending:Vec::new() },
+      propagation : HashMap::new(),
+      bounded : BoundedResults{ capacity:HashMap::new(), queues:HashMap::new(),
+                         entries:HashMap::new(), tick:0 },
+      cancel : None,
+      suspended : HashMap::new(),
       dcg_count : 0,
       dcg_hash : 0, // XXX This makes assumptions about hashing implementation
     }
@@ -1308,8 +2137,9 @@ impl Adapton for DCG {
         }
       };            
       let hash = my_hash(&(&path,&id));
-      let loc  = Rc::new(Loc{path:path,id:id,hash:hash})
+      let loc  = canonical_loc(self, Rc::new(Loc{path:path,id:id,hash:hash}))
       ;
+      engine_span!("alloc", loc, "") ;
       let (do_dirty, do_set, succs, do_insert) =
         if self.table.contains_key(&loc) {
           let node : &Box<Node<T>> = res_node_of_loc(self, &loc) ;
@@ -1345,7 +2175,7 @@ impl Adapton for DCG {
           dirty:false}
       );      
       if do_dirty { dirty_alloc(self, &loc) } ;
-      if do_set { set_(self, AbsArt::Loc(loc.clone()), val.clone()) } ;
+      if do_set { let _ = set_(self, AbsArt::Loc(loc.clone()), val.clone(), true) ; } ;
       match succs { Some(succs) => revoke_succs(self, &loc, &succs), None => () } ;
       dcg_effect_end!();
       
@@ -1375,7 +2205,7 @@ impl Adapton for DCG {
   fn set<T:Eq+Debug> (self:&mut Self, cell:AbsArt<T,Self::Loc>, val:T) {
     wf::check_dcg(self);
     assert!( self.stack.is_empty() ); // => outer layer has control.
-    set_(self, cell, val);
+    let _ = set_(self, cell, val, true);
     wf::check_dcg(self);
   }
 
@@ -1402,12 +2232,26 @@ impl Adapton for DCG {
       ArtIdChoice::Structural => {
         wf::check_dcg(self);
         let hash = my_hash (&(&prog_pt, &arg)) ;
-        let loc = loc_of_id(current_path(self),
-                            Rc::new(ArtId::Structural(hash)));
+        let loc = canonical_loc(self, loc_of_id(current_path(self),
+                                                Rc::new(ArtId::Structural(hash))));
+        engine_span!("alloc", loc, prog_pt.symbol) ;
         {   // If the node exists, return early.
           let node = self.table.get_mut(&loc);
           match node { None    => { },
-                       Some(_) => { return AbsArt::Loc(loc) }, // Nothing to do; it already exists.
+                       Some(node) => {
+                         // Nothing to do; it already exists.  Unless it was restored by
+                         // `load`, in which case, it gets its code (see `Restored`).
+                         let node: &mut Box<Node<Res>> = unsafe { transmute::<_,_>( node ) } ;
+                         if let Node::Comp(ref mut nd) = **node {
+                           if nd.producer.is_restored() {
+                             nd.producer = Box::new(App{prog_pt:prog_pt,
+                                                        fn_box:fn_box,
+                                                        arg:arg,
+                                                        spurious:spurious})
+                           }
+                         } ;
+                         return AbsArt::Loc(loc)
+                       },
           }
         } ;
         // assert: node does not exist.
@@ -1442,8 +2286,9 @@ impl Adapton for DCG {
 
       ArtIdChoice::Nominal(nm) => {
         wf::check_dcg(self);
-        let loc = loc_of_id(current_path(self),
-                            Rc::new(ArtId::Nominal(nm)));
+        let loc = canonical_loc(self, loc_of_id(current_path(self),
+                                                Rc::new(ArtId::Nominal(nm))));
+        engine_span!("alloc", loc, prog_pt.symbol) ;
         let producer : App<Arg,Spurious,Res> =
           App{prog_pt:prog_pt.clone(),
               fn_box:fn_box,
@@ -1464,6 +2309,15 @@ impl Adapton for DCG {
               Node::Mut(_) => {
                 (true, true) // Todo: Do we need to preserve preds?
               },
+              Node::Comp(ref mut comp_nd) if comp_nd.producer.is_restored() => {
+                // Case: Restored by `load`: It gets its code, and keeps
+                // its result if the argument is the same (see `Restored`).
+                let same_arg = comp_nd.producer.prog_pt() == producer.prog_pt() &&
+                  comp_nd.producer.arg_hash() == my_hash(&arg) ;
+                comp_nd.producer = Box::new(producer.clone()) ;
+                if same_arg { (false, false) }
+                else { comp_nd.res = None ; (true, false) }
+              },
               Node::Comp(ref mut comp_nd) => {
                 let equal_producer_prog_pts : bool =
                   comp_nd.producer.prog_pt().eq( producer.prog_pt() ) ;
@@ -1561,6 +2415,7 @@ impl Adapton for DCG {
     match *art {
       AbsArt::Rc(ref v) => (**v).clone(),
       AbsArt::Loc(ref loc) => {
+        engine_span!("force", loc, prog_pt_symbol(&mut *g.borrow_mut(), loc)) ;
         let (is_comp, is_pure, cached_result) : (bool, bool, Option<T>) = {
           let st : &mut DCG = &mut *g.borrow_mut();
           let is_pure_opt : bool = st.flags.use_purity_optimization ;
@@ -1608,6 +2463,8 @@ impl Adapton for DCG {
               let _ = ProducerDep{res:res.clone()}.clean(g, &loc) ;
               dcg_effect_end!();
               let st : &mut DCG = &mut *g.borrow_mut();
+              st.cnt.hit += 1;
+              bounded_touch(st, &loc);
               let node : &mut Node<T> = res_node_of_loc(st, &loc) ;
               match *node {
                 Node::Comp(ref nd) => match nd.res {
@@ -1671,6 +2528,23 @@ pub struct Art<T> {
   art:EnumArt<T>,
 }
 
+/// Serializes the content of the art, forcing it.
+#[cfg(feature = "serde")]
+impl<T:Hash+Eq+Debug+Clone+'static+Serialize> Serialize for Art<T> {
+  fn serialize<S:Serializer>(&self, serializer:S) -> ::std::result::Result<S::Ok, S::Error> {
+    force(self).serialize(serializer)
+  }
+}
+
+/// Deserializes the content of an art, and holds it in a new art, as
+/// `put` does; the name of the original art, if any, is not recorded.
+#[cfg(feature = "serde")]
+impl<'de, T:Eq+Debug+Clone+Deserialize<'de>> Deserialize<'de> for Art<T> {
+  fn deserialize<D:Deserializer<'de>>(deserializer:D) -> ::std::result::Result<Self, D::Error> {
+    T::deserialize(deserializer).map(put)
+  }
+}
+
 #[derive(Clone)]
 enum EnumArt<T> {
   /// No entry in table. No dependency tracking.
@@ -1852,6 +2726,16 @@ pub fn name_of_str (s:&'static str) -> Name {
   Name{ hash:h, symbol:Rc::new(s) }
 }
 
+/// Create a name from a path of strings, e.g., `&["module", "structure"]`
+/// (nested pairs of names, from the left); the empty path gives `name_unit()`.
+pub fn name_of_path (path:&[&str]) -> Name {
+  let mut names = path.iter().map(|s| name_of_string(s.to_string())) ;
+  match names.next() {
+    None => name_unit(),
+    Some(n) => names.fold(n, name_pair),
+  }
+}
+
 /// Create two names from one
 pub fn name_fork (n:Name) -> (Name, Name) {
   let h1 = my_hash( &(&n, 11111111) ) ; // TODO-Later: make this hashing better.
@@ -1892,6 +2776,117 @@ pub fn ns<T,F> (n:Name, body:F) -> T
     })   
   }
 
+/// The name of the current namespace (see `ns`), for logging and
+/// debugging: `name_unit()` outside of any namespace; within nested
+/// namespaces, nested pairs of their names, from the outermost (e.g.,
+/// `name_of_path(&["a", "b"])` within namespaces `a`, then `b`).  The
+/// `Naive` engine does not track namespaces; for it, this is always
+/// `name_unit()`.
+pub fn current_namespace () -> Name {
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => name_of_ns_path(&*dcg.borrow().path),
+      Engine::Naive => name_unit(),
+    }
+  })
+}
+
+/// The given name, qualified by the name of the current namespace
+/// (see `current_namespace`).
+pub fn name_under_current_ns (n:Name) -> Name {
+  name_pair(current_namespace(), n)
+}
+
+fn name_of_ns_path (path:&Path) -> Name {
+  match *path {
+    Path::Empty => name_unit(),
+    Path::Child(ref p, ref n) => match **p {
+      Path::Empty => n.clone(),
+      _ => name_pair(name_of_ns_path(p), n.clone()),
+    }
+  }
+}
+
+/// Creates or re-enters a given namespace, like `ns`; performs the
+/// given computation there, passing it a `Namer` for the namespace's
+/// name, from which it may derive distinct sub-names and
+/// sub-namespaces (e.g., for its left and right recursive calls).
+pub fn with_ns<T,F> (n:Name, body:F) -> T
+  where F:FnOnce(Namer) -> T {
+    ns(n.clone(), || body(Namer::new(n)))
+  }
+
+/// Derives distinct names, and the namespaces that they identify,
+/// from a single name.  Combinators that allocate in more than one
+/// place (e.g., once per branch of a binary fold) accept a `Namer`,
+/// and `fork` it, rather than reuse one name (such as `name_unit()`)
+/// for each place; two such reuses, by two different combinators
+/// over the same input, would otherwise collide.
+#[derive(Hash,Debug,PartialEq,Eq,Clone)]
+pub struct Namer {
+  name : Name,
+}
+
+impl Namer {
+  /// A namer for the given name.
+  pub fn new (n:Name) -> Namer { Namer{ name:n } }
+  /// A namer for the given string.
+  pub fn of_str (s:&'static str) -> Namer { Namer::new(name_of_str(s)) }
+  /// The name of this namer.
+  pub fn name (&self) -> Name { self.name.clone() }
+  /// Two distinct namers, e.g., for left and right recursive calls.
+  pub fn fork (&self) -> (Namer, Namer) {
+    let (n1,n2) = name_fork(self.name.clone());
+    (Namer::new(n1), Namer::new(n2))
+  }
+  /// Three distinct namers.
+  pub fn fork3 (&self) -> (Namer, Namer, Namer) {
+    let (n1,n2,n3) = name_fork3(self.name.clone());
+    (Namer::new(n1), Namer::new(n2), Namer::new(n3))
+  }
+  /// A namer for the `i`th of a family of sub-names.
+  pub fn child (&self, i:usize) -> Namer {
+    Namer::new(name_pair(self.name.clone(), name_of_usize(i)))
+  }
+  /// Creates or re-enters the namespace of this namer; performs the
+  /// given computation there.
+  pub fn ns<T,F> (&self, body:F) -> T
+    where F:FnOnce() -> T {
+      ns(self.name.clone(), body)
+    }
+}
+
+/// Generates fresh names, by counting: The `i`th name from a
+/// generator seeded with name `n` is `name_pair(n, name_of_usize(i))`.
+/// Code that builds large structures may thread a generator (or one
+/// forked from it, for each sub-structure) rather than derive names
+/// from loop indices, which collide across structures that share a
+/// namespace.
+#[derive(Hash,Debug,PartialEq,Eq,Clone)]
+pub struct NameGen {
+  seed : Name,
+  next : usize,
+}
+
+impl NameGen {
+  /// A generator seeded with the given name.
+  pub fn new (seed:Name) -> NameGen { NameGen{ seed:seed, next:0 } }
+  /// A generator seeded with the given string.
+  pub fn of_str (s:&'static str) -> NameGen { NameGen::new(name_of_str(s)) }
+  /// The number of names (and forks) generated so far.
+  pub fn count (&self) -> usize { self.next }
+  /// A fresh name, distinct from the others of this generator.
+  pub fn fresh (&mut self) -> Name {
+    let n = name_pair(self.seed.clone(), name_of_usize(self.next)) ;
+    self.next += 1 ;
+    n
+  }
+  /// A generator seeded with a fresh name; its names are distinct
+  /// from those of this generator, and of its other forks.
+  pub fn fork (&mut self) -> NameGen { NameGen::new(self.fresh()) }
+}
+
+
 /// Enters a special "namespace" where all name uses are ignored; instead, Adapton uses structural identity.
 pub fn structural<T,F> (body:F) -> T
   where F:FnOnce() -> T {
@@ -1916,16 +2911,24 @@ pub fn put<T:Eq+Debug+Clone> (val:T) -> Art<T> {
 /// archivist's perspective, this cell is a "one-shot" reference cell:
 /// Once allocated, it is immutable.
 pub fn cell<T:Hash+Eq+Debug+Clone+'static> (n:Name, val:T) -> Art<T> {
-  GLOBALS.with(|g| {
+  let art = GLOBALS.with(|g| {
     match g.borrow().engine {
       Engine::DCG(ref dcg) => {
-        if
-          let AbsArt::Loc(loc) = (dcg.borrow_mut()).cell(n,val) {
-            Art{art:EnumArt::Loc(loc)} }
+        let recorded = if is_recording() { Some(val.clone()) } else { None } ;
+        let art = (dcg.borrow_mut()).cell(n,val);
+        if let (&AbsArt::Loc(ref loc), Some(val)) = (&art, recorded) {
+          record(dcg, replay::Op::Cell{ loc:loc.reflect(), ty:type_name::<T>().to_string(),
+                                        val:reflect::reflect_val(&val) })
+        }
+        if let AbsArt::Loc(ref loc) = art { propagate(dcg, loc) }
+        if let AbsArt::Loc(loc) = art {
+          Art{art:EnumArt::Loc(loc)} }
         else { unreachable!() } }
       Engine::Naive => Art{art:EnumArt::Rc(Rc::new(val))}
     }
-  })
+  });
+  run_notifications();
+  art
 }
 
 /// Mutates a mutable articulation.
@@ -1938,24 +2941,83 @@ pub fn set<T:Eq+Debug+Clone> (a:&Art<T>, val:T) {
         match g.borrow().engine {
           Engine::Naive => unimplemented!(), // TODO: Think more about this case.
           Engine::DCG(ref dcg) => {
-            (dcg.borrow_mut()).set(AbsArt::Loc(l.clone()), val)
+            if is_recording() {
+              record(dcg, replay::Op::Set{ loc:l.reflect(), ty:type_name::<T>().to_string(),
+                                           val:reflect::reflect_val(&val) })
+            }
+            (dcg.borrow_mut()).set(AbsArt::Loc(l.clone()), val);
+            propagate(dcg, l)
           }
         }
-      })
+      });
+      run_notifications()
     }
   }
 }
 
-/// Allocates a thunk, an `Art<T>` that consists of a suspended
-/// computation that produces a value of type `T`.
-///
-/// Use the macros `thunk!`, `memo!` and `eager!` to create and force
-/// thunks with less typing.
-///
-/// A full invocation of `thunk` consists of the following:
-///
-///  - It has an `id` of type `ArtIdChoice`, either giving a `Name`,
-///    requesting structural identity, or requesting no caching
+/// Mutates a mutable articulation, like `set`, but without comparing
+/// the new value to the current one: Its dependents re-evaluate, as
+/// though invalidated (see `invalidate`), even if the two are equal
+/// (per `Eq`).  Use this for types whose `Eq` does not capture every
+/// change that matters.
+pub fn set_always<T:Eq+Debug+Clone+'static> (a:&Art<T>, val:T) {
+  match (*a).art {
+    EnumArt::Rc(_)    => { panic!("set_always: Cannot mutate immutable Rc articulation; use an DCG cell instead") },
+    EnumArt::Force(_) => { panic!("set_always: Cannot mutate immutable Force articulation; use an DCG cell instead") },
+    EnumArt::Loc(ref l) => {
+      GLOBALS.with(|g| {
+        match g.borrow().engine {
+          Engine::Naive => unimplemented!(),
+          Engine::DCG(ref dcg) => {
+            {
+              let st = &mut *dcg.borrow_mut();
+              assert!( st.stack.is_empty() ); // => outer layer has control.
+              let _ = set_(st, AbsArt::Loc(l.clone()), val, false);
+              invalidate_(st, l);
+              wf::check_dcg(st);
+            }
+            propagate(dcg, l)
+          }
+        }
+      });
+      run_notifications()
+    }
+  }
+}
+
+/// Allocates (or re-allocates) the cell with the given name, in the
+/// current namespace, like `cell`, and returns whether this changed
+/// anything: false iff the cell already holds an equal value (per
+/// `Eq`), in which case nothing is dirtied.  Use this to re-feed input
+/// values, some of which may be unchanged, from the outer layer;
+/// within a thunk, and for the `Naive` engine, it always returns true.
+pub fn set_cell_if_changed<T:Hash+Eq+Debug+Clone+'static> (n:Name, val:T) -> bool {
+  let unchanged = GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => {
+        let st = &mut *dcg.borrow_mut();
+        let loc = loc_of_id(current_path(st), Rc::new(ArtId::Nominal(n.clone())));
+        st.stack.is_empty() && st.table.contains_key(&loc) && {
+          let node : &mut Node<T> = res_node_of_loc(st, &loc) ;
+          match *node { Node::Mut(ref nd) => nd.val == val, _ => false }
+        }
+      },
+      Engine::Naive => false,
+    }
+  });
+  if unchanged { false } else { let _ = cell(n, val); true }
+}
+
+/// Allocates a thunk, an `Art<T>` that consists of a suspended
+/// computation that produces a value of type `T`.
+///
+/// Use the macros `thunk!`, `memo!` and `eager!` to create and force
+/// thunks with less typing.
+///
+/// A full invocation of `thunk` consists of the following:
+///
+///  - It has an `id` of type `ArtIdChoice`, either giving a `Name`,
+///    requesting structural identity, or requesting no caching
 ///    whatsoever.
 ///
 ///  - Its code resides at a _program point_, of type `ProgPt`.  This
@@ -1992,10 +3054,16 @@ pub fn thunk<Arg:Hash+Eq+Debug+Clone+'static,Spurious:Clone+'static,Res:Hash+Eq+
   GLOBALS.with(|g| {
     match g.borrow().engine {
       Engine::DCG(ref dcg) => {
-        Art{art:EnumArt::Loc({
+        let recorded = if is_recording() { Some((prog_pt.clone(), arg.clone())) } else { None } ;
+        let loc = {
           if let AbsArt::Loc(loc) = 
             (dcg.borrow_mut()).thunk(id, prog_pt, fn_box, arg, spurious)
-          { loc } else { unreachable!() }})}              
+          { loc } else { unreachable!() }} ;
+        if let Some((prog_pt, arg)) = recorded {
+          record(dcg, replay::Op::Thunk{ loc:loc.reflect(), ty:type_name::<Res>().to_string(),
+                                         prog_pt:prog_pt, arg:reflect::reflect_val(&arg) })
+        }
+        Art{art:EnumArt::Loc(loc)}
       },
       Engine::Naive => {
         Art{art:EnumArt::Force(
@@ -2006,22 +3074,893 @@ pub fn thunk<Arg:Hash+Eq+Debug+Clone+'static,Spurious:Clone+'static,Res:Hash+Eq+
   })
 }
 
+/// Demands the value of an `&Art<T>`, and returns its projection by
+/// `map`, observing only this projection: Unlike after `force`, the
+/// current thunk is not re-evaluated for changes to the value that
+/// leave the projection unchanged (per `Eq` on `U`), e.g., changes to
+/// the other fields of a large structure.
+pub fn force_map<T,U,F> (a:&Art<T>, map:F) -> U
+  where T:Hash+Eq+Debug+Clone+'static,
+        U:Hash+Eq+Debug+Clone+'static,
+        F:Fn(&T) -> U + 'static
+{
+  match a.art {
+    EnumArt::Force(ref f) => map(&f.force()),
+    EnumArt::Rc(ref rc) => map(&**rc),
+    EnumArt::Loc(ref loc) => {
+      let res = GLOBALS.with(|g| {
+        match g.borrow().engine {
+          Engine::DCG(ref dcg_refcell) => {
+            let val = <DCG as Adapton>::force(dcg_refcell, &AbsArt::Loc(loc.clone()));
+            let res = map(&val);
+            { // Replace the dependency of the edge that force recorded, if any:
+              let st = &mut *dcg_refcell.borrow_mut();
+              match st.stack.last_mut() { None => (), Some(frame) => {
+                match frame.succs.last_mut() {
+                  Some(ref mut succ) if succ.effect == Effect::Observe && &succ.loc == loc => {
+                    succ.dep = Rc::new(Box::new(MapDep{map:Rc::new(map), res:res.clone()}))
+                  },
+                  _ => (), // Case: No edge, by the purity optimization
+                }
+              }}
+            }
+            res
+          },
+          Engine::Naive => panic!("cannot force a non-naive location with the naive engine")
+      }});
+      run_notifications();
+      res
+    }
+  }
+}
+
 /// Demands and observes the value of an `&Art<T>`, returning a (cloned) value of type `T`.
 pub fn force<T:Hash+Eq+Debug+Clone+'static> (a:&Art<T>) -> T {
   match a.art {
     EnumArt::Force(ref f) => f.force(),
     EnumArt::Rc(ref rc) => (&**rc).clone(),
     EnumArt::Loc(ref loc) => {
-      GLOBALS.with(|g| {
+      let res = GLOBALS.with(|g| {
         match g.borrow().engine {
-          Engine::DCG(ref dcg_refcell) => 
-            <DCG as Adapton>::force(dcg_refcell, &AbsArt::Loc(loc.clone())),
+          Engine::DCG(ref dcg_refcell) => {
+            let res = <DCG as Adapton>::force(dcg_refcell, &AbsArt::Loc(loc.clone()));
+            if is_recording() {
+              record(dcg_refcell, replay::Op::Force{ loc:loc.reflect(),
+                                                     ty:type_name::<T>().to_string(),
+                                                     val:reflect::reflect_val(&res) })
+            }
+            res
+          },
           Engine::Naive => panic!("cannot force a non-naive location with the naive engine")
-      }})
+      }});
+      run_notifications();
+      res
     }
   }
 }
 
+/// The engine's counts (see `Cnt`) since it was initialized, or
+/// since the last call to `reset_stats`: thunks (and cells) created,
+/// memo hits, evaluations (and re-evaluations) of thunks, and edges
+/// dirtied and cleaned.  Unlike `manage::cnt`, these counts span
+/// several calls into the engine, e.g., before and after a change to
+/// its input.  The `Naive` engine counts nothing.
+pub fn stats () -> Cnt {
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => dcg.borrow().cnt.clone(),
+      Engine::Naive => Cnt::zero(),
+    }
+  })
+}
+
+/// Resets the counts returned by `stats` to zero.
+pub fn reset_stats () {
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => dcg.borrow_mut().cnt = Cnt::zero(),
+      Engine::Naive => (),
+    }
+  })
+}
+
+/// Overrides the engine's propagation policy (see `Propagation` and
+/// `manage::set_propagation`) for changes to the given cell; `None`
+/// removes the override.  Does nothing if the engine is `Naive`.
+pub fn set_cell_propagation<T> (cell:&Art<T>, policy:Option<Propagation>) {
+  match cell.art {
+    EnumArt::Loc(ref loc) => {
+      GLOBALS.with(|g| {
+        match g.borrow().engine {
+          Engine::DCG(ref dcg) => {
+            let st = &mut *dcg.borrow_mut();
+            match policy {
+              Some(p) => { st.propagation.insert(loc.clone(), p); },
+              None    => { st.propagation.remove(loc); },
+            }
+          },
+          Engine::Naive => (),
+        }
+      })
+    },
+    _ => panic!("set_cell_propagation: not a DCG cell"),
+  }
+}
+
+/// Registers a callback for the cell or thunk with the given name, in
+/// the current namespace, and with values of type `T`: Whenever its
+/// value changes (by `set`, by a `cell` that reallocates it, or by
+/// re-evaluation during change propagation), the engine calls back
+/// with the new value, once the outer layer regains control (e.g., at
+/// the end of the `set` or `force` that changed it).  Callbacks may
+/// use the engine, e.g., to `force` other thunks.  The first call
+/// comes with the first value (or the first changed value) after
+/// registration.  Returns false (and does nothing) if no cell or
+/// thunk has this name, or if the engine is `Naive`.  The engine does
+/// not check that `T` is the type of the node's values; callbacks
+/// of another type are never called.
+pub fn observe<T:Eq+Clone+'static, F:Fn(&T)+'static> (name:&Name, callback:F) -> bool {
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => {
+        let st : &mut DCG = &mut *dcg.borrow_mut();
+        let loc = loc_of_id(current_path(st), Rc::new(ArtId::Nominal(name.clone())));
+        if !st.table.contains_key(&loc) { return false }
+        let loc = canonical_loc(st, loc);
+        let observer : Rc<AnyObserver> =
+          Rc::new(Observer{ last:RefCell::new(None), callback:Box::new(callback) });
+        st.observers.table.entry(loc).or_insert(Vec::new()).push(observer);
+        true
+      },
+      Engine::Naive => false,
+    }
+  })
+}
+
+/// A token for cancelling recomputation; see `with_cancel`.  Clones
+/// share the token, and may cancel it from other threads.
+#[derive(Debug,Clone,Default)]
+pub struct CancelToken {
+  cancelled : Arc<AtomicBool>,
+}
+
+impl CancelToken {
+  /// A token that is not (yet) cancelled.
+  pub fn new () -> CancelToken { CancelToken::default() }
+  /// Cancels the computations that poll this token.
+  pub fn cancel (&self) { self.cancelled.store(true, Ordering::SeqCst) }
+  /// True iff the token is cancelled.
+  pub fn is_cancelled (&self) -> bool { self.cancelled.load(Ordering::SeqCst) }
+}
+
+/// The error of a computation cancelled by its `CancelToken`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Cancelled;
+
+/// Performs the given computation (e.g., forcing an output, or
+/// `propagate_with_budget`), aborting it if the token is cancelled:
+/// Before evaluating each thunk, the engine polls the token; once it
+/// is cancelled, the engine unwinds the computation, and this returns
+/// `Err(Cancelled)`.  The thunks whose evaluations were aborted lose
+/// their cached results, and the edges that were not yet cleaned
+/// remain dirty; so, the DCG is consistent, and the next demand
+/// resumes the work (e.g., for a newer edit, after cancelling the
+/// work for an older one).  Like `set`, use this outside of thunks.
+/// The `Naive` engine does not poll the token.
+pub fn with_cancel<T,F> (token:&CancelToken, body:F) -> ::std::result::Result<T, Cancelled>
+  where F:FnOnce() -> T {
+    let saved = GLOBALS.with(|g| {
+      match g.borrow().engine {
+        Engine::DCG(ref dcg) => Some(replace(&mut dcg.borrow_mut().cancel, Some(token.clone()))),
+        Engine::Naive => None,
+      }
+    }) ;
+    let saved = match saved { None => return Ok(body()), Some(saved) => saved } ;
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(body)) ;
+    GLOBALS.with(|g| {
+      if let Engine::DCG(ref dcg) = g.borrow().engine { dcg.borrow_mut().cancel = saved }
+    }) ;
+    match res {
+      Ok(x) => Ok(x),
+      Err(payload) =>
+        if payload.is::<Cancelled>() { Err(Cancelled) } else { panic::resume_unwind(payload) }
+    }
+  }
+
+/// The panic payload with which an async thunk unwinds the engine
+/// when its body is not yet ready (see `force_async`).
+struct AsyncPending;
+
+/// The body of an async thunk, suspended at an await point, with its
+/// argument and the edges that it recorded before this point.
+struct Suspended<Arg,T> {
+  arg   : Arg,
+  fut   : Pin<Box<Future<Output=T>>>,
+  succs : Vec<Succ>,
+}
+
+thread_local!(static ASYNC_WAKER: RefCell<Option<Waker>> = RefCell::new(None));
+
+/// Wakes a thread blocked in `block_on`.
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+  fn wake (self:Arc<Self>) { self.0.unpark() }
+}
+
+/// Polls the future to completion, parking the current thread
+/// between polls.
+fn block_on<F:Future+Unpin> (mut fut:F) -> F::Output {
+  let waker = Waker::from(Arc::new(ThreadWaker(thread::current()))) ;
+  let mut cx = Context::from_waker(&waker) ;
+  loop {
+    match Pin::new(&mut fut).poll(&mut cx) {
+      Poll::Ready(res) => return res,
+      Poll::Pending => thread::park(),
+    }
+  }
+}
+
+/// Allocates an async thunk: Like `thunk`, except that its body
+/// returns a future, which may await I/O (e.g., from the network or
+/// from files).  When demanded by `force_async`, and when its body
+/// is not ready, the engine suspends this body and unwinds the
+/// demand, leaving the DCG consistent-but-dirty (as with
+/// `with_cancel`); when polled again, the engine resumes the
+/// suspended body, with the dependencies that it recorded before its
+/// await point.  If these dependencies changed in the meantime, the
+/// engine discards the suspended body, and starts it afresh.  When
+/// demanded by `force` (or by the `Naive` engine), this blocks the
+/// current thread until the body is ready, which requires that its
+/// futures do not depend on an executor of this thread.
+pub fn thunk_async<Arg,T,Fut>
+  (id:ArtIdChoice, prog_pt:ProgPt, body:Rc<Box<Fn(Arg) -> Fut>>, arg:Arg) -> Art<T>
+  where Arg:Hash+Eq+Debug+Clone+'static,
+        T:Hash+Eq+Debug+Clone+'static,
+        Fut:Future<Output=T>+'static
+{
+  thunk(id, prog_pt, Rc::new(Box::new(async_produce::<Arg,T,Fut>)), arg, body)
+}
+
+/// The producer of an async thunk; see `thunk_async`.
+fn async_produce<Arg,T,Fut> (arg:Arg, body:Rc<Box<Fn(Arg) -> Fut>>) -> T
+  where Arg:Hash+Eq+Debug+Clone+'static,
+        T:Hash+Eq+Debug+Clone+'static,
+        Fut:Future<Output=T>+'static
+{
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => async_produce_dcg(dcg, arg, body),
+      Engine::Naive => block_on(Box::pin(body(arg))),
+    }
+  })
+}
+
+/// `async_produce`, within the DCG engine.
+fn async_produce_dcg<Arg,T,Fut> (dcg:&RefCell<DCG>, arg:Arg, body:Rc<Box<Fn(Arg) -> Fut>>) -> T
+  where Arg:Hash+Eq+Debug+Clone+'static,
+        T:Hash+Eq+Debug+Clone+'static,
+        Fut:Future<Output=T>+'static
+{
+  let loc = match dcg.borrow().stack.last() {
+    None => panic!("internal error: expected the frame of an async thunk"),
+    Some(frame) => frame.loc.clone(),
+  } ;
+  let suspended = dcg.borrow_mut().suspended.remove(&loc) ;
+  let suspended = match suspended.map(|s| s.downcast::<Suspended<Arg,T>>()) {
+    Some(Ok(s)) => {
+      let changed = s.succs.iter().any(|succ| succ.dep.clean(dcg, &succ.loc).changed) ;
+      if s.arg == arg && !changed { Some(*s) } else { None }
+    },
+    _ => None,
+  } ;
+  let mut fut = match suspended {
+    None => Box::pin(body(arg.clone())) as Pin<Box<Future<Output=T>>>,
+    Some(s) => {
+      // Resume with the edges recorded before the await point:
+      let st = &mut *dcg.borrow_mut() ;
+      st.stack.last_mut().unwrap().succs.extend(s.succs) ;
+      s.fut
+    }
+  } ;
+  let waker = ASYNC_WAKER.with(|w| w.borrow().clone()) ;
+  let waker = match waker { None => return block_on(fut), Some(waker) => waker } ;
+  match fut.as_mut().poll(&mut Context::from_waker(&waker)) {
+    Poll::Ready(res) => res,
+    Poll::Pending => {
+      {
+        let st = &mut *dcg.borrow_mut() ;
+        let succs = replace(&mut st.stack.last_mut().unwrap().succs, Vec::new()) ;
+        st.suspended.insert(loc, Box::new(Suspended{arg:arg, fut:fut, succs:succs})) ;
+      }
+      panic::resume_unwind(Box::new(AsyncPending))
+    }
+  }
+}
+
+/// The future of `force_async`.
+struct AsyncForce<T> { art:Art<T> }
+
+impl<T:Hash+Eq+Debug+Clone+'static> Future for AsyncForce<T> {
+  type Output = T;
+  fn poll (self:Pin<&mut Self>, cx:&mut Context) -> Poll<T> {
+    let saved = ASYNC_WAKER.with(|w| replace(&mut *w.borrow_mut(), Some(cx.waker().clone()))) ;
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| force(&self.art))) ;
+    ASYNC_WAKER.with(|w| *w.borrow_mut() = saved) ;
+    match res {
+      Ok(res) => Poll::Ready(res),
+      Err(payload) =>
+        if payload.is::<AsyncPending>() { Poll::Pending } else { panic::resume_unwind(payload) }
+    }
+  }
+}
+
+/// Demands the value of the given `Art`, like `force`, without
+/// blocking on the async thunks (see `thunk_async`) that it demands:
+/// Each poll of the future re-demands this value, reusing the work
+/// of the prior polls, and resuming the suspended async thunks.
+/// Within the body of an async thunk, use this to await the value of
+/// another async thunk, without restarting this body.
+pub fn force_async<T:Hash+Eq+Debug+Clone+'static> (a:&Art<T>) -> impl Future<Output=T> {
+  AsyncForce{ art:a.clone() }
+}
+
+/// A budget for `propagate_with_budget`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Budget {
+  /// Clean (or re-evaluate) at most this many dirty thunks
+  Nodes(usize),
+  /// Stop once this much time elapses; the time is checked between
+  /// thunks, so the last one may overrun it
+  Time(Duration),
+}
+
+/// The work that remains after `propagate_with_budget`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum PropagationStatus {
+  /// No thunk is dirty
+  Done,
+  /// The budget ran out, with this many thunks still dirty
+  Pending(usize),
+}
+
+/// Propagates the changes made so far, by cleaning (or re-evaluating)
+/// the dirty thunks, from those nearest to the changes outward, until
+/// none is dirty, or until the budget runs out: Interactive
+/// applications can thus propagate within a frame deadline, and resume
+/// later, by calling this again, or by demanding their outputs.  Like
+/// `set`, use this outside of thunks.  Unlike `force`, this cleans
+/// every dirty thunk, including those that nothing will demand again.
+/// For the `Naive` engine, there is nothing to do.
+pub fn propagate_with_budget (budget:Budget) -> PropagationStatus {
+  let status = GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => {
+        assert!( dcg.borrow().stack.is_empty() ); // => outer layer has control.
+        let start = Instant::now();
+        let mut cleaned = 0;
+        let status = 'propagate: loop {
+          // Re-evaluations may dirty other thunks, by re-allocating.
+          let order = dirty_bottom_up(&*dcg.borrow());
+          if order.is_empty() { break PropagationStatus::Done } ;
+          for &(ref loc, demand) in order.iter() {
+            let exhausted = match budget {
+              Budget::Nodes(n) => cleaned >= n,
+              Budget::Time(d)  => start.elapsed() >= d,
+            } ;
+            if exhausted {
+              let pending = dcg.borrow().table.values().filter(|node| is_dirty(node)).count();
+              break 'propagate PropagationStatus::Pending(pending)
+            } ;
+            if is_dirty(&dcg.borrow().table[loc]) {
+              demand(dcg, loc);
+              cleaned += 1;
+            }
+          }
+        } ;
+        match status {
+          PropagationStatus::Pending(0) => PropagationStatus::Done,
+          status => status,
+        }
+      },
+      Engine::Naive => PropagationStatus::Done,
+    }
+  });
+  run_notifications();
+  status
+}
+
+/// The provenance of a DCG node, as listed by `dirty_nodes`.
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub enum NameOrProgPt {
+  /// A node with a `Nominal` identity: its namespace (see
+  /// `current_namespace`), and its name there
+  Name{ ns:Name, name:Name },
+  /// A node with a `Structural` identity: the program point of its
+  /// computation
+  ProgPt(ProgPt),
+}
+
+/// The thunks that are currently dirty, i.e., that some change (by
+/// `set`, `cell` or `invalidate`) may affect, and that have not yet
+/// been cleaned or re-evaluated, by demand: Use this to see what an
+/// edit invalidated, before forcing the outputs.  The list is sorted
+/// by DCG location; for the `Naive` engine, it is empty.
+pub fn dirty_nodes () -> Vec<NameOrProgPt> {
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => {
+        let st = &*dcg.borrow();
+        let mut nodes : Vec<(String, NameOrProgPt)> = Vec::new();
+        for (loc, node) in st.table.iter() {
+          if !is_dirty(node) { continue } ;
+          let prog_pt = node.prog_pt().unwrap() ;
+          let provenance = match *loc.id {
+            ArtId::Nominal(ref n) =>
+              NameOrProgPt::Name{ ns:name_of_ns_path(&loc.path), name:n.clone() },
+            ArtId::Structural(_) => NameOrProgPt::ProgPt(prog_pt.clone()),
+          } ;
+          nodes.push((format!("{:?}", loc), provenance))
+        } ;
+        nodes.sort_by(|a,b| a.0.cmp(&b.0));
+        nodes.into_iter().map(|(_, p)| p).collect()
+      },
+      Engine::Naive => Vec::new(),
+    }
+  })
+}
+
+/// Approximate memory use of a group of DCG nodes; see `memory_stats`.
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq,Hash)]
+pub struct MemoryUse {
+  /// Number of cells
+  pub cells  : usize,
+  /// Number of thunks
+  pub thunks : usize,
+  /// Number of thunks with cached results, i.e., memo table entries
+  pub cached : usize,
+  /// Number of edges from the thunks to their successors
+  pub edges  : usize,
+  /// Approximate number of bytes that the DCG holds for these nodes
+  /// and edges: Excludes the heap data of their arguments, values and
+  /// cached results (e.g., the contents of a `Vec`).
+  pub bytes  : usize,
+}
+
+impl Add for MemoryUse {
+  type Output=MemoryUse;
+  fn add(self, rhs: Self) -> Self::Output {
+    MemoryUse {
+      cells  : self.cells + rhs.cells,
+      thunks : self.thunks + rhs.thunks,
+      cached : self.cached + rhs.cached,
+      edges  : self.edges + rhs.edges,
+      bytes  : self.bytes + rhs.bytes,
+    }
+  }
+}
+
+/// The memory use of the DCG; see `memory_stats`.
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct MemoryStats {
+  /// The memory use of all nodes
+  pub total      : MemoryUse,
+  /// The memory use of the nodes in each namespace (see
+  /// `current_namespace`), excluding its sub-namespaces
+  pub namespaces : HashMap<Name, MemoryUse>,
+  /// The memory use of the thunks of each program point, i.e., of
+  /// each function or combinator that computes thunks
+  pub prog_pts   : HashMap<ProgPt, MemoryUse>,
+}
+
+/// The memory use of the DCG, in total, by namespace and by program
+/// point: Use this to find which combinator (or which part of the
+/// input) is responsible for memory growth over a long sequence of
+/// edits.  The sizes are approximate (see `MemoryUse::bytes`); for
+/// the `Naive` engine, they are all zero.
+pub fn memory_stats () -> MemoryStats {
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => {
+        let st = &*dcg.borrow();
+        let mut stats = MemoryStats::default() ;
+        for (loc, node) in st.table.iter() {
+          let mem_use = node.mem_use() ;
+          stats.total = stats.total + mem_use ;
+          let ns = stats.namespaces.entry(name_of_ns_path(&loc.path))
+            .or_insert_with(Default::default) ;
+          *ns = *ns + mem_use ;
+          if let Some(prog_pt) = node.prog_pt() {
+            let pp = stats.prog_pts.entry(prog_pt.clone()).or_insert_with(Default::default) ;
+            *pp = *pp + mem_use ;
+          }
+        } ;
+        stats
+      },
+      Engine::Naive => MemoryStats::default(),
+    }
+  })
+}
+
+/// A node that `collect_garbage` could not collect, because code
+/// outside of the DCG refers to it; see `GarbageReport`.
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct LiveArt {
+  /// The name of the node (or for a structural thunk, its program point)
+  pub name    : NameOrProgPt,
+  /// The number of `Art`s for the node, held by user code (including
+  /// the arguments and values of other cells and thunks)
+  pub handles : usize,
+  /// The number of nodes that the node retains, including itself,
+  /// i.e., that `collect_garbage` would collect without its `Art`s
+  /// (unless other `Art`s retain them too)
+  pub retains : usize,
+}
+
+/// The outcome of `collect_garbage`.
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct GarbageReport {
+  /// The number of nodes collected
+  pub collected : usize,
+  /// In debug mode (see `manage::set_track_arts`), the named nodes
+  /// that remain, since user code refers to them, by decreasing
+  /// `retains`: To track down accidental retention of an old version
+  /// of a structure, look for (the root of) this version here.
+  pub live      : Vec<LiveArt>,
+}
+
+/// The nodes that the DCG itself refers to, with their numbers of
+/// references, by pointer; the remaining references to each node are
+/// `Art`s, outside of the DCG.
+fn dcg_loc_refs (st:&DCG) -> HashMap<*const Loc, usize> {
+  let mut refs : HashMap<*const Loc, usize> = HashMap::new() ;
+  {
+    let mut count = |loc:&Rc<Loc>| { *refs.entry(&**loc as *const Loc).or_insert(0) += 1 } ;
+    for (loc, node) in st.table.iter() {
+      count(loc) ;
+      node.locs_each(&mut count)
+    } ;
+    for loc in st.propagation.keys() { count(loc) } ;
+    for loc in st.bounded.entries.keys() { count(loc) } ;
+    for queue in st.bounded.queues.values() { for loc in queue.values() { count(loc) } } ;
+    for loc in st.suspended.keys() { count(loc) } ;
+    for loc in st.observers.table.keys() { count(loc) } ;
+  }
+  refs
+}
+
+/// The roots of `collect_garbage`: The nodes that `Art`s outside of
+/// the DCG refer to, with the numbers of these `Art`s, and the nodes
+/// that observers observe (perhaps with zero `Art`s).
+fn dcg_handles (st:&DCG) -> Vec<(Rc<Loc>, usize)> {
+  let refs = dcg_loc_refs(st) ;
+  let handles : Vec<(&Rc<Loc>, usize)> = st.table.keys().filter_map(|loc| {
+    let handles = Rc::strong_count(loc) - refs[&(&**loc as *const Loc)] ;
+    if handles > 0 || st.observers.table.contains_key(loc) { Some((loc, handles)) } else { None }
+  }).collect() ;
+  // Clone the locations only after counting the references to them:
+  handles.into_iter().map(|(loc, handles)| (loc.clone(), handles)).collect()
+}
+
+/// The nodes reachable from `roots`, by the edges to successors.
+fn dcg_reachable (st:&DCG, roots:Vec<Rc<Loc>>) -> HashSet<Rc<Loc>> {
+  let mut reachable : HashSet<Rc<Loc>> = HashSet::new() ;
+  let mut todo = roots ;
+  while let Some(loc) = todo.pop() {
+    if reachable.contains(&loc) { continue } ;
+    match st.table.get(&loc) {
+      Some(node) if node.succs_def() => for succ in node.succs() { todo.push(succ.loc.clone()) },
+      _ => (),
+    } ;
+    reachable.insert(loc) ;
+  } ;
+  reachable
+}
+
+/// Removes the nodes that no `Art` outside of the DCG refers to,
+/// directly or through the edges of the nodes that remain, and that
+/// no observer (see `observe`) observes.  Since the arguments and
+/// values of cells and thunks may hold `Art`s, each node that this
+/// removes may free others, so this repeats until it removes no more.
+/// Re-allocating a removed node creates it afresh, without its prior
+/// cached result.  Like `set`, use this outside of thunks.  For the
+/// `Naive` engine, this does nothing.
+pub fn collect_garbage () -> GarbageReport {
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => {
+        let st = &mut *dcg.borrow_mut();
+        assert!( st.stack.is_empty() ); // => outer layer has control.
+        let mut report = GarbageReport::default() ;
+        loop {
+          let handles = dcg_handles(st) ;
+          let reachable = dcg_reachable(st, handles.into_iter().map(|(loc, _)| loc).collect()) ;
+          let garbage : Vec<Rc<Loc>> =
+            st.table.keys().filter(|loc| !reachable.contains(*loc)).cloned().collect() ;
+          if garbage.is_empty() { break } ;
+          for loc in garbage.iter() {
+            let succs = if lookup_abs(st, loc).succs_def() { lookup_abs(st, loc).succs().clone() }
+                        else { Vec::new() } ;
+            for succ in succs.iter() {
+              if reachable.contains(&succ.loc) { lookup_abs(st, &succ.loc).preds_remove(loc) }
+            }
+          } ;
+          for loc in garbage.iter() {
+            st.table.remove(loc) ;
+            st.propagation.remove(loc) ;
+            st.suspended.remove(loc) ;
+            if let Some(entry) = st.bounded.entries.remove(loc) {
+              st.bounded.queues.get_mut(&entry.prog_pt).unwrap().remove(&entry.tick) ;
+            }
+          } ;
+          report.collected += garbage.len() ;
+        } ;
+        if st.flags.track_arts {
+          let mut live : Vec<(String, LiveArt)> = Vec::new() ;
+          for (loc, handles) in dcg_handles(st) {
+            if handles == 0 { continue } ;
+            let name = match *loc.id {
+              ArtId::Nominal(ref n) =>
+                NameOrProgPt::Name{ ns:name_of_ns_path(&loc.path), name:n.clone() },
+              ArtId::Structural(_) => match lookup_abs(st, &loc).prog_pt() {
+                Some(prog_pt) => NameOrProgPt::ProgPt(prog_pt.clone()),
+                None => continue,
+              }
+            } ;
+            let retains = dcg_reachable(st, vec![loc.clone()]).len() ;
+            let live_art = LiveArt{ name:name, handles:handles, retains:retains } ;
+            live.push((format!("{:?}", loc), live_art))
+          } ;
+          live.sort_by(|a,b| b.1.retains.cmp(&a.1.retains).then(a.0.cmp(&b.0))) ;
+          report.live = live.into_iter().map(|(_, l)| l).collect() ;
+        } ;
+        wf::check_dcg(st);
+        report
+      },
+      Engine::Naive => GarbageReport::default(),
+    }
+  })
+}
+
+/// Invalidates the cell or thunk with the given name, in the current
+/// namespace: Dirties it and its transitive observers, and discards
+/// their cached results, so that each re-evaluates when next forced,
+/// even if the values that it observes compare equal to before.  Use
+/// this when a value is a handle to external state (e.g., a file or
+/// a database) that changed out of band; like `set`, use it outside
+/// of thunks.  Returns false (and does nothing) if no cell or thunk
+/// has this name, or if the engine is `Naive`.
+pub fn invalidate (name:&Name) -> bool {
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => {
+        let st : &mut DCG = &mut *dcg.borrow_mut();
+        let loc = loc_of_id(current_path(st), Rc::new(ArtId::Nominal(name.clone())));
+        if st.table.contains_key(&loc) { invalidate_(st, &loc); true } else { false }
+      },
+      Engine::Naive => false,
+    }
+  })
+}
+
+/// The types of the values that `save` and `load` persist (cells'
+/// values, and thunks' cached results), with their serializations.
+#[cfg(feature = "persist")]
+pub struct PersistTypes {
+  types : HashMap<String, PersistType>,
+}
+
+#[cfg(feature = "persist")]
+struct PersistType {
+  encode : Box<Fn(&Any) -> ::bincode::Result<Vec<u8>>>,
+  /// Decodes a cell, or (given its program point and the hash of its
+  /// argument) a restored thunk, without edges
+  decode : Box<Fn(&[u8], Option<(ProgPt, u64)>) -> ::bincode::Result<Box<GraphNode>>>,
+}
+
+#[cfg(feature = "persist")]
+impl PersistTypes {
+  /// No types: `save` saves nothing.
+  pub fn new () -> PersistTypes {
+    PersistTypes{ types:HashMap::new() }
+  }
+
+  /// Registers `T`, the type of the values of some cells and thunks.
+  pub fn register<T> (&mut self)
+    where T:Hash+Eq+Debug+Clone+'static+Serialize+for<'de> Deserialize<'de> {
+      let encode = |val:&Any| ::bincode::serialize(val.downcast_ref::<T>().unwrap()) ;
+      let decode = |bytes:&[u8], thunk:Option<(ProgPt, u64)>| {
+        let val : T = ::bincode::deserialize(bytes)? ;
+        let node = match thunk {
+          None => Node::Mut(MutNode{ preds:Vec::new(), val:val }),
+          Some((prog_pt, arg_hash)) => Node::Comp(CompNode{
+            preds:Vec::new(),
+            succs:Vec::new(),
+            producer:Box::new(Restored{ prog_pt:prog_pt, arg_hash:arg_hash }),
+            res:Some(val),
+          }),
+        } ;
+        Ok(Box::new(node) as Box<GraphNode>)
+      } ;
+      self.types.insert(type_name::<T>().to_string(),
+                        PersistType{ encode:Box::new(encode), decode:Box::new(decode) });
+    }
+}
+
+/// A node of the DCG, as `save` saves it.
+#[cfg(feature = "persist")]
+#[derive(Serialize,Deserialize)]
+struct SavedNode {
+  loc   : reflect::Loc,
+  ty    : String,
+  val   : Vec<u8>,
+  preds : Vec<reflect::Pred>,
+  thunk : Option<SavedThunk>,
+}
+
+/// For a saved thunk: its program point, the hash of its argument, and its edges.
+#[cfg(feature = "persist")]
+type SavedThunk = (ProgPt, u64, Vec<(reflect::Effect, reflect::Loc)>);
+
+/// Saves the current DCG to the file at `path`, so that `load` may
+/// restore it, e.g., after the process restarts: its cells and their
+/// values, its thunks and their cached results, and the edges among
+/// them.  Saves only the nodes whose values have types in `types`,
+/// and of these, only the cells, and the thunks with cached results
+/// whose edges are clean, and go to nodes that it saves.  Values are
+/// saved as `serde` serializes them; so, the articulations within
+/// them are saved as their content.  Fails if the engine is `Naive`,
+/// or is evaluating a thunk.
+#[cfg(feature = "persist")]
+pub fn save<P:AsRef<::std::path::Path>> (path:P, types:&PersistTypes) -> ::std::io::Result<()> {
+  use std::io;
+  let nodes = GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => saved_nodes(&*dcg.borrow(), types),
+      Engine::Naive => Err(io::Error::new(io::ErrorKind::Other,
+                                          "save: the naive engine has no DCG")),
+    }
+  })? ;
+  // Encode the values after the DCG is released, since doing so may
+  // force the articulations within them.
+  let mut saved = Vec::new() ;
+  for (loc, ty, val, preds, thunk) in nodes {
+    let val = (types.types[ty].encode)(&*val)
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))? ;
+    saved.push(SavedNode{ loc:loc, ty:ty.to_string(), val:val, preds:preds, thunk:thunk })
+  } ;
+  let file = ::std::fs::File::create(path)? ;
+  ::bincode::serialize_into(io::BufWriter::new(file), &saved)
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+/// The nodes that `save` saves, with (clones of) their values, in the
+/// order of their locations.
+#[cfg(feature = "persist")]
+fn saved_nodes (st:&DCG, types:&PersistTypes)
+                -> ::std::io::Result<Vec<(reflect::Loc, &'static str, Box<Any>, Vec<reflect::Pred>,
+                                          Option<SavedThunk>)>>
+{
+  if !st.stack.is_empty() {
+    return Err(::std::io::Error::new(::std::io::ErrorKind::Other,
+                                     "save: the engine is evaluating a thunk"))
+  } ;
+  let mut locs : HashSet<Rc<Loc>> = st.table.iter().filter(|&(_, node)| {
+    types.types.contains_key(node.res_type()) && node.res_clone().is_some() &&
+      (!node.succs_def() || node.succs().iter().all(|succ| !succ.dirty))
+  }).map(|(loc, _)| loc.clone()).collect() ;
+  loop {
+    let dropped : Vec<Rc<Loc>> = locs.iter().filter(|loc| {
+      let node = &st.table[*loc] ;
+      node.succs_def() && node.succs().iter().any(|succ| !locs.contains(&succ.loc))
+    }).cloned().collect() ;
+    if dropped.is_empty() { break } ;
+    for loc in dropped { locs.remove(&loc) ; }
+  } ;
+  let mut nodes : Vec<_> = locs.iter().map(|loc| {
+    let node = &st.table[loc] ;
+    let preds =
+      node.preds_alloc().into_iter().map(|l| (reflect::Effect::Alloc, l)).chain(
+        node.preds_obs().into_iter().map(|l| (reflect::Effect::Force, l)))
+      .filter(|&(_, ref l)| locs.contains(l))
+      .map(|(effect, l)| reflect::Pred{ effect:effect, loc:l.reflect() })
+      .collect() ;
+    let thunk = if node.succs_def() {
+      let succs = node.succs().iter().map(|succ| (succ.effect.reflect(), succ.loc.reflect())) ;
+      Some((node.prog_pt().unwrap().clone(), node.arg_hash().unwrap(), succs.collect()))
+    } else { None } ;
+    (loc.reflect(), node.res_type(), node.res_clone().unwrap(), preds, thunk)
+  }).collect() ;
+  nodes.sort_by(|a, b| reflect::string_of_loc(&a.0).cmp(&reflect::string_of_loc(&b.0))) ;
+  Ok(nodes)
+}
+
+/// Restores the DCG saved by `save` in the file at `path` into the
+/// current engine, which must be a fresh DCG-based engine (see
+/// `manage::init_dcg`).  The types of its values must be in `types`.
+/// A restored thunk lacks its code, until the program allocates it
+/// again (with `thunk`, or the `thunk!` macro, etc.), with the same
+/// program point; then, it keeps its cached result if its argument
+/// is the same, and its inputs have not changed since.
+#[cfg(feature = "persist")]
+pub fn load<P:AsRef<::std::path::Path>> (path:P, types:&PersistTypes) -> ::std::io::Result<()> {
+  use std::io;
+  let invalid = |msg:String| io::Error::new(io::ErrorKind::InvalidData, msg) ;
+  let file = ::std::fs::File::open(path)? ;
+  let saved : Vec<SavedNode> = ::bincode::deserialize_from(io::BufReader::new(file))
+    .map_err(|e| invalid(format!("load: {}", e)))? ;
+  // Decode the values before borrowing the DCG, since they may
+  // contain articulations.
+  let mut nodes = Vec::new() ;
+  for n in saved {
+    let node = match types.types.get(&n.ty) {
+      None => return Err(invalid(format!("load: type {} is not registered", n.ty))),
+      Some(ty) => (ty.decode)(&n.val, n.thunk.as_ref().map(|t| (t.0.clone(), t.1)))
+        .map_err(|e| invalid(format!("load: {}", e)))?,
+    } ;
+    nodes.push((n, node))
+  } ;
+  GLOBALS.with(|g| {
+    match g.borrow().engine {
+      Engine::DCG(ref dcg) => {
+        let st = &mut *dcg.borrow_mut() ;
+        if !st.table.is_empty() || !st.stack.is_empty() {
+          return Err(io::Error::new(io::ErrorKind::Other, "load: the DCG is not fresh"))
+        } ;
+        let res = restore_nodes(st, nodes) ;
+        if res.is_err() { st.table.clear() } ;
+        res
+      },
+      Engine::Naive => Err(io::Error::new(io::ErrorKind::Other,
+                                          "load: the naive engine has no DCG")),
+    }
+  })
+}
+
+/// Inserts the nodes that `load` decoded into the (empty) table of
+/// the DCG, then their edges.
+#[cfg(feature = "persist")]
+fn restore_nodes (st:&mut DCG, nodes:Vec<(SavedNode, Box<GraphNode>)>) -> ::std::io::Result<()> {
+  fn locate (st:&DCG, loc:&reflect::Loc) -> ::std::io::Result<Rc<Loc>> {
+    let l = canonical_loc(st, loc_of_reflect(loc)) ;
+    if st.table.contains_key(&l) { Ok(l) } else {
+      Err(::std::io::Error::new(::std::io::ErrorKind::InvalidData,
+                                format!("load: dangling location {}", reflect::string_of_loc(loc))))
+    }
+  }
+  let nodes : Vec<(Rc<Loc>, SavedNode)> = nodes.into_iter().map(|(n, node)| {
+    let loc = loc_of_reflect(&n.loc) ;
+    st.table.insert(loc.clone(), node) ;
+    (loc, n)
+  }).collect() ;
+  for (loc, n) in nodes {
+    for pred in n.preds.iter() {
+      let effect = match pred.effect {
+        reflect::Effect::Force => Effect::Observe,
+        reflect::Effect::Alloc => Effect::Allocate,
+      } ;
+      let pred = locate(st, &pred.loc)? ;
+      lookup_abs(st, &loc).preds_insert(effect, &pred)
+    } ;
+    if let Some((_, _, edges)) = n.thunk {
+      let mut succs = Vec::new() ;
+      for (effect, succ) in edges {
+        let succ = locate(st, &succ)? ;
+        let (effect, dep) : (Effect, Rc<Box<DCGDep>>) = match effect {
+          reflect::Effect::Force => (Effect::Observe, lookup_abs(st, &succ).observed_dep()),
+          reflect::Effect::Alloc => (Effect::Allocate, match *succ.id {
+            ArtId::Structural(_) => Rc::new(Box::new(NoDependency)),
+            ArtId::Nominal(_) => Rc::new(Box::new(AllocDependency{val:()})),
+          }),
+        } ;
+        succs.push(Succ{ dirty:false, loc:succ, effect:effect, dep:dep })
+      } ;
+      *lookup_abs(st, &loc).succs_mut() = succs
+    }
+  } ;
+  Ok(())
+}
+
 /// Operations that monitor and alter the active engine.  Incremental
 /// applications should not use these operations directly.
 pub mod manage {
@@ -2043,7 +3982,9 @@ pub mod manage {
     use std::mem;
     let mut engine = engine;
     GLOBALS.with(|g| {
-      mem::swap(&mut g.borrow_mut().engine, &mut engine);
+      let mut g = g.borrow_mut();
+      mem::swap(&mut g.engine, &mut engine);
+      g.handle = None;
     });
     return engine
   }
@@ -2064,6 +4005,78 @@ pub mod manage {
     })
   }
 
+  /// Sets the current engine's propagation policy (see `Propagation`)
+  /// for changes to cells, except those with their own policy (see
+  /// `set_cell_propagation`); returns the old policy.  The `Naive`
+  /// engine has no policy; for it, this does nothing, and returns `Lazy`.
+  pub fn set_propagation (policy:Propagation) -> Propagation {
+    GLOBALS.with(|g| {
+      match g.borrow().engine {
+        Engine::DCG(ref dcg) => replace(&mut dcg.borrow_mut().flags.propagation, policy),
+        Engine::Naive => Propagation::Lazy,
+      }
+    })
+  }
+
+  /// Bounds the current engine's cached results for the thunks at
+  /// `prog_pt` (e.g., `prog_pt!("f")` for the thunks of `thunk!(f, ..)`
+  /// and `eager!(.. =>> f, ..)`) to at most `capacity` of them, or
+  /// unbounds them (the default), for `None`.  Beyond the bound, the
+  /// engine evicts results by its policy (see `Eviction`), counting
+  /// them in `Cnt::evict`; the thunks of evicted results re-evaluate
+  /// when next demanded.  Eviction frees the results, but not the
+  /// thunks, nor their edges; to free those, see `collect_garbage`.
+  /// Only results cached after the bound is set count toward it.  For
+  /// the `Naive` engine, this does nothing.
+  pub fn bound_cached_results (prog_pt:ProgPt, capacity:Option<usize>) {
+    GLOBALS.with(|g| {
+      match g.borrow().engine {
+        Engine::DCG(ref dcg) => {
+          let st = &mut *dcg.borrow_mut();
+          match capacity {
+            Some(capacity) => {
+              st.bounded.capacity.insert(prog_pt.clone(), capacity);
+              bounded_evict(st, &prog_pt, capacity)
+            },
+            None => {
+              st.bounded.capacity.remove(&prog_pt);
+              match st.bounded.queues.remove(&prog_pt) {
+                None => (),
+                Some(queue) => for loc in queue.values() { st.bounded.entries.remove(loc); }
+              }
+            }
+          }
+        },
+        Engine::Naive => (),
+      }
+    })
+  }
+
+  /// Sets the current engine's eviction policy for its bounded memo
+  /// results (see `bound_cached_results`); returns the old policy.  For the
+  /// `Naive` engine, this does nothing, and returns `Lru`.
+  pub fn set_eviction (policy:Eviction) -> Eviction {
+    GLOBALS.with(|g| {
+      match g.borrow().engine {
+        Engine::DCG(ref dcg) => replace(&mut dcg.borrow_mut().flags.eviction, policy),
+        Engine::Naive => Eviction::Lru,
+      }
+    })
+  }
+
+  /// Sets the debug mode of the current engine in which
+  /// `collect_garbage` reports the `Art`s that it could not collect
+  /// (see `GarbageReport::live`); returns the old mode.  For the
+  /// `Naive` engine, this does nothing, and returns false.
+  pub fn set_track_arts (on:bool) -> bool {
+    GLOBALS.with(|g| {
+      match g.borrow().engine {
+        Engine::DCG(ref dcg) => replace(&mut dcg.borrow_mut().flags.track_arts, on),
+        Engine::Naive => false,
+      }
+    })
+  }
+
   /// True iff the current engine is `Naive`
   pub fn engine_is_naive () -> bool {
     GLOBALS.with(|g| {
@@ -2083,6 +4096,206 @@ pub mod manage {
   }
 }
 
+/// True iff the engine is recording operations (see `replay`).
+fn is_recording () -> bool {
+  RECORDING.with(|r| r.borrow().is_some())
+}
+
+/// Records the operation in the current recording (see `replay`), noting
+/// whether the outer layer performs it.
+fn record (dcg:&RefCell<DCG>, op:replay::Op) {
+  let outer = dcg.borrow().stack.is_empty() ;
+  RECORDING.with(|r| match *r.borrow_mut() {
+    None => (),
+    Some(ref mut steps) => steps.push(replay::Step{ outer:outer, op:op }),
+  })
+}
+
+/// Recording and replaying the operations that a program performs on
+/// the engine, e.g., to reproduce a bug (say, a memo mismatch) that
+/// only occurs deep within a large application, without the rest of
+/// the application.  Between `record_begin` and `record_end`, the DCG
+/// engine records each `cell`, `set`, `thunk` and `force` as a `Step`,
+/// with (reflected) locations and values, which are serializable (with
+/// the `serde` feature).  A `Replayer` re-executes the steps that the
+/// outer layer performed, in a fresh engine, say; the steps within
+/// thunks re-execute as their code re-executes.  Replaying a `force`
+/// checks that it produces the recorded value.  Since the code of a
+/// thunk is not recorded, and neither are the types of values, the
+/// replayer must know them (see `Replayer::register_type` and
+/// `Replayer::register_thunk`).  The `Naive` engine records nothing.
+pub mod replay {
+  use super::*;
+  #[cfg(feature = "serde")]
+  use serde::{Serialize, Deserialize};
+
+  /// An operation on the engine, as recorded.  Each operation
+  /// records the location of its articulation, and the type of its
+  /// content (see `std::any::type_name`).
+  #[derive(Debug,Clone,PartialEq,Eq,Hash)]
+  #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+  pub enum Op {
+    /// `cell`, with its (initial) value
+    Cell{ loc:reflect::Loc, ty:String, val:reflect::Val },
+    /// `set`, with the new value
+    Set{ loc:reflect::Loc, ty:String, val:reflect::Val },
+    /// `thunk`, with the program point and the argument of its code
+    Thunk{ loc:reflect::Loc, ty:String, prog_pt:ProgPt, arg:reflect::Val },
+    /// `force`, with the value that it produced
+    Force{ loc:reflect::Loc, ty:String, val:reflect::Val },
+  }
+
+  /// A recorded operation, and whether the outer layer performed it
+  /// (rather than a thunk).  Steps are recorded in the order that the
+  /// operations complete; hence, each force follows the steps within it.
+  #[derive(Debug,Clone,PartialEq,Eq,Hash)]
+  #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+  pub struct Step {
+    pub outer : bool,
+    pub op    : Op,
+  }
+
+  /// Begins recording the operations on the current engine.  Panics
+  /// if already recording.
+  pub fn record_begin () {
+    RECORDING.with(|r| {
+      let mut r = r.borrow_mut();
+      if r.is_some() { panic!("cannot currently nest calls to record_begin().") }
+      *r = Some(Vec::new())
+    })
+  }
+
+  /// Stops recording, and returns the steps recorded since
+  /// `record_begin()`, which must precede it.
+  pub fn record_end () -> Vec<Step> {
+    RECORDING.with(|r| match r.borrow_mut().take() {
+      None => panic!("record_end() without a corresponding record_begin()."),
+      Some(steps) => steps,
+    })
+  }
+
+  /// The articulation of the given reflected value, if it is one (e.g.,
+  /// within the reflected argument of a thunk; see
+  /// `Replayer::register_thunk`).  Its content must have type `T`.
+  pub fn art_of_val<T> (val:&reflect::Val) -> Option<Art<T>> {
+    match *val {
+      reflect::Val::Art(ref loc, _) => Some(art_of_loc(loc)),
+      _ => None,
+    }
+  }
+
+  fn art_of_loc<T> (loc:&reflect::Loc) -> Art<T> {
+    let loc = loc_of_reflect(loc) ;
+    let loc = GLOBALS.with(|g| {
+      match g.borrow().engine {
+        Engine::DCG(ref dcg) => canonical_loc(&*dcg.borrow(), loc),
+        Engine::Naive => loc,
+      }
+    }) ;
+    Art{art:EnumArt::Loc(loc)}
+  }
+
+  /// Performs the computation within the namespaces of the given path.
+  fn in_path<T,F> (path:&[Name], body:F) -> T
+    where F:FnOnce() -> T {
+      match path.split_first() {
+        None => body(),
+        Some((n, path)) => ns(n.clone(), || in_path(path, body)),
+      }
+    }
+
+  /// Where a replay diverged from its recording: the index of the
+  /// `force` step, the value that it recorded, and the value that
+  /// the replay produced instead.
+  #[derive(Debug,Clone,PartialEq,Eq)]
+  pub struct Divergence {
+    pub step     : usize,
+    pub recorded : reflect::Val,
+    pub replayed : reflect::Val,
+  }
+
+  struct TypeOps {
+    cell  : Box<Fn(Name, &reflect::Val)>,
+    set   : Box<Fn(&reflect::Loc, &reflect::Val)>,
+    force : Box<Fn(&reflect::Loc) -> reflect::Val>,
+  }
+
+  /// Replays recorded steps in the current engine; see the module doc.
+  pub struct Replayer {
+    types  : HashMap<String, TypeOps>,
+    thunks : HashMap<ProgPt, Box<Fn(Name, &reflect::Val)>>,
+  }
+
+  impl Replayer {
+    /// A replayer that knows no types, and no thunks.
+    pub fn new () -> Replayer {
+      Replayer{ types:HashMap::new(), thunks:HashMap::new() }
+    }
+
+    /// Registers `T`, the type of the content of some articulations,
+    /// with a function that decodes the reflected values of this type
+    /// (e.g., for replaying `cell` and `set`).
+    pub fn register_type<T,F> (&mut self, decode:F)
+      where T:Hash+Eq+Debug+Clone+'static,
+            F:Fn(&reflect::Val) -> T + 'static {
+        let decode = Rc::new(decode);
+        let decode_cell = decode.clone();
+        let ops = TypeOps{
+          cell  : Box::new(move |n, val| { let _ = cell(n, decode_cell(val)); }),
+          set   : Box::new(move |loc, val| set(&art_of_loc::<T>(loc), decode(val))),
+          force : Box::new(|loc| reflect::reflect_val(&force(&art_of_loc::<T>(loc)))),
+        } ;
+        self.types.insert(type_name::<T>().to_string(), ops);
+      }
+
+    /// Registers the code of the thunks at `prog_pt`, for replaying
+    /// the thunks that the outer layer allocates there: `alloc`
+    /// receives the name of each (a hash, for structural thunks) and
+    /// its reflected argument, and allocates it anew (e.g., with
+    /// `thunk!`, and `art_of_val` for arguments that are arts).
+    pub fn register_thunk<F> (&mut self, prog_pt:ProgPt, alloc:F)
+      where F:Fn(Name, &reflect::Val) + 'static {
+        self.thunks.insert(prog_pt, Box::new(alloc));
+      }
+
+    /// Replays the steps of the outer layer, in order; stops at the
+    /// first `force` that produces a different value than recorded.
+    /// Panics for a type or thunk that is not registered.
+    pub fn replay (&self, steps:&[Step]) -> ::std::result::Result<(), Divergence> {
+      for (i, step) in steps.iter().enumerate() {
+        if !step.outer { continue }
+        match step.op {
+          Op::Cell{ref loc, ref ty, ref val} =>
+            in_path(&loc.path, || (self.type_ops(ty).cell)(loc.name.clone(), val)),
+          Op::Set{ref loc, ref ty, ref val} =>
+            (self.type_ops(ty).set)(loc, val),
+          Op::Thunk{ref loc, ref prog_pt, ref arg, ..} => {
+            let alloc = match self.thunks.get(prog_pt) {
+              None => panic!("replay: thunks at {:?} are not registered", prog_pt),
+              Some(alloc) => alloc,
+            } ;
+            in_path(&loc.path, || alloc(loc.name.clone(), arg))
+          },
+          Op::Force{ref loc, ref ty, ref val} => {
+            let replayed = (self.type_ops(ty).force)(loc);
+            if &replayed != val {
+              return Err(Divergence{ step:i, recorded:val.clone(), replayed:replayed })
+            }
+          },
+        }
+      } ;
+      Ok(())
+    }
+
+    fn type_ops (&self, ty:&String) -> &TypeOps {
+      match self.types.get(ty) {
+        None => panic!("replay: type {} is not registered", ty),
+        Some(ops) => ops,
+      }
+    }
+  }
+}
+
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - 
 ///
 /// Well-formedness tests; for documentation and for debugging.
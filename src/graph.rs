@@ -1,19 +1,35 @@
 /// Incremental Graph Representations
 
 use std::fmt::Debug;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::io::BufRead;
+use std::marker::PhantomData;
+use std::ops::{Add, Sub};
+use std::cmp::{min, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::collections::hash_map::DefaultHasher;
 use std::rc::Rc;
 
-use adapton::engine::{cell, ns, name_of_str, name_unit, Name};
-use adapton::collections::{list_of_tree, tree_fold_seq, tree_of_list, Dir2, List, ListIntro,
-                           MapIntro, MapElim, SetIntro, Tree, TreeIntro};
-use adapton::collections::trie::{trie_fold_seq, Set, Trie, TrieIntro};
+use adapton::engine::*;
+use adapton::collections::{filter_list_of_tree, filter_tree_of_tree, list_fold, list_of_tree,
+                           list_pop, tree_fold_seq, tree_of_list, Dir2, Level, List, ListElim,
+                           ListIntro, MapIntro, MapElim, SetIntro, SetElim, Tree, TreeElim,
+                           TreeIntro};
+use adapton::collections::trie::{trie_fold, trie_fold_seq, trie_fold_up, trie_retain, Set, Trie,
+                                 TrieElim, TrieIntro};
+use macros::*;
 
 /// Representation of a graph as a list of edges, where edges are
-/// a pair of node ids.
+/// a pair of node ids.  Parallel edges are kept, each under the name
+/// it was added with; `edges` collapses them, while `edge_multiset`
+/// and `edge_multiplicity` count them.
 #[derive(Debug,PartialEq,Eq,Hash,Clone)]
 pub struct Graph<Node> {
     edge_tree: Tree<(Node, Node)>,
+    /// The ranks of the chunks at the front of `edge_tree`, the front
+    /// chunk last; see `push_chunk`.
+    chunk_ranks: Vec<usize>,
 }
 
 /// Representation of a graph as finite map from node ids to
@@ -23,6 +39,45 @@ pub struct AdjacencyGraph<Node>
     where Node: Debug + Clone + Hash + PartialEq + Eq + 'static
 {
     adjacency_map: Trie<(Node, Tree<Node>)>,
+    /// The number of edges into each vertex, kept up to date by
+    /// `add_edge` and `remove_edge`.
+    in_degree_map: Trie<(Node, usize)>,
+}
+
+/// Representation of a graph whose edges carry weights, as a finite
+/// map from node ids to an outgoing adjacency list of node ids, each
+/// paired with the weight of its edge.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub struct WeightedGraph<Node, W>
+    where Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+          W: Debug + Clone + Hash + PartialEq + Eq + 'static
+{
+    adjacency_map: Trie<(Node, Tree<(Node, W)>)>,
+}
+
+/// A graph whose vertices and edges carry attributes.  The attributes
+/// are held in tries layered over an adjacency-list graph, and each
+/// update to them is named after the vertex or edge it labels.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub struct LabeledGraph<Node, NodeAttr, EdgeAttr>
+    where Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+          NodeAttr: Debug + Clone + Hash + PartialEq + Eq + 'static,
+          EdgeAttr: Debug + Clone + Hash + PartialEq + Eq + 'static
+{
+    graph: AdjacencyGraph<Node>,
+    node_attrs: Trie<(Node, NodeAttr)>,
+    edge_attrs: Trie<((Node, Node), EdgeAttr)>,
+}
+
+/// An adjacency-list graph that also keeps the reverse adjacency
+/// lists up to date, so that the predecessors of a vertex are a
+/// lookup, and reversing the graph takes constant time.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+pub struct BiAdjacencyGraph<Node>
+    where Node: Debug + Clone + Hash + PartialEq + Eq + 'static
+{
+    forward: AdjacencyGraph<Node>,
+    backward: AdjacencyGraph<Node>,
 }
 
 trait NamedGraph<Node>: Debug + Clone + Hash + PartialEq + Eq {
@@ -35,6 +90,14 @@ pub trait GraphIntro<Node>: Debug + Clone + Hash + PartialEq + Eq {
     fn empty() -> Self;
     /// Adds the edge `(src, dst)` named `nm` to the graph `graph`.
     fn add_edge(graph: Self, nm: Name, src: Node, dst: Node) -> Self;
+    /// Removes the edge `(src, dst)` from the graph `graph`, naming the
+    /// result `nm`.  The vertices of the edge remain in the graph.
+    fn remove_edge(graph: Self, nm: Name, src: Node, dst: Node) -> Self;
+    /// Builds a graph of `edges`, naming every `name_stride`-th edge
+    /// `i`, counting from zero, `name_of_usize(i)`.  A `name_stride`
+    /// of zero inserts no names.
+    fn from_edges<I>(edges: I, name_stride: usize) -> Self
+        where I: IntoIterator<Item = (Node, Node)>;
 }
 
 /// Reduce a graph to a value.
@@ -43,34 +106,194 @@ pub trait GraphElim<Node>: Debug + Clone + Hash + PartialEq + Eq {
     fn edges(graph: &Self) -> Set<(Node, Node)>;
     /// Returns a set of the vertices of the graph `graph`.
     fn vertices(graph: &Self) -> Set<Node>;
+    /// Returns an iterator over the edges of `graph`, which forces the
+    /// graph's articulations only as it reaches them.  Unlike `edges`,
+    /// a parallel edge is yielded once per occurrence.
+    fn edges_iter(graph: &Self) -> EdgesIter<Node>;
     /// Returns a graph whose set of edges are the reversed edges of `graph`.
     fn reverse_edges(graph: &Self) -> Self;
+    /// Returns the vertices reachable from `root`, grouped by their
+    /// distance from `root`: the head of the list is `{root}`.
+    fn bfs(graph: &Self, root: Node) -> List<Set<Node>>;
+    /// Returns the targets of the edges out of `node`, or `None` if
+    /// `node` has no outgoing edges in `graph`.
+    fn successors(graph: &Self, node: &Node) -> Option<Tree<Node>>;
+    /// Like `successors`, but as a list.
+    fn successors_list(graph: &Self, node: &Node) -> Option<List<Node>>
+        where Node: Debug + Clone + Hash + PartialEq + Eq + 'static
+    {
+        Self::successors(graph, node).map(|dsts| list_of_tree(dsts, Dir2::Left))
+    }
+    /// Returns the subgraph of `graph` whose edges `(src, dst)` satisfy
+    /// `edge_pred(&src, &dst)` and join vertices that satisfy
+    /// `node_pred`.  Preserves the names of `graph`.
+    fn filter_graph<NodeP, EdgeP>(graph: &Self, node_pred: Rc<NodeP>, edge_pred: Rc<EdgeP>) -> Self
+        where NodeP: Fn(&Node) -> bool + 'static,
+              EdgeP: Fn(&Node, &Node) -> bool + 'static;
+    /// Returns a graph with the edges of both `graph` and `other`.
+    /// The two graphs are merged structurally, so the names of both
+    /// are preserved.  An edge of both graphs is an edge of the union
+    /// twice over, as if it had been added twice.
+    fn union(graph: &Self, other: &Self) -> Self;
+    /// Returns a hash of the set of edges of `graph`, which does not
+    /// depend on the order the edges were added in, on how often they
+    /// were added, or on where the graph's names fall.  It sums a hash
+    /// of each edge, folding over `edges`.
+    fn graph_fingerprint(graph: &Self) -> u64
+        where Node: Debug + Clone + Hash + PartialEq + Eq + 'static
+    {
+        ns(name_of_str("graph_fingerprint"), || {
+            trie_fold(Self::edges(graph),
+                      0,
+                      Rc::new(|(edge, ()): ((Node, Node), ()), sum: u64| {
+                          sum.wrapping_add(hash_of(&edge))
+                      }))
+        })
+    }
+    /// Returns the number of times the edge `(src, dst)` was added to
+    /// `graph` and not since removed.
+    fn edge_multiplicity(graph: &Self, src: &Node, dst: &Node) -> usize
+        where Node: Debug + Clone + Hash + PartialEq + Eq + 'static
+    {
+        match Self::successors(graph, src) {
+            None => 0,
+            Some(dsts) => tree_count(dsts, dst.clone()),
+        }
+    }
+    /// Counts the edges of `graph`, counting parallel edges once per
+    /// occurrence, as `edge_multiplicity` does.
+    fn edge_count(graph: &Self) -> usize;
+    /// Counts the vertices of `graph`.
+    fn vertex_count(graph: &Self) -> usize;
+    /// Returns the subgraph of `graph` whose edges are also edges of
+    /// `other`.  Like `filter_graph`, preserves the names of `graph`.
+    fn intersect(graph: &Self, other: &Self) -> Self
+        where Node: Debug + Clone + Hash + PartialEq + Eq + 'static
+    {
+        let other_edges = Self::edges(other);
+        Self::filter_graph(graph,
+                           Rc::new(|_: &Node| true),
+                           Rc::new(move |src: &Node, dst: &Node| {
+                               SetElim::is_mem(&other_edges, &(src.clone(), dst.clone()))
+                           }))
+    }
+    /// Returns the subgraph of `graph` whose edges are not edges of
+    /// `other`.  Like `filter_graph`, preserves the names of `graph`.
+    fn difference(graph: &Self, other: &Self) -> Self
+        where Node: Debug + Clone + Hash + PartialEq + Eq + 'static
+    {
+        let other_edges = Self::edges(other);
+        Self::filter_graph(graph,
+                           Rc::new(|_: &Node| true),
+                           Rc::new(move |src: &Node, dst: &Node| {
+                               !SetElim::is_mem(&other_edges, &(src.clone(), dst.clone()))
+                           }))
+    }
+    /// Returns `k` edges of `graph` chosen pseudo-randomly from `seed`,
+    /// or every edge if `graph` has no more than `k`.  The sample is
+    /// the `k` edges that hash lowest together with `seed`, found by a
+    /// fold over the set of edges that is memoized at each of its
+    /// names; so an edit only revisits the subtries whose edges changed.
+    fn sample_edges(graph: &Self, k: usize, seed: u64) -> Set<(Node, Node)>
+        where Node: Debug + Clone + Hash + PartialEq + Eq + 'static
+    {
+        let nm = name_pair(name_of_usize(k), name_of_usize(seed as usize));
+        ns(name_pair(name_of_str("sample_edges"), nm), || {
+            let lowest = trie_fold_up(Self::edges(graph),
+                                      Rc::new(|_| Vec::new()),
+                                      Rc::new(move |_, (edge, ())| {
+                                          vec![(hash_of(&(seed, &edge)), edge)]
+                                      }),
+                                      Rc::new(move |_, l, r| lowest_hashed(l, r, k)),
+                                      Rc::new(|_, res| res),
+                                      Rc::new(|_, res| res));
+            lowest.into_iter().fold(SetIntro::empty(), |set, (_, edge)| SetIntro::add(set, edge))
+        })
+    }
+}
+
+/// Merges two lists sorted by hash, keeping the `k` lowest.
+fn lowest_hashed<X>(l: Vec<(u64, X)>, r: Vec<(u64, X)>, k: usize) -> Vec<(u64, X)> {
+    let mut merged = Vec::with_capacity(min(k, l.len() + r.len()));
+    let mut l = l.into_iter().peekable();
+    let mut r = r.into_iter().peekable();
+    while merged.len() < k {
+        let take_left = match (l.peek(), r.peek()) {
+            (Some(&(hl, _)), Some(&(hr, _))) => hl <= hr,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => break,
+        };
+        merged.push(if take_left { l.next() } else { r.next() }.unwrap());
+    }
+    merged
+}
+
+/// Produce a weighted graph.
+pub trait WeightedGraphIntro<Node, W>: Debug + Clone + Hash + PartialEq + Eq {
+    /// Yields an empty graph, i.e. no vertices or edges.
+    fn empty() -> Self;
+    /// Adds the edge `(src, dst)` with weight `weight`, named `nm`, to
+    /// the graph `graph`.
+    fn add_edge(graph: Self, nm: Name, src: Node, dst: Node, weight: W) -> Self;
+}
+
+/// Reduce a weighted graph to a value.
+pub trait WeightedGraphElim<Node, W>: Debug + Clone + Hash + PartialEq + Eq {
+    /// Returns a map from the edges of the graph `graph` to their
+    /// weights.  If an edge was added more than once, the map holds
+    /// one of its weights.
+    fn edges(graph: &Self) -> Trie<((Node, Node), W)>;
+    /// Returns a set of the vertices of the graph `graph`.
+    fn vertices(graph: &Self) -> Set<Node>;
+    /// Returns a graph whose edges are the reversed edges of `graph`,
+    /// with their weights unchanged.
+    fn reverse_edges(graph: &Self) -> Self;
 }
 
 impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static> NamedGraph<Node> for Graph<Node> {
     fn name(nm: Name, g: Graph<Node>) -> Graph<Node> {
-        let edge_list = ns(name_of_str("list_of_tree"),
-                           move || list_of_tree(g.edge_tree, Dir2::Left));
-        let el = List::name_art(Some(nm), edge_list);
-        let edge_tree = ns(name_of_str("tree_of_list"),
-                           || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, el));
-        Graph::<Node> { edge_tree: edge_tree }
+        push_chunk(nm, Tree::nil(), g)
     }
 }
 
 impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static> GraphIntro<Node> for Graph<Node> {
     fn empty() -> Graph<Node> {
-        Graph::<Node> { edge_tree: Tree::nil() }
+        Graph::<Node> {
+            edge_tree: Tree::nil(),
+            chunk_ranks: vec![],
+        }
     }
 
+    /// Takes amortized constant time; see `push_chunk`.
     fn add_edge(graph: Graph<Node>, nm: Name, src: Node, dst: Node) -> Graph<Node> {
-        let edge_list = ns(name_of_str("list_of_tree"),
-                           move || list_of_tree(graph.edge_tree, Dir2::Left));
+        push_chunk(nm, Tree::leaf((src, dst)), graph)
+    }
+
+    fn remove_edge(graph: Graph<Node>, nm: Name, src: Node, dst: Node) -> Graph<Node> {
+        let edge_list = ns(name_of_str("filter_list_of_tree"), move || {
+            filter_list_of_tree(graph.edge_tree,
+                                Box::new(move |&(ref s, ref d): &(Node, Node)| {
+                                    *s != src || *d != dst
+                                }))
+        });
         let el = List::name_art(Some(nm), edge_list);
-        let el = List::cons((src, dst), el);
         let edge_tree = ns(name_of_str("tree_of_list"),
                            || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, el));
-        Graph::<Node> { edge_tree: edge_tree }
+        Graph::<Node> {
+            edge_tree: edge_tree,
+            chunk_ranks: vec![],
+        }
+    }
+
+    fn from_edges<I>(edges: I, name_stride: usize) -> Graph<Node>
+        where I: IntoIterator<Item = (Node, Node)>
+    {
+        let edges = edges.into_iter().collect();
+        Graph::<Node> {
+            edge_tree: edge_tree_of_edges(edges, name_of_usize, name_stride),
+            chunk_ranks: vec![],
+        }
     }
 }
 
@@ -86,6 +309,10 @@ impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static> GraphElim<Node> for
                       }))
     }
 
+    fn edges_iter(graph: &Graph<Node>) -> EdgesIter<Node> {
+        EdgesIter { stack: vec![Pending::Edges(graph.edge_tree.clone())] }
+    }
+
     fn vertices(graph: &Graph<Node>) -> Set<Node> {
         let edge_trie = ns(name_of_str("edge_trie_vertices"), || Self::edges(graph));
         trie_fold_seq(edge_trie,
@@ -108,14 +335,147 @@ impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static> GraphElim<Node> for
                       Rc::new(|g| g),
                       Rc::new(|nm: Name, g| Self::name(nm, g)))
     }
+
+    fn bfs(graph: &Graph<Node>, root: Node) -> List<Set<Node>> {
+        let adj = ns(name_of_str("adjacency_of_edge_list"),
+                     || adjacency_of_edge_list(graph));
+        bfs_levels(&adj, root)
+    }
+
+    fn successors(graph: &Graph<Node>, node: &Node) -> Option<Tree<Node>> {
+        let adj = ns(name_of_str("adjacency_of_edge_list"),
+                     || adjacency_of_edge_list(graph));
+        MapElim::find(&adj.adjacency_map, node)
+    }
+
+    fn filter_graph<NodeP, EdgeP>(graph: &Graph<Node>,
+                                  node_pred: Rc<NodeP>,
+                                  edge_pred: Rc<EdgeP>)
+                                  -> Graph<Node>
+        where NodeP: Fn(&Node) -> bool + 'static,
+              EdgeP: Fn(&Node, &Node) -> bool + 'static
+    {
+        let edge_tree = ns(name_of_str("filter_graph"), || {
+            filter_tree_of_tree(graph.edge_tree.clone(),
+                                Box::new(move |&(ref src, ref dst): &(Node, Node)| {
+                                    node_pred(src) && node_pred(dst) && edge_pred(src, dst)
+                                }))
+        });
+        Graph::<Node> {
+            edge_tree: edge_tree,
+            chunk_ranks: vec![],
+        }
+    }
+
+    fn union(graph: &Graph<Node>, other: &Graph<Node>) -> Graph<Node> {
+        Graph::<Node> {
+            edge_tree: tree_append(graph.edge_tree.clone(), other.edge_tree.clone()),
+            chunk_ranks: vec![],
+        }
+    }
+
+    fn edge_count(graph: &Graph<Node>) -> usize {
+        ns(name_of_str("edge_count"), || tree_size(graph.edge_tree.clone()))
+    }
+
+    /// An edge list has no index of its vertices, so this counts the
+    /// set of endpoints built by `vertices`.
+    fn vertex_count(graph: &Graph<Node>) -> usize {
+        ns(name_of_str("vertex_count"), || trie_size(Self::vertices(graph)))
+    }
+}
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static> Graph<Node> {
+    /// Like `edges`, but keeps parallel edges: maps each edge of
+    /// `graph` to the number of times it occurs.  The map carries the
+    /// names of the edge list.
+    pub fn edge_multiset(graph: &Graph<Node>) -> Trie<((Node, Node), usize)> {
+        ns(name_of_str("edge_multiset"), || {
+            tree_fold_seq(graph.edge_tree.clone(),
+                          Dir2::Left,
+                          MapIntro::empty(),
+                          Rc::new(|edge, map: Trie<((Node, Node), usize)>| {
+                              let count = MapElim::find(&map, &edge).unwrap_or(0);
+                              MapIntro::update(map, edge, count + 1)
+                          }),
+                          Rc::new(|_, map| map),
+                          Rc::new(|nm: Name, _, map| {
+                              TrieIntro::name(nm.clone(), TrieIntro::art(cell(nm, map)))
+                          }))
+        })
+    }
+}
+
+impl Graph<usize> {
+    /// Reads a graph from an edge list, one `src dst` pair per line, as
+    /// in the SNAP datasets.  The two vertices may be separated by
+    /// whitespace or a comma; blank lines and lines starting with `#`
+    /// or `%` are skipped, as are any fields after the second.  Every
+    /// `stride`-th edge, counting from zero, is preceded by a name,
+    /// `namer(i)` for the `i`th edge; a `stride` of zero inserts no
+    /// names.  The edges are kept in the order they were read.
+    pub fn from_edge_list_reader<R, F>(r: R, namer: F, stride: usize) -> io::Result<Graph<usize>>
+        where R: BufRead,
+              F: Fn(usize) -> Name
+    {
+        let mut edges = Vec::new();
+        for line in r.lines() {
+            let line = try!(line);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+                continue;
+            }
+            let mut fields = line.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|f| !f.is_empty())
+                .map(|f| f.parse::<usize>());
+            match (fields.next(), fields.next()) {
+                (Some(Ok(src)), Some(Ok(dst))) => edges.push((src, dst)),
+                _ => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                              format!("bad edge: {:?}", line)))
+                }
+            }
+        }
+        Ok(Graph::<usize> {
+            edge_tree: edge_tree_of_edges(edges, namer, stride),
+            chunk_ranks: vec![],
+        })
+    }
+}
+
+/// Builds an edge tree holding `edges` in order, with the name
+/// `namer(i)` before every `stride`-th edge `i`, or no names if
+/// `stride` is zero.
+fn edge_tree_of_edges<Node, F>(edges: Vec<(Node, Node)>,
+                               namer: F,
+                               stride: usize)
+                               -> Tree<(Node, Node)>
+    where Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+          F: Fn(usize) -> Name
+{
+    let mut el: List<(Node, Node)> = List::nil();
+    for (i, edge) in edges.into_iter().enumerate().rev() {
+        el = List::cons(edge, el);
+        if stride > 0 && i % stride == 0 {
+            el = List::name_art(Some(namer(i)), el);
+        }
+    }
+    ns(name_of_str("tree_of_list"),
+       || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, el))
 }
 
 impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static> NamedGraph<Node>
     for AdjacencyGraph<Node> {
     fn name(nm: Name, g: AdjacencyGraph<Node>) -> AdjacencyGraph<Node> {
+        let (_, nm_in) = name_fork(nm.clone());
         let adj = TrieIntro::name(nm.clone(),
                                   TrieIntro::art(cell(nm, g.adjacency_map)));
-        AdjacencyGraph { adjacency_map: adj }
+        let in_degrees = TrieIntro::name(nm_in.clone(),
+                                         TrieIntro::art(cell(nm_in, g.in_degree_map)));
+        AdjacencyGraph {
+            adjacency_map: adj,
+            in_degree_map: in_degrees,
+        }
     }
 }
 
@@ -124,44 +484,77 @@ impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
         fn empty() -> AdjacencyGraph<Node> {
             AdjacencyGraph::<Node> {
                 adjacency_map: MapIntro::empty(),
+                in_degree_map: MapIntro::empty(),
             }
         }
 
         fn add_edge(graph: AdjacencyGraph<Node>,
                     nm: Name, src: Node, dst: Node) -> AdjacencyGraph<Node> {
-            let src_adj_list = MapElim::find(&graph.adjacency_map, &src);
-            match src_adj_list {
-                None => {
-                    // let adj = List::name_art(Some(nm.clone()), List::nil());
-                    let adj = List::cons(dst, List::nil());
-                    let edge_tree = tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, adj);
-                    AdjacencyGraph::<Node> {
-                        adjacency_map: TrieIntro::extend(nm, graph.adjacency_map,
-                                                         (src, edge_tree)),
-                    }
-                }
+            let (nm_adj, nm_in) = name_fork(nm);
+            let in_degree = MapElim::find(&graph.in_degree_map, &dst).unwrap_or(0);
+            let in_degree_map = Trie::map_extend(nm_in, graph.in_degree_map,
+                                                 dst.clone(), in_degree + 1);
+            let adjacency_map =
+                MapIntro::update_with(graph.adjacency_map, nm_adj, src, Rc::new(move |adj_nodes| {
+                    let adj = match adj_nodes {
+                        None => List::nil(),
+                        Some(adj_nodes) => list_of_tree(adj_nodes, Dir2::Left),
+                    };
+                    let adj = List::cons(dst.clone(), adj);
+                    tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, adj)
+                }));
+            AdjacencyGraph::<Node> {
+                adjacency_map: adjacency_map,
+                in_degree_map: in_degree_map,
+            }
+        }
+
+        fn remove_edge(graph: AdjacencyGraph<Node>,
+                       nm: Name, src: Node, dst: Node) -> AdjacencyGraph<Node> {
+            match MapElim::find(&graph.adjacency_map, &src) {
+                None => graph,
                 Some(adj_nodes) => {
-                    let adj = list_of_tree(adj_nodes, Dir2::Left);
-                    // let adj = List::name_art(Some(nm.clone()), adj);
-                    let adj = List::cons(dst, adj);
+                    let (nm_adj, nm_in) = name_fork(nm);
+                    let removed = tree_count(adj_nodes.clone(), dst.clone());
+                    let in_degree_map = if removed == 0 {
+                        graph.in_degree_map
+                    } else {
+                        let in_degree = MapElim::find(&graph.in_degree_map, &dst).unwrap_or(0);
+                        Trie::map_extend(nm_in, graph.in_degree_map,
+                                         dst.clone(), in_degree - removed)
+                    };
+                    let adj: List<_> = filter_list_of_tree(adj_nodes,
+                                                           Box::new(move |d: &Node| *d != dst));
                     let edge_tree = tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, adj);
+                    let adjacency_map = Trie::map_extend(nm_adj, graph.adjacency_map,
+                                                         src, edge_tree);
                     AdjacencyGraph::<Node> {
-                        adjacency_map: TrieIntro::extend(nm, graph.adjacency_map,
-                                                         (src, edge_tree)),
+                        adjacency_map: adjacency_map,
+                        in_degree_map: in_degree_map,
                     }
                 }
             }
         }
+
+        /// Builds the edge-list graph of `edges`, then converts it, so
+        /// that the adjacency graph is named at the same strides.
+        fn from_edges<I>(edges: I, name_stride: usize) -> AdjacencyGraph<Node>
+            where I: IntoIterator<Item = (Node, Node)>
+        {
+            let graph = Graph::from_edges(edges, name_stride);
+            ns(name_of_str("adjacency_of_edge_list"),
+               || adjacency_of_edge_list(&graph))
+        }
     }
 
-impl<Node: Debug + Copy + Clone + Hash + PartialEq + Eq + 'static>
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
     GraphElim<Node> for AdjacencyGraph<Node> {
         fn edges(graph: &AdjacencyGraph<Node>) -> Set<(Node, Node)> {
             trie_fold_seq(graph.adjacency_map.clone(), SetIntro::empty(),
-                          Rc::new(|(src, dsts), set|
+                          Rc::new(|(src, dsts): (Node, Tree<Node>), set|
                                   tree_fold_seq(dsts, Dir2::Left, set,
                                                 Rc::new(move |dst, set|
-                                                        SetIntro::add(set, (src, dst))),
+                                                        SetIntro::add(set, (src.clone(), dst))),
                                                 Rc::new(|_, set| set),
                                                 Rc::new(|nm: Name, _, set| {
                                                     TrieIntro::name(nm.clone(),
@@ -174,6 +567,10 @@ impl<Node: Debug + Copy + Clone + Hash + PartialEq + Eq + 'static>
                                                   TrieIntro::art(cell(nm, set)))))
         }
 
+        fn edges_iter(graph: &AdjacencyGraph<Node>) -> EdgesIter<Node> {
+            EdgesIter { stack: vec![Pending::Sources(graph.adjacency_map.clone())] }
+        }
+
         fn vertices(graph: &AdjacencyGraph<Node>) -> Set<Node> {
             trie_fold_seq(graph.adjacency_map.clone(), SetIntro::empty(),
                           Rc::new(|(src, dsts), set| {
@@ -198,46 +595,2137 @@ impl<Node: Debug + Copy + Clone + Hash + PartialEq + Eq + 'static>
                           Rc::new(|g| g),
                           Rc::new(|nm: Name, g| Self::name(nm, g)))
         }
+
+        fn bfs(graph: &AdjacencyGraph<Node>, root: Node) -> List<Set<Node>> {
+            bfs_levels(graph, root)
+        }
+
+        fn successors(graph: &AdjacencyGraph<Node>, node: &Node) -> Option<Tree<Node>> {
+            MapElim::find(&graph.adjacency_map, node)
+        }
+
+        fn filter_graph<NodeP, EdgeP>(graph: &AdjacencyGraph<Node>,
+                                      node_pred: Rc<NodeP>,
+                                      edge_pred: Rc<EdgeP>)
+                                      -> AdjacencyGraph<Node>
+            where NodeP: Fn(&Node) -> bool + 'static,
+                  EdgeP: Fn(&Node, &Node) -> bool + 'static
+        {
+            filter_adjacency(graph, node_pred, edge_pred)
+        }
+
+        fn union(graph: &AdjacencyGraph<Node>,
+                 other: &AdjacencyGraph<Node>)
+                 -> AdjacencyGraph<Node> {
+            ns(name_of_str("graph_union"), || {
+                let adjacency_map = Trie::map_union(graph.adjacency_map.clone(),
+                                                    other.adjacency_map.clone(),
+                                                    Rc::new(tree_append));
+                // Merge the in-degrees in a namespace of their own, apart
+                // from the nodes of the merged adjacency lists.
+                let in_degree_map = ns(name_of_str("in_degree"), || {
+                    Trie::map_union(graph.in_degree_map.clone(),
+                                    other.in_degree_map.clone(),
+                                    Rc::new(|d1, d2| d1 + d2))
+                });
+                AdjacencyGraph {
+                    adjacency_map: adjacency_map,
+                    in_degree_map: in_degree_map,
+                }
+            })
+        }
+
+        fn edge_count(graph: &AdjacencyGraph<Node>) -> usize {
+            ns(name_of_str("edge_count"), || {
+                trie_fold(graph.adjacency_map.clone(),
+                          0,
+                          Rc::new(|(_, dsts), count| count + tree_size(dsts)))
+            })
+        }
+
+        /// Counts the sources of the adjacency map, and then the
+        /// vertices with edges into them that are not sources, without
+        /// building the set of vertices.  Both counts are memoized at
+        /// each name of the map they count.
+        fn vertex_count(graph: &AdjacencyGraph<Node>) -> usize {
+            ns(name_of_str("vertex_count"), || {
+                let sources = ns(name_of_str("sources"),
+                                 || trie_size(graph.adjacency_map.clone()));
+                let targets = ns(name_of_str("targets"), || {
+                    count_non_sources(graph.in_degree_map.clone(), graph.adjacency_map.clone())
+                });
+                sources + targets
+            })
+        }
     }
 
-pub fn adjacency_of_edge_list<X: Hash + Clone + Debug + PartialEq + Eq>(el_graph: &Graph<X>)
-                                                                        -> AdjacencyGraph<X> {
-    let adj_graph = AdjacencyGraph::empty();
-    tree_fold_seq(el_graph.edge_tree.clone(),
-                  Dir2::Left,
-                  adj_graph,
-                  Rc::new(|(src, dst), g| AdjacencyGraph::add_edge(g, name_unit(), src, dst)),
-                  Rc::new(|_, set| set),
-                  Rc::new(|nm: Name, _, g: AdjacencyGraph<_>| AdjacencyGraph::name(nm, g)))
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     W: Debug + Clone + Hash + PartialEq + Eq + 'static> NamedGraph<Node>
+    for WeightedGraph<Node, W> {
+    fn name(nm: Name, g: WeightedGraph<Node, W>) -> WeightedGraph<Node, W> {
+        let adj = TrieIntro::name(nm.clone(),
+                                  TrieIntro::art(cell(nm, g.adjacency_map)));
+        WeightedGraph { adjacency_map: adj }
+    }
 }
 
-pub fn edge_list_of_adjacency
-    <X: Hash + Clone + Debug + PartialEq + Eq>(adj_graph: &AdjacencyGraph<X>)
-                                               -> Graph<X> {
-    let edge_list = Graph::empty();
-    trie_fold_seq(adj_graph.adjacency_map.clone(),
-                  edge_list,
-                  Rc::new(|(src, dsts): (X, Tree<X>), graph: Graph<X>| {
-        let edge_tree = tree_fold_seq(dsts,
-                                      Dir2::Left,
-                                      graph.edge_tree,
-                                      Rc::new(move |dst, g| {
-            let edge_list = ns(name_of_str("list_of_tree"),
-                               move || list_of_tree(g, Dir2::Left));
-            let el = List::cons((src.clone(), dst), edge_list);
-            ns(name_of_str("tree_of_list"),
-               || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, el))
-        }),
-                                      Rc::new(|_, g| g),
-                                      Rc::new(|nm: Name, _, g| {
-            let edge_list = ns(name_of_str("list_of_tree"),
-                               move || list_of_tree(g, Dir2::Left));
-            let el = List::name_art(Some(nm), edge_list);
-            ns(name_of_str("tree_of_list"),
-               || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, el))
-        }));
-        Graph::<X> { edge_tree: edge_tree }
-    }),
-                  Rc::new(|g| g),
-                  Rc::new(|nm: Name, g: Graph<X>| Graph::name(nm, g)))
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     W: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    WeightedGraphIntro<Node, W> for WeightedGraph<Node, W> {
+        fn empty() -> WeightedGraph<Node, W> {
+            WeightedGraph { adjacency_map: MapIntro::empty() }
+        }
+
+        fn add_edge(graph: WeightedGraph<Node, W>,
+                    nm: Name, src: Node, dst: Node, weight: W) -> WeightedGraph<Node, W> {
+            let adj = match MapElim::find(&graph.adjacency_map, &src) {
+                None => List::nil(),
+                Some(adj_nodes) => list_of_tree(adj_nodes, Dir2::Left),
+            };
+            let adj = List::cons((dst, weight), adj);
+            let edge_tree = tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, adj);
+            WeightedGraph {
+                adjacency_map: Trie::map_extend(nm, graph.adjacency_map, src, edge_tree),
+            }
+        }
+    }
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     W: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    WeightedGraphElim<Node, W> for WeightedGraph<Node, W> {
+        fn edges(graph: &WeightedGraph<Node, W>) -> Trie<((Node, Node), W)> {
+            trie_fold_seq(graph.adjacency_map.clone(), MapIntro::empty(),
+                          Rc::new(|(src, dsts): (Node, Tree<(Node, W)>), map|
+                                  tree_fold_seq(dsts, Dir2::Left, map,
+                                                Rc::new(move |(dst, w), map| {
+                                                    MapIntro::update(map, (src.clone(), dst), w)
+                                                }),
+                                                Rc::new(|_, map| map),
+                                                Rc::new(|nm: Name, _, map| {
+                                                    TrieIntro::name(nm.clone(),
+                                                                    TrieIntro::art(cell(nm, map)))
+                                                }))),
+                          Rc::new(|map| map),
+                          Rc::new(|nm: Name, map|
+                                  TrieIntro::name(nm.clone(),
+                                                  TrieIntro::art(cell(nm, map)))))
+        }
+
+        fn vertices(graph: &WeightedGraph<Node, W>) -> Set<Node> {
+            trie_fold_seq(graph.adjacency_map.clone(), SetIntro::empty(),
+                          Rc::new(|(src, dsts), set| {
+                              let src_set = SetIntro::add(set, src);
+                              tree_fold_seq(dsts, Dir2::Left, src_set,
+                                            Rc::new(|(dst, _), set| SetIntro::add(set, dst)),
+                                            Rc::new(|_, set| set),
+                                            Rc::new(|nm: Name, _, set|
+                                                    TrieIntro::name(nm.clone(),
+                                                                    TrieIntro::art(cell(nm, set)))))
+                          }),
+                          Rc::new(|set| set),
+                          Rc::new(|nm: Name, set| TrieIntro::name(nm.clone(),
+                                                                  TrieIntro::art(cell(nm, set)))))
+        }
+
+        fn reverse_edges(graph: &WeightedGraph<Node, W>) -> WeightedGraph<Node, W> {
+            let es = Self::edges(graph);
+            trie_fold_seq(es,
+                          Self::empty(),
+                          Rc::new(|((src, dst), w), g| {
+                              Self::add_edge(g, name_unit(), dst, src, w)
+                          }),
+                          Rc::new(|g| g),
+                          Rc::new(|nm: Name, g| Self::name(nm, g)))
+        }
+    }
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     NodeAttr: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     EdgeAttr: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    GraphIntro<Node> for LabeledGraph<Node, NodeAttr, EdgeAttr> {
+        fn empty() -> Self {
+            LabeledGraph {
+                graph: GraphIntro::empty(),
+                node_attrs: MapIntro::empty(),
+                edge_attrs: MapIntro::empty(),
+            }
+        }
+
+        fn add_edge(graph: Self, nm: Name, src: Node, dst: Node) -> Self {
+            LabeledGraph { graph: GraphIntro::add_edge(graph.graph, nm, src, dst), ..graph }
+        }
+
+        /// Removes the edge; any attribute it carries is kept, and
+        /// applies again if the edge is re-added.
+        fn remove_edge(graph: Self, nm: Name, src: Node, dst: Node) -> Self {
+            LabeledGraph { graph: GraphIntro::remove_edge(graph.graph, nm, src, dst), ..graph }
+        }
+
+        fn from_edges<I>(edges: I, name_stride: usize) -> Self
+            where I: IntoIterator<Item = (Node, Node)>
+        {
+            LabeledGraph {
+                graph: GraphIntro::from_edges(edges, name_stride),
+                node_attrs: MapIntro::empty(),
+                edge_attrs: MapIntro::empty(),
+            }
+        }
+    }
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     NodeAttr: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     EdgeAttr: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    GraphElim<Node> for LabeledGraph<Node, NodeAttr, EdgeAttr> {
+        fn edges(graph: &Self) -> Set<(Node, Node)> {
+            GraphElim::edges(&graph.graph)
+        }
+
+        fn vertices(graph: &Self) -> Set<Node> {
+            GraphElim::vertices(&graph.graph)
+        }
+
+        fn edges_iter(graph: &Self) -> EdgesIter<Node> {
+            GraphElim::edges_iter(&graph.graph)
+        }
+
+        /// Reverses the edges, carrying each edge's attribute over to
+        /// its reversal.
+        fn reverse_edges(graph: &Self) -> Self {
+            let edge_attrs = trie_fold_seq(graph.edge_attrs.clone(),
+                                           MapIntro::empty(),
+                                           Rc::new(|((src, dst), attr), map| {
+                                               MapIntro::update(map, (dst, src), attr)
+                                           }),
+                                           Rc::new(|map| map),
+                                           Rc::new(|nm: Name, map| {
+                                               TrieIntro::name(nm.clone(),
+                                                               TrieIntro::art(cell(nm, map)))
+                                           }));
+            LabeledGraph {
+                graph: GraphElim::reverse_edges(&graph.graph),
+                node_attrs: graph.node_attrs.clone(),
+                edge_attrs: edge_attrs,
+            }
+        }
+
+        fn bfs(graph: &Self, root: Node) -> List<Set<Node>> {
+            GraphElim::bfs(&graph.graph, root)
+        }
+
+        fn successors(graph: &Self, node: &Node) -> Option<Tree<Node>> {
+            GraphElim::successors(&graph.graph, node)
+        }
+
+        /// Filters the underlying graph, and drops the attributes of
+        /// the vertices and edges that are filtered out.
+        fn filter_graph<NodeP, EdgeP>(graph: &Self, node_pred: Rc<NodeP>, edge_pred: Rc<EdgeP>)
+                                      -> Self
+            where NodeP: Fn(&Node) -> bool + 'static,
+                  EdgeP: Fn(&Node, &Node) -> bool + 'static
+        {
+            ns(name_of_str("filter_labels"), || {
+                let np = node_pred.clone();
+                let node_attrs = trie_retain(graph.node_attrs.clone(),
+                                             Rc::new(move |&(ref v, _): &(Node, NodeAttr)| {
+                                                 np(v)
+                                             }));
+                let (np, ep) = (node_pred.clone(), edge_pred.clone());
+                let edge_attrs = trie_retain(graph.edge_attrs.clone(),
+                                             Rc::new(move |&((ref s, ref d), _):
+                                                           &((Node, Node), EdgeAttr)| {
+                                                 np(s) && np(d) && ep(s, d)
+                                             }));
+                LabeledGraph {
+                    graph: GraphElim::filter_graph(&graph.graph, node_pred, edge_pred),
+                    node_attrs: node_attrs,
+                    edge_attrs: edge_attrs,
+                }
+            })
+        }
+
+        fn edge_count(graph: &Self) -> usize {
+            GraphElim::edge_count(&graph.graph)
+        }
+
+        fn vertex_count(graph: &Self) -> usize {
+            GraphElim::vertex_count(&graph.graph)
+        }
+
+        /// Where both graphs label the same vertex or edge, the label
+        /// from `other` wins.
+        fn union(graph: &Self, other: &Self) -> Self {
+            let node_attrs = ns(name_of_str("node_attrs"), || {
+                Trie::map_union(graph.node_attrs.clone(),
+                                other.node_attrs.clone(),
+                                Rc::new(|_, attr| attr))
+            });
+            let edge_attrs = ns(name_of_str("edge_attrs"), || {
+                Trie::map_union(graph.edge_attrs.clone(),
+                                other.edge_attrs.clone(),
+                                Rc::new(|_, attr| attr))
+            });
+            LabeledGraph {
+                graph: GraphElim::union(&graph.graph, &other.graph),
+                node_attrs: node_attrs,
+                edge_attrs: edge_attrs,
+            }
+        }
+    }
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     NodeAttr: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     EdgeAttr: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    LabeledGraph<Node, NodeAttr, EdgeAttr> {
+    /// Sets the attribute of `node`, replacing any previous one.  The
+    /// update is named after `node`.
+    pub fn set_node_attr(graph: Self, node: Node, attr: NodeAttr) -> Self {
+        let nm = name_of_node(&node);
+        LabeledGraph { node_attrs: Trie::map_extend(nm, graph.node_attrs, node, attr), ..graph }
+    }
+
+    /// Returns the attribute of `node`, if it has one.
+    pub fn get_node_attr(graph: &Self, node: &Node) -> Option<NodeAttr> {
+        MapElim::find(&graph.node_attrs, node)
+    }
+
+    /// Sets the attribute of the edge `(src, dst)`, replacing any
+    /// previous one.  The update is named after the edge's endpoints.
+    pub fn set_edge_attr(graph: Self, src: Node, dst: Node, attr: EdgeAttr) -> Self {
+        let nm = name_pair(name_of_node(&src), name_of_node(&dst));
+        LabeledGraph {
+            edge_attrs: Trie::map_extend(nm, graph.edge_attrs, (src, dst), attr),
+            ..graph
+        }
+    }
+
+    /// Returns the attribute of the edge `(src, dst)`, if it has one.
+    pub fn get_edge_attr(graph: &Self, src: &Node, dst: &Node) -> Option<EdgeAttr> {
+        MapElim::find(&graph.edge_attrs, &(src.clone(), dst.clone()))
+    }
+}
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    GraphIntro<Node> for BiAdjacencyGraph<Node> {
+        fn empty() -> Self {
+            BiAdjacencyGraph {
+                forward: GraphIntro::empty(),
+                backward: GraphIntro::empty(),
+            }
+        }
+
+        fn add_edge(graph: Self, nm: Name, src: Node, dst: Node) -> Self {
+            let (nm_fwd, nm_bwd) = name_fork(nm);
+            BiAdjacencyGraph {
+                forward: GraphIntro::add_edge(graph.forward, nm_fwd, src.clone(), dst.clone()),
+                backward: GraphIntro::add_edge(graph.backward, nm_bwd, dst, src),
+            }
+        }
+
+        fn remove_edge(graph: Self, nm: Name, src: Node, dst: Node) -> Self {
+            let (nm_fwd, nm_bwd) = name_fork(nm);
+            BiAdjacencyGraph {
+                forward: GraphIntro::remove_edge(graph.forward, nm_fwd, src.clone(), dst.clone()),
+                backward: GraphIntro::remove_edge(graph.backward, nm_bwd, dst, src),
+            }
+        }
+
+        fn from_edges<I>(edges: I, name_stride: usize) -> Self
+            where I: IntoIterator<Item = (Node, Node)>
+        {
+            let edges: Vec<_> = edges.into_iter().collect();
+            let reversed: Vec<_> = edges.iter()
+                .map(|&(ref src, ref dst)| (dst.clone(), src.clone()))
+                .collect();
+            BiAdjacencyGraph {
+                forward: ns(name_of_str("forward"),
+                            || GraphIntro::from_edges(edges, name_stride)),
+                backward: ns(name_of_str("backward"),
+                             || GraphIntro::from_edges(reversed, name_stride)),
+            }
+        }
+    }
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    GraphElim<Node> for BiAdjacencyGraph<Node> {
+        fn edges(graph: &Self) -> Set<(Node, Node)> {
+            GraphElim::edges(&graph.forward)
+        }
+
+        fn vertices(graph: &Self) -> Set<Node> {
+            GraphElim::vertices(&graph.forward)
+        }
+
+        fn edges_iter(graph: &Self) -> EdgesIter<Node> {
+            GraphElim::edges_iter(&graph.forward)
+        }
+
+        /// Swaps the two directions, in constant time.
+        fn reverse_edges(graph: &Self) -> Self {
+            BiAdjacencyGraph {
+                forward: graph.backward.clone(),
+                backward: graph.forward.clone(),
+            }
+        }
+
+        fn bfs(graph: &Self, root: Node) -> List<Set<Node>> {
+            GraphElim::bfs(&graph.forward, root)
+        }
+
+        fn successors(graph: &Self, node: &Node) -> Option<Tree<Node>> {
+            GraphElim::successors(&graph.forward, node)
+        }
+
+        fn filter_graph<NodeP, EdgeP>(graph: &Self, node_pred: Rc<NodeP>, edge_pred: Rc<EdgeP>)
+                                      -> Self
+            where NodeP: Fn(&Node) -> bool + 'static,
+                  EdgeP: Fn(&Node, &Node) -> bool + 'static
+        {
+            let (np, ep) = (node_pred.clone(), edge_pred.clone());
+            let forward = ns(name_of_str("forward"),
+                             || filter_adjacency(&graph.forward, np, ep));
+            let backward = ns(name_of_str("backward"), || {
+                filter_adjacency(&graph.backward,
+                                 node_pred,
+                                 Rc::new(move |dst: &Node, src: &Node| edge_pred(src, dst)))
+            });
+            BiAdjacencyGraph {
+                forward: forward,
+                backward: backward,
+            }
+        }
+
+        fn union(graph: &Self, other: &Self) -> Self {
+            BiAdjacencyGraph {
+                forward: ns(name_of_str("forward"),
+                            || GraphElim::union(&graph.forward, &other.forward)),
+                backward: ns(name_of_str("backward"),
+                             || GraphElim::union(&graph.backward, &other.backward)),
+            }
+        }
+
+        fn edge_count(graph: &Self) -> usize {
+            GraphElim::edge_count(&graph.forward)
+        }
+
+        fn vertex_count(graph: &Self) -> usize {
+            GraphElim::vertex_count(&graph.forward)
+        }
+    }
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static> BiAdjacencyGraph<Node> {
+    /// Returns the sources of the edges into `node`, or `None` if
+    /// `node` has no incoming edges in `graph`.
+    pub fn predecessors(graph: &Self, node: &Node) -> Option<Tree<Node>> {
+        MapElim::find(&graph.backward.adjacency_map, node)
+    }
+
+    /// Returns the number of edges into `node`.
+    pub fn in_degree(graph: &Self, node: &Node) -> usize {
+        in_degree(&graph.forward, node)
+    }
+
+    /// Returns the number of edges out of `node`.
+    pub fn out_degree(graph: &Self, node: &Node) -> usize {
+        in_degree(&graph.backward, node)
+    }
+}
+
+/// An iterator over the edges of a graph, returned by
+/// `GraphElim::edges_iter`.  It walks the graph's trees and tries
+/// depth first, forcing each articulation when it reaches it.
+pub struct EdgesIter<Node> {
+    stack: Vec<Pending<Node>>,
+}
+
+/// A part of a graph that an `EdgesIter` has yet to visit.
+enum Pending<Node> {
+    /// Part of an edge-list graph's tree of edges.
+    Edges(Tree<(Node, Node)>),
+    /// Part of an adjacency graph's map from sources to targets.
+    Sources(Trie<(Node, Tree<Node>)>),
+    /// Part of the targets of the edges out of a source.
+    Targets(Node, Tree<Node>),
+}
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static> Iterator for EdgesIter<Node> {
+    type Item = (Node, Node);
+
+    fn next(&mut self) -> Option<(Node, Node)> {
+        while let Some(pending) = self.stack.pop() {
+            match pending {
+                Pending::Edges(tree) => {
+                    match tree {
+                        Tree::Nil => (),
+                        Tree::Leaf(edge) => return Some(edge),
+                        Tree::Bin(_, l, r) |
+                        Tree::Name(_, _, l, r) => {
+                            self.stack.push(Pending::Edges(*r));
+                            self.stack.push(Pending::Edges(*l));
+                        }
+                        Tree::Art(a) => self.stack.push(Pending::Edges(force(&a))),
+                    }
+                }
+                Pending::Sources(trie) => {
+                    match trie {
+                        Trie::Nil(_) => (),
+                        Trie::Leaf(_, (src, dsts)) => self.stack.push(Pending::Targets(src, dsts)),
+                        Trie::Bin(_, l, r) => {
+                            self.stack.push(Pending::Sources(*r));
+                            self.stack.push(Pending::Sources(*l));
+                        }
+                        Trie::Root(_, t) |
+                        Trie::Name(_, t) => self.stack.push(Pending::Sources(*t)),
+                        Trie::Art(a) => self.stack.push(Pending::Sources(force(&a))),
+                    }
+                }
+                Pending::Targets(src, tree) => {
+                    match tree {
+                        Tree::Nil => (),
+                        Tree::Leaf(dst) => return Some((src, dst)),
+                        Tree::Bin(_, l, r) |
+                        Tree::Name(_, _, l, r) => {
+                            self.stack.push(Pending::Targets(src.clone(), *r));
+                            self.stack.push(Pending::Targets(src, *l));
+                        }
+                        Tree::Art(a) => self.stack.push(Pending::Targets(src, force(&a))),
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Builds a graph one edge at a time, naming each edge for you.  The
+/// `i`th edge added is named `name_pair(base, name_of_usize(i))`, and
+/// every insertion runs in the namespace `base`, so that two builders
+/// with different bases never share names.
+#[derive(Debug,Clone)]
+pub struct GraphBuilder<Node, G> {
+    base: Name,
+    count: usize,
+    graph: G,
+    phantom: PhantomData<Node>,
+}
+
+impl<Node, G: GraphIntro<Node>> GraphBuilder<Node, G> {
+    /// Starts building an empty graph, whose names derive from `base`.
+    pub fn new(base: Name) -> Self {
+        GraphBuilder {
+            base: base,
+            count: 0,
+            graph: G::empty(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Adds the edge `(src, dst)` under a fresh name.
+    pub fn edge(self, src: Node, dst: Node) -> Self {
+        let nm = name_pair(self.base.clone(), name_of_usize(self.count));
+        let graph = self.graph;
+        let graph = ns(self.base.clone(), || G::add_edge(graph, nm, src, dst));
+        GraphBuilder {
+            count: self.count + 1,
+            graph: graph,
+            ..self
+        }
+    }
+
+    /// Adds each of `edges`, in order, as by `edge`.
+    pub fn edges<I>(self, edges: I) -> Self
+        where I: IntoIterator<Item = (Node, Node)>
+    {
+        edges.into_iter().fold(self, |b, (src, dst)| b.edge(src, dst))
+    }
+
+    /// Returns the graph built so far.
+    pub fn build(self) -> G {
+        self.graph
+    }
+}
+
+/// `GraphElim::filter_graph` for adjacency graphs.
+fn filter_adjacency<Node, NodeP, EdgeP>(graph: &AdjacencyGraph<Node>,
+                                        node_pred: Rc<NodeP>,
+                                        edge_pred: Rc<EdgeP>)
+                                        -> AdjacencyGraph<Node>
+    where Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+          NodeP: Fn(&Node) -> bool + 'static,
+          EdgeP: Fn(&Node, &Node) -> bool + 'static
+{
+    ns(name_of_str("filter_graph"), || {
+        // Filtering only changes the adjacency lists, never the
+        // keys, so the trie keeps its shape and its names.
+        let adjacency_map =
+            trie_fold_up(graph.adjacency_map.clone(),
+                         Rc::new(|bs| Trie::nil(bs)),
+                         Rc::new(move |bs, (src, dsts): (Node, Tree<Node>)| {
+                             if !node_pred(&src) {
+                                 return Trie::nil(bs);
+                             }
+                             let node_pred = node_pred.clone();
+                             let edge_pred = edge_pred.clone();
+                             let s = src.clone();
+                             let dsts = filter_tree_of_tree(dsts,
+                                                            Box::new(move |dst: &Node| {
+                                 node_pred(dst) && edge_pred(&s, dst)
+                             }));
+                             Trie::leaf(bs, (src, dsts))
+                         }),
+                         Rc::new(|bs, l, r| Trie::bin(bs, l, r)),
+                         Rc::new(|meta, t| Trie::root(meta, t)),
+                         Rc::new(|n: Name, t| {
+                             let (n_art, _) = name_fork(n.clone());
+                             Trie::name(n, Trie::art(cell(n_art, t)))
+                         }));
+        let in_degree_map =
+            trie_fold(adjacency_map.clone(),
+                      MapIntro::empty(),
+                      Rc::new(|(_, dsts): (Node, Tree<Node>), map| {
+                          tree_fold_seq(dsts,
+                                        Dir2::Left,
+                                        map,
+                                        Rc::new(|dst, map: Trie<(Node, usize)>| {
+                                            let deg = MapElim::find(&map, &dst).unwrap_or(0);
+                                            MapIntro::update(map, dst, deg + 1)
+                                        }),
+                                        Rc::new(|_, map| map),
+                                        Rc::new(|_, _, map| map))
+                      }));
+        AdjacencyGraph {
+            adjacency_map: adjacency_map,
+            in_degree_map: in_degree_map,
+        }
+    })
+}
+
+/// The subgraph of `graph` induced by `vs`: the edges of `graph`
+/// whose ends are both in `vs`.  The adjacency map keeps its shape and
+/// its names, so adding or removing one vertex of `vs` only dirties
+/// the adjacency entries that mention it.
+pub fn induced_subgraph<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     vs: &Set<Node>)
+     -> AdjacencyGraph<Node> {
+    let vs = vs.clone();
+    filter_adjacency(graph,
+                     Rc::new(move |v: &Node| SetElim::is_mem(&vs, v)),
+                     Rc::new(|_: &Node, _: &Node| true))
+}
+
+/// Joins two adjacency trees under a new `Bin`, in constant time.
+fn tree_append<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (left: Tree<Node>,
+     right: Tree<Node>)
+     -> Tree<Node> {
+    let lev = Level::max(&TreeElim::lev_of_tree(&left), &TreeElim::lev_of_tree(&right));
+    TreeIntro::bin(Level::inc(&lev), left, right)
+}
+
+/// Pushes `chunk` onto the front of the edge tree of `graph`.  A tree
+/// built by `add_edge` is a chain of chunks, each named, holding a
+/// balanced tree of rank `r` (that is, of about `2^r` edges) with the
+/// rest of the chain in a cell under the same name; ranks rise along
+/// the chain, and are kept in the graph's `chunk_ranks`.  Like
+/// incrementing a binary counter, the new chunk, of rank zero, is
+/// merged with each chunk of equal rank at the front of the chain, so
+/// the push takes amortized constant time, and keeps the tree's depth
+/// logarithmic.  A graph whose tree has any other shape has no ranks,
+/// and its tree is treated as the end of a chain.
+fn push_chunk<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (nm: Name,
+     chunk: Tree<(Node, Node)>,
+     graph: Graph<Node>)
+     -> Graph<Node> {
+    let Graph { edge_tree: mut rest, chunk_ranks: mut ranks } = graph;
+    let mut chunk = chunk;
+    let mut rank = 0;
+    while ranks.last() == Some(&rank) {
+        let (front_nm, front, front_rest) = match rest {
+            Tree::Name(front_nm, _, front, front_rest) => {
+                match *front_rest {
+                    Tree::Art(ref a) => (front_nm, *front, force(a)),
+                    _ => panic!("push_chunk: the chain of chunks ends early"),
+                }
+            }
+            _ => panic!("push_chunk: the chain of chunks ends early"),
+        };
+        let lev = Level::max(&TreeElim::lev_of_tree(&chunk), &TreeElim::lev_of_tree(&front));
+        chunk = TreeIntro::name(front_nm, Level::inc(&lev), chunk, front);
+        ranks.pop();
+        rank += 1;
+        rest = front_rest;
+    }
+    ranks.push(rank);
+    let lev = Level::max(&TreeElim::lev_of_tree(&chunk), &TreeElim::lev_of_tree(&rest));
+    let rest = TreeIntro::art(cell(nm.clone(), rest));
+    Graph::<Node> {
+        edge_tree: TreeIntro::name(nm, Level::inc(&lev), chunk, rest),
+        chunk_ranks: ranks,
+    }
+}
+
+/// Counts the occurrences of `node` in an adjacency tree.
+fn tree_count<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (tree: Tree<Node>,
+     node: Node)
+     -> usize {
+    tree_fold_seq(tree,
+                  Dir2::Left,
+                  0,
+                  Rc::new(move |n, cnt| if n == node { cnt + 1 } else { cnt }),
+                  Rc::new(|_, cnt| cnt),
+                  Rc::new(|_, _, cnt| cnt))
+}
+
+/// Counts the nodes of an adjacency tree.
+fn tree_size<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>(tree: Tree<Node>) -> usize {
+    tree_fold_seq(tree,
+                  Dir2::Left,
+                  0,
+                  Rc::new(|_, cnt| cnt + 1),
+                  Rc::new(|_, cnt| cnt),
+                  Rc::new(|_, _, cnt| cnt))
+}
+
+/// Counts the elements of a trie, memoizing the count of each subtrie
+/// at its name.
+fn trie_size<X: Debug + Clone + Hash + PartialEq + Eq + 'static>(trie: Trie<X>) -> usize {
+    trie_fold_up(trie,
+                 Rc::new(|_| 0),
+                 Rc::new(|_, _| 1),
+                 Rc::new(|_, l, r| l + r),
+                 Rc::new(|_, cnt| cnt),
+                 Rc::new(|_, cnt| cnt))
+}
+
+/// Counts the vertices of `in_degrees` with edges into them that are
+/// not sources of `adjacency_map`.  The count of each subtrie is
+/// memoized at its name, keyed on the subtrie and the adjacency map.
+fn count_non_sources<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (in_degrees: Trie<(Node, usize)>,
+     adjacency_map: Trie<(Node, Tree<Node>)>)
+     -> usize {
+    Trie::elim_arg(in_degrees,
+                   adjacency_map,
+                   |_, _| 0,
+                   |_, (v, deg), map| if deg > 0 && MapElim::find(&map, &v).is_none() {
+                       1
+                   } else {
+                       0
+                   },
+                   |_, l, r, map| count_non_sources(l, map.clone()) + count_non_sources(r, map),
+                   |_, t, map| count_non_sources(t, map),
+                   |nm, t, map| {
+                       memo!(nm =>> count_non_sources::<Node>, in_degrees:t, adjacency_map:map)
+                   })
+}
+
+/// Returns the number of edges out of `node`.
+pub fn out_degree<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     node: &Node)
+     -> usize {
+    MapElim::find(&graph.adjacency_map, node).map_or(0, tree_size)
+}
+
+/// Returns the number of edges into `node`.  In-degrees are maintained
+/// as edges are added and removed, so this is a single lookup.
+pub fn in_degree<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     node: &Node)
+     -> usize {
+    MapElim::find(&graph.in_degree_map, node).unwrap_or(0)
+}
+
+/// Maps each vertex to its degree: the number of edges into it plus
+/// the number of edges out of it.
+pub fn degrees<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>)
+     -> Trie<(Node, usize)> {
+    ns(name_of_str("degrees"), || {
+        let in_degrees = graph.in_degree_map.clone();
+        trie_fold(graph.adjacency_map.clone(),
+                  in_degrees,
+                  Rc::new(|(src, dsts): (Node, Tree<Node>), map: Trie<(Node, usize)>| {
+            let deg = MapElim::find(&map, &src).unwrap_or(0);
+            MapIntro::update(map, src, deg + tree_size(dsts))
+        }))
+    })
+}
+
+/// Breadth-first search from `root`, as a list of levels.  The search
+/// runs in a namespace derived from `root`, so that searches from
+/// different roots keep separate levels, and reads the adjacency map
+/// through a single named cell.
+fn bfs_levels<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     root: Node)
+     -> List<Set<Node>> {
+    ns(name_pair(name_of_str("bfs"), name_of_node(&root)), || {
+        let adj = cell(name_of_str("adjacency"), graph.adjacency_map.clone());
+        bfs_from(adj, root)
+    })
+}
+
+/// Breadth-first search from `root` in the adjacency map held by `adj`.
+fn bfs_from<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: Art<Trie<(Node, Tree<Node>)>>,
+     root: Node)
+     -> List<Set<Node>> {
+    let frontier = SetIntro::add(SetIntro::empty(), root.clone());
+    let visited = SetIntro::add(SetIntro::empty(), root);
+    bfs_rec(adj, frontier, visited, 0)
+}
+
+/// The successors of `src` in the adjacency map held by `adj`.
+fn bfs_successors<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: Art<Trie<(Node, Tree<Node>)>>,
+     src: Node)
+     -> Tree<Node> {
+    MapElim::find(&force(&adj), &src).unwrap_or(Tree::nil())
+}
+
+/// One level of a breadth-first search: `frontier` holds the vertices
+/// at distance `level` from the root, and `visited` every vertex seen
+/// so far.  Each level is a named thunk, and each successor lookup is
+/// memoized per vertex, so that after an edit to the map the search
+/// re-executes only the levels whose vertices gained or lost edges.
+fn bfs_rec<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: Art<Trie<(Node, Tree<Node>)>>,
+     frontier: Set<Node>,
+     visited: Set<Node>,
+     level: usize)
+     -> List<Set<Node>> {
+    if TrieElim::is_empty(&frontier) {
+        return List::nil();
+    }
+    let (next, visited) = ns(name_pair(name_of_str("level"), name_of_usize(level)), || {
+        let adj = adj.clone();
+        trie_fold(frontier.clone(),
+                  (SetIntro::empty(), visited),
+                  Rc::new(move |(src, ()), acc| {
+            let dsts = memo!(name_of_node(&src) =>> bfs_successors, adj:adj.clone(), src:src);
+            tree_fold_seq(dsts,
+                          Dir2::Left,
+                          acc,
+                          Rc::new(|dst, (next, visited): (Set<Node>, Set<Node>)| {
+                if SetElim::is_mem(&visited, &dst) {
+                    (next, visited)
+                } else {
+                    (SetIntro::add(next, dst.clone()), SetIntro::add(visited, dst))
+                }
+            }),
+                          Rc::new(|_, acc| acc),
+                          Rc::new(|_, _, acc| acc))
+        }))
+    });
+    let nm = name_of_usize(level + 1);
+    let (rest, _) = eager!(nm.clone() =>> bfs_rec,
+                           adj:adj, frontier:next, visited:visited, level:level + 1);
+    List::cons(frontier, List::name(nm, List::art(rest)))
+}
+
+pub fn adjacency_of_edge_list<X: Hash + Clone + Debug + PartialEq + Eq>(el_graph: &Graph<X>)
+                                                                        -> AdjacencyGraph<X> {
+    let adj_graph = AdjacencyGraph::empty();
+    tree_fold_seq(el_graph.edge_tree.clone(),
+                  Dir2::Left,
+                  adj_graph,
+                  Rc::new(|(src, dst), g| AdjacencyGraph::add_edge(g, name_unit(), src, dst)),
+                  Rc::new(|_, set| set),
+                  Rc::new(|nm: Name, _, g: AdjacencyGraph<_>| AdjacencyGraph::name(nm, g)))
+}
+
+pub fn edge_list_of_adjacency
+    <X: Hash + Clone + Debug + PartialEq + Eq>(adj_graph: &AdjacencyGraph<X>)
+                                               -> Graph<X> {
+    let edge_list = Graph::empty();
+    trie_fold_seq(adj_graph.adjacency_map.clone(),
+                  edge_list,
+                  Rc::new(|(src, dsts): (X, Tree<X>), graph: Graph<X>| {
+        let edge_tree = tree_fold_seq(dsts,
+                                      Dir2::Left,
+                                      graph.edge_tree,
+                                      Rc::new(move |dst, g| {
+            let edge_list = ns(name_of_str("list_of_tree"),
+                               move || list_of_tree(g, Dir2::Left));
+            let el = List::cons((src.clone(), dst), edge_list);
+            ns(name_of_str("tree_of_list"),
+               || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, el))
+        }),
+                                      Rc::new(|_, g| g),
+                                      Rc::new(|nm: Name, _, g| {
+            let edge_list = ns(name_of_str("list_of_tree"),
+                               move || list_of_tree(g, Dir2::Left));
+            let el = List::name_art(Some(nm), edge_list);
+            ns(name_of_str("tree_of_list"),
+               || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, el))
+        }));
+        Graph::<X> {
+            edge_tree: edge_tree,
+            chunk_ranks: vec![],
+        }
+    }),
+                  Rc::new(|g| g),
+                  Rc::new(|nm: Name, g: Graph<X>| Graph::name(nm, g)))
+}
+
+/// Hashes `x` with the default hasher, whose keys are fixed.
+fn hash_of<T: Hash>(x: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    x.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Derives a name for a vertex from its hash, for use as a memo point.
+fn name_of_node<Node: Hash>(node: &Node) -> Name {
+    name_of_usize(hash_of(node) as usize)
+}
+
+/// The state threaded through a depth-first search: the pre-order and
+/// post-order numberings so far, and the next number of each.
+type DfsState<Node> = (Trie<(Node, usize)>, Trie<(Node, usize)>, usize, usize);
+
+/// Depth-first search from `root`.  Returns the pre-order and the
+/// post-order numbering of every vertex reachable from `root`, as
+/// maps from vertices to numbers starting at zero.  Successors are
+/// visited in the order of their adjacency trees, and each vertex's
+/// visit is memoized under a name derived from the vertex.
+pub fn dfs<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     root: Node)
+     -> (Trie<(Node, usize)>, Trie<(Node, usize)>) {
+    ns(name_of_str("dfs"), || {
+        let state = (MapIntro::empty(), MapIntro::empty(), 0, 0);
+        let (pre, post, _, _) = dfs_visit(graph.adjacency_map.clone(), root, state);
+        (pre, post)
+    })
+}
+
+fn dfs_visit<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adjacency_map: Trie<(Node, Tree<Node>)>,
+     node: Node,
+     state: DfsState<Node>)
+     -> DfsState<Node> {
+    let (pre, post, next_pre, next_post) = state;
+    let pre = MapIntro::update(pre, node.clone(), next_pre);
+    let dsts = MapElim::find(&adjacency_map, &node).unwrap_or(Tree::nil());
+    let map = adjacency_map.clone();
+    let (pre, post, next_pre, next_post) =
+        tree_fold_seq(dsts,
+                      Dir2::Left,
+                      (pre, post, next_pre + 1, next_post),
+                      Rc::new(move |dst: Node, state: DfsState<Node>| {
+                          if MapElim::find(&state.0, &dst).is_some() {
+                              state
+                          } else {
+                              let nm = name_of_node(&dst);
+                              memo!(nm =>> dfs_visit, adjacency_map:map.clone(), node:dst,
+                                    state:state)
+                          }
+                      }),
+                      Rc::new(|_, state| state),
+                      Rc::new(|_, _, state| state));
+    let post = MapIntro::update(post, node, next_post);
+    (pre, post, next_pre, next_post + 1)
+}
+
+/// Approximate betweenness centrality: estimates, for each vertex, the
+/// number of shortest paths between other vertices that pass through
+/// it, rounded to the nearest integer.  Runs Brandes' algorithm from
+/// `sample_sources` of the vertices and scales the sum up to all of
+/// them; the estimate is exact when every vertex is a source.  The
+/// sources are the vertices that hash lowest together with `seed`, so
+/// the sample only changes where an edit adds or removes one of them.
+/// The search from each source, and its dependencies, are memoized
+/// under names derived from the source, reading the adjacency map
+/// through a named cell; so an edit only re-runs the sources whose
+/// searches read the edited vertices.  The scores are written by a
+/// chain of `map_extend`s, each named by its vertex.
+pub fn betweenness<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     sample_sources: usize,
+     seed: u64)
+     -> Trie<(Node, u64)> {
+    ns(name_of_str("betweenness"), || {
+        let vertices: Set<Node> = trie_fold(graph.adjacency_map.clone(),
+                                            SetIntro::empty(),
+                                            Rc::new(|(src, dsts): (Node, Tree<Node>), set| {
+                                                tree_fold_seq(dsts,
+                                                              Dir2::Left,
+                                                              SetIntro::add(set, src),
+                                                              Rc::new(|dst, set| {
+                                                                  SetIntro::add(set, dst)
+                                                              }),
+                                                              Rc::new(|_, set| set),
+                                                              Rc::new(|_, _, set| set))
+                                            }));
+        let mut vertices = vec_of_set(vertices);
+        vertices.sort_by_key(|v| hash_of(&(seed, v)));
+        let sources = min(sample_sources, vertices.len());
+        let adj = cell(name_of_str("adjacency"), graph.adjacency_map.clone());
+        let mut scores: HashMap<Node, f64> = HashMap::new();
+        for src in &vertices[..sources] {
+            let nm = name_pair(name_of_str("dependencies"), name_of_node(src));
+            let deps = memo!(nm =>> source_dependencies, adj:adj.clone(), src:src.clone());
+            for (v, Score(dep)) in deps {
+                *scores.entry(v).or_insert(0.0) += dep;
+            }
+        }
+        let scale = if sources == 0 { 0.0 } else { vertices.len() as f64 / sources as f64 };
+        vertices.into_iter().fold(MapIntro::empty(), |map, v| {
+            let score = scores.get(&v).cloned().unwrap_or(0.0) * scale;
+            let nm = name_pair(name_of_str("score"), name_of_node(&v));
+            Trie::map_extend(nm, map, v, score.round() as u64)
+        })
+    })
+}
+
+/// A random walk of at most `len` steps from `start`, as the list of
+/// vertices it visits, beginning with `start`.  Each step follows one
+/// of the current vertex's edges, chosen by hashing `seed` with the
+/// step number and the vertex, so the walk is reproducible; it ends
+/// early at a vertex with no outgoing edges.  Each step is a named
+/// thunk, keyed on its vertex, step number and seed, that reads the
+/// vertex's edges through a memoized lookup in a named cell; so after
+/// an edit the walk re-executes from the first step whose vertex's
+/// edges changed, and walks that meet at the same step share the rest.
+pub fn random_walk<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     start: Node,
+     len: usize,
+     seed: u64)
+     -> List<Node> {
+    ns(name_pair(name_of_str("random_walk"), name_of_usize(len)), || {
+        let adj = cell(name_of_str("adjacency"), graph.adjacency_map.clone());
+        walk_rec(adj, start, 0, len, seed)
+    })
+}
+
+/// One step of a random walk, from `node` after `step` steps.
+fn walk_rec<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: Art<Trie<(Node, Tree<Node>)>>,
+     node: Node,
+     step: usize,
+     len: usize,
+     seed: u64)
+     -> List<Node> {
+    if step == len {
+        return List::cons(node, List::nil());
+    }
+    let dsts = memo!(name_of_node(&node) =>> bfs_successors, adj:adj.clone(), src:node.clone());
+    let dsts: Vec<Node> = tree_fold_seq(dsts,
+                                        Dir2::Left,
+                                        Vec::new(),
+                                        Rc::new(|dst, mut dsts: Vec<Node>| {
+                                            dsts.push(dst);
+                                            dsts
+                                        }),
+                                        Rc::new(|_, dsts| dsts),
+                                        Rc::new(|_, _, dsts| dsts));
+    if dsts.is_empty() {
+        return List::cons(node, List::nil());
+    }
+    let next = dsts[(hash_of(&(seed, step, &node)) % dsts.len() as u64) as usize].clone();
+    let nm = name_of_node(&(seed, step + 1, &next));
+    let (rest, _) = eager!(nm.clone() =>> walk_rec,
+                           adj:adj, node:next, step:step + 1, len:len, seed:seed);
+    List::cons(node, List::name(nm, List::art(rest)))
+}
+
+/// The vertices reachable from `src`, in the order of their distance
+/// from `src`, each with its distance and its number of shortest paths
+/// from `src`.
+fn source_paths<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: Art<Trie<(Node, Tree<Node>)>>,
+     src: Node)
+     -> Vec<(Node, usize, u64)> {
+    let levels = ns(name_pair(name_of_str("bfs"), name_of_node(&src)),
+                    || bfs_from(adj.clone(), src.clone()));
+    // `list_fold` folds from the right, so the levels come out deepest first.
+    let mut levels: Vec<Vec<Node>> = list_fold(levels,
+                                               Vec::new(),
+                                               Rc::new(|level, mut levels: Vec<Vec<Node>>| {
+                                                   levels.push(vec_of_set(level));
+                                                   levels
+                                               }));
+    levels.reverse();
+    let mut depth: HashMap<Node, usize> = HashMap::new();
+    for (d, level) in levels.iter().enumerate() {
+        for v in level {
+            depth.insert(v.clone(), d);
+        }
+    }
+    let mut paths: HashMap<Node, u64> = HashMap::new();
+    paths.insert(src.clone(), 1);
+    let mut out = Vec::new();
+    for (d, level) in levels.into_iter().enumerate() {
+        for u in level {
+            let through_u = paths[&u];
+            for w in dag_successors(&adj, &u, &depth, d) {
+                *paths.entry(w).or_insert(0) += through_u;
+            }
+            out.push((u, d, through_u));
+        }
+    }
+    out
+}
+
+/// The successors of `u`, at depth `d`, on shortest paths from the
+/// root of `depth`.
+fn dag_successors<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: &Art<Trie<(Node, Tree<Node>)>>,
+     u: &Node,
+     depth: &HashMap<Node, usize>,
+     d: usize)
+     -> Vec<Node> {
+    let dsts = memo!(name_of_node(u) =>> bfs_successors, adj:adj.clone(), src:u.clone());
+    tree_fold_seq(dsts,
+                  Dir2::Left,
+                  Vec::new(),
+                  Rc::new(|w, mut ws: Vec<Node>| {
+                      ws.push(w);
+                      ws
+                  }),
+                  Rc::new(|_, ws| ws),
+                  Rc::new(|_, _, ws| ws))
+        .into_iter()
+        .filter(|w| depth.get(w) == Some(&(d + 1)))
+        .collect()
+}
+
+/// A dependency of `betweenness`, compared and hashed by its bits, so
+/// that the dependencies of a source can be memoized.
+#[derive(Debug, Clone, Copy)]
+struct Score(f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Score) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Score {}
+
+impl Hash for Score {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state)
+    }
+}
+
+/// The dependencies of `src` on every other vertex, given the shortest
+/// paths found by `source_paths`: the number of shortest paths from
+/// `src` that pass through the vertex, where a path's share of a vertex
+/// is split evenly between the shortest paths to the path's end.
+fn source_dependencies<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: Art<Trie<(Node, Tree<Node>)>>,
+     src: Node)
+     -> Vec<(Node, Score)> {
+    let nm = name_pair(name_of_str("paths"), name_of_node(&src));
+    let paths = memo!(nm =>> source_paths, adj:adj.clone(), src:src.clone());
+    let depth: HashMap<Node, usize> = paths.iter().map(|&(ref v, d, _)| (v.clone(), d)).collect();
+    let counts: HashMap<Node, f64> =
+        paths.iter().map(|&(ref v, _, n)| (v.clone(), n as f64)).collect();
+    // Accumulate from the deepest vertices up.
+    let mut deps: HashMap<Node, f64> = HashMap::new();
+    for &(ref u, d, _) in paths.iter().rev() {
+        let dep = dag_successors(&adj, u, &depth, d).into_iter().fold(0.0, |dep, w| {
+            dep + counts[u] / counts[&w] * (1.0 + deps.get(&w).cloned().unwrap_or(0.0))
+        });
+        deps.insert(u.clone(), dep);
+    }
+    // List them in the order of `paths`, so that equal dependencies
+    // compare equal.
+    paths.into_iter()
+        .filter(|&(ref v, _, _)| *v != src)
+        .map(|(v, _, _)| {
+            let dep = deps[&v];
+            (v, Score(dep))
+        })
+        .collect()
+}
+
+fn vec_of_set<X: Debug + Clone + Hash + PartialEq + Eq + 'static>(set: Set<X>) -> Vec<X> {
+    trie_fold(set, Vec::new(), Rc::new(|(x, ()), mut xs: Vec<X>| {
+        xs.push(x);
+        xs
+    }))
+}
+
+/// Unweighted single-source shortest paths: maps each vertex reachable
+/// from `src` to its distance, in edges, from `src`.  The distances
+/// are found by chaotic iteration: each vertex's distance is held in a
+/// cell named by the vertex, and each round relaxes the out-edges of
+/// the vertices whose distance the previous round lowered, comparing
+/// each offer with the cell of its target, until no distance changes.
+/// The relaxation of each vertex is memoized under a name derived from
+/// the vertex, keyed on the adjacency map and the vertex's cell; so
+/// after a small edit only the relaxations that read the edited
+/// vertices, or a changed distance, are re-run.
+pub fn shortest_paths<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     src: Node)
+     -> Trie<(Node, u64)> {
+    ns(name_pair(name_of_str("shortest_paths"), name_of_node(&src)), || {
+        let mut dists: HashMap<Node, Art<u64>> = HashMap::new();
+        dists.insert(src.clone(), dist_cell(&src, 0));
+        let mut changed = vec![src];
+        while !changed.is_empty() {
+            changed = relax_round(&graph.adjacency_map, &mut dists, changed);
+        }
+        dists.into_iter().fold(MapIntro::empty(),
+                               |map, (v, d)| MapIntro::update(map, v, force(&d)))
+    })
+}
+
+/// The cell holding the distance of `v` in `shortest_paths`.
+fn dist_cell<Node: Hash>(v: &Node, d: u64) -> Art<u64> {
+    cell(name_pair(name_of_str("dist"), name_of_node(v)), d)
+}
+
+/// One round of the iteration in `shortest_paths`: relaxes the
+/// out-edges of the vertices in `changed`, and lowers the distance
+/// cells of the targets that they offer a shorter distance.  Returns
+/// the targets whose distance was lowered.
+fn relax_round<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adjacency_map: &Trie<(Node, Tree<Node>)>,
+     dists: &mut HashMap<Node, Art<u64>>,
+     changed: Vec<Node>)
+     -> Vec<Node> {
+    let mut lowered = Vec::new();
+    for v in changed {
+        let nm = name_pair(name_of_str("relax"), name_of_node(&v));
+        let dist = dists[&v].clone();
+        let offers = memo!(nm =>> relax_vertex,
+                           adjacency_map:adjacency_map.clone(), node:v, dist:dist);
+        for (dst, d) in offers {
+            let lower = match dists.get(&dst) {
+                None => true,
+                Some(old) => d < force(old),
+            };
+            // The vertices of a round share their distance, so each
+            // target is lowered at most once per round.
+            if lower {
+                dists.insert(dst.clone(), dist_cell(&dst, d));
+                lowered.push(dst);
+            }
+        }
+    }
+    lowered
+}
+
+/// The distances that the out-edges of `node`, at the distance held
+/// in `dist`, offer to their targets.
+fn relax_vertex<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adjacency_map: Trie<(Node, Tree<Node>)>,
+     node: Node,
+     dist: Art<u64>)
+     -> Vec<(Node, u64)> {
+    let d = force(&dist);
+    let dsts = MapElim::find(&adjacency_map, &node).unwrap_or(Tree::nil());
+    tree_fold_seq(dsts,
+                  Dir2::Left,
+                  Vec::new(),
+                  Rc::new(move |dst, mut offers: Vec<(Node, u64)>| {
+                      offers.push((dst, d + 1));
+                      offers
+                  }),
+                  Rc::new(|_, offers| offers),
+                  Rc::new(|_, _, offers| offers))
+}
+
+/// Tests whether there is a path from `src` to `dst`.  The answer is
+/// read off the `shortest_paths` map from `src`, which serves as the
+/// reachability set of `src`.  Each source gets a namespace of its
+/// own, so under the DCG engine the distances of every source queried
+/// are kept, and a repeated query after a small edit only revisits the
+/// relaxations that the edit affects.
+pub fn reachable<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>,
+     src: &Node,
+     dst: &Node)
+     -> bool {
+    let dists = shortest_paths(graph, src.clone());
+    MapElim::find(&dists, dst).is_some()
+}
+
+/// Follows the parent links of a union-find forest to the root of `node`.
+fn uf_find<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (parents: &Trie<(Node, Node)>,
+     node: Node)
+     -> Node {
+    match MapElim::find(parents, &node) {
+        Some(parent) => {
+            if parent == node {
+                node
+            } else {
+                uf_find(parents, parent)
+            }
+        }
+        None => node,
+    }
+}
+
+/// Connected components, reading each edge as undirected.  Maps every
+/// vertex to the least vertex of its component.  The components are
+/// built by folding the edge set into a union-find forest, so the
+/// fold is memoized at the names of the edge set.
+pub fn connected_components<Node: Debug + Clone + Hash + PartialEq + Eq + Ord + 'static>
+    (graph: &Graph<Node>)
+     -> Trie<(Node, Node)> {
+    ns(name_of_str("connected_components"), || {
+        let edges = ns(name_of_str("edges"), || GraphElim::edges(graph));
+        let vertices = ns(name_of_str("vertices"), || GraphElim::vertices(graph));
+        let parents = ns(name_of_str("union"), || {
+            trie_fold(edges,
+                      MapIntro::empty(),
+                      Rc::new(|((src, dst), ()), parents: Trie<(Node, Node)>| {
+                          let src = uf_find(&parents, src);
+                          let dst = uf_find(&parents, dst);
+                          if src < dst {
+                              MapIntro::update(parents, dst, src)
+                          } else if dst < src {
+                              MapIntro::update(parents, src, dst)
+                          } else {
+                              parents
+                          }
+                      }))
+        });
+        ns(name_of_str("find"), || {
+            trie_fold(vertices,
+                      MapIntro::empty(),
+                      Rc::new(move |(v, ()): (Node, ()), reps| {
+                          let rep = uf_find(&parents, v.clone());
+                          MapIntro::update(reps, v, rep)
+                      }))
+        })
+    })
+}
+
+/// The state threaded through a round of Borůvka's algorithm: the
+/// union-find forest of the components so far, and the spanning
+/// forest's edges with their weights.
+type MstState<Node, W> = (Trie<(Node, Node)>, Trie<((Node, Node), W)>);
+
+/// Minimum spanning forest, reading each edge as undirected.  Runs
+/// Borůvka's algorithm: each round picks the lightest edge out of
+/// every component, and joins the components along those edges, so
+/// there are at most logarithmically many rounds.  Each round runs in
+/// a namespace of its own, and its folds are memoized at the names of
+/// the edge map, so the forest is maintained as edge weights change.
+/// Ties between weights are broken by the edges' endpoints.
+pub fn mst<Node: Debug + Clone + Hash + PartialEq + Eq + Ord + 'static,
+           W: Debug + Clone + Hash + PartialEq + Eq + Ord + 'static>
+    (graph: &WeightedGraph<Node, W>)
+     -> WeightedGraph<Node, W> {
+    ns(name_of_str("mst"), || {
+        let edges = ns(name_of_str("edges"), || WeightedGraphElim::edges(graph));
+        let mut state: MstState<Node, W> = (MapIntro::empty(), MapIntro::empty());
+        let mut round = 0;
+        loop {
+            let (next, merged) = ns(name_of_usize(round),
+                                    || mst_round(edges.clone(), state));
+            state = next;
+            if !merged {
+                break;
+            }
+            round += 1;
+        }
+        let (_, forest) = state;
+        ns(name_of_str("forest"), || {
+            trie_fold(forest,
+                      WeightedGraphIntro::empty(),
+                      Rc::new(|((src, dst), w): ((Node, Node), W), g| {
+                          let nm = name_of_node(&(src.clone(), dst.clone()));
+                          WeightedGraphIntro::add_edge(g, nm, src, dst, w)
+                      }))
+        })
+    })
+}
+
+/// One round of Borůvka's algorithm.  Returns the new state, and
+/// whether any two components were joined.
+fn mst_round<Node: Debug + Clone + Hash + PartialEq + Eq + Ord + 'static,
+             W: Debug + Clone + Hash + PartialEq + Eq + Ord + 'static>
+    (edges: Trie<((Node, Node), W)>,
+     state: MstState<Node, W>)
+     -> (MstState<Node, W>, bool) {
+    let (parents, forest) = state;
+    // The union-find forest rides along in the accumulator, so that
+    // the fold's memo entries are keyed on it.
+    let (parents, cheapest) = ns(name_of_str("cheapest"), || {
+        trie_fold(edges,
+                  (parents, MapIntro::empty()),
+                  Rc::new(|((src, dst), w): ((Node, Node), W), (parents, cheapest)| {
+                      let src_rep = uf_find(&parents, src.clone());
+                      let dst_rep = uf_find(&parents, dst.clone());
+                      if src_rep == dst_rep {
+                          return (parents, cheapest);
+                      }
+                      let edge = (w, (src, dst));
+                      let cheapest = keep_lightest(cheapest, src_rep, edge.clone());
+                      (parents, keep_lightest(cheapest, dst_rep, edge))
+                  }))
+    });
+    ns(name_of_str("join"), || {
+        trie_fold(cheapest,
+                  ((parents, forest), false),
+                  Rc::new(|(_, (w, (src, dst))): (Node, (W, (Node, Node))),
+                           ((parents, forest), merged)| {
+                      let src_rep = uf_find(&parents, src.clone());
+                      let dst_rep = uf_find(&parents, dst.clone());
+                      if src_rep == dst_rep {
+                          return ((parents, forest), merged);
+                      }
+                      let parents = if src_rep < dst_rep {
+                          MapIntro::update(parents, dst_rep, src_rep)
+                      } else {
+                          MapIntro::update(parents, src_rep, dst_rep)
+                      };
+                      ((parents, MapIntro::update(forest, (src, dst), w)), true)
+                  }))
+    })
+}
+
+/// Records `edge` as the lightest edge out of the component `rep`,
+/// unless a lighter one is already recorded.
+fn keep_lightest<Node: Debug + Clone + Hash + PartialEq + Eq + Ord + 'static,
+                 W: Debug + Clone + Hash + PartialEq + Eq + Ord + 'static>
+    (cheapest: Trie<(Node, (W, (Node, Node)))>,
+     rep: Node,
+     edge: (W, (Node, Node)))
+     -> Trie<(Node, (W, (Node, Node)))> {
+    match MapElim::find(&cheapest, &rep) {
+        Some(ref best) if *best <= edge => cheapest,
+        _ => MapIntro::update(cheapest, rep, edge),
+    }
+}
+
+/// Maximum flow from `src` to `sink`, reading the weight of each edge
+/// as its capacity (if an edge was added more than once, one of its
+/// weights).  Returns the value of the flow, the flow along each edge,
+/// and the source side of a minimum cut: the vertices still reachable
+/// from `src` in the residual graph.  Runs FIFO push-relabel.  Flow is
+/// kept per edge, so antiparallel edges have separate residuals; a
+/// self-loop carries no flow.  Each vertex's excess and height, and
+/// each edge's flow, are held in cells named after them, and the flow
+/// returned is read from those cells.  Each discharge of a vertex (its
+/// pushes and relabellings) is memoized under a name derived from the
+/// vertex, keyed on the cells that it reads; so a discharge re-runs
+/// only when one of them has changed.  `src` and `sink` must differ.
+pub fn max_flow<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+                W: Debug + Copy + Hash + PartialEq + Eq + Ord + Default + 'static>
+    (graph: &WeightedGraph<Node, W>,
+     src: Node,
+     sink: Node)
+     -> (W, Trie<((Node, Node), W)>, Set<Node>)
+    where W: Add<Output = W> + Sub<Output = W>
+{
+    assert!(src != sink, "max_flow: the source is the sink");
+    ns(name_of_str("max_flow"), || {
+        let caps = ns(name_of_str("edges"), || WeightedGraphElim::edges(graph));
+        let arcs = ns(name_of_str("arcs"), || {
+            trie_fold(caps.clone(),
+                      MapIntro::empty(),
+                      Rc::new(|((u, v), _): ((Node, Node), W), arcs| if u == v {
+                          arcs
+                      } else {
+                          let arcs = add_arc(arcs, u.clone(), ((u.clone(), v.clone()), true));
+                          add_arc(arcs, v.clone(), ((u, v), false))
+                      }))
+        });
+        let arcs_of = |u: &Node| {
+            list_fold(MapElim::find(&arcs, u).unwrap_or(List::nil()),
+                      Vec::new(),
+                      Rc::new(|arc, mut arcs: Vec<FlowArc<Node>>| {
+                          arcs.push(arc);
+                          arcs
+                      }))
+        };
+        let vertices = trie_fold(arcs.clone(),
+                                 Vec::new(),
+                                 Rc::new(|(v, _), mut vs: Vec<Node>| {
+                                     vs.push(v);
+                                     vs
+                                 }));
+        let mut state = FlowState {
+            caps: caps.clone(),
+            flow: HashMap::new(),
+            excess: HashMap::new(),
+            height: HashMap::new(),
+        };
+        let edges = trie_fold(caps.clone(),
+                              Vec::new(),
+                              Rc::new(|(edge, _), mut edges: Vec<(Node, Node)>| {
+                                  edges.push(edge);
+                                  edges
+                              }));
+        for edge in &edges {
+            state.set_flow(edge, W::default());
+        }
+        for v in &vertices {
+            state.set_excess(v, W::default());
+            state.set_height(v, 0);
+        }
+        state.set_height(&src, vertices.len());
+        // Saturate the edges out of `src`, which starts with just
+        // enough excess to do so.
+        let supply = arcs_of(&src).iter().fold(W::default(), |s, arc| s + state.residual(arc));
+        state.set_excess(&src, supply);
+        let mut active = VecDeque::new();
+        for arc in arcs_of(&src) {
+            let res = state.residual(&arc);
+            if res > W::default() {
+                let was_idle = state.excess_of(arc_head(&arc)) == W::default();
+                let v = state.push(&arc, res);
+                if was_idle && v != sink {
+                    active.push_back(v);
+                }
+            }
+        }
+        while let Some(u) = active.pop_front() {
+            let nm = name_pair(name_of_str("discharge"), name_of_node(&u));
+            let arcs = state.cells_of_arcs(arcs_of(&u));
+            let excess = state.excess[&u].clone();
+            let height = state.height[&u].clone();
+            let (pushes, h) = memo!(nm =>> discharge, caps:caps.clone(), arcs:arcs,
+                                    excess:excess, height:height);
+            for (arc, amount) in pushes {
+                let was_idle = state.excess_of(arc_head(&arc)) == W::default();
+                let v = state.push(&arc, amount);
+                if was_idle && v != src && v != sink {
+                    active.push_back(v);
+                }
+            }
+            state.set_height(&u, h);
+        }
+        let mut cut = SetIntro::add(SetIntro::empty(), src.clone());
+        let mut queue = VecDeque::new();
+        queue.push_back(src.clone());
+        while let Some(u) = queue.pop_front() {
+            for arc in arcs_of(&u) {
+                let v = arc_head(&arc).clone();
+                if state.residual(&arc) > W::default() && !SetElim::is_mem(&cut, &v) {
+                    cut = SetIntro::add(cut, v.clone());
+                    queue.push_back(v);
+                }
+            }
+        }
+        let value = state.excess_of(&sink);
+        let flow = ns(name_of_str("flow"), || {
+            trie_fold(caps,
+                      MapIntro::empty(),
+                      Rc::new(move |(edge, _): ((Node, Node), W), flow| {
+                          let used = state.flow_of(&edge);
+                          MapIntro::update(flow, edge, used)
+                      }))
+        });
+        (value, flow, cut)
+    })
+}
+
+/// An arc of the residual graph of `max_flow`: an edge, and whether
+/// the arc runs along it (`true`) or against it, cancelling flow.
+type FlowArc<Node> = ((Node, Node), bool);
+
+/// The vertex that `arc` leads to.
+fn arc_head<Node>(arc: &FlowArc<Node>) -> &Node {
+    match *arc {
+        ((_, ref v), true) => v,
+        ((ref u, _), false) => u,
+    }
+}
+
+fn add_arc<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (arcs: Trie<(Node, List<FlowArc<Node>>)>,
+     u: Node,
+     arc: FlowArc<Node>)
+     -> Trie<(Node, List<FlowArc<Node>>)> {
+    let rest = MapElim::find(&arcs, &u).unwrap_or(List::nil());
+    MapIntro::update(arcs, u, List::cons(arc, rest))
+}
+
+/// The preflow of `max_flow`: the capacity of each edge, and the cells
+/// holding the flow of each edge and the excess and height of each
+/// vertex.
+struct FlowState<Node, W: 'static> {
+    caps: Trie<((Node, Node), W)>,
+    flow: HashMap<(Node, Node), Art<W>>,
+    excess: HashMap<Node, Art<W>>,
+    height: HashMap<Node, Art<usize>>,
+}
+
+impl<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+     W: Debug + Copy + Hash + PartialEq + Eq + Ord + Default + 'static> FlowState<Node, W>
+    where W: Add<Output = W> + Sub<Output = W>
+{
+    fn excess_of(&self, v: &Node) -> W {
+        self.excess.get(v).map(force).unwrap_or_default()
+    }
+
+    fn set_excess(&mut self, v: &Node, e: W) {
+        let art = cell(name_pair(name_of_str("excess"), name_of_node(v)), e);
+        self.excess.insert(v.clone(), art);
+    }
+
+    fn set_height(&mut self, v: &Node, h: usize) {
+        let art = cell(name_pair(name_of_str("height"), name_of_node(v)), h);
+        self.height.insert(v.clone(), art);
+    }
+
+    fn flow_of(&self, edge: &(Node, Node)) -> W {
+        self.flow.get(edge).map(force).unwrap_or_default()
+    }
+
+    fn set_flow(&mut self, edge: &(Node, Node), f: W) {
+        let art = cell(name_pair(name_of_str("flow"), name_of_node(edge)), f);
+        self.flow.insert(edge.clone(), art);
+    }
+
+    /// The capacity left on `arc`: what its edge can still carry when
+    /// the arc runs along it, and the edge's flow when it runs against.
+    fn residual(&self, arc: &FlowArc<Node>) -> W {
+        flow_residual(&self.caps, arc, self.flow_of(&arc.0))
+    }
+
+    /// Pairs each of `arcs` with the cells of its edge's flow and of
+    /// its head's height, for `discharge`.
+    fn cells_of_arcs(&self, arcs: Vec<FlowArc<Node>>) -> Vec<(FlowArc<Node>, Art<W>, Art<usize>)> {
+        arcs.into_iter()
+            .map(|arc| {
+                let flow = self.flow[&arc.0].clone();
+                let height = self.height[arc_head(&arc)].clone();
+                (arc, flow, height)
+            })
+            .collect()
+    }
+
+    /// Pushes `amount` of excess along `arc`, returning its head.
+    fn push(&mut self, arc: &FlowArc<Node>, amount: W) -> Node {
+        let flow = self.flow_of(&arc.0);
+        let flow = if arc.1 { flow + amount } else { flow - amount };
+        self.set_flow(&arc.0, flow);
+        let (tail, head) = if arc.1 {
+            ((arc.0).0.clone(), (arc.0).1.clone())
+        } else {
+            ((arc.0).1.clone(), (arc.0).0.clone())
+        };
+        let e = self.excess_of(&tail);
+        self.set_excess(&tail, e - amount);
+        let e = self.excess_of(&head);
+        self.set_excess(&head, e + amount);
+        head
+    }
+}
+
+/// The capacity left on `arc`, given the flow of its edge.
+fn flow_residual<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+                 W: Debug + Copy + Hash + PartialEq + Eq + Default + 'static>
+    (caps: &Trie<((Node, Node), W)>,
+     arc: &FlowArc<Node>,
+     flow: W)
+     -> W
+    where W: Sub<Output = W>
+{
+    if arc.1 {
+        MapElim::find(caps, &arc.0).unwrap_or_default() - flow
+    } else {
+        flow
+    }
+}
+
+/// Discharges a vertex of `max_flow`, whose excess and height are held
+/// in `excess` and `height`: pushes its excess along its admissible
+/// arcs, relabelling it whenever it has excess left but no admissible
+/// arc.  Each of `arcs` comes with the cells of its edge's flow and of
+/// its head's height.  Returns the pushes, in order, and the vertex's
+/// new height.
+fn discharge<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+             W: Debug + Copy + Hash + PartialEq + Eq + Ord + Default + 'static>
+    (caps: Trie<((Node, Node), W)>,
+     arcs: Vec<(FlowArc<Node>, Art<W>, Art<usize>)>,
+     excess: Art<W>,
+     height: Art<usize>)
+     -> (Vec<(FlowArc<Node>, W)>, usize)
+    where W: Add<Output = W> + Sub<Output = W>
+{
+    let mut e = force(&excess);
+    let mut h = force(&height);
+    // A vertex has no self-loop arcs, so its pushes only lower the
+    // residuals of its own arcs.
+    let mut residuals: Vec<W> =
+        arcs.iter().map(|&(ref arc, ref flow, _)| flow_residual(&caps, arc, force(flow))).collect();
+    let heights: Vec<usize> = arcs.iter().map(|&(_, _, ref height)| force(height)).collect();
+    let mut pushes = Vec::new();
+    while e > W::default() {
+        for (i, &(ref arc, _, _)) in arcs.iter().enumerate() {
+            if residuals[i] == W::default() || h != heights[i] + 1 {
+                continue;
+            }
+            let amount = min(e, residuals[i]);
+            residuals[i] = residuals[i] - amount;
+            pushes.push((arc.clone(), amount));
+            e = e - amount;
+            if e == W::default() {
+                break;
+            }
+        }
+        if e > W::default() {
+            let lowest = (0..arcs.len())
+                .filter(|&i| residuals[i] > W::default())
+                .map(|i| heights[i])
+                .min()
+                .expect("max_flow: excess with no residual arc");
+            h = lowest + 1;
+        }
+    }
+    (pushes, h)
+}
+
+/// A* search for a lightest path from `src` to `dst`, guided by the
+/// heuristic `h`, which must be consistent: `h(dst)` is zero, and for
+/// each edge from `u` to `v` of weight `w`, `h(u)` is at most `w +
+/// h(v)`.  (A settled vertex is never reopened, so a heuristic that is
+/// merely admissible can yield a heavier path.)  Returns the path, as
+/// the list of vertices from `src` to `dst`, and its weight; or `None`
+/// if `dst` is unreachable.  The adjacency map is read through a named
+/// cell, and the expansion of each settled vertex is memoized under a
+/// name derived from the vertex, so that repeated queries, for example
+/// from nearby sources, reuse the expansions they share.
+pub fn astar<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+             W: Debug + Copy + Hash + PartialEq + Eq + Ord + Default + 'static,
+             H: Fn(&Node) -> W>
+    (graph: &WeightedGraph<Node, W>,
+     src: Node,
+     dst: Node,
+     h: H)
+     -> Option<(List<Node>, W)>
+    where W: Add<Output = W>
+{
+    ns(name_of_str("astar"), || {
+        let adj = cell(name_of_str("adjacency"), graph.adjacency_map.clone());
+        // The heap orders entries by estimated total weight, then by
+        // when they were pushed; `pushed` holds each entry's vertex and
+        // weight so far.
+        let mut heap = BinaryHeap::new();
+        let mut pushed = vec![(src.clone(), W::default())];
+        let mut best: Trie<(Node, W)> = MapIntro::empty();
+        best = MapIntro::update(best, src.clone(), W::default());
+        let mut parents: Trie<(Node, Node)> = MapIntro::empty();
+        let mut settled: Set<Node> = SetIntro::empty();
+        heap.push((Reverse(h(&src)), Reverse(0)));
+        while let Some((_, Reverse(i))) = heap.pop() {
+            let (node, dist) = pushed[i].clone();
+            if SetElim::is_mem(&settled, &node) {
+                continue;
+            }
+            if node == dst {
+                let mut path = List::cons(node.clone(), List::nil());
+                let mut v = node;
+                while let Some(u) = MapElim::find(&parents, &v) {
+                    path = List::cons(u.clone(), path);
+                    v = u;
+                }
+                return Some((path, dist));
+            }
+            settled = SetIntro::add(settled, node.clone());
+            let expansion = memo!(name_of_node(&node) =>> weighted_successors,
+                                  adj:adj.clone(), node:node.clone());
+            for (next, w) in expansion {
+                if SetElim::is_mem(&settled, &next) {
+                    continue;
+                }
+                let next_dist = dist + w;
+                match MapElim::find(&best, &next) {
+                    Some(d) if d <= next_dist => continue,
+                    _ => (),
+                }
+                best = MapIntro::update(best, next.clone(), next_dist);
+                parents = MapIntro::update(parents, next.clone(), node.clone());
+                heap.push((Reverse(next_dist + h(&next)), Reverse(pushed.len())));
+                pushed.push((next, next_dist));
+            }
+        }
+        None
+    })
+}
+
+/// The targets and weights of the edges out of `node`, in the
+/// adjacency map held by `adj`.
+fn weighted_successors<Node: Debug + Clone + Hash + PartialEq + Eq + 'static,
+                       W: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: Art<Trie<(Node, Tree<(Node, W)>)>>,
+     node: Node)
+     -> Vec<(Node, W)> {
+    match MapElim::find(&force(&adj), &node) {
+        None => vec![],
+        Some(dsts) => {
+            tree_fold_seq(dsts,
+                          Dir2::Left,
+                          vec![],
+                          Rc::new(|dst, mut dsts: Vec<(Node, W)>| {
+                              dsts.push(dst);
+                              dsts
+                          }),
+                          Rc::new(|_, dsts| dsts),
+                          Rc::new(|_, _, dsts| dsts))
+        }
+    }
+}
+
+/// Two-colors `graph`, reading each edge as undirected.  Returns a
+/// map from each vertex with an edge to its color, or, if the graph is
+/// not bipartite, an odd cycle as the list of vertices along it.  The
+/// coloring alternates between the levels of a breadth-first search
+/// from one root per component; each search runs in a namespace named
+/// after its root, and is memoized at its levels.
+pub fn is_bipartite<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>)
+     -> Result<Trie<(Node, bool)>, List<Node>> {
+    ns(name_of_str("is_bipartite"), || {
+        let undirected = ns(name_of_str("undirected"), || {
+            trie_fold(graph.adjacency_map.clone(),
+                      graph.clone(),
+                      Rc::new(|(src, dsts): (Node, Tree<Node>), g| {
+                          tree_fold_seq(dsts,
+                                        Dir2::Left,
+                                        g,
+                                        Rc::new(move |dst: Node, g| {
+                                            let nm = name_of_node(&(dst.clone(), src.clone()));
+                                            GraphIntro::add_edge(g, nm, dst, src.clone())
+                                        }),
+                                        Rc::new(|_, g| g),
+                                        Rc::new(|_, _, g| g))
+                      }))
+        });
+        let adj = undirected.adjacency_map.clone();
+        let depths = ns(name_of_str("depths"), || {
+            trie_fold(adj.clone(),
+                      MapIntro::empty(),
+                      Rc::new(move |(v, _): (Node, Tree<Node>), depths: BfsDepths<Node>| {
+                          if MapElim::find(&depths, &v).is_some() {
+                              return depths;
+                          }
+                          let levels = bfs_levels(&undirected, v.clone());
+                          depths_of_levels(levels, v, 0, depths)
+                      }))
+        });
+        let conflict = ns(name_of_str("conflict"), || {
+            let depths = depths.clone();
+            trie_fold(adj.clone(),
+                      None,
+                      Rc::new(move |(src, dsts): (Node, Tree<Node>), found: Option<_>| {
+                          if found.is_some() {
+                              return found;
+                          }
+                          let src_depth = MapElim::find(&depths, &src);
+                          let depths = depths.clone();
+                          tree_fold_seq(dsts,
+                                        Dir2::Left,
+                                        None,
+                                        Rc::new(move |dst: Node, found: Option<(Node, Node)>| {
+                                            if found.is_none() &&
+                                               MapElim::find(&depths, &dst) == src_depth {
+                                                Some((src.clone(), dst))
+                                            } else {
+                                                found
+                                            }
+                                        }),
+                                        Rc::new(|_, found| found),
+                                        Rc::new(|_, _, found| found))
+                      }))
+        });
+        match conflict {
+            None => {
+                Ok(trie_fold(depths,
+                             MapIntro::empty(),
+                             Rc::new(|(v, (_, depth)): (Node, (Node, usize)), colors| {
+                                 MapIntro::update(colors, v, depth % 2 == 1)
+                             })))
+            }
+            Some((src, dst)) => Err(odd_cycle(&adj, &depths, src, dst)),
+        }
+    })
+}
+
+/// Maps each vertex reached by a breadth-first search to the root of
+/// the search and the vertex's distance from it.
+type BfsDepths<Node> = Trie<(Node, (Node, usize))>;
+
+fn depths_of_levels<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (levels: List<Set<Node>>,
+     root: Node,
+     depth: usize,
+     map: BfsDepths<Node>)
+     -> BfsDepths<Node> {
+    ListElim::elim_arg(levels,
+                       (root, depth, map),
+                       |_, (_, _, map)| map,
+                       |level, rest, (root, depth, map)| {
+                           let r = root.clone();
+                           let map = trie_fold(level,
+                                               map,
+                                               Rc::new(move |(v, ()), map| {
+                                                   MapIntro::update(map, v, (r.clone(), depth))
+                                               }));
+                           depths_of_levels(rest, root, depth + 1, map)
+                       },
+                       |nm, rest, (root, depth, map)| {
+                           memo!(nm =>> depths_of_levels,
+                                 levels:rest, root:root, depth:depth, map:map)
+                       })
+}
+
+/// Builds an odd cycle through the edge `(src, dst)`, whose ends lie
+/// at the same depth of the same breadth-first search, by following
+/// both ends up the search until they meet.
+fn odd_cycle<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adj: &Trie<(Node, Tree<Node>)>,
+     depths: &BfsDepths<Node>,
+     src: Node,
+     dst: Node)
+     -> List<Node> {
+    let parent = |node: &Node| -> Node {
+        let (_, depth) = MapElim::find(depths, node).unwrap();
+        let depths = depths.clone();
+        let nbrs = MapElim::find(adj, node).unwrap_or(Tree::nil());
+        tree_fold_seq(nbrs,
+                      Dir2::Left,
+                      None,
+                      Rc::new(move |n: Node, found: Option<Node>| {
+                          match (found, MapElim::find(&depths, &n)) {
+                              (None, Some((_, d))) if d + 1 == depth => Some(n),
+                              (found, _) => found,
+                          }
+                      }),
+                      Rc::new(|_, found| found),
+                      Rc::new(|_, _, found| found))
+            .unwrap()
+    };
+    let mut src_path = vec![src];
+    let mut dst_path = vec![dst];
+    while src_path.last() != dst_path.last() {
+        let s = parent(src_path.last().unwrap());
+        let d = parent(dst_path.last().unwrap());
+        src_path.push(s);
+        dst_path.push(d);
+    }
+    dst_path.pop();
+    let mut cycle = List::nil();
+    for node in dst_path.into_iter().chain(src_path.into_iter().rev()) {
+        cycle = List::cons(node, cycle);
+    }
+    cycle
+}
+
+/// Returns a cycle of `graph`, if it has one, as the list of vertices
+/// along the cycle.  Each vertex with outgoing edges gets a verdict of
+/// its own: the first cycle that a depth-first search from the vertex
+/// finds.  Verdicts are memoized under names derived from the vertices,
+/// keyed on the vertex and the adjacency map only, and the search over
+/// them at the names of the adjacency map; so re-checking after an edit
+/// only re-runs the verdicts of the vertices that reach the edit.
+pub fn find_cycle<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>)
+     -> Option<List<Node>> {
+    ns(name_of_str("find_cycle"),
+       || first_cycle(graph.adjacency_map.clone(), graph.adjacency_map.clone()))
+}
+
+/// Tests whether `graph` has a cycle; see `find_cycle`.
+pub fn has_cycle<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (graph: &AdjacencyGraph<Node>)
+     -> bool {
+    find_cycle(graph).is_some()
+}
+
+/// Returns the verdict of the first source of `sources`, in
+/// left-to-right order, that has a cycle.  Memoized at each name of
+/// `sources`, keyed on the subtrie and the adjacency map.
+fn first_cycle<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (sources: Trie<(Node, Tree<Node>)>,
+     adjacency_map: Trie<(Node, Tree<Node>)>)
+     -> Option<List<Node>> {
+    Trie::elim_arg(sources,
+                   adjacency_map,
+                   |_, _| None,
+                   |_, (src, _), map| {
+                       let nm = name_of_node(&src);
+                       ns(name_of_str("verdicts"),
+                          || memo!(nm =>> cycle_from, adjacency_map:map, node:src))
+                   },
+                   |_, l, r, map| match first_cycle(l, map.clone()) {
+                       None => first_cycle(r, map),
+                       found => found,
+                   },
+                   |_, t, map| first_cycle(t, map),
+                   |nm, t, map| memo!(nm =>> first_cycle::<Node>, sources:t, adjacency_map:map))
+}
+
+/// Marks each visited vertex as active (`false`) or finished (`true`),
+/// along with the cycle found so far, if any.
+type CycleState<Node> = (Trie<(Node, bool)>, Option<List<Node>>);
+
+/// The verdict of `node`: a cycle found by a depth-first search from
+/// `node`, if any.  The search keeps its marks to itself, so that the
+/// verdict depends only on `node` and `adjacency_map`.
+fn cycle_from<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adjacency_map: Trie<(Node, Tree<Node>)>,
+     node: Node)
+     -> Option<List<Node>> {
+    let (_, cycle) = cycle_visit(&adjacency_map, node, List::nil(), MapIntro::empty());
+    cycle
+}
+
+fn cycle_visit<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (adjacency_map: &Trie<(Node, Tree<Node>)>,
+     node: Node,
+     path: List<Node>,
+     marks: Trie<(Node, bool)>)
+     -> CycleState<Node> {
+    let marks = MapIntro::update(marks, node.clone(), false);
+    let path = List::cons(node.clone(), path);
+    let dsts = MapElim::find(adjacency_map, &node).unwrap_or(Tree::nil());
+    let map = adjacency_map.clone();
+    let (marks, cycle) =
+        tree_fold_seq(dsts,
+                      Dir2::Left,
+                      (marks, None),
+                      Rc::new(move |dst: Node, (marks, cycle): CycleState<Node>| {
+                          if cycle.is_some() {
+                              return (marks, cycle);
+                          }
+                          match MapElim::find(&marks, &dst) {
+                              Some(false) => {
+                                  let cycle = cycle_of_path(path.clone(), &dst);
+                                  (marks, Some(cycle))
+                              }
+                              Some(true) => (marks, cycle),
+                              None => cycle_visit(&map, dst, path.clone(), marks),
+                          }
+                      }),
+                      Rc::new(|_, state| state),
+                      Rc::new(|_, _, state| state));
+    (MapIntro::update(marks, node, true), cycle)
+}
+
+/// Pops the active path, whose head is the most recently entered
+/// vertex, back to `dst`; the result runs from `dst` to that vertex.
+fn cycle_of_path<Node: Debug + Clone + Hash + PartialEq + Eq + 'static>
+    (path: List<Node>,
+     dst: &Node)
+     -> List<Node> {
+    let mut cycle = List::nil();
+    let mut path = path;
+    loop {
+        let (node, rest) = list_pop(path);
+        let done = node == *dst;
+        cycle = List::cons(node, cycle);
+        if done {
+            return cycle;
+        }
+        path = rest;
+    }
+}
+
+/// Generators of random and regular graphs, for tests and benchmarks.
+/// Each edge is named after its endpoints (and, for the random
+/// generators, the seed), so a generator yields the same graph, with
+/// the same names, on every run.
+pub mod gen {
+    use adapton::engine::*;
+    use super::GraphIntro;
+
+    /// A small pseudo-random number generator (SplitMix64), so that the
+    /// generated graphs depend only on the seed.
+    struct SplitMix64(u64);
+
+    impl SplitMix64 {
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        }
+
+        /// A float drawn uniformly from `[0, 1)`.
+        fn next_f64(&mut self) -> f64 {
+            (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        /// An integer drawn uniformly from `[0, n)`.
+        fn below(&mut self, n: usize) -> usize {
+            (self.next_u64() % n as u64) as usize
+        }
+    }
+
+    fn edge_name(seed: u64, src: usize, dst: usize) -> Name {
+        name_pair(name_of_usize(seed as usize),
+                  name_pair(name_of_usize(src), name_of_usize(dst)))
+    }
+
+    /// A directed Erdős–Rényi graph on the vertices `0..n`: each of the
+    /// `n * (n - 1)` possible edges is present with probability `p`.
+    pub fn erdos_renyi<G: GraphIntro<usize>>(n: usize, p: f64, seed: u64) -> G {
+        let mut rng = SplitMix64(seed);
+        let mut graph = G::empty();
+        for src in 0..n {
+            for dst in 0..n {
+                if src != dst && rng.next_f64() < p {
+                    graph = G::add_edge(graph, edge_name(seed, src, dst), src, dst);
+                }
+            }
+        }
+        graph
+    }
+
+    /// A Barabási–Albert preferential-attachment graph on the vertices
+    /// `0..n`.  Starting from the vertices `0..m`, each later vertex
+    /// gets edges to `m` distinct earlier vertices, chosen with
+    /// probability proportional to their degree.
+    pub fn barabasi_albert<G: GraphIntro<usize>>(n: usize, m: usize, seed: u64) -> G {
+        let mut rng = SplitMix64(seed);
+        let mut graph = G::empty();
+        // Each vertex appears here once per edge it has, so a uniform
+        // draw picks vertices in proportion to their degree.
+        let mut ends: Vec<usize> = Vec::new();
+        let mut targets: Vec<usize> = (0..m).collect();
+        for src in m..n {
+            for &dst in &targets {
+                graph = G::add_edge(graph, edge_name(seed, src, dst), src, dst);
+                ends.push(src);
+                ends.push(dst);
+            }
+            targets.clear();
+            while targets.len() < m {
+                let dst = ends[rng.below(ends.len())];
+                if !targets.contains(&dst) {
+                    targets.push(dst);
+                }
+            }
+        }
+        graph
+    }
+
+    /// A `w` by `h` grid.  The vertex in column `x` and row `y` is
+    /// `y * w + x`, with an edge to its right and lower neighbors.
+    pub fn grid<G: GraphIntro<usize>>(w: usize, h: usize) -> G {
+        let mut graph = G::empty();
+        for y in 0..h {
+            for x in 0..w {
+                let v = y * w + x;
+                if x + 1 < w {
+                    graph = G::add_edge(graph, name_pair(name_of_usize(v), name_of_usize(v + 1)),
+                                        v, v + 1);
+                }
+                if y + 1 < h {
+                    graph = G::add_edge(graph, name_pair(name_of_usize(v), name_of_usize(v + w)),
+                                        v, v + w);
+                }
+            }
+        }
+        graph
+    }
 }
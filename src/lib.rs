@@ -25,6 +25,16 @@
 // #[macro_use]
 // extern crate log;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+#[cfg(feature = "bincode")]
+extern crate bincode;
+
+#[cfg(feature = "tracing")]
+extern crate tracing;
+
 #[macro_use]
 pub mod macros ;
 pub mod engine ;
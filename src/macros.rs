@@ -13,6 +13,13 @@
 use std::hash::{Hash,Hasher};
 use std::collections::hash_map::DefaultHasher;
 use std::fmt::{Formatter,Result,Debug};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+#[cfg(feature = "serde")]
+use std::cell::RefCell;
+#[cfg(feature = "serde")]
+use std::collections::HashSet;
 //use std::mem::replace;
 
 #[derive(PartialEq,Eq,Clone,Hash)]
@@ -31,6 +38,42 @@ impl Debug for ProgPt {
   fn fmt(&self, f: &mut Formatter) -> Result { self.symbol.fmt(f) }
 }
 
+/// Serializes the symbol of the program point.
+#[cfg(feature = "serde")]
+impl Serialize for ProgPt {
+  fn serialize<S:Serializer>(&self, serializer:S) -> ::std::result::Result<S::Ok, S::Error> {
+    self.symbol.serialize(serializer)
+  }
+}
+
+// The symbols of deserialized program points; see `intern_symbol`.
+#[cfg(feature = "serde")]
+thread_local!(static SYMBOLS: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new()));
+
+/// The `'static` string of the given symbol: Since program points are
+/// few, this leaks each distinct symbol once (per thread), and shares
+/// it thereafter, e.g., for the many program points of `engine::load`.
+#[cfg(feature = "serde")]
+fn intern_symbol(symbol:String) -> &'static str {
+  SYMBOLS.with(|symbols| {
+    let mut symbols = symbols.borrow_mut();
+    if let Some(symbol) = symbols.get(symbol.as_str()) { return *symbol }
+    let symbol : &'static str = Box::leak(symbol.into_boxed_str());
+    symbols.insert(symbol);
+    symbol
+  })
+}
+
+/// Deserializes the symbol of a program point, interning it (see
+/// `intern_symbol`).
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ProgPt {
+  fn deserialize<D:Deserializer<'de>>(deserializer:D) -> ::std::result::Result<Self, D::Error> {
+    let symbol = String::deserialize(deserializer)?;
+    Ok(ProgPt{ symbol:intern_symbol(symbol) })
+  }
+}
+
 pub fn my_hash<T>(obj: T) -> u64
   where T: Hash
 {
@@ -6,6 +6,9 @@ use std::fmt::Debug;
 use std::rc::Rc;
 use std::collections::HashMap;
 
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
 // /// Reflected primitive data
 //pub trait Data : Debug { }
 
@@ -54,6 +57,7 @@ impl<'a,S,T:Reflect<S>+Debug> Reflect<S> for Rc<T> {
 /// Rust values and this type is what makes the DCG engine "reflected"
 /// by the definitions in this module, and not identical to them.
 #[derive(Debug,Clone,Hash,Eq,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Val {
   /// Constructor, with a sequence of value parameters.
   Constr(Name,Vec<Val>),
@@ -85,6 +89,7 @@ pub enum Val {
 
 /// Primitive constants
 #[derive(Debug,Clone,Hash,Eq,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Const {
   /// Integers
   Num(isize),
@@ -97,6 +102,7 @@ pub enum Const {
 /// The content of an articulation: Either a cell holding a value, or
 /// a thunk that has optionally produced a value.
 #[derive(Debug,Clone,Hash,Eq,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ArtContent {
   /// The `Art` consists of a ref cell holding a value
   Val(Rc<Val>),
@@ -117,6 +123,7 @@ pub enum ArtContent {
 /// thought of roughly like a file path in UNIX (but Adapton has
 /// nothing to do with files, or with UNIX, directly).
 #[derive(PartialEq,Eq,Debug,Hash,Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Loc {
   /// The path of the `Loc` is a list of `Name`s.
   pub path: Path,
@@ -129,6 +136,7 @@ pub type Path = Vec<Name>;
 
 /// Reflected version of `engine::Effect`
 #[derive(Debug,Clone,Eq,PartialEq,Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Effect {
   /// The effect consists of a thunk observing the value of another
   /// thunk or reference cell.  That is, the effect consists of
@@ -146,6 +154,7 @@ pub enum Effect {
 /// `Succ` type, this version stores a reflected value (of type
 /// `Val`).
 #[derive(Debug,Clone,Eq,PartialEq,Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Succ {
   /// Dirty invariant: If this edge is dirty, then all predecessors of
   /// the edge are dirty too.
@@ -170,6 +179,7 @@ impl Reflect<Succ> for Succ {
 
 /// Reflected version of `engine::Pred`
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Pred {
   /// The predecessor of the node in question
   pub loc:    Loc,
@@ -181,6 +191,7 @@ pub struct Pred {
 /// of type `Option<Val>`, which is `None` when the node has not yet
 /// been executed, and `Some(_)` otherwise.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CompNode {
   pub preds:   Vec<Pred>,
   pub succs:   Vec<Succ>,
@@ -190,6 +201,7 @@ pub struct CompNode {
 
 /// Reflected version of `engine::MutNode`.  Stores a reflected value of type `Val`.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RefNode {  
   pub preds: Vec<Pred>,
   pub value: Val,
@@ -197,6 +209,7 @@ pub struct RefNode {
 
 /// Reflected version of `engine::PureNode`.  Stores a reflected value of type `Val`.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PureNode {  
   pub value: Val,
 }
@@ -205,6 +218,7 @@ pub struct PureNode {
 /// these nodes are not parameterized by a value type.  Instead, their
 /// values are all reflected into type `Val`.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Node {
   Comp(CompNode),
   Ref(RefNode),
@@ -249,6 +263,45 @@ pub struct DCG {
   pub path:  Vec<Name>,
 }
 
+/// An edge of a reflected `DCG`: its source node (always a thunk),
+/// and the effect and target of the edge.  See `DCG::edges`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Edge<'a> {
+  /// The location of the node doing the effect
+  pub src:  &'a Loc,
+  /// The effect, its target and its dirty bit
+  pub succ: &'a Succ,
+}
+
+impl DCG {
+  /// The node at the given location, if any.
+  pub fn node (&self, loc:&Loc) -> Option<&Node> {
+    self.table.get(loc)
+  }
+
+  /// The nodes of the DCG, with their locations, in no particular order.
+  pub fn nodes<'a> (&'a self) -> Box<Iterator<Item=(&'a Loc, &'a Node)> + 'a> {
+    Box::new(self.table.iter())
+  }
+
+  /// The edges of the DCG, in no particular order.  Each edge is the
+  /// outgoing edge (a `Succ`) of some thunk; the incoming edges of
+  /// each node (its `Pred`s) mirror these.
+  pub fn edges<'a> (&'a self) -> Box<Iterator<Item=Edge<'a>> + 'a> {
+    Box::new(self.table.keys().flat_map(move |loc| self.edges_from(loc)))
+  }
+
+  /// The outgoing edges of the node at the given location, in the
+  /// order of their effects; none if the node is not a thunk.
+  pub fn edges_from<'a> (&'a self, loc:&Loc) -> Box<Iterator<Item=Edge<'a>> + 'a> {
+    Box::new(self.table.get_key_value(loc).into_iter().flat_map(|(loc, nd)| {
+      succs_of_node(nd).into_iter().flat_map(move |succs| {
+        succs.iter().map(move |succ| Edge{src:loc, succ:succ})
+      })
+    }))
+  }
+}
+
 /// Gives effects and traces for cleaning and dirtying, the engine's
 /// internal DCG traversal/processing.  By contrast, the enclosing
 /// module (`reflect`) only gives reflected versions of the DCG
@@ -1,10 +1,13 @@
+use std::fmt;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
+use std::collections::HashMap;
 use std::collections::hash_map::DefaultHasher;
 use std::rc::Rc;
-use std::cmp::min;
+use std::cmp::{max, min, Ordering};
 
-use adapton::collections::{ListIntro, ListElim, MapIntro, MapElim, list_fold};
+use adapton::collections::{Dir2, Foldable, List, ListIntro, ListElim, MapIntro, MapElim, NameElse,
+                           SetElim, list_fold, list_map_eager, list_mergesort, vec_of_list};
 use adapton::bitstring::*;
 use adapton::engine::*;
 use macros::*;
@@ -31,6 +34,24 @@ pub struct Meta {
     pub min_depth: i64,
 }
 
+/// When an insertion splits colliding leaves more than this many
+/// levels below `min_depth`, the trie is rebuilt with a larger
+/// `min_depth`.
+const REBALANCE_SLACK: i64 = 8;
+
+impl Meta {
+    /// Chooses a `min_depth` for a trie expected to hold about
+    /// `expected_size` elements: deep enough that most leaves land in
+    /// their own bucket, without exceeding `BS::MAX_LEN`.
+    pub fn auto(expected_size: usize) -> Meta {
+        let mut min_depth = 1;
+        while min_depth < BS::MAX_LEN && (1usize << min_depth) < expected_size {
+            min_depth += 1;
+        }
+        Meta { min_depth: min_depth }
+    }
+}
+
 pub trait MetaT {
     fn hash_seeded(&self, u64);
 }
@@ -99,9 +120,86 @@ pub trait TrieElim<X>: Debug + Hash + PartialEq + Eq + Clone + 'static {
               NameC: FnOnce(&Name, &Self) -> Res;
 }
 
+/// How elements are placed in a trie: the hash that chooses their
+/// path, and the test for when a new element replaces an existing one.
+/// Sets place elements by their whole value; maps place bindings by
+/// key, so that updating a key replaces its previous binding.
+#[derive(Clone)]
+struct Placement<X> {
+    hash: fn(&X) -> u64,
+    same: fn(&X, &X) -> bool,
+}
+
+fn hash_elt<X: Hash>(elt: &X) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    elt.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn same_elt<X: PartialEq>(e1: &X, e2: &X) -> bool {
+    e1 == e2
+}
+
+fn hash_key<K: Hash, V>(&(ref k, _): &(K, V)) -> u64 {
+    hash_elt(k)
+}
+
+fn same_key<K: PartialEq, V>(&(ref k1, _): &(K, V), &(ref k2, _): &(K, V)) -> bool {
+    k1 == k2
+}
+
 impl<X: Debug + Hash + PartialEq + Eq + Clone + 'static> Trie<X> {
-    fn mfn(nm: Name, meta: Meta, trie: Self, bs: BS, elt: X, hash: u64) -> Self {
-        match trie {
+    fn elt_placement() -> Placement<X> {
+        Placement {
+            hash: hash_elt,
+            same: same_elt,
+        }
+    }
+
+    /// Like `split_atomic`, but places the leaf's element by `place`.
+    fn split_leaf(bs: BS, e: X, place: &Placement<X>) -> Self {
+        let bs0 = BS::prepend(0, bs);
+        let bs1 = BS::prepend(1, bs);
+        if BS::is_set(BS::length(bs), (place.hash)(&e) as i64) {
+            Self::bin(bs, Self::nil(bs0), Self::leaf(bs1, e))
+        } else {
+            Self::bin(bs, Self::leaf(bs0, e), Self::nil(bs1))
+        }
+    }
+
+    /// Re-introduces one level of a spine collapsed by `trie_compress`.
+    /// Nodes whose bitstring is longer than the current position `bs`
+    /// are hung below a fresh `Bin`, with a `Nil` sibling, so that
+    /// insertion can keep consuming one bit per level.
+    fn expand_spine(trie: Self, bs: BS) -> Self {
+        let node_bs = match trie {
+            Trie::Leaf(node_bs, _) |
+            Trie::Bin(node_bs, _, _) => node_bs,
+            t => return t,
+        };
+        if BS::length(node_bs) <= BS::length(bs) {
+            return trie;
+        }
+        let bs0 = BS::prepend(0, bs);
+        let bs1 = BS::prepend(1, bs);
+        if BS::is_set(BS::length(bs), node_bs.value) {
+            Self::bin(bs, Self::nil(bs0), trie)
+        } else {
+            Self::bin(bs, trie, Self::nil(bs1))
+        }
+    }
+
+    /// Inserts `elt` below position `bs`, returning the new subtrie
+    /// along with the depth at which `elt` was placed.
+    fn mfn(nm: Name,
+           meta: Meta,
+           trie: Self,
+           bs: BS,
+           elt: X,
+           hash: u64,
+           place: &Placement<X>)
+           -> (Self, i64) {
+        match Self::expand_spine(trie, bs) {
             Trie::Nil(_) if BS::length(bs) < meta.min_depth => {
                 let h_ = hash >> 1;
                 let bs0 = BS::prepend(0, bs);
@@ -109,63 +207,107 @@ impl<X: Debug + Hash + PartialEq + Eq + Clone + 'static> Trie<X> {
                 let mt0 = Self::nil(bs0);
                 let mt1 = Self::nil(bs1);
                 if hash % 2 == 0 {
-                    Self::bin(bs, Self::mfn(nm, meta, mt0, bs0, elt, h_), mt1)
+                    let (l, depth) = Self::mfn(nm, meta, mt0, bs0, elt, h_, place);
+                    (Self::bin(bs, l, mt1), depth)
                 } else {
-                    Self::bin(bs, mt0, Self::mfn(nm, meta, mt1, bs1, elt, h_))
+                    let (r, depth) = Self::mfn(nm, meta, mt1, bs1, elt, h_, place);
+                    (Self::bin(bs, mt0, r), depth)
                 }
             }
-            Trie::Nil(_) => Trie::Leaf(bs, elt),
+            Trie::Nil(_) => (Trie::Leaf(bs, elt), BS::length(bs)),
             Trie::Leaf(_, e) => {
                 let depth = BS::length(bs);
-                if depth >= BS::MAX_LEN || e == elt {
-                    Self::leaf(bs, e)
-                } else if depth < BS::MAX_LEN {
+                if (place.same)(&e, &elt) {
+                    (Self::leaf(bs, elt), depth)
+                } else if depth >= BS::MAX_LEN {
+                    (Self::leaf(bs, e), depth)
+                } else {
                     Self::mfn(nm,
                               meta,
-                              Self::split_atomic(Self::leaf(bs, e)),
+                              Self::split_leaf(bs, e, place),
                               bs,
                               elt,
-                              hash)
-                } else {
-                    panic!("Bad value found in nadd:\nLeaf(bs, e)\n{:?}",
-                           Self::leaf(bs, e));
+                              hash,
+                              place)
                 }
             }
             Trie::Bin(bs, left, right) => {
                 let h_ = hash >> 1;
                 if hash % 2 == 0 {
-                    let l = Self::mfn(nm, meta, *left, BS::prepend(0, bs), elt, h_);
-                    Self::bin(bs, l, *right)
+                    let bs0 = BS::prepend(0, bs);
+                    let (l, depth) = Self::mfn(nm, meta, *left, bs0, elt, h_, place);
+                    (Self::bin(bs, l, *right), depth)
                 } else {
-                    let r = Self::mfn(nm, meta, *right, BS::prepend(1, bs), elt, h_);
-                    Self::bin(bs, *left, r)
+                    let bs1 = BS::prepend(1, bs);
+                    let (r, depth) = Self::mfn(nm, meta, *right, bs1, elt, h_, place);
+                    (Self::bin(bs, *left, r), depth)
                 }
             }
-            Trie::Name(_, box Trie::Art(a)) => Self::mfn(nm, meta, force(&a), bs, elt, hash),
+            Trie::Name(_, box Trie::Art(a)) => {
+                Self::mfn(nm, meta, force(&a), bs, elt, hash, place)
+            }
             t => panic!("Bad value found in nadd:\n{:?}\n", t),
         }
     }
 
-    fn root_mfn(_: Name, nm: Name, trie: Self, elt: X) -> Self {
+    /// Rebuilds `trie` for a larger `min_depth`: leaves above the new
+    /// `min_depth` are pushed down along their hash, and everything
+    /// else keeps its position.  `Name` nodes are kept, each with a
+    /// fresh articulation, so consumers memoized at those names are
+    /// not invalidated wholesale.
+    fn rebalance(nm: Name, meta: Meta, trie: Self, place: &Placement<X>) -> Self {
+        TrieElim::elim_arg(trie,
+                           (nm, meta),
+                           |bs, _| Self::nil(bs),
+                           |bs, x, (nm, meta)| if BS::length(bs) < meta.min_depth {
+                               let hash = (place.hash)(&x) >> BS::length(bs);
+                               Self::mfn(nm, meta, Self::nil(bs), bs, x, hash, place).0
+                           } else {
+                               Self::leaf(bs, x)
+                           },
+                           |bs, l, r, (nm, meta)| {
+                               let l = Self::rebalance(nm.clone(), meta.clone(), l, place);
+                               let r = Self::rebalance(nm, meta, r, place);
+                               Self::bin(bs, l, r)
+                           },
+                           |_, t, (nm, meta)| Self::rebalance(nm, meta, t, place),
+                           |n, t, (nm, meta)| {
+                               let t = Self::rebalance(nm, meta, t, place);
+                               let (n_art, _) = name_fork(n.clone());
+                               Self::name(n, Self::art(cell(n_art, t)))
+                           })
+    }
+
+    fn root_mfn(_: Name, nm: Name, trie: Self, elt: X, place: &Placement<X>) -> Self {
         match trie {
             Trie::Name(_, box Trie::Art(a)) => {
                 match force(&a) {
                     Trie::Root(meta, t) => {
                         let (nm, nm_) = name_fork(nm);
-                        let mut hasher = DefaultHasher::new();
-                        elt.hash(&mut hasher);
-                        let a = Self::mfn(nm_,
-                                          meta.clone(),
-                                          *t,
-                                          BS {
-                                              length: 0,
-                                              value: 0,
-                                          },
-                                          elt,
-                                          hasher.finish());
+                        let hash = (place.hash)(&elt);
+                        let (a, depth) = Self::mfn(nm_.clone(),
+                                                   meta.clone(),
+                                                   *t,
+                                                   BS {
+                                                       length: 0,
+                                                       value: 0,
+                                                   },
+                                                   elt,
+                                                   hash,
+                                                   place);
+                        let (meta, a) = if depth > meta.min_depth + REBALANCE_SLACK &&
+                                               meta.min_depth < BS::MAX_LEN {
+                            let min_depth = min((meta.min_depth + depth) / 2, BS::MAX_LEN);
+                            let meta = Meta { min_depth: min_depth };
+                            (meta.clone(), Self::rebalance(nm_, meta, a, place))
+                        } else {
+                            (meta, a)
+                        };
                         Self::root(meta, Self::name(nm, Self::art(put(a))))
                     }
-                    t @ Trie::Name(_, box Trie::Art(_)) => Self::root_mfn(nm.clone(), nm, t, elt),
+                    t @ Trie::Name(_, box Trie::Art(_)) => {
+                        Self::root_mfn(nm.clone(), nm, t, elt, place)
+                    }
                     t => panic!("Non-root node entry to `Trie.extend': {:?}", t),
                 }
             }
@@ -221,7 +363,8 @@ impl<X: Debug + Hash + PartialEq + Eq + Clone + 'static> TrieIntro<X> for Trie<X
     fn extend(nm: Name, trie: Self, elt: X) -> Self {
         let (nm, nm_) = name_fork(nm);
         // let a = Self::root_mfn(nm.clone(), nm_, trie, elt);
-        let root_mfn_art = put(Self::root_mfn(nm.clone(), nm_, trie, elt));
+        let place = Self::elt_placement();
+        let root_mfn_art = put(Self::root_mfn(nm.clone(), nm_, trie, elt, &place));
         Self::name(nm, Self::art(root_mfn_art))
     }
 }
@@ -252,15 +395,101 @@ impl<X: Debug + Hash + PartialEq + Eq + Clone + 'static> Hash for Trie<X> {
     }
 }
 
+/// Elements that can be printed by the `Display` implementation of
+/// `Trie`.  Map bindings print as `k: v`; set elements, of the form
+/// `(x, ())`, print as just `x`.
+pub trait DisplayElt {
+    fn fmt_elt(&self, f: &mut fmt::Formatter) -> fmt::Result;
+    /// True for values that are elided after a key, i.e., `()`.
+    fn is_unit(&self) -> bool {
+        false
+    }
+}
+
+impl DisplayElt for () {
+    fn fmt_elt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "()")
+    }
+    fn is_unit(&self) -> bool {
+        true
+    }
+}
+
+impl<K: fmt::Display, V: DisplayElt> DisplayElt for (K, V) {
+    fn fmt_elt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        if self.1.is_unit() {
+            Ok(())
+        } else {
+            write!(f, ": ")?;
+            self.1.fmt_elt(f)
+        }
+    }
+}
+
+macro_rules! display_elt {
+    ( $( $t:ty ),* ) => {
+        $( impl DisplayElt for $t {
+            fn fmt_elt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(self, f)
+            }
+        } )*
+    }
+}
+
+display_elt!(usize, isize, u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, char, bool, String);
+
+/// Prints the elements as `{x, y, z}`, forcing arts and eliding the
+/// `Name`, `Root` and `Bin` structure.  The alternate form (`{:#}`)
+/// keeps the names, printing each named subtrie as `@name {..}`.
+impl<X: DisplayElt + Debug + Hash + PartialEq + Eq + Clone + 'static> fmt::Display for Trie<X> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn sep(f: &mut fmt::Formatter, first: &mut bool) -> fmt::Result {
+            if *first {
+                *first = false;
+                Ok(())
+            } else {
+                write!(f, ", ")
+            }
+        }
+        fn write_trie<X: DisplayElt + Debug + Hash + PartialEq + Eq + Clone + 'static>
+            (trie: &Trie<X>, f: &mut fmt::Formatter, first: &mut bool) -> fmt::Result {
+            match *trie {
+                Trie::Nil(_) => Ok(()),
+                Trie::Leaf(_, ref x) => {
+                    sep(f, first)?;
+                    x.fmt_elt(f)
+                }
+                Trie::Bin(_, ref l, ref r) => {
+                    write_trie(l, f, first)?;
+                    write_trie(r, f, first)
+                }
+                Trie::Root(_, ref t) => write_trie(t, f, first),
+                Trie::Name(ref nm, ref t) if f.alternate() => {
+                    sep(f, first)?;
+                    write!(f, "@{} {{", reflect::string_of_name(nm))?;
+                    write_trie(t, f, &mut true)?;
+                    write!(f, "}}")
+                }
+                Trie::Name(_, ref t) => write_trie(t, f, first),
+                Trie::Art(ref art) => write_trie(&force(art), f, first),
+            }
+        }
+        write!(f, "{{")?;
+        write_trie(self, f, &mut true)?;
+        write!(f, "}}")
+    }
+}
+
 impl<X: Debug + Hash + PartialEq + Eq + Clone + 'static> TrieElim<X> for Trie<X> {
     fn find(trie: &Self, elt: &X, i: i64) -> Option<X> {
         Self::elim_ref(trie,
                        |_| None,
                        |_, x| if *elt == *x { Some(x.clone()) } else { None },
-                       |_, left, right| if i % 2 == 0 {
-                           Self::find(left, elt, i >> 1)
+                       |bs, left, right| if (i >> BS::length(*bs)) % 2 == 0 {
+                           Self::find(left, elt, i)
                        } else {
-                           Self::find(right, elt, i >> 1)
+                           Self::find(right, elt, i)
                        },
                        |_, t| Self::find(t, elt, i),
                        |_, t| Self::find(t, elt, i))
@@ -389,10 +618,29 @@ impl<Dom:Debug+Hash+PartialEq+Eq+Clone+'static,
             })
         }
         fn update (map:Self, d:Dom, c:Cod) -> Self {
-            TrieIntro::extend(name_unit(), map, (d,c))
+            Self::map_extend(name_unit(), map, d, c)
+        }
+
+        /// Memoizes the call to `f` at one fork of `nm`, and names the
+        /// new version of the map (see `map_extend`) by the other.
+        fn update_with<F:'static> (map:Self, nm:Name, d:Dom, f:Rc<F>) -> Self
+            where F:Fn(Option<Cod>) -> Cod
+        {
+            let (nm1, nm2) = name_fork(nm);
+            let found = MapElim::find(&map, &d);
+            let c = memo!(nm1 =>> map_update_with_cod::<Dom,Cod,F>,
+                          d:d.clone(), found:found ;; f:f);
+            Self::map_extend(nm2, map, d, c)
         }
 }
 
+/// The new value of `d` for `MapIntro::update_with`, given its binding.
+fn map_update_with_cod<Dom, Cod, F: 'static>(_d: Dom, found: Option<Cod>, f: Rc<F>) -> Cod
+    where F: Fn(Option<Cod>) -> Cod
+{
+    f(found)
+}
+
 impl<Dom:Debug+Hash+PartialEq+Eq+Clone+'static,
      Cod:Debug+Hash+PartialEq+Eq+Clone+'static>
     MapElim<Dom,Cod>
@@ -413,10 +661,10 @@ impl<Dom:Debug+Hash+PartialEq+Eq+Clone+'static,
                                        } else {
                                            None
                                        },
-                                       |_, ref left, ref right| if i % 2 == 0 {
-                                           find_hash(left, d, i >> 1)
+                                       |bs, ref left, ref right| if (i >> BS::length(*bs)) % 2 == 0 {
+                                           find_hash(left, d, i)
                                        } else {
-                                           find_hash(right, d, i >> 1)
+                                           find_hash(right, d, i)
                                        },
                                        |_, ref t| find_hash(t, d, i),
                                        |_, ref t| {
@@ -438,13 +686,159 @@ impl<Dom:Debug+Hash+PartialEq+Eq+Clone+'static,
             trie_fold(map, res, Rc::new(move |(d,c),r|(*body)(d,c,r)) )
         }
 
-        fn append(_map:Self, _other:Self) -> Self {
-            unimplemented!()
+        fn append(map:Self, other:Self) -> Self {
+            Self::map_union(map, other, Rc::new(|_, c| c))
+        }
+
+        /// Merges the tries along their structure; see `trie_union`.
+        fn merge_with<F:'static>(map:Self, other:Self, f:Rc<F>) -> Self
+            where F:Fn(&Dom, Cod, Cod) -> Cod
+        {
+            let place = Placement {
+                hash: hash_key,
+                same: same_key,
+            };
+            let mtbs = BS {
+                length: 0,
+                value: 0,
+            };
+            ns(name_of_str("map_union"), || {
+                trie_merge(map,
+                           other,
+                           mtbs,
+                           place,
+                           Rc::new(move |(k, c1), (_, c2)| {
+                               let c = f(&k, c1, c2);
+                               (k, c)
+                           }))
+            })
+        }
+    }
+
+impl<K: Debug + Hash + PartialEq + Eq + Clone + 'static,
+     V: Debug + Hash + PartialEq + Eq + Clone + 'static>
+    From<HashMap<K, V>> for Trie<(K, V)> {
+    fn from(map: HashMap<K, V>) -> Self {
+        map.into_iter().fold(MapIntro::empty(), |t, (k, v)| MapIntro::update(t, k, v))
+    }
+}
+
+impl<K: Debug + Hash + PartialEq + Eq + Clone + 'static,
+     V: Debug + Hash + PartialEq + Eq + Clone + 'static> Trie<(K, V)> {
+    /// Binds `k` to `v` in the map, replacing any previous binding for
+    /// `k`.  Like `TrieIntro::extend`, the new version is named `nm`.
+    pub fn map_extend(nm: Name, map: Self, k: K, v: V) -> Self {
+        let place = Placement {
+            hash: hash_key,
+            same: same_key,
+        };
+        let (nm, nm_) = name_fork(nm);
+        let root_mfn_art = put(Self::root_mfn(nm.clone(), nm_, map, (k, v), &place));
+        Self::name(nm, Self::art(root_mfn_art))
+    }
+
+    /// Merges two maps; see `trie_union`.  A key bound in both maps is
+    /// bound to `combine` of its value in `map` and its value in
+    /// `other`.
+    pub fn map_union<F: 'static>(map: Self, other: Self, combine: Rc<F>) -> Self
+        where F: Fn(V, V) -> V
+    {
+        MapElim::merge_with(map, other, Rc::new(move |_: &K, c1, c2| combine(c1, c2)))
+    }
+
+    /// Collects the entries of the map into a `HashMap`, forcing every
+    /// articulation along the way.
+    pub fn into_hashmap(self) -> HashMap<K, V> {
+        fn collect<K: Debug + Hash + PartialEq + Eq + Clone + 'static,
+                   V: Debug + Hash + PartialEq + Eq + Clone + 'static>
+            (trie: Trie<(K, V)>, map: &mut HashMap<K, V>) {
+            TrieElim::elim_arg(trie,
+                               map,
+                               |_, _| (),
+                               |_, (k, v), map| {
+                                   map.insert(k, v);
+                               },
+                               |_, l, r, map| {
+                                   collect(l, map);
+                                   collect(r, map)
+                               },
+                               |_, t, map| collect(t, map),
+                               |_, t, map| collect(t, map))
         }
+        let mut map = HashMap::new();
+        collect(self, &mut map);
+        map
     }
+}
 
 pub type Set<X> = Trie<(X, ())>;
 
+/// Finite maps from keys to lists of values, most recently inserted
+/// first.
+pub type MultiMap<K, V> = Trie<(K, List<V>)>;
+
+impl<K: Debug + Hash + PartialEq + Eq + Clone + 'static,
+     V: Debug + Hash + PartialEq + Eq + Clone + 'static> Trie<(K, List<V>)> {
+    /// Adds `v` to the values of `k`, naming the new version of the
+    /// map `nm`.  The values of `k` before the insertion follow `v`
+    /// under a name and articulation, also forked from `nm`.
+    pub fn insert(nm: Name, map: Self, k: K, v: V) -> Self {
+        let (nm_map, nm_vals) = name_fork(nm);
+        let vals = Self::get_all(&map, &k);
+        let vals = if ListElim::is_empty(&vals) {
+            List::cons(v, List::nil())
+        } else {
+            List::cons(v, List::name(nm_vals.clone(), List::art(cell(nm_vals, vals))))
+        };
+        Self::map_extend(nm_map, map, k, vals)
+    }
+
+    /// Returns the values of `k`, or the empty list if it has none.
+    pub fn get_all(map: &Self, k: &K) -> List<V> {
+        MapElim::find(map, k).unwrap_or(List::nil())
+    }
+
+    /// Removes the most recently inserted occurrence of `v` from the
+    /// values of `k`, naming the new version of the map `nm`.  The map
+    /// is unchanged when `k` has no such value.
+    pub fn remove_one(nm: Name, map: Self, k: &K, v: &V) -> Self {
+        match multimap_remove_one(Self::get_all(&map, k), v) {
+            None => map,
+            Some(vals) => Self::map_extend(nm, map, k.clone(), vals),
+        }
+    }
+
+    /// Returns a list of each key paired with each of its values.
+    /// Memoizes at each name in the map, as `trie_fold` does.
+    pub fn flatten(map: Self) -> List<(K, V)> {
+        ns(name_of_str("multimap_flatten"), || {
+            trie_fold(map,
+                      List::nil(),
+                      Rc::new(|(k, vals): (K, List<V>), pairs| {
+                          list_fold(vals, pairs, Rc::new(move |v, pairs| {
+                              List::cons((k.clone(), v), pairs)
+                          }))
+                      }))
+        })
+    }
+}
+
+/// Removes the first occurrence of `v` from `vals`, keeping the names
+/// before it, or returns `None` if `v` does not occur.
+fn multimap_remove_one<V: Debug + Hash + PartialEq + Eq + Clone + 'static>(vals: List<V>,
+                                                                        v: &V)
+                                                                        -> Option<List<V>> {
+    ListElim::elim_arg(vals,
+                       v,
+                       |_, _| None,
+                       |x, tl, v| if x == *v {
+                           Some(tl)
+                       } else {
+                           multimap_remove_one(tl, v).map(|tl| List::cons(x, tl))
+                       },
+                       |nm, tl, v| multimap_remove_one(tl, v).map(|tl| List::name(nm, tl)))
+}
+
 pub fn trie_fold
     <X, T:TrieElim<X>, Res:Hash+Debug+Eq+Clone+'static, F: 'static>
     (t: T, res:Res, f: Rc<F>) -> Res
@@ -493,6 +887,68 @@ pub fn trie_fold_seq<X,
                 })
 }
 
+/// Like `trie_fold_seq`, except that for `Dir2::Right`, the elements
+/// and names of the trie are visited in the reverse order.
+pub fn trie_fold_seq_dir<X,
+                         T: TrieElim<X>,
+                         Res: Hash + Debug + Eq + Clone + 'static,
+                         LeafC: 'static,
+                         BinC: 'static,
+                         NameC: 'static>
+    (trie: T,
+     dir: Dir2,
+     res: Res,
+     leaf: Rc<LeafC>,
+     bin: Rc<BinC>,
+     name: Rc<NameC>)
+     -> Res
+    where LeafC: Fn(X, Res) -> Res,
+          BinC: Fn(Res) -> Res,
+          NameC: Fn(Name, Res) -> Res
+{
+    T::elim_arg(trie,
+                (res, (leaf, bin, name)),
+                |_, (res, _)| res,
+                |_, x, (res, (leaf, _, _))| leaf(x, res),
+                |_, left, right, (res, (leaf, bin, name))| {
+        let (first, second) = match dir {
+            Dir2::Left => (left, right),
+            Dir2::Right => (right, left),
+        };
+        let res = trie_fold_seq_dir(first, dir, res, leaf.clone(), bin.clone(), name.clone());
+        let res = (&bin)(res);
+        trie_fold_seq_dir(second, dir, res, leaf, bin, name)
+    },
+                |_, t, (res, (leaf, bin, name))| trie_fold_seq_dir(t, dir, res, leaf, bin, name),
+                |nm, t, (res, (leaf, bin, name))| match dir {
+        Dir2::Left => {
+            let res = memo!(nm.clone() =>> trie_fold_seq_dir, trie:t, dir:dir, res:res ;;
+                            leaf:leaf, bin:bin, name:name.clone());
+            name(nm, res)
+        }
+        Dir2::Right => {
+            let res = name(nm.clone(), res);
+            memo!(nm =>> trie_fold_seq_dir, trie:t, dir:dir, res:res ;;
+                  leaf:leaf, bin:bin, name:name)
+        }
+    })
+}
+
+impl<X: Debug + Hash + PartialEq + Eq + Clone + 'static> Foldable<X> for Trie<X> {
+    fn fold_seq<Res, LeafC, NameC>(trie: Self,
+                                   dir: Dir2,
+                                   res: Res,
+                                   leaf: Rc<LeafC>,
+                                   name: Rc<NameC>)
+                                   -> Res
+        where Res: Hash + Debug + Eq + Clone + 'static,
+              LeafC: Fn(X, Res) -> Res + 'static,
+              NameC: Fn(Name, Res) -> Res + 'static
+    {
+        trie_fold_seq_dir(trie, dir, res, leaf, Rc::new(|res| res), name)
+    }
+}
+
 pub fn trie_fold_seq_nm<X,
                         T: TrieElim<X>,
                         Res: Hash + Debug + Eq + Clone + 'static,
@@ -564,6 +1020,129 @@ pub fn list_of_trieset<X: Hash + Clone + Debug + 'static,
                   Rc::new(|(elt, ()), list| ListIntro::cons(elt, list)))
 }
 
+/// An element of a set, with its sort key and hash, for
+/// `set_to_sorted_list`.  Orders by the key, then by the hash.
+#[derive(Debug,PartialEq,Eq,Hash,Clone)]
+struct Keyed<K, X> {
+    key: K,
+    hash: u64,
+    elt: X,
+}
+
+impl<K: Ord + Eq, X: Eq> PartialOrd for Keyed<K, X> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord + Eq, X: Eq> Ord for Keyed<K, X> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key).then(self.hash.cmp(&other.hash))
+    }
+}
+
+/// Lists the elements of the set in order of `key`, breaking ties by
+/// the hashes of the elements.  Unlike the order of `trie_fold`, which
+/// depends on where the hashes of the elements place them in the trie,
+/// this order is stable as the set changes.  The names of the set
+/// (other than `name_unit`) name the list that is sorted, which
+/// memoizes both the listing and the sorting (see `list_mergesort`).
+pub fn set_to_sorted_list<X: Debug + Hash + Eq + Clone + 'static,
+                          K: Debug + Hash + Ord + Clone + 'static,
+                          F: 'static>
+    (set: Set<X>,
+     key: Rc<F>)
+     -> List<X>
+    where F: Fn(&X) -> K
+{
+    ns(name_of_str("set_to_sorted_list"), move || {
+        let keyed: List<Keyed<K, X>> =
+            trie_fold_seq(set,
+                          List::nil(),
+                          Rc::new(move |(x, ()), l| {
+                              let keyed = Keyed {
+                                  key: key(&x),
+                                  hash: my_hash(&x),
+                                  elt: x,
+                              };
+                              List::cons(keyed, l)
+                          }),
+                          Rc::new(|l| l),
+                          Rc::new(|nm: Name, l| {
+                              if nm == name_unit() { l } else { List::name(nm, l) }
+                          }));
+        let sorted = list_mergesort(keyed);
+        ns(name_of_str("elts"),
+           || list_map_eager(sorted, Rc::new(|keyed: Keyed<K, X>| keyed.elt)))
+    })
+}
+
+/// Lists the elements of the set in order of their hashes; see
+/// `set_to_sorted_list`.
+pub fn set_to_hash_sorted_list<X: Debug + Hash + Eq + Clone + 'static>(set: Set<X>) -> List<X> {
+    set_to_sorted_list(set, Rc::new(|_: &X| ()))
+}
+
+/// Hashes the elements of the trie, regardless of the order of their
+/// insertion, and of where the trie holds names and articulations (on
+/// which the `Hash` of `Trie` itself depends); so, tries built by
+/// different histories of edits hash alike, as memo keys.  Memoizes
+/// at the names of the trie, as `trie_fold` does.
+pub fn trie_hash_commutative<X: Debug + Hash + PartialEq + Eq + Clone + 'static>(trie: &Trie<X>)
+                                                                              -> u64 {
+    ns(name_of_str("trie_hash_commutative"), || {
+        trie_fold(trie.clone(), 0, Rc::new(|x, h: u64| h.wrapping_add(my_hash(&x))))
+    })
+}
+
+/// Hashes the elements of the set; see `trie_hash_commutative`.
+pub fn set_hash_commutative<X: Debug + Hash + PartialEq + Eq + Clone + 'static>(set: &Set<X>)
+                                                                             -> u64 {
+    trie_hash_commutative(set)
+}
+
+/// Tests if the sets have the same elements, regardless of how they
+/// were built.  First compares their `set_hash_commutative` and their
+/// sizes, then tests each element of `a` for membership in `b`; these
+/// tests depend on both sets, and are not memoized.
+pub fn set_logical_eq<X: Debug + Hash + PartialEq + Eq + Clone + 'static>(a: &Set<X>,
+                                                                          b: &Set<X>)
+                                                                          -> bool {
+    if set_hash_commutative(a) != set_hash_commutative(b) || MapElim::len(a) != MapElim::len(b) {
+        return false;
+    }
+    let elts: List<X> = ns(name_of_str("set_logical_eq"), || list_of_trieset(a.clone()));
+    vec_of_list(elts, None).into_iter().all(|x| match x {
+        NameElse::Else(x) => SetElim::is_mem(b, &x),
+        NameElse::Name(_) => true,
+    })
+}
+
+/// Hashes the bindings of the map; see `trie_hash_commutative`.
+pub fn map_hash_commutative<K: Debug + Hash + PartialEq + Eq + Clone + 'static,
+                            V: Debug + Hash + PartialEq + Eq + Clone + 'static>
+    (map: &Trie<(K, V)>)
+     -> u64 {
+    trie_hash_commutative(map)
+}
+
+/// Tests if the maps have the same bindings, regardless of how they
+/// were built; see `set_logical_eq`.
+pub fn map_logical_eq<K: Debug + Hash + PartialEq + Eq + Clone + 'static,
+                      V: Debug + Hash + PartialEq + Eq + Clone + 'static>
+    (a: &Trie<(K, V)>,
+     b: &Trie<(K, V)>)
+     -> bool {
+    if map_hash_commutative(a) != map_hash_commutative(b) || MapElim::len(a) != MapElim::len(b) {
+        return false;
+    }
+    let bindings: List<(K, V)> = ns(name_of_str("map_logical_eq"), || list_of_trie(a.clone()));
+    vec_of_list(bindings, None).into_iter().all(|kv| match kv {
+        NameElse::Else((k, v)) => MapElim::find(b, &k) == Some(v),
+        NameElse::Name(_) => true,
+    })
+}
+
 pub fn trie_fold_up<X,
                     T: TrieElim<X>,
                     Res: Hash + Debug + Eq + Clone + 'static,
@@ -628,3 +1207,264 @@ pub fn eager_trie_of_trie<X: Hash + Clone + 'static,
                  Rc::new(|meta, t| TI::root(meta, t)),
                  Rc::new(|n, t| TI::name(n, t)))
 }
+
+/// Merges two subtries at position `bs` by walking them side by
+/// side, splitting leaves where the other trie branches.  Elements
+/// that `place` deems the same are combined with `combine`.  Each name
+/// met along the way (from `a`, or else from `b`) names the merge of
+/// the subtries below it.
+fn trie_merge<X: Debug + Hash + PartialEq + Eq + Clone + 'static, F: 'static>
+    (a: Trie<X>,
+     b: Trie<X>,
+     bs: BS,
+     place: Placement<X>,
+     combine: Rc<F>)
+     -> Trie<X>
+    where F: Fn(X, X) -> X
+{
+    match (Trie::expand_spine(a, bs), Trie::expand_spine(b, bs)) {
+        (Trie::Art(a), b) => trie_merge(force(&a), b, bs, place, combine),
+        (a, Trie::Art(b)) => trie_merge(a, force(&b), bs, place, combine),
+        (Trie::Name(nm, a), b) => {
+            let (art, _) = eager!(nm.clone() =>> trie_merge =>> <X, F>,
+                                  a:*a, b:b, bs:bs ;; place:place, combine:combine);
+            Trie::name(nm, Trie::art(art))
+        }
+        (a, Trie::Name(nm, b)) => {
+            let (art, _) = eager!(nm.clone() =>> trie_merge =>> <X, F>,
+                                  a:a, b:*b, bs:bs ;; place:place, combine:combine);
+            Trie::name(nm, Trie::art(art))
+        }
+        (Trie::Nil(_), t) |
+        (t, Trie::Nil(_)) => t,
+        (Trie::Root(m1, a), Trie::Root(m2, b)) => {
+            let meta = Meta { min_depth: max(m1.min_depth, m2.min_depth) };
+            Trie::root(meta, trie_merge(*a, *b, bs, place, combine))
+        }
+        (Trie::Leaf(_, x), Trie::Leaf(_, y)) => {
+            if (place.same)(&x, &y) {
+                Trie::leaf(bs, combine(x, y))
+            } else if BS::length(bs) >= BS::MAX_LEN {
+                Trie::leaf(bs, x)
+            } else {
+                let a = Trie::split_leaf(bs, x, &place);
+                trie_merge(a, Trie::leaf(bs, y), bs, place, combine)
+            }
+        }
+        (Trie::Leaf(_, x), b) => {
+            let a = Trie::split_leaf(bs, x, &place);
+            trie_merge(a, b, bs, place, combine)
+        }
+        (a, Trie::Leaf(_, y)) => {
+            let b = Trie::split_leaf(bs, y, &place);
+            trie_merge(a, b, bs, place, combine)
+        }
+        (Trie::Bin(_, l1, r1), Trie::Bin(_, l2, r2)) => {
+            let l = trie_merge(*l1, *l2, BS::prepend(0, bs), place.clone(), combine.clone());
+            let r = trie_merge(*r1, *r2, BS::prepend(1, bs), place, combine);
+            Trie::bin(bs, l, r)
+        }
+        (a, b) => panic!("Bad values found in trie merge:\n{:?}\n{:?}\n", a, b),
+    }
+}
+
+/// Merges two tries by walking their structure together, rather than
+/// extending one with each element of the other.  An element in both
+/// tries is kept once.  The result reuses the names of `a`, and those
+/// of `b` where `a` has none.
+pub fn trie_union<X: Debug + Hash + PartialEq + Eq + Clone + 'static>(a: Trie<X>,
+                                                                      b: Trie<X>)
+                                                                      -> Trie<X> {
+    let mtbs = BS {
+        length: 0,
+        value: 0,
+    };
+    ns(name_of_str("trie_union"), || {
+        trie_merge(a, b, mtbs, Trie::elt_placement(), Rc::new(|x, _| x))
+    })
+}
+
+/// Collapses unary spines: each `Bin` with a `Nil` child is replaced
+/// by its other child, and a `Bin` of two `Nil`s by a single `Nil`.
+/// Surviving nodes keep their full bitstrings, so `find` and `extend`
+/// still work on the result.  `Name` nodes are kept, each with a
+/// fresh articulation, so the pass stays incremental.
+pub fn trie_compress<X: Hash + Clone + Debug + 'static,
+                     T: TrieElim<X> + TrieIntro<X> + 'static>
+    (trie: T)
+     -> T {
+    fn is_nil<X, T: TrieElim<X>>(t: &T) -> bool {
+        T::elim_ref(t, |_| true, |_, _| false, |_, _, _| false, |_, _| false, |_, _| false)
+    }
+    ns(name_of_str("trie_compress"), || {
+        trie_fold_up(trie,
+                     Rc::new(|bs| T::nil(bs)),
+                     Rc::new(|bs, x| T::leaf(bs, x)),
+                     Rc::new(|bs, l: T, r: T| match (is_nil(&l), is_nil(&r)) {
+                         (true, true) => T::nil(bs),
+                         (true, false) => r,
+                         (false, true) => l,
+                         (false, false) => T::bin(bs, l, r),
+                     }),
+                     Rc::new(|meta, t| T::root(meta, t)),
+                     Rc::new(|n: Name, t| {
+                         let (n_art, _) = name_fork(n.clone());
+                         T::name(n, T::art(cell(n_art, t)))
+                     }))
+    })
+}
+
+/// Rebuilds the articulations of a trie.  Existing `Name` and `Art`
+/// nodes (e.g., those left behind by a long sequence of `extend`s)
+/// are dropped, and a fresh name, derived from `nm` and the node's
+/// bitstring, is placed above each `Bin` that covers at least `k`
+/// leaves not already covered by a deeper name.  The result holds the
+/// same elements, at the same positions, as the input.
+pub fn trie_compact<X: Hash + Clone + Debug + 'static,
+                    T: TrieElim<X> + TrieIntro<X> + 'static>
+    (trie: T,
+     nm: Name,
+     k: usize)
+     -> T {
+    fn compact_rec<X, T: TrieElim<X> + TrieIntro<X>>(trie: T, nm: &Name, k: usize) -> (T, usize) {
+        T::elim_arg(trie,
+                    (nm, k),
+                    |bs, _| (T::nil(bs), 0),
+                    |bs, x, _| (T::leaf(bs, x), 1),
+                    |bs, l, r, (nm, k)| {
+                        let (l, nl) = compact_rec(l, nm, k);
+                        let (r, nr) = compact_rec(r, nm, k);
+                        let t = T::bin(bs, l, r);
+                        if nl + nr >= k {
+                            let n = name_pair(nm.clone(),
+                                              name_pair(name_of_isize(bs.length as isize),
+                                                        name_of_isize(bs.value as isize)));
+                            (T::name(n.clone(), T::art(cell(n, t))), 0)
+                        } else {
+                            (t, nl + nr)
+                        }
+                    },
+                    |meta, t, (nm, k)| {
+                        let (t, cnt) = compact_rec(t, nm, k);
+                        (T::root(meta, t), cnt)
+                    },
+                    |_, t, (nm, k)| compact_rec(t, nm, k))
+    }
+    let (t, _) = compact_rec(trie, &nm, k);
+    // `extend` expects a named articulation at the top of the trie.
+    let n = name_pair(nm, name_of_str("root"));
+    T::name(n.clone(), T::art(cell(n, t)))
+}
+
+/// Drops the elements that fail `pred`, leaving a `Nil` in the place
+/// of each.  The `Bin`, `Root` and `Name` skeleton of the input is
+/// kept intact, so that consumers memoized at its names only re-run
+/// where the retained elements actually changed.
+pub fn trie_retain<X: Hash + Clone + Debug + 'static,
+                   T: TrieElim<X> + TrieIntro<X> + 'static,
+                   F: 'static>
+    (trie: T,
+     pred: Rc<F>)
+     -> T
+    where F: Fn(&X) -> bool
+{
+    T::elim_arg(trie,
+                pred,
+                |bs, _| T::nil(bs),
+                |bs, x, pred| if pred(&x) { T::leaf(bs, x) } else { T::nil(bs) },
+                |bs, l, r, pred| T::bin(bs, trie_retain(l, pred.clone()), trie_retain(r, pred)),
+                |meta, t, pred| T::root(meta, trie_retain(t, pred)),
+                |nm, t, pred| {
+                    let (art, _) = eager!(nm.clone() =>> trie_retain =>> <X, T, F>,
+                                          trie:t ;; pred:pred);
+                    T::name(nm, T::art(art))
+                })
+}
+
+/// Splits a trie in a single pass into the elements that satisfy
+/// `pred` and those that do not.  Both outputs keep the input's
+/// skeleton, and the work is memoized at each name in the input.
+pub fn trie_partition<X: Hash + Clone + Debug + 'static,
+                      T: TrieElim<X> + TrieIntro<X> + 'static,
+                      F: 'static>
+    (trie: T,
+     pred: Rc<F>)
+     -> (T, T)
+    where F: Fn(&X) -> bool
+{
+    T::elim_arg(trie,
+                pred,
+                |bs, _| (T::nil(bs), T::nil(bs)),
+                |bs, x, pred| if pred(&x) {
+                    (T::leaf(bs, x), T::nil(bs))
+                } else {
+                    (T::nil(bs), T::leaf(bs, x))
+                },
+                |bs, l, r, pred| {
+                    let (l_yes, l_no) = trie_partition(l, pred.clone());
+                    let (r_yes, r_no) = trie_partition(r, pred);
+                    (T::bin(bs, l_yes, r_yes), T::bin(bs, l_no, r_no))
+                },
+                |meta, t, pred| {
+                    let (yes, no) = trie_partition(t, pred);
+                    (T::root(meta.clone(), yes), T::root(meta, no))
+                },
+                |nm, t, pred| {
+                    let (nm1, nm2, nm3) = name_fork3(nm.clone());
+                    let (yes, no) = memo!(nm1 =>> trie_partition, trie:t ;; pred:pred);
+                    (T::name(nm.clone(), T::art(cell(nm2, yes))),
+                     T::name(nm, T::art(cell(nm3, no))))
+                })
+}
+
+/// Returns an element satisfying `pred`, if any, visiting subtries in
+/// left-to-right order and stopping at the first match.  The verdict
+/// for each subtrie is memoized at its name.  Since `pred` is not
+/// compared with the predicate of an earlier verdict, callers must use
+/// a separate namespace for each predicate.
+pub fn trie_find_first<X: Hash + Eq + Clone + Debug + 'static,
+                       T: TrieElim<X> + 'static,
+                       F: 'static>
+    (trie: T,
+     pred: Rc<F>)
+     -> Option<X>
+    where F: Fn(&X) -> bool
+{
+    T::elim_arg(trie,
+                pred,
+                |_, _| None,
+                |_, x, pred| if pred(&x) { Some(x) } else { None },
+                |_, l, r, pred| match trie_find_first(l, pred.clone()) {
+                    None => trie_find_first(r, pred),
+                    found => found,
+                },
+                |_, t, pred| trie_find_first(t, pred),
+                |nm, t, pred| memo!(nm =>> trie_find_first, trie:t ;; pred:pred))
+}
+
+/// Tests whether some element satisfies `pred`; see `trie_find_first`.
+pub fn trie_any<X: Hash + Eq + Clone + Debug + 'static,
+                T: TrieElim<X> + 'static,
+                F: 'static>
+    (trie: T,
+     pred: Rc<F>)
+     -> bool
+    where F: Fn(&X) -> bool
+{
+    trie_find_first(trie, pred).is_some()
+}
+
+/// Tests whether every element satisfies `pred`; see `trie_find_first`.
+/// Searches for a counterexample in a namespace of its own, apart from
+/// the verdicts of `trie_any` and `trie_find_first`.
+pub fn trie_all<X: Hash + Eq + Clone + Debug + 'static,
+                T: TrieElim<X> + 'static,
+                F: 'static>
+    (trie: T,
+     pred: Rc<F>)
+     -> bool
+    where F: Fn(&X) -> bool
+{
+    ns(name_of_str("trie_all"),
+       || trie_find_first(trie, Rc::new(move |x: &X| !pred(x))).is_none())
+}
@@ -0,0 +1,128 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+struct Noop;
+
+impl Wake for Noop {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Polls the future once.
+fn poll_once<F: Future + Unpin>(fut: &mut F) -> Poll<F::Output> {
+    let waker = Waker::from(Arc::new(Noop));
+    Pin::new(fut).poll(&mut Context::from_waker(&waker))
+}
+
+/// An input, such as from the network, that is pending until `put`.
+#[derive(Clone)]
+struct Input(Rc<Cell<Option<usize>>>);
+
+impl Input {
+    fn new() -> Input { Input(Rc::new(Cell::new(None))) }
+    fn put(&self, x: usize) { self.0.set(Some(x)) }
+}
+
+thread_local!(static STARTS: Cell<usize> = Cell::new(0));
+
+/// The body of an async thunk: Forces `c`, awaits `input`, and then
+/// adds the two.
+struct Body { c: Art<usize>, input: Input, seen: Option<usize> }
+
+impl Future for Body {
+    type Output = usize;
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context) -> Poll<usize> {
+        if self.seen.is_none() {
+            STARTS.with(|s| s.set(s.get() + 1));
+            let x = force(&self.c);
+            self.seen = Some(x);
+        }
+        match self.input.0.get() {
+            None => Poll::Pending,
+            Some(y) => Poll::Ready(self.seen.unwrap() + y),
+        }
+    }
+}
+
+fn fetch(c: Art<usize>, input: &Input) -> Art<usize> {
+    let input = input.clone();
+    thunk_async(ArtIdChoice::Nominal(name_of_str("fetch")), prog_pt!("fetch"),
+                Rc::new(Box::new(move |c| Body { c: c, input: input.clone(), seen: None })),
+                c)
+}
+
+fn starts() -> usize {
+    STARTS.with(|s| s.get())
+}
+
+#[test]
+fn test_force_async_suspends_and_resumes() {
+    init_dcg();
+    STARTS.with(|s| s.set(0));
+    let input = Input::new();
+    let c = cell(name_of_str("c"), 1usize);
+    let t = fetch(c.clone(), &input);
+    let d = thunk!(name_of_str("d") =>> double, a:t);
+    let mut fut = Box::pin(force_async(&d));
+    assert_eq!(poll_once(&mut fut), Poll::Pending);
+    assert_eq!(poll_once(&mut fut), Poll::Pending);
+    input.put(10);
+    assert_eq!(poll_once(&mut fut), Poll::Ready(22));
+    // The body resumed after its await point, rather than restarting.
+    assert_eq!(starts(), 1);
+    assert_eq!(dirty_nodes(), vec![]);
+    // The dependency on `c`, from before the await point, persists.
+    set(&c, 2);
+    assert_eq!(poll_once(&mut Box::pin(force_async(&d))), Poll::Ready(24));
+    assert_eq!(starts(), 2);
+    assert_eq!(force(&d), 24);
+}
+
+#[test]
+fn test_changed_dependency_restarts_body() {
+    init_dcg();
+    STARTS.with(|s| s.set(0));
+    let input = Input::new();
+    let c = cell(name_of_str("c"), 1usize);
+    let t = fetch(c.clone(), &input);
+    let mut fut = Box::pin(force_async(&t));
+    assert_eq!(poll_once(&mut fut), Poll::Pending);
+    set(&c, 5);
+    input.put(10);
+    assert_eq!(poll_once(&mut fut), Poll::Ready(15));
+    assert_eq!(starts(), 2);
+}
+
+/// A future that is pending once, and wakes itself.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = usize;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<usize> {
+        if self.0 { Poll::Ready(7) } else { self.0 = true; cx.waker().wake_by_ref(); Poll::Pending }
+    }
+}
+
+#[test]
+fn test_force_blocks() {
+    init_dcg();
+    let t = thunk_async(ArtIdChoice::Nominal(name_of_str("y")), prog_pt!("y"),
+                        Rc::new(Box::new(|()| YieldOnce(false))), ());
+    assert_eq!(force(&t), 7);
+    init_naive();
+    let t = thunk_async(ArtIdChoice::Nominal(name_of_str("y")), prog_pt!("y"),
+                        Rc::new(Box::new(|()| YieldOnce(false))), ());
+    assert_eq!(force(&t), 7);
+}
@@ -0,0 +1,47 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::collections::trie::Set;
+use adapton::engine::*;
+
+fn bloom_of(xs: &[usize]) -> BloomSet<usize> {
+    let s = BloomSet::empty(name_of_str("bloom"), 1024, 3, 4);
+    xs.iter().fold(s, |s, x| BloomSet::add(name_of_usize(*x), s, x))
+}
+
+#[test]
+fn test_add_contains() {
+    let empty = BloomSet::<usize>::empty(name_of_str("bloom"), 1024, 3, 4);
+    assert!(!BloomSet::maybe_contains(&empty, &7));
+    let xs: Vec<usize> = (0..50).map(|x| x * 7).collect();
+    let s = bloom_of(&xs);
+    for x in xs.iter() {
+        assert!(BloomSet::maybe_contains(&s, x));
+    }
+    // With few of the bits set, false positives are rare.
+    let false_pos = (1000..1100).filter(|x| BloomSet::maybe_contains(&s, x)).count();
+    assert!(false_pos < 10);
+}
+
+#[test]
+fn test_of_set() {
+    let xs: Vec<usize> = (0..50).map(|x| x * 7).collect();
+    let set: Set<usize> = xs.iter().fold(SetIntro::empty(), |s, &x| SetIntro::add(s, x));
+    let b = BloomSet::of_set(name_of_str("bloom"), set, 1024, 3, 4);
+    for x in xs.iter() {
+        assert!(BloomSet::maybe_contains(&b, x));
+    }
+    assert_eq!(b, bloom_of(&xs));
+}
+
+#[test]
+fn test_bloom_dcg() {
+    manage::init_dcg();
+    let xs: Vec<usize> = (0..50).collect();
+    let set: Set<usize> = xs.iter().fold(SetIntro::empty(), |s, &x| SetIntro::add(s, x));
+    let b = BloomSet::of_set(name_of_str("bloom"), set.clone(), 256, 2, 1);
+    assert!(xs.iter().all(|x| BloomSet::maybe_contains(&b, x)));
+    let set = SetIntro::add(set, 100);
+    let b = BloomSet::of_set(name_of_str("bloom"), set, 256, 2, 1);
+    assert!(BloomSet::maybe_contains(&b, &100));
+}
@@ -0,0 +1,81 @@
+#[macro_use]
+extern crate adapton;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+
+fn square(x: usize) -> usize {
+    x * x
+}
+
+fn square_of(a: Art<usize>) -> usize {
+    let x = force(&a);
+    x * x
+}
+
+fn sum_of_squares(a: Art<usize>, b: Art<usize>) -> usize {
+    let sa = thunk!(name_of_str("sa") =>> square_of, a:a);
+    let sb = thunk!(name_of_str("sb") =>> square_of, a:b);
+    force(&sa) + force(&sb)
+}
+
+/// Forces each thunk, returning the number of evaluations and evictions.
+fn force_all(ts: &[&Art<usize>]) -> (usize, usize) {
+    reset_stats();
+    for t in ts {
+        let _ = force(t);
+    }
+    let s = stats();
+    (s.eval, s.evict)
+}
+
+#[test]
+fn test_lru_eviction() {
+    init_dcg();
+    bound_cached_results(prog_pt!("square"), Some(2));
+    let t1 = thunk!(square, x:1usize);
+    let t2 = thunk!(square, x:2usize);
+    let t3 = thunk!(square, x:3usize);
+    assert_eq!(force_all(&[&t1, &t2, &t3]), (3, 1));
+    // t1 was evicted; forcing it evicts t3, since t2 was used since.
+    assert_eq!(force_all(&[&t2, &t1]), (1, 1));
+    assert_eq!(force_all(&[&t1, &t2]), (0, 0));
+    assert_eq!(force_all(&[&t3]), (1, 1));
+    // Unbounded, the table evicts nothing more.
+    bound_cached_results(prog_pt!("square"), None);
+    assert_eq!(force_all(&[&t1, &t2, &t3]), (1, 0));
+    assert_eq!(force_all(&[&t1, &t2, &t3]), (0, 0));
+}
+
+#[test]
+fn test_second_chance_eviction() {
+    init_dcg();
+    assert_eq!(set_eviction(Eviction::SecondChance), Eviction::Lru);
+    bound_cached_results(prog_pt!("square"), Some(2));
+    let t1 = thunk!(square, x:1usize);
+    let t2 = thunk!(square, x:2usize);
+    let t3 = thunk!(square, x:3usize);
+    assert_eq!(force_all(&[&t1, &t2, &t1]), (2, 0));
+    // t1 was used, so it gets a second chance, and t2 is evicted.
+    assert_eq!(force_all(&[&t3]), (1, 1));
+    assert_eq!(force_all(&[&t1, &t3]), (0, 0));
+    assert_eq!(force_all(&[&t2]), (1, 1));
+}
+
+#[test]
+fn test_eviction_keeps_dependencies() {
+    init_dcg();
+    bound_cached_results(prog_pt!("square_of"), Some(0));
+    let a = cell(name_of_str("a"), 3usize);
+    let b = cell(name_of_str("b"), 4usize);
+    let (t, v) = eager!(name_of_str("sum") =>> sum_of_squares, a:a.clone(), b:b.clone());
+    assert_eq!(v, 25);
+    assert_eq!(stats().evict, 2);
+    // Nothing changed, so nothing re-evaluates.
+    assert_eq!(force_all(&[&t]), (0, 0));
+    // Changes still propagate through the evicted thunks.
+    set(&a, 6);
+    assert_eq!(force(&t), 52);
+}
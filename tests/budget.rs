@@ -0,0 +1,50 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::time::Duration;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+#[test]
+fn test_propagate_with_node_budget() {
+    init_dcg();
+    let (c, t) = chain(5);
+    assert_eq!(force(&t), 32);
+    assert_eq!(propagate_with_budget(Budget::Nodes(0)), PropagationStatus::Done);
+    set(&c, 2);
+    assert_eq!(dirty_nodes().len(), 5);
+    reset_stats();
+    // Cleaning proceeds from the changed cell outward.
+    assert_eq!(propagate_with_budget(Budget::Nodes(2)), PropagationStatus::Pending(3));
+    assert_eq!(stats().eval, 2);
+    let first = NameOrProgPt::Name { ns: name_unit(), name: name_of_usize(0) };
+    assert!(!dirty_nodes().contains(&first));
+    assert_eq!(propagate_with_budget(Budget::Nodes(10)), PropagationStatus::Done);
+    assert_eq!(stats().eval, 5);
+    assert_eq!(dirty_nodes(), vec![]);
+    reset_stats();
+    assert_eq!(force(&t), 64);
+    assert_eq!(stats().eval, 0);
+}
+
+#[test]
+fn test_propagate_with_time_budget() {
+    init_dcg();
+    let (c, t) = chain(5);
+    assert_eq!(force(&t), 32);
+    set(&c, 3);
+    assert_eq!(propagate_with_budget(Budget::Time(Duration::from_secs(0))),
+               PropagationStatus::Pending(5));
+    assert_eq!(propagate_with_budget(Budget::Time(Duration::from_secs(60))),
+               PropagationStatus::Done);
+    assert_eq!(force(&t), 96);
+}
+
+#[test]
+fn test_propagate_with_budget_naive() {
+    init_naive();
+    assert_eq!(propagate_with_budget(Budget::Nodes(1)), PropagationStatus::Done);
+}
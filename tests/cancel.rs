@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::cell::RefCell;
+use std::panic;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+thread_local!(static TOKEN: RefCell<Option<CancelToken>> = RefCell::new(None));
+thread_local!(static PANIC_AT: RefCell<Option<usize>> = RefCell::new(None));
+
+/// Doubles the value of `a`; along the way, cancels `TOKEN` upon
+/// reaching 16, and panics upon reaching `PANIC_AT`.
+fn double(a: Art<usize>) -> usize {
+    let x = force(&a);
+    if x == 16 {
+        TOKEN.with(|t| t.borrow().as_ref().map(|t| t.cancel()));
+    }
+    if PANIC_AT.with(|p| *p.borrow()) == Some(x) {
+        panic!("user panic");
+    }
+    x * 2
+}
+
+#[test]
+fn test_uncancelled() {
+    init_dcg();
+    let (_c, t) = chain_of(5, double);
+    assert_eq!(with_cancel(&CancelToken::new(), || force(&t)), Ok(32));
+}
+
+#[test]
+fn test_cancel_then_resume() {
+    init_dcg();
+    let (c, t) = chain_of(5, double);
+    assert_eq!(force(&t), 32);
+    set(&c, 2);
+    let token = CancelToken::new();
+    TOKEN.with(|t| *t.borrow_mut() = Some(token.clone()));
+    assert_eq!(with_cancel(&token, || force(&t)), Err(Cancelled));
+    assert!(token.is_cancelled());
+    TOKEN.with(|t| *t.borrow_mut() = None);
+    assert!(dirty_nodes().len() > 0);
+    // A newer edit, whose demand resumes the work.
+    set(&c, 3);
+    assert_eq!(force(&t), 96);
+    assert_eq!(dirty_nodes(), vec![]);
+    set(&c, 1);
+    assert_eq!(with_cancel(&CancelToken::new(), || force(&t)), Ok(32));
+}
+
+#[test]
+fn test_cancelled_token_aborts_fresh_thunks() {
+    init_dcg();
+    let (_c, t) = chain_of(5, double);
+    let token = CancelToken::new();
+    token.cancel();
+    assert_eq!(with_cancel(&token, || force(&t)), Err(Cancelled));
+    assert_eq!(force(&t), 32);
+}
+
+#[test]
+fn test_user_panic_unwinds() {
+    init_dcg();
+    let (c, t) = chain_of(5, double);
+    assert_eq!(force(&t), 32);
+    set(&c, 2);
+    PANIC_AT.with(|p| *p.borrow_mut() = Some(8));
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        with_cancel(&CancelToken::new(), || force(&t))
+    }));
+    assert!(res.is_err());
+    PANIC_AT.with(|p| *p.borrow_mut() = None);
+    assert_eq!(force(&t), 64);
+}
@@ -0,0 +1,82 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+use std::rc::Rc;
+
+fn elems(l: ChunkedList<usize>) -> Vec<usize> {
+    ChunkedList::fold(l, vec![], Rc::new(|x, mut xs: Vec<usize>| {
+        xs.push(x);
+        xs
+    }))
+}
+
+fn chunk_lens(l: &ChunkedList<usize>) -> Vec<usize> {
+    match *l {
+        ChunkedList::Nil => vec![],
+        ChunkedList::Chunk(ref xs, ref tl) => {
+            let mut lens = vec![xs.len()];
+            lens.extend(chunk_lens(tl));
+            lens
+        }
+        ChunkedList::Name(_, ref tl) => chunk_lens(tl),
+        ChunkedList::Art(ref a) => chunk_lens(&force(a)),
+    }
+}
+
+#[test]
+fn test_cons_fills_chunks() {
+    let len = 2 * CHUNK_LEN + 6;
+    let l = (0..len).rev().fold(ChunkedList::nil(), |l, i| ChunkedList::cons(i, l));
+    assert_eq!(chunk_lens(&l), vec![6, CHUNK_LEN, CHUNK_LEN]);
+    assert_eq!(elems(l), (0..len).collect::<Vec<_>>());
+}
+
+// The tail of a chunk shares its elements, and compares equal to a
+// chunk of the same elements.
+#[test]
+fn test_tail_of_chunk() {
+    let l = ChunkedList::chunk((0..5).collect(), ChunkedList::nil());
+    let (hd, tl) = ListElim::elim_arg(l,
+                                      (),
+                                      |_, _| panic!("empty"),
+                                      |hd, tl, _| (hd, tl),
+                                      |_, _, _| panic!("named"));
+    assert_eq!(hd, 0);
+    assert_eq!(chunk_lens(&tl), vec![4]);
+    assert_eq!(tl, ChunkedList::chunk((1..5).collect(), ChunkedList::nil()));
+}
+
+#[test]
+fn test_of_vec() {
+    let l = ChunkedList::of_vec((0..10).collect(), 4, name_of_str("l"));
+    assert_eq!(chunk_lens(&l), vec![4, 4, 2]);
+    assert_eq!(elems(l.clone()), (0..10).collect::<Vec<_>>());
+    let names: Vec<Name> = vec_of_list(l, None)
+        .into_iter()
+        .filter_map(|x| match x {
+            NameElse::Name(n) => Some(n),
+            NameElse::Else(_) => None,
+        })
+        .collect();
+    assert_eq!(names,
+               (0..3).map(|i| name_pair(name_of_str("l"), name_of_usize(i))).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_list_functions() {
+    let l = ChunkedList::of_vec((0..20).collect(), 8, name_of_str("l"));
+    let evens: List<usize> = list_filter_eager(l, Rc::new(|x: &usize| x % 2 == 0));
+    let evens: Vec<usize> = vec_of_list_elms(evens);
+    assert_eq!(evens, (0..10).map(|x| 2 * x).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_fold_dcg() {
+    manage::init_dcg();
+    let sum = |l| ChunkedList::fold(l, 0, Rc::new(|x, s| x + s));
+    let l = ChunkedList::of_vec((0..1000).collect(), 50, name_of_str("l"));
+    assert_eq!(sum(l), 499500);
+    let l = ChunkedList::of_vec((1..1001).collect(), 50, name_of_str("l"));
+    assert_eq!(sum(l), 500500);
+}
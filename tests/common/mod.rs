@@ -0,0 +1,36 @@
+//! Fixtures that the tests of the engine share.
+#![allow(dead_code)]
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+
+pub fn double(a: Art<usize>) -> usize {
+    force(&a) * 2
+}
+
+/// A chain of `n` thunks, each doubling the last, from a cell.
+pub fn chain(n: usize) -> (Art<usize>, Art<usize>) {
+    chain_of(n, double)
+}
+
+/// A chain of `n` thunks, like `chain`, but doubling by `double`,
+/// e.g., to instrument the doubling.
+pub fn chain_of(n: usize, double: fn(Art<usize>) -> usize) -> (Art<usize>, Art<usize>) {
+    let c = cell(name_of_str("c"), 1usize);
+    let mut t = c.clone();
+    for i in 0..n {
+        t = thunk(ArtIdChoice::Nominal(name_of_usize(i)),
+                  prog_pt!("double"),
+                  Rc::new(Box::new(move |a, ()| double(a))),
+                  t,
+                  ());
+    }
+    (c, t)
+}
+
+/// A chain of `n` thunks, like `chain`, in the namespace `ns_name`;
+/// returns the last thunk.
+pub fn chain_in(ns_name: &'static str, n: usize) -> Art<usize> {
+    ns(name_of_str(ns_name), || chain(n).1)
+}
@@ -0,0 +1,46 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+
+fn sketch_of(xs: &[usize]) -> CountMinSketch {
+    let s = CountMinSketch::empty(name_of_str("cms"), 4, 64, 16);
+    xs.iter().enumerate().fold(s, |s, (i, x)| CountMinSketch::add(name_of_usize(i), s, x, 1))
+}
+
+#[test]
+fn test_estimate() {
+    let s = CountMinSketch::empty(name_of_str("cms"), 4, 64, 16);
+    assert_eq!(CountMinSketch::estimate(&s, &7), 0);
+    let xs: Vec<usize> = (0..100).map(|i| i % 10).collect();
+    let s = sketch_of(&xs);
+    for x in 0..10usize {
+        assert!(CountMinSketch::estimate(&s, &x) >= 10);
+    }
+    let s = CountMinSketch::add(name_of_str("more"), s, &"many", 1000);
+    assert!(CountMinSketch::estimate(&s, &"many") >= 1000);
+    assert!(CountMinSketch::estimate(&s, &"few") <= 1100);
+}
+
+#[test]
+fn test_exact_when_sparse() {
+    // With few distinct values, in a wide sketch, some row is free of
+    // collisions for each value.
+    let s = sketch_of(&[1, 2, 2, 3, 3, 3]);
+    assert_eq!(CountMinSketch::estimate(&s, &1usize), 1);
+    assert_eq!(CountMinSketch::estimate(&s, &2usize), 2);
+    assert_eq!(CountMinSketch::estimate(&s, &3usize), 3);
+}
+
+#[test]
+fn test_estimate_dcg() {
+    manage::init_dcg();
+    let xs: Vec<usize> = (0..100).map(|i| i % 10).collect();
+    let s = sketch_of(&xs);
+    let estimate = |s: &CountMinSketch| {
+        ns(name_of_str("estimate"), || CountMinSketch::estimate(s, &3usize))
+    };
+    assert!(estimate(&s) >= 10);
+    let s = CountMinSketch::add(name_of_str("more"), s, &3usize, 5);
+    assert!(estimate(&s) >= 15);
+}
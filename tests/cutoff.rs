@@ -0,0 +1,48 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+#[test]
+fn test_set_cell_if_changed() {
+    init_dcg();
+    assert!(set_cell_if_changed(name_of_str("a"), 3usize));
+    let a = cell(name_of_str("a"), 3usize);
+    let (t, v) = eager!(name_of_str("double") =>> double, a:a.clone());
+    assert_eq!(v, 6);
+    reset_stats();
+    assert!(!set_cell_if_changed(name_of_str("a"), 3usize));
+    assert_eq!(stats().dirty, 0);
+    assert_eq!(force(&t), 6);
+    assert_eq!(stats().eval, 0);
+    assert!(set_cell_if_changed(name_of_str("a"), 4usize));
+    assert_eq!(force(&t), 8);
+    assert_eq!(stats().eval, 1);
+}
+
+#[test]
+fn test_set_always() {
+    init_dcg();
+    let a = cell(name_of_str("a"), 3usize);
+    let (t, _) = eager!(name_of_str("double") =>> double, a:a.clone());
+    reset_stats();
+    set(&a, 3);
+    assert_eq!(force(&t), 6);
+    assert_eq!(stats().eval, 0);
+    set_always(&a, 3);
+    assert_eq!(force(&t), 6);
+    assert_eq!(stats().eval, 1);
+}
+
+#[test]
+fn test_set_cell_if_changed_naive() {
+    init_naive();
+    assert!(set_cell_if_changed(name_of_str("a"), 3usize));
+    assert!(set_cell_if_changed(name_of_str("a"), 3usize));
+}
@@ -0,0 +1,39 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use adapton::engine::reflect::dcg_to_dot;
+use common::*;
+
+#[test]
+fn test_dcg_to_dot_naive() {
+    init_naive();
+    assert_eq!(dcg_to_dot(), "digraph dcg {\n}\n");
+}
+
+#[test]
+fn test_dcg_to_dot() {
+    init_dcg();
+    let c = cell(name_of_str("input"), 3);
+    let (_, v) = eager!(name_of_str("double") =>> double, a:c.clone());
+    assert_eq!(v, 6);
+    let dot = dcg_to_dot();
+    assert!(dot.starts_with("digraph dcg {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("shape=box"));
+    assert!(dot.contains("shape=ellipse"));
+    assert!(dot.contains("style=solid"));
+    assert!(dot.contains("double"));
+    assert!(!dot.contains("color=red"));
+    // Changing the input dirties the force edge, and the thunk.
+    set(&c, 4);
+    let dot = dcg_to_dot();
+    assert!(dot.contains("[style=solid,color=red]"));
+    assert!(dot.contains(",color=red];"));
+    assert_eq!(dot, dcg_to_dot());
+}
@@ -0,0 +1,47 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+fn plus(a: Art<usize>, b: Art<usize>) -> usize {
+    force(&a) + force(&b)
+}
+
+fn nominal(ns: &'static str, name: &'static str) -> NameOrProgPt {
+    NameOrProgPt::Name { ns: name_of_str(ns), name: name_of_str(name) }
+}
+
+#[test]
+fn test_dirty_nodes() {
+    init_dcg();
+    let a = cell(name_of_str("a"), 1usize);
+    let b = cell(name_of_str("b"), 2usize);
+    let (da, db, t) = ns(name_of_str("out"), || {
+        let (da, _) = eager!(name_of_str("da") =>> double, a:a.clone());
+        let (db, _) = eager!(name_of_str("db") =>> double, a:b.clone());
+        let (t, _) = eager!(plus, a:da.clone(), b:db.clone());
+        (da, db, t)
+    });
+    assert_eq!(dirty_nodes(), vec![]);
+    set(&a, 10);
+    let dirty = dirty_nodes();
+    assert_eq!(dirty.len(), 2);
+    assert!(dirty.contains(&nominal("out", "da")));
+    assert!(dirty.contains(&NameOrProgPt::ProgPt(prog_pt!("plus"))));
+    assert!(!dirty.contains(&nominal("out", "db")));
+    assert_eq!(force(&t), 24);
+    assert_eq!(dirty_nodes(), vec![]);
+    assert_eq!((force(&da), force(&db)), (20, 4));
+}
+
+#[test]
+fn test_dirty_nodes_naive() {
+    init_naive();
+    assert_eq!(dirty_nodes(), vec![]);
+}
@@ -0,0 +1,113 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::panic;
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+#[test]
+fn test_engines_are_independent() {
+    let e1 = EngineHandle::new_dcg();
+    let e2 = EngineHandle::new_dcg();
+    let c1 = e1.cell(name_of_str("input"), 3);
+    let c2 = e2.cell(name_of_str("input"), 30);
+    let t1 = e1.run(|_| eager!(name_of_str("double") =>> double, a:c1.clone()).0);
+    let t2 = e2.run(|_| eager!(name_of_str("double") =>> double, a:c2.clone()).0);
+    assert_eq!(e1.force(&t1), 6);
+    assert_eq!(e2.force(&t2), 60);
+    e1.set(&c1, 4);
+    assert_eq!(e1.force(&t1), 8);
+    assert_eq!(e2.force(&t2), 60);
+    assert_eq!(e1.stats().create, 2);
+    assert_eq!(e2.stats().eval, 1);
+}
+
+#[test]
+fn test_run_restores_engine() {
+    init_naive();
+    let e = EngineHandle::new_dcg();
+    assert!(!e.is_in_use());
+    e.run(|e| {
+        assert!(e.is_in_use());
+        assert!(engine_is_dcg());
+        // Re-entering the engine in use is allowed.
+        e.run(|_| assert!(engine_is_dcg()));
+    });
+    assert!(engine_is_naive());
+    let r = panic::catch_unwind(panic::AssertUnwindSafe(|| e.run(|_| panic!("in run"))));
+    assert!(r.is_err());
+    assert!(engine_is_naive());
+}
+
+fn cell_of_double(e: EngineHandle, a: Art<usize>) -> Art<usize> {
+    let x = e.force(&a) * 2;
+    e.cell(name_of_str("doubled"), x)
+}
+
+#[test]
+fn test_handle_within_thunk() {
+    let e = EngineHandle::new_dcg();
+    let c = e.cell(name_of_str("input"), 5);
+    let out = e.run(|e| {
+        let e = e.clone();
+        force(&thunk(ArtIdChoice::Nominal(name_of_str("t")),
+                     prog_pt!("cell_of_double"),
+                     Rc::new(Box::new(|a, e| cell_of_double(e, a))),
+                     c.clone(),
+                     e))
+    });
+    assert_eq!(e.force(&out), 10);
+}
+
+fn run_other(e: EngineHandle, _a: Art<usize>) -> usize {
+    e.run(|_| 0)
+}
+
+#[test]
+#[should_panic(expected = "cannot switch engines")]
+fn test_run_other_within_thunk() {
+    let e = EngineHandle::new_dcg();
+    let other = EngineHandle::new_dcg();
+    let c = e.cell(name_of_str("input"), 5);
+    e.run(|_| {
+        force(&thunk(ArtIdChoice::Nominal(name_of_str("t")),
+                     prog_pt!("run_other"),
+                     Rc::new(Box::new(|a, e| run_other(e, a))),
+                     c.clone(),
+                     other.clone()))
+    });
+}
+
+fn sum_of_doubles(xs: &[usize]) -> usize {
+    let cs: Vec<Art<usize>> = xs.iter().enumerate()
+        .map(|(i, x)| cell(name_of_usize(i), *x)).collect();
+    let ds: Vec<usize> = cs.into_iter().enumerate()
+        .map(|(i, c)| ns(name_of_usize(i), || eager!(name_of_str("double") =>> double, a:c).1))
+        .collect();
+    ds.iter().sum()
+}
+
+#[test]
+fn test_with_engine() {
+    init_dcg();
+    let c = cell(name_of_str("input"), 3usize);
+    let xs = vec![1, 2, 3, 4];
+    let naive = with_engine(EngineKind::Naive, || {
+        assert!(engine_is_naive());
+        sum_of_doubles(&xs)
+    });
+    let dcg = with_engine(EngineKind::DCG, || {
+        assert_eq!(stats().create, 0);
+        sum_of_doubles(&xs)
+    });
+    assert_eq!(naive, dcg);
+    // The engine in use before is restored, with its state.
+    assert!(engine_is_dcg());
+    assert_eq!(force(&c), 3);
+    assert_eq!(stats().create, 1);
+}
@@ -0,0 +1,80 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::collections::trie::*;
+use adapton::engine::*;
+use std::rc::Rc;
+
+/// The list `0, 1, ..., len - 1`, with the name `i` before every
+/// element `i` divisible by `stride`.
+fn named_list(len: usize, stride: usize) -> List<usize> {
+    (0..len).rev().fold(List::nil(), |l, i| {
+        let l = List::cons(i, l);
+        if i % stride == 0 {
+            List::name_art(Some(name_of_usize(i)), l)
+        } else {
+            l
+        }
+    })
+}
+
+/// The elements and names of any foldable collection, in sequence.
+fn seq<C: Foldable<usize>>(c: C, dir: Dir2) -> Vec<NameElse<usize>> {
+    ns(name_of_str("seq"), || {
+        C::fold_seq(c, dir, vec![],
+                    Rc::new(|x, mut v: Vec<NameElse<usize>>| { v.push(NameElse::Else(x)); v }),
+                    Rc::new(|n, mut v: Vec<NameElse<usize>>| { v.push(NameElse::Name(n)); v }))
+    })
+}
+
+fn sum<C: Foldable<usize>>(c: C) -> usize {
+    ns(name_of_str("sum"), || C::fold_elms(c, Dir2::Left, 0, Rc::new(|x, s| x + s)))
+}
+
+fn reversed<X: Clone>(v: &[X]) -> Vec<X> {
+    v.iter().rev().cloned().collect()
+}
+
+#[test]
+fn test_list() {
+    let l = named_list(10, 4);
+    let left = seq(l.clone(), Dir2::Left);
+    assert_eq!(left, vec_of_list(l.clone(), None));
+    assert_eq!(seq(l.clone(), Dir2::Right), reversed(&left));
+    assert_eq!(sum(l), 45);
+}
+
+#[test]
+fn test_tree() {
+    let t: Tree<usize> = tree_of_list(Dir2::Left, named_list(50, 10));
+    let left = seq(t.clone(), Dir2::Left);
+    let elms: Vec<NameElse<usize>> = (0..50).map(NameElse::Else).collect();
+    assert_eq!(left.iter().filter(|x| match **x { NameElse::Else(_) => true, _ => false })
+                   .cloned().collect::<Vec<_>>(),
+               elms);
+    assert_eq!(seq(t.clone(), Dir2::Right), reversed(&left));
+    assert_eq!(sum(t), 1225);
+}
+
+#[test]
+fn test_trie() {
+    let s: Trie<usize> = (0..20).fold(TrieIntro::empty(Meta { min_depth: 1 }),
+                                      |s, i| TrieIntro::extend(name_of_usize(i), s, i));
+    let left = seq(s.clone(), Dir2::Left);
+    assert_eq!(left.iter().filter(|x| match **x { NameElse::Else(_) => true, _ => false }).count(),
+               20);
+    assert_eq!(seq(s.clone(), Dir2::Right), reversed(&left));
+    assert_eq!(sum(s), 190);
+}
+
+#[test]
+fn test_foldable_dcg() {
+    manage::init_dcg();
+    // The folds of the list and the tree memoize at the same names,
+    // and so occur in distinct namespaces.
+    let l = named_list(100, 10);
+    assert_eq!(ns(name_of_str("list"), || sum(l.clone())), 4950);
+    let t: Tree<usize> = ns(name_of_str("tree_of_list"), || tree_of_list(Dir2::Left, l.clone()));
+    assert_eq!(ns(name_of_str("tree"), || sum(t)), 4950);
+    assert_eq!(ns(name_of_str("list"), || sum(List::cons(100, l))), 5050);
+}
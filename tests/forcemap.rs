@@ -0,0 +1,75 @@
+#[macro_use]
+extern crate adapton;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+
+fn first(p: Art<(usize, usize)>) -> usize {
+    force_map(&p, |p| p.0) + 1
+}
+
+fn both(p: Art<(usize, usize)>) -> usize {
+    force_map(&p, |p| p.0) + force_map(&p, |p| p.1) * 10
+}
+
+fn pair_of(a: Art<usize>) -> (usize, usize) {
+    let a = force(&a);
+    (a / 10, a % 10)
+}
+
+fn tens(a: Art<usize>) -> usize {
+    let p = thunk!(name_of_str("pair") =>> pair_of, a:a);
+    force_map(&p, |p| p.0)
+}
+
+#[test]
+fn test_force_map_cell() {
+    init_dcg();
+    let p = cell(name_of_str("p"), (1usize, 2usize));
+    let (t, v) = eager!(name_of_str("first") =>> first, p:p.clone());
+    assert_eq!(v, 2);
+    // A change to the other field does not re-evaluate `first`.
+    set(&p, (1, 3));
+    reset_stats();
+    assert_eq!(force(&t), 2);
+    assert_eq!(stats().eval, 0);
+    set(&p, (4, 3));
+    assert_eq!(force(&t), 5);
+    assert_eq!(stats().eval, 1);
+}
+
+#[test]
+fn test_force_map_projections() {
+    init_dcg();
+    let p = cell(name_of_str("p"), (1usize, 2usize));
+    let (t, v) = eager!(name_of_str("both") =>> both, p:p.clone());
+    assert_eq!(v, 21);
+    set(&p, (1, 3));
+    assert_eq!(force(&t), 31);
+    set(&p, (2, 3));
+    assert_eq!(force(&t), 32);
+}
+
+#[test]
+fn test_force_map_thunk() {
+    init_dcg();
+    let a = cell(name_of_str("a"), 42usize);
+    let (t, v) = eager!(name_of_str("tens") =>> tens, a:a.clone());
+    assert_eq!(v, 4);
+    set(&a, 47);
+    reset_stats();
+    assert_eq!(force(&t), 4);
+    // Only the pair re-evaluates.
+    assert_eq!(stats().eval, 1);
+    set(&a, 51);
+    assert_eq!(force(&t), 5);
+}
+
+#[test]
+fn test_force_map_naive() {
+    init_naive();
+    let p = cell(name_of_str("p"), (1usize, 2usize));
+    assert_eq!(force_map(&p, |p| p.1), 2);
+}
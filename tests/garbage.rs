@@ -0,0 +1,111 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+fn nodes() -> usize {
+    let total = memory_stats().total;
+    total.cells + total.thunks
+}
+
+#[test]
+fn test_collect_unreferenced() {
+    init_dcg();
+    {
+        let t = chain_in("a", 3);
+        assert_eq!(force(&t), 8);
+        assert_eq!(collect_garbage().collected, 0);
+        assert_eq!(nodes(), 4);
+    }
+    assert_eq!(collect_garbage().collected, 4);
+    assert_eq!(nodes(), 0);
+    // Re-allocating the collected nodes creates them afresh.
+    let t = chain_in("a", 3);
+    assert_eq!(force(&t), 8);
+}
+
+#[test]
+fn test_keep_referenced() {
+    init_dcg();
+    let c = cell(name_of_str("c"), 1usize);
+    let t = thunk!(name_of_str("t") =>> double, a:c.clone());
+    assert_eq!(force(&t), 2);
+    let _u = thunk!(name_of_str("u") =>> double, a:c.clone());
+    // A re-allocation shares the node's existing `Art`.
+    let t2 = thunk!(name_of_str("t") =>> double, a:c.clone());
+    drop(t);
+    assert_eq!(collect_garbage().collected, 0);
+    drop(t2);
+    assert_eq!(collect_garbage().collected, 1);
+    set(&c, 2);
+    let t = thunk!(name_of_str("t") =>> double, a:c.clone());
+    assert_eq!(force(&t), 4);
+}
+
+#[test]
+fn test_collect_observers_of_kept_nodes() {
+    init_dcg();
+    let c = cell(name_of_str("c"), 1usize);
+    {
+        let t = thunk!(name_of_str("t") =>> double, a:c.clone());
+        assert_eq!(force(&t), 2);
+    }
+    assert_eq!(collect_garbage().collected, 1);
+    set(&c, 2);
+    assert_eq!(dirty_nodes(), vec![]);
+    assert_eq!(force(&c), 2);
+}
+
+#[test]
+fn test_report_live_arts() {
+    init_dcg();
+    assert_eq!(set_track_arts(true), false);
+    let old = chain_in("v1", 4);
+    assert_eq!(force(&old), 16);
+    let new = chain_in("v2", 2);
+    assert_eq!(force(&new), 4);
+    let report = collect_garbage();
+    assert_eq!(report.collected, 0);
+    assert_eq!(report.live[0],
+               LiveArt { name: NameOrProgPt::Name { ns: name_of_str("v1"), name: name_of_usize(3) },
+                         handles: 1, retains: 5 });
+    drop(old);
+    let report = collect_garbage();
+    assert_eq!(report.collected, 5);
+    assert_eq!(report.live[0],
+               LiveArt { name: NameOrProgPt::Name { ns: name_of_str("v2"), name: name_of_usize(1) },
+                         handles: 1, retains: 3 });
+    assert!(set_track_arts(false));
+    assert_eq!(collect_garbage().live, vec![]);
+}
+
+// Observed nodes remain, without counting their observers as handles.
+#[test]
+fn test_keep_observed() {
+    init_dcg();
+    assert_eq!(set_track_arts(true), false);
+    let t = chain_in("a", 2);
+    assert_eq!(force(&t), 4);
+    assert!(ns(name_of_str("a"), || observe(&name_of_usize(1), |_: &usize| ())));
+    let name = NameOrProgPt::Name { ns: name_of_str("a"), name: name_of_usize(1) };
+    assert_eq!(collect_garbage().live[0],
+               LiveArt { name: name.clone(), handles: 1, retains: 3 });
+    drop(t);
+    let report = collect_garbage();
+    assert_eq!(report.collected, 0);
+    assert!(report.live.iter().all(|live| live.name != name));
+}
+
+#[test]
+fn test_naive() {
+    init_naive();
+    let t = chain_in("a", 3);
+    assert_eq!(force(&t), 8);
+    assert_eq!(collect_garbage(), GarbageReport::default());
+}
@@ -265,3 +265,838 @@ mod graph_conversion_dcg {
         assert!(!TrieElim::is_empty(&GraphElim::vertices(&adj_g)));
     }
 }
+
+mod graph_removal {
+    use super::*;
+
+    fn check_remove_edge<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let g = G::add_edge(G::empty(), name_of_usize(0), 0, 1);
+        let g = G::add_edge(g, name_of_usize(1), 0, 2);
+        let g = G::add_edge(g, name_of_usize(2), 1, 2);
+        let g = G::remove_edge(g, name_of_usize(3), 0, 2);
+        let es = G::edges(&g);
+        assert!(SetElim::is_mem(&es, &(0, 1)));
+        assert!(!SetElim::is_mem(&es, &(0, 2)));
+        assert!(SetElim::is_mem(&es, &(1, 2)));
+        let g = G::remove_edge(g, name_of_usize(4), 3, 4);
+        assert!(SetElim::is_mem(&G::edges(&g), &(0, 1)));
+        assert!(SetElim::is_mem(&G::vertices(&g), &2));
+    }
+
+    #[test]
+    fn test_remove_edge_graph() {
+        check_remove_edge::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_remove_edge_adj_graph() {
+        check_remove_edge::<AdjacencyGraph<usize>>();
+    }
+}
+
+mod graph_multiplicity {
+    use super::*;
+    use adapton::collections::MapElim;
+
+    fn check_multiplicity<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let g = G::add_edge(G::empty(), name_of_usize(0), 0, 1);
+        let g = G::add_edge(g, name_of_usize(1), 1, 2);
+        let g = G::add_edge(g, name_of_usize(2), 0, 1);
+        assert_eq!(G::edge_multiplicity(&g, &0, &1), 2);
+        assert_eq!(G::edge_multiplicity(&g, &1, &2), 1);
+        assert_eq!(G::edge_multiplicity(&g, &1, &0), 0);
+        let g = G::remove_edge(g, name_of_usize(3), 0, 1);
+        assert_eq!(G::edge_multiplicity(&g, &0, &1), 0);
+    }
+
+    #[test]
+    fn test_multiplicity_graph() {
+        check_multiplicity::<Graph<usize>>();
+        let g = GraphIntro::add_edge(GraphIntro::empty(), name_of_usize(0), 0, 1);
+        let g = GraphIntro::add_edge(g, name_of_usize(1), 0, 1);
+        let g: Graph<usize> = GraphIntro::add_edge(g, name_of_usize(2), 1, 0);
+        let counts = Graph::edge_multiset(&g);
+        assert_eq!(MapElim::find(&counts, &(0, 1)), Some(2));
+        assert_eq!(MapElim::find(&counts, &(1, 0)), Some(1));
+        assert_eq!(MapElim::find(&counts, &(1, 1)), None);
+    }
+
+    #[test]
+    fn test_multiplicity_adj_graph() {
+        check_multiplicity::<AdjacencyGraph<usize>>();
+    }
+}
+
+mod graph_counts {
+    use super::*;
+
+    fn check_counts<G: GraphIntro<usize> + GraphElim<usize>>() {
+        assert_eq!(G::edge_count(&G::empty()), 0);
+        assert_eq!(G::vertex_count(&G::empty()), 0);
+        let g = G::from_edges(vec![(0, 1), (1, 2), (2, 0), (2, 3), (0, 1)], 2);
+        assert_eq!(G::edge_count(&g), 5);
+        assert_eq!(G::vertex_count(&g), 4);
+        let g = G::add_edge(g, name_of_usize(10), 4, 1);
+        assert_eq!(G::edge_count(&g), 6);
+        assert_eq!(G::vertex_count(&g), 5);
+        // A target that becomes a source is counted once.
+        let g = G::add_edge(g, name_of_usize(11), 3, 4);
+        assert_eq!(G::edge_count(&g), 7);
+        assert_eq!(G::vertex_count(&g), 5);
+    }
+
+    #[test]
+    fn test_counts_graph() {
+        check_counts::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_counts_adj_graph() {
+        check_counts::<AdjacencyGraph<usize>>();
+    }
+
+    #[test]
+    fn test_counts_after_many_edges() {
+        let g = (0..1000).fold(Graph::<usize>::empty(), |g, i| {
+            GraphIntro::add_edge(g, name_of_usize(i), i, (i + 1) % 1000)
+        });
+        assert_eq!(GraphElim::edge_count(&g), 1000);
+        let edges: Vec<(usize, usize)> = GraphElim::edges_iter(&g).collect();
+        assert_eq!(edges.len(), 1000);
+        assert_eq!(edges[0], (999, 0));
+        assert_eq!(edges[999], (0, 1));
+    }
+}
+
+mod graph_edges_iter {
+    use super::*;
+
+    fn check_edges_iter<G: GraphIntro<usize> + GraphElim<usize>>() {
+        assert_eq!(G::edges_iter(&G::empty()).next(), None);
+        let g = G::from_edges(vec![(0, 1), (1, 2), (2, 0), (2, 3), (0, 1)], 2);
+        let mut edges: Vec<(usize, usize)> = G::edges_iter(&g).collect();
+        edges.sort();
+        assert_eq!(edges, vec![(0, 1), (0, 1), (1, 2), (2, 0), (2, 3)]);
+        let edges = GraphElim::edges(&g);
+        assert!(G::edges_iter(&g).all(|edge| SetElim::is_mem(&edges, &edge)));
+    }
+
+    #[test]
+    fn test_edges_iter_graph() {
+        check_edges_iter::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_edges_iter_adj_graph() {
+        check_edges_iter::<AdjacencyGraph<usize>>();
+    }
+}
+
+mod graph_string_nodes {
+    use super::*;
+
+    fn check_string_nodes<G: GraphIntro<String> + GraphElim<String>>() {
+        let g = G::from_edges(vec![("a".to_string(), "b".to_string()),
+                                   ("b".to_string(), "c".to_string())],
+                              1);
+        let edges = G::edges(&g);
+        assert!(SetElim::is_mem(&edges, &("a".to_string(), "b".to_string())));
+        assert!(SetElim::is_mem(&edges, &("b".to_string(), "c".to_string())));
+        assert_eq!(G::vertex_count(&g), 3);
+        assert!(SetElim::is_mem(&G::vertices(&g), &"c".to_string()));
+        let reversed = G::edges(&G::reverse_edges(&g));
+        assert!(SetElim::is_mem(&reversed, &("c".to_string(), "b".to_string())));
+    }
+
+    #[test]
+    fn test_string_nodes_graph() {
+        check_string_nodes::<Graph<String>>();
+    }
+
+    #[test]
+    fn test_string_nodes_adj_graph() {
+        check_string_nodes::<AdjacencyGraph<String>>();
+    }
+
+    #[test]
+    fn test_string_nodes_bi_adj_graph() {
+        check_string_nodes::<BiAdjacencyGraph<String>>();
+    }
+}
+
+mod bi_adjacency_graphs {
+    use super::*;
+    use std::rc::Rc;
+    use adapton::collections::{list_of_tree, vec_of_list_elms, List};
+
+    fn sorted_preds(g: &BiAdjacencyGraph<usize>, node: usize) -> Vec<usize> {
+        let preds = match BiAdjacencyGraph::predecessors(g, &node) {
+            None => return vec![],
+            Some(preds) => preds,
+        };
+        let preds: List<usize> = list_of_tree(preds, Dir2::Left);
+        let mut v: Vec<usize> = vec_of_list_elms(preds);
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn test_predecessors() {
+        let g: BiAdjacencyGraph<usize> = GraphIntro::from_edges(vec![(0, 2), (1, 2), (2, 3)], 1);
+        assert_eq!(sorted_preds(&g, 2), vec![0, 1]);
+        assert_eq!(sorted_preds(&g, 0), Vec::<usize>::new());
+        assert_eq!(BiAdjacencyGraph::in_degree(&g, &2), 2);
+        assert_eq!(BiAdjacencyGraph::out_degree(&g, &2), 1);
+        let g = GraphIntro::add_edge(g, name_of_usize(3), 3, 2);
+        assert_eq!(sorted_preds(&g, 2), vec![0, 1, 3]);
+        let g = GraphIntro::remove_edge(g, name_of_usize(4), 0, 2);
+        assert_eq!(sorted_preds(&g, 2), vec![1, 3]);
+        assert_eq!(BiAdjacencyGraph::in_degree(&g, &2), 2);
+        let rev = GraphElim::reverse_edges(&g);
+        assert!(SetElim::is_mem(&GraphElim::edges(&rev), &(2, 1)));
+        assert!(!SetElim::is_mem(&GraphElim::edges(&rev), &(1, 2)));
+        assert_eq!(sorted_preds(&rev, 2), vec![3]);
+    }
+
+    #[test]
+    fn test_filter_and_union() {
+        let g: BiAdjacencyGraph<usize> = GraphIntro::from_edges(vec![(0, 1), (1, 2)], 1);
+        let h: BiAdjacencyGraph<usize> = GraphIntro::from_edges(vec![(3, 2)], 1);
+        let u = GraphElim::union(&g, &h);
+        assert_eq!(sorted_preds(&u, 2), vec![1, 3]);
+        let f = GraphElim::filter_graph(&u,
+                                        Rc::new(|v: &usize| *v != 3),
+                                        Rc::new(|src: &usize, _: &usize| *src != 0));
+        assert_eq!(sorted_preds(&f, 2), vec![1]);
+        assert_eq!(sorted_preds(&f, 1), Vec::<usize>::new());
+        assert_eq!(GraphElim::edge_count(&f), 1);
+    }
+}
+
+mod graph_degrees {
+    use super::*;
+    use adapton::collections::MapElim;
+
+    #[test]
+    fn test_degrees() {
+        let g: AdjacencyGraph<usize> = GraphIntro::empty();
+        let g = GraphIntro::add_edge(g, name_of_usize(0), 0, 1);
+        let g = GraphIntro::add_edge(g, name_of_usize(1), 0, 2);
+        let g = GraphIntro::add_edge(g, name_of_usize(2), 1, 2);
+        let g = GraphIntro::add_edge(g, name_of_usize(3), 2, 0);
+        assert_eq!(out_degree(&g, &0), 2);
+        assert_eq!(out_degree(&g, &2), 1);
+        assert_eq!(out_degree(&g, &3), 0);
+        assert_eq!(in_degree(&g, &2), 2);
+        assert_eq!(in_degree(&g, &0), 1);
+        assert_eq!(in_degree(&g, &3), 0);
+        let degs = degrees(&g);
+        assert_eq!(MapElim::find(&degs, &0), Some(3));
+        assert_eq!(MapElim::find(&degs, &1), Some(2));
+        assert_eq!(MapElim::find(&degs, &2), Some(3));
+        let g = GraphIntro::remove_edge(g, name_of_usize(4), 1, 2);
+        assert_eq!(in_degree(&g, &2), 1);
+        assert_eq!(out_degree(&g, &1), 0);
+    }
+}
+
+mod graph_successors {
+    use super::*;
+    use adapton::collections::{list_of_tree, vec_of_list_elms, List};
+
+    fn sorted(list: List<usize>) -> Vec<usize> {
+        let mut v: Vec<usize> = vec_of_list_elms(list);
+        v.sort();
+        v
+    }
+
+    fn check_successors<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let g = G::add_edge(G::empty(), name_of_usize(0), 0, 1);
+        let g = G::add_edge(g, name_of_usize(1), 0, 2);
+        let g = G::add_edge(g, name_of_usize(2), 1, 2);
+        let succs = G::successors(&g, &0).unwrap();
+        assert_eq!(sorted(list_of_tree(succs, Dir2::Left)), vec![1, 2]);
+        assert_eq!(sorted(G::successors_list(&g, &1).unwrap()), vec![2]);
+        assert!(G::successors(&g, &2).is_none());
+    }
+
+    #[test]
+    fn test_successors_graph() {
+        check_successors::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_successors_adj_graph() {
+        check_successors::<AdjacencyGraph<usize>>();
+    }
+}
+
+mod graph_filter {
+    use super::*;
+    use adapton::collections::SetIntro;
+    use std::rc::Rc;
+
+    fn check_filter_graph<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let g = G::add_edge(G::empty(), name_of_usize(0), 0, 1);
+        let g = G::add_edge(g, name_of_usize(1), 0, 2);
+        let g = G::add_edge(g, name_of_usize(2), 1, 2);
+        let g = G::add_edge(g, name_of_usize(3), 2, 3);
+        let h = G::filter_graph(&g,
+                                Rc::new(|v: &usize| *v != 3),
+                                Rc::new(|src: &usize, dst: &usize| (*src, *dst) != (0, 1)));
+        let es = G::edges(&h);
+        assert!(!SetElim::is_mem(&es, &(0, 1)));
+        assert!(SetElim::is_mem(&es, &(0, 2)));
+        assert!(SetElim::is_mem(&es, &(1, 2)));
+        assert!(!SetElim::is_mem(&es, &(2, 3)));
+        assert!(!SetElim::is_mem(&G::vertices(&h), &3));
+    }
+
+    #[test]
+    fn test_induced_subgraph() {
+        let edges = vec![(0, 1), (1, 2), (2, 0), (2, 3)];
+        let g: AdjacencyGraph<usize> = GraphIntro::from_edges(edges, 1);
+        let vs = [0, 2, 3].iter().fold(SetIntro::empty(), |vs: Set<usize>, &v| {
+            SetIntro::add(vs, v)
+        });
+        let h = induced_subgraph(&g, &vs);
+        let es = GraphElim::edges(&h);
+        assert!(SetElim::is_mem(&es, &(2, 0)));
+        assert!(SetElim::is_mem(&es, &(2, 3)));
+        assert!(!SetElim::is_mem(&es, &(0, 1)));
+        assert!(!SetElim::is_mem(&es, &(1, 2)));
+        assert_eq!(in_degree(&h, &0), 1);
+        assert_eq!(in_degree(&h, &1), 0);
+    }
+
+    #[test]
+    fn test_filter_graph() {
+        check_filter_graph::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_filter_adj_graph() {
+        check_filter_graph::<AdjacencyGraph<usize>>();
+    }
+}
+
+mod graph_union {
+    use super::*;
+
+    fn check_union<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let g = G::add_edge(G::empty(), name_of_usize(0), 0, 1);
+        let g = G::add_edge(g, name_of_usize(1), 1, 2);
+        let h = G::add_edge(G::empty(), name_of_usize(2), 1, 3);
+        let h = G::add_edge(h, name_of_usize(3), 4, 0);
+        let u = G::union(&g, &h);
+        let es = G::edges(&u);
+        for e in &[(0, 1), (1, 2), (1, 3), (4, 0)] {
+            assert!(SetElim::is_mem(&es, e));
+        }
+        assert!(!SetElim::is_mem(&es, &(2, 1)));
+        assert!(SetElim::is_mem(&G::vertices(&u), &3));
+        let u = G::add_edge(u, name_of_usize(4), 3, 2);
+        assert!(SetElim::is_mem(&G::edges(&u), &(3, 2)));
+    }
+
+    #[test]
+    fn test_union_graph() {
+        check_union::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_union_adj_graph() {
+        check_union::<AdjacencyGraph<usize>>();
+        let g = AdjacencyGraph::add_edge(GraphIntro::empty(), name_of_usize(0), 0, 2);
+        let h = AdjacencyGraph::add_edge(GraphIntro::empty(), name_of_usize(1), 1, 2);
+        let u = AdjacencyGraph::union(&g, &h);
+        assert_eq!(in_degree(&u, &2), 2);
+    }
+}
+
+mod graph_set_ops {
+    use super::*;
+
+    fn check_intersect_difference<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let g = G::add_edge(G::empty(), name_of_usize(0), 0, 1);
+        let g = G::add_edge(g, name_of_usize(1), 1, 2);
+        let g = G::add_edge(g, name_of_usize(2), 2, 3);
+        let h = G::add_edge(g.clone(), name_of_usize(3), 3, 4);
+        let h = G::remove_edge(h, name_of_usize(4), 0, 1);
+        let both = G::edges(&G::intersect(&g, &h));
+        assert!(!SetElim::is_mem(&both, &(0, 1)));
+        assert!(SetElim::is_mem(&both, &(1, 2)));
+        assert!(SetElim::is_mem(&both, &(2, 3)));
+        assert!(!SetElim::is_mem(&both, &(3, 4)));
+        let only_g = G::edges(&G::difference(&g, &h));
+        assert!(SetElim::is_mem(&only_g, &(0, 1)));
+        assert!(!SetElim::is_mem(&only_g, &(1, 2)));
+        let only_h = G::edges(&G::difference(&h, &g));
+        assert!(SetElim::is_mem(&only_h, &(3, 4)));
+        assert!(!SetElim::is_mem(&only_h, &(2, 3)));
+    }
+
+    fn check_fingerprint<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let g = G::add_edge(G::empty(), name_of_usize(0), 0, 1);
+        let g = G::add_edge(g, name_of_usize(1), 1, 2);
+        let h = G::add_edge(G::empty(), name_of_usize(5), 1, 2);
+        let h = G::add_edge(h, name_of_usize(6), 0, 1);
+        let h = G::add_edge(h, name_of_usize(7), 0, 1);
+        assert_eq!(G::graph_fingerprint(&g), G::graph_fingerprint(&h));
+        let h = G::add_edge(h, name_of_usize(8), 2, 1);
+        assert!(G::graph_fingerprint(&g) != G::graph_fingerprint(&h));
+        assert!(G::graph_fingerprint(&G::empty()) != G::graph_fingerprint(&g));
+    }
+
+    #[test]
+    fn test_fingerprint_graph() {
+        check_fingerprint::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_fingerprint_adj_graph() {
+        check_fingerprint::<AdjacencyGraph<usize>>();
+    }
+
+    #[test]
+    fn test_intersect_difference_graph() {
+        check_intersect_difference::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_intersect_difference_adj_graph() {
+        check_intersect_difference::<AdjacencyGraph<usize>>();
+    }
+}
+
+mod graph_from_edges {
+    use super::*;
+
+    fn check_from_edges<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let edges = vec![(0, 1), (1, 2), (2, 0), (2, 3), (0, 1)];
+        for &stride in &[0, 1, 2, 10] {
+            let g = G::from_edges(edges.clone(), stride);
+            let es = G::edges(&g);
+            for e in &edges {
+                assert!(SetElim::is_mem(&es, e));
+            }
+            assert!(!SetElim::is_mem(&es, &(1, 0)));
+            assert_eq!(G::edge_multiplicity(&g, &0, &1), 2);
+        }
+        let g = G::add_edge(G::from_edges(edges, 2), name_of_str("extra"), 3, 1);
+        assert!(SetElim::is_mem(&G::edges(&g), &(3, 1)));
+    }
+
+    #[test]
+    fn test_from_edges_graph() {
+        check_from_edges::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_from_edges_adj_graph() {
+        check_from_edges::<AdjacencyGraph<usize>>();
+    }
+}
+
+mod graph_builder {
+    use super::*;
+
+    fn check_builder<G: GraphIntro<usize> + GraphElim<usize>>() {
+        let g: G = GraphBuilder::new(name_of_str("g"))
+            .edge(0, 1)
+            .edges(vec![(1, 2), (2, 0)])
+            .build();
+        let es = G::edges(&g);
+        for e in &[(0, 1), (1, 2), (2, 0)] {
+            assert!(SetElim::is_mem(&es, e));
+        }
+        assert_eq!(G::edge_count(&g), 3);
+    }
+
+    #[test]
+    fn test_builder_graph() {
+        check_builder::<Graph<usize>>();
+    }
+
+    #[test]
+    fn test_builder_adj_graph() {
+        check_builder::<AdjacencyGraph<usize>>();
+    }
+}
+
+mod graph_reader {
+    use super::*;
+
+    #[test]
+    fn test_from_edge_list_reader() {
+        let input = "# Directed graph\n0\t1\n1 2\n\n% comment\n2,0\n3 1 extra\n";
+        let g = Graph::from_edge_list_reader(input.as_bytes(), name_of_usize, 2).unwrap();
+        let es = GraphElim::edges(&g);
+        for e in &[(0, 1), (1, 2), (2, 0), (3, 1)] {
+            assert!(SetElim::is_mem(&es, e));
+        }
+        assert!(!SetElim::is_mem(&es, &(1, 0)));
+        assert!(Graph::from_edge_list_reader("0 x\n".as_bytes(), name_of_usize, 1).is_err());
+    }
+}
+
+mod graph_traversal {
+    use super::*;
+    use adapton::collections::{vec_of_list_elms, List};
+
+    fn graph_of_edges<G: GraphIntro<usize>>(edges: &[(usize, usize)]) -> G {
+        edges.iter().fold(G::empty(), |g, &(src, dst)| {
+            G::add_edge(g, name_pair(name_of_usize(src), name_of_usize(dst)), src, dst)
+        })
+    }
+
+    fn sorted_elems(set: Set<usize>) -> Vec<usize> {
+        let mut v: Vec<usize> = vec_of_list_elms(list_of_trieset::<_, _, List<_>>(set));
+        v.sort();
+        v
+    }
+
+    fn sorted_levels(levels: List<Set<usize>>) -> Vec<Vec<usize>> {
+        vec_of_list_elms(levels).into_iter().map(sorted_elems).collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let edges = [(0, 1), (1, 2), (0, 3), (3, 2), (2, 4), (5, 0)];
+        let expected = vec![vec![0], vec![1, 3], vec![2], vec![4]];
+        let g: Graph<usize> = graph_of_edges(&edges);
+        assert_eq!(sorted_levels(GraphElim::bfs(&g, 0)), expected);
+        let g: AdjacencyGraph<usize> = graph_of_edges(&edges);
+        assert_eq!(sorted_levels(GraphElim::bfs(&g, 0)), expected);
+        assert_eq!(sorted_levels(GraphElim::bfs(&g, 4)), vec![vec![4]]);
+    }
+
+    #[test]
+    fn test_dfs() {
+        use adapton::collections::MapElim;
+        let g: AdjacencyGraph<usize> = graph_of_edges(&[(0, 1), (1, 2), (2, 0), (0, 3), (4, 0)]);
+        let (pre, post) = dfs(&g, 0);
+        assert_eq!(MapElim::find(&pre, &0), Some(0));
+        assert_eq!(MapElim::find(&post, &0), Some(3));
+        assert_eq!(MapElim::find(&pre, &4), None);
+        // 2 is a descendant of 1: entered later, finished earlier.
+        assert!(MapElim::find(&pre, &1) < MapElim::find(&pre, &2));
+        assert!(MapElim::find(&post, &2) < MapElim::find(&post, &1));
+        for v in 0..4 {
+            assert!(MapElim::find(&pre, &v).is_some());
+            assert!(MapElim::find(&post, &v).unwrap() < 4);
+        }
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        use adapton::collections::MapElim;
+        let g: AdjacencyGraph<usize> = graph_of_edges(&[(0, 1), (1, 2), (0, 2), (2, 3), (4, 0)]);
+        let dist = shortest_paths(&g, 0);
+        assert_eq!(MapElim::find(&dist, &0), Some(0));
+        assert_eq!(MapElim::find(&dist, &1), Some(1));
+        assert_eq!(MapElim::find(&dist, &2), Some(1));
+        assert_eq!(MapElim::find(&dist, &3), Some(2));
+        assert_eq!(MapElim::find(&dist, &4), None);
+    }
+
+    #[test]
+    fn test_reachable() {
+        let g: AdjacencyGraph<usize> = graph_of_edges(&[(0, 1), (1, 2), (3, 0)]);
+        assert!(reachable(&g, &0, &2));
+        assert!(reachable(&g, &3, &2));
+        assert!(reachable(&g, &1, &1));
+        assert!(!reachable(&g, &2, &0));
+        assert!(!reachable(&g, &0, &3));
+        let g = GraphIntro::add_edge(g, name_pair(name_of_usize(2), name_of_usize(3)), 2, 3);
+        assert!(reachable(&g, &0, &3));
+        assert!(reachable(&g, &2, &1));
+    }
+
+    #[test]
+    fn test_betweenness() {
+        use adapton::collections::MapElim;
+        let g: AdjacencyGraph<usize> =
+            graph_of_edges(&[(0, 1), (1, 0), (1, 2), (2, 1), (2, 3), (3, 2), (1, 4)]);
+        let exact = betweenness(&g, 5, 7);
+        for &(v, score) in [(0, 0), (1, 7), (2, 5), (3, 0), (4, 0)].iter() {
+            assert_eq!(MapElim::find(&exact, &v), Some(score));
+        }
+        let sampled = betweenness(&g, 2, 7);
+        for v in 0..5 {
+            assert!(MapElim::find(&sampled, &v).is_some());
+        }
+        assert_eq!(sampled, betweenness(&g, 2, 7));
+    }
+
+    #[test]
+    fn test_connected_components() {
+        use adapton::collections::MapElim;
+        let g: Graph<usize> = graph_of_edges(&[(3, 1), (1, 2), (5, 4), (6, 6), (7, 5)]);
+        let cc = connected_components(&g);
+        for &(v, rep) in [(1, 1), (2, 1), (3, 1), (4, 4), (5, 4), (7, 4), (6, 6)].iter() {
+            assert_eq!(MapElim::find(&cc, &v), Some(rep));
+        }
+        assert_eq!(MapElim::find(&cc, &0), None);
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let g: AdjacencyGraph<usize> = graph_of_edges(&[(0, 1), (1, 2), (0, 2), (2, 3)]);
+        assert!(!has_cycle(&g));
+        assert_eq!(find_cycle(&g), None);
+        let g = GraphIntro::add_edge(g, name_pair(name_of_usize(3), name_of_usize(1)), 3, 1);
+        assert!(has_cycle(&g));
+        let mut cycle: Vec<_> = vec_of_list_elms(find_cycle(&g).unwrap());
+        // The cycle may start at any of its vertices.
+        while cycle[0] != 1 {
+            let v = cycle.remove(0);
+            cycle.push(v);
+        }
+        assert_eq!(cycle, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_is_bipartite() {
+        use adapton::collections::MapElim;
+        let g: AdjacencyGraph<usize> = graph_of_edges(&[(0, 1), (2, 1), (2, 3), (3, 0), (4, 5)]);
+        let colors = is_bipartite(&g).unwrap();
+        for &(src, dst) in &[(0, 1), (2, 1), (2, 3), (3, 0), (4, 5)] {
+            assert!(MapElim::find(&colors, &src) != MapElim::find(&colors, &dst));
+        }
+        let g = GraphIntro::add_edge(g, name_pair(name_of_usize(0), name_of_usize(2)), 0, 2);
+        let cycle: Vec<_> = vec_of_list_elms(is_bipartite(&g).unwrap_err());
+        assert_eq!(cycle.len() % 2, 1);
+        let adjacent = |a: usize, b: usize| {
+            [(0, 1), (2, 1), (2, 3), (3, 0), (0, 2)].iter().any(|&e| e == (a, b) || e == (b, a))
+        };
+        for i in 0..cycle.len() {
+            assert!(adjacent(cycle[i], cycle[(i + 1) % cycle.len()]));
+        }
+    }
+}
+
+mod graph_sampling {
+    use super::*;
+    use std::rc::Rc;
+    use adapton::collections::vec_of_list_elms;
+
+    fn graph_of_edges<G: GraphIntro<usize>>(edges: &[(usize, usize)]) -> G {
+        edges.iter().fold(G::empty(), |g, &(src, dst)| {
+            G::add_edge(g, name_pair(name_of_usize(src), name_of_usize(dst)), src, dst)
+        })
+    }
+
+    #[test]
+    fn test_sample_edges() {
+        let g: Graph<usize> = graph_of_edges(&[(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)]);
+        let edges = GraphElim::edges(&g);
+        let sample = GraphElim::sample_edges(&g, 2, 42);
+        assert_eq!(trie_fold(sample.clone(), 0, Rc::new(|_, n| n + 1)), 2);
+        assert!(trie_fold(sample.clone(), true, Rc::new(move |(edge, ()), all| {
+            all && SetElim::is_mem(&edges, &edge)
+        })));
+        assert_eq!(sample, GraphElim::sample_edges(&g, 2, 42));
+        let all = GraphElim::sample_edges(&g, 10, 42);
+        assert_eq!(trie_fold(all, 0, Rc::new(|_, n| n + 1)), 5);
+    }
+
+    #[test]
+    fn test_random_walk() {
+        let walk_of = |g: &AdjacencyGraph<usize>, len, seed| -> Vec<usize> {
+            vec_of_list_elms(random_walk(g, 0, len, seed))
+        };
+        let cycle: AdjacencyGraph<usize> = graph_of_edges(&[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(walk_of(&cycle, 5, 3), vec![0, 1, 2, 0, 1, 2]);
+        let g: AdjacencyGraph<usize> = graph_of_edges(&[(0, 1), (0, 2), (1, 0), (2, 0), (2, 3)]);
+        let walk = walk_of(&g, 20, 9);
+        assert_eq!(walk, walk_of(&g, 20, 9));
+        assert_eq!(walk[0], 0);
+        for step in walk.windows(2) {
+            assert!(GraphElim::edge_multiplicity(&g, &step[0], &step[1]) > 0);
+        }
+        assert!(walk.len() == 21 || walk.last() == Some(&3));
+    }
+}
+
+mod weighted_graphs {
+    use super::*;
+    use adapton::collections::MapElim;
+
+    #[test]
+    fn test_weighted_edges() {
+        let g: WeightedGraph<usize, u32> = WeightedGraphIntro::empty();
+        assert!(TrieElim::is_empty(&WeightedGraphElim::edges(&g)));
+        let g = WeightedGraphIntro::add_edge(g, name_of_usize(0), 0, 1, 10);
+        let g = WeightedGraphIntro::add_edge(g, name_of_usize(1), 0, 2, 20);
+        let g = WeightedGraphIntro::add_edge(g, name_of_usize(2), 2, 1, 5);
+        let es = WeightedGraphElim::edges(&g);
+        assert_eq!(MapElim::find(&es, &(0, 1)), Some(10));
+        assert_eq!(MapElim::find(&es, &(0, 2)), Some(20));
+        assert_eq!(MapElim::find(&es, &(2, 1)), Some(5));
+        assert_eq!(MapElim::find(&es, &(1, 0)), None);
+        let vs = WeightedGraphElim::vertices(&g);
+        assert!(SetElim::is_mem(&vs, &0) && SetElim::is_mem(&vs, &1) && SetElim::is_mem(&vs, &2));
+        let rev = WeightedGraphElim::edges(&WeightedGraphElim::reverse_edges(&g));
+        assert_eq!(MapElim::find(&rev, &(1, 0)), Some(10));
+        assert_eq!(MapElim::find(&rev, &(1, 2)), Some(5));
+        assert_eq!(MapElim::find(&rev, &(0, 1)), None);
+    }
+
+    #[test]
+    fn test_max_flow() {
+        let edges = [(0, 1, 16), (0, 2, 13), (1, 2, 10), (2, 1, 4), (1, 3, 12), (3, 2, 9),
+                     (2, 4, 14), (4, 3, 7), (3, 5, 20), (4, 5, 4)];
+        let mut g: WeightedGraph<usize, u64> = WeightedGraphIntro::empty();
+        for (i, &(src, dst, w)) in edges.iter().enumerate() {
+            g = WeightedGraphIntro::add_edge(g, name_of_usize(i), src, dst, w);
+        }
+        let (value, flow, cut) = max_flow(&g, 0, 5);
+        assert_eq!(value, 23);
+        let flow = flow.into_hashmap();
+        let mut net = [0i64; 6];
+        for &(src, dst, w) in edges.iter() {
+            let f = flow[&(src, dst)];
+            assert!(f <= w);
+            net[src] -= f as i64;
+            net[dst] += f as i64;
+        }
+        assert_eq!(net, [-23, 0, 0, 0, 0, 23]);
+        let mut cut: Vec<usize> = cut.into_hashmap().into_iter().map(|(v, ())| v).collect();
+        cut.sort();
+        assert_eq!(cut, vec![0, 1, 2, 4]);
+    }
+
+    // Antiparallel edges keep separate flows and residuals.
+    #[test]
+    fn test_max_flow_antiparallel() {
+        let mut g: WeightedGraph<usize, u64> = WeightedGraphIntro::empty();
+        for (i, &(src, dst, w)) in [(0, 1, 5), (1, 0, 3), (1, 2, 4)].iter().enumerate() {
+            g = WeightedGraphIntro::add_edge(g, name_of_usize(i), src, dst, w);
+        }
+        let (value, flow, _) = max_flow(&g, 0, 2);
+        assert_eq!(value, 4);
+        let flow = flow.into_hashmap();
+        assert!(flow[&(0, 1)] <= 5 && flow[&(1, 0)] <= 3);
+        assert_eq!(flow[&(0, 1)] - flow[&(1, 0)], 4);
+        assert_eq!(flow[&(1, 2)], 4);
+    }
+
+    // A self-loop carries no flow.
+    #[test]
+    fn test_max_flow_self_loop() {
+        let mut g: WeightedGraph<usize, u64> = WeightedGraphIntro::empty();
+        for (i, &(src, dst, w)) in [(0, 1, 5), (1, 1, 3), (1, 2, 4)].iter().enumerate() {
+            g = WeightedGraphIntro::add_edge(g, name_of_usize(i), src, dst, w);
+        }
+        let (value, flow, _) = max_flow(&g, 0, 2);
+        assert_eq!(value, 4);
+        let flow = flow.into_hashmap();
+        assert_eq!(flow[&(1, 1)], 0);
+        assert_eq!(flow[&(0, 1)], 4);
+    }
+
+    #[test]
+    fn test_astar() {
+        use adapton::collections::vec_of_list_elms;
+        // A 4x4 grid, with unit weights to the right and down, plus a
+        // heavy shortcut from the start to the goal.
+        let mut g: WeightedGraph<usize, u32> = WeightedGraphIntro::empty();
+        for v in 0..16 {
+            let nm = name_of_usize(v);
+            if v % 4 < 3 {
+                g = WeightedGraphIntro::add_edge(g, name_pair(nm.clone(), name_of_usize(v + 1)),
+                                                 v, v + 1, 1);
+            }
+            if v < 12 {
+                g = WeightedGraphIntro::add_edge(g, name_pair(nm, name_of_usize(v + 4)),
+                                                 v, v + 4, 1);
+            }
+        }
+        g = WeightedGraphIntro::add_edge(g, name_of_str("shortcut"), 0, 15, 10);
+        let manhattan = |v: &usize| ((3 - v % 4) + (3 - v / 4)) as u32;
+        let (path, cost) = astar(&g, 0, 15, manhattan).unwrap();
+        assert_eq!(cost, 6);
+        let path: Vec<usize> = vec_of_list_elms(path);
+        assert_eq!(path.len(), 7);
+        assert_eq!((path[0], path[6]), (0, 15));
+        assert_eq!(astar(&g, 0, 15, |_: &usize| 0).unwrap().1, 6);
+        assert!(astar(&g, 15, 0, |_: &usize| 0).is_none());
+    }
+
+    #[test]
+    fn test_mst() {
+        let edges = [(0, 1, 4), (1, 2, 1), (2, 0, 2), (2, 3, 7), (3, 4, 3), (1, 3, 5), (5, 6, 1)];
+        let mut g: WeightedGraph<usize, u32> = WeightedGraphIntro::empty();
+        for (i, &(src, dst, w)) in edges.iter().enumerate() {
+            g = WeightedGraphIntro::add_edge(g, name_of_usize(i), src, dst, w);
+        }
+        let forest = WeightedGraphElim::edges(&mst(&g));
+        let mut es: Vec<((usize, usize), u32)> = forest.into_hashmap().into_iter().collect();
+        es.sort();
+        assert_eq!(es, vec![((1, 2), 1), ((1, 3), 5), ((2, 0), 2), ((3, 4), 3), ((5, 6), 1)]);
+    }
+}
+
+mod labeled_graphs {
+    use super::*;
+
+    #[test]
+    fn test_attributes() {
+        let g: LabeledGraph<usize, &'static str, u32> = GraphIntro::empty();
+        let g = GraphIntro::add_edge(g, name_of_usize(0), 0, 1);
+        let g = GraphIntro::add_edge(g, name_of_usize(1), 1, 2);
+        let g = LabeledGraph::set_node_attr(g, 0, "source");
+        let g = LabeledGraph::set_node_attr(g, 2, "sink");
+        let g = LabeledGraph::set_node_attr(g, 2, "target");
+        let g = LabeledGraph::set_edge_attr(g, 0, 1, 7);
+        assert_eq!(LabeledGraph::get_node_attr(&g, &0), Some("source"));
+        assert_eq!(LabeledGraph::get_node_attr(&g, &1), None);
+        assert_eq!(LabeledGraph::get_node_attr(&g, &2), Some("target"));
+        assert_eq!(LabeledGraph::get_edge_attr(&g, &0, &1), Some(7));
+        assert_eq!(LabeledGraph::get_edge_attr(&g, &1, &2), None);
+        assert!(SetElim::is_mem(&GraphElim::edges(&g), &(1, 2)));
+        let rev = GraphElim::reverse_edges(&g);
+        assert!(SetElim::is_mem(&GraphElim::edges(&rev), &(2, 1)));
+        assert_eq!(LabeledGraph::get_edge_attr(&rev, &1, &0), Some(7));
+    }
+}
+
+mod graph_gen {
+    use super::*;
+    use adapton::collections::graph::gen::*;
+
+    fn edge_count(g: &AdjacencyGraph<usize>) -> usize {
+        GraphElim::edges(g).into_hashmap().len()
+    }
+
+    #[test]
+    fn test_grid() {
+        let g: AdjacencyGraph<usize> = grid(4, 3);
+        assert_eq!(edge_count(&g), 3 * 3 + 4 * 2);
+        let es = GraphElim::edges(&g);
+        assert!(SetElim::is_mem(&es, &(0, 1)) && SetElim::is_mem(&es, &(0, 4)));
+        assert!(!SetElim::is_mem(&es, &(3, 4)));
+    }
+
+    #[test]
+    fn test_erdos_renyi() {
+        let g: AdjacencyGraph<usize> = erdos_renyi(10, 0.0, 1);
+        assert_eq!(edge_count(&g), 0);
+        let g: AdjacencyGraph<usize> = erdos_renyi(10, 1.0, 1);
+        assert_eq!(edge_count(&g), 90);
+        let g1: AdjacencyGraph<usize> = erdos_renyi(20, 0.2, 7);
+        let g2: AdjacencyGraph<usize> = erdos_renyi(20, 0.2, 7);
+        let g3: AdjacencyGraph<usize> = erdos_renyi(20, 0.2, 8);
+        assert_eq!(GraphElim::graph_fingerprint(&g1), GraphElim::graph_fingerprint(&g2));
+        assert!(GraphElim::graph_fingerprint(&g1) != GraphElim::graph_fingerprint(&g3));
+    }
+
+    #[test]
+    fn test_barabasi_albert() {
+        let g: AdjacencyGraph<usize> = barabasi_albert(30, 3, 5);
+        assert_eq!(edge_count(&g), (30 - 3) * 3);
+        let h: AdjacencyGraph<usize> = barabasi_albert(30, 3, 5);
+        assert_eq!(GraphElim::graph_fingerprint(&g), GraphElim::graph_fingerprint(&h));
+    }
+}
@@ -0,0 +1,55 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+use std::rc::Rc;
+
+/// The `width` by `height` grid whose cell at column `x` of row `y`
+/// holds `y * width + x`.
+fn grid(width: usize, height: usize, chunk_len: usize) -> Grid<usize> {
+    let rows = (0..height).map(|y| (0..width).map(|x| y * width + x).collect()).collect();
+    Grid::of_rows(rows, chunk_len, name_of_str("grid"))
+}
+
+fn sum(g: &Grid<usize>, lo: (usize, usize), hi: (usize, usize)) -> usize {
+    Grid::region_monoid(g, lo, hi, 0, Rc::new(|x, y| x + y))
+}
+
+#[test]
+fn test_get_set() {
+    let g = grid(10, 5, 4);
+    assert_eq!((Grid::width(&g), Grid::height(&g)), (10, 5));
+    assert_eq!(Grid::get(&g, 9, 4), Some(49));
+    assert_eq!(Grid::get(&g, 10, 0), None);
+    assert_eq!(Grid::get(&g, 0, 5), None);
+    let g2 = Grid::set(name_of_str("set"), g.clone(), 5, 2, 0);
+    assert_eq!(Grid::get(&g2, 5, 2), Some(0));
+    assert_eq!(Grid::get(&g2, 4, 2), Some(24));
+    assert_eq!(Grid::get(&g, 5, 2), Some(25));
+}
+
+#[test]
+fn test_reductions() {
+    let g = grid(10, 5, 4);
+    assert_eq!(Grid::row_monoid(&g, 1, 0, Rc::new(|x, y| x + y)), (10..20).sum());
+    assert_eq!(sum(&g, (0, 0), (10, 5)), (0..50).sum());
+    let expected: usize = (1..4).flat_map(|y| (3..9).map(move |x| y * 10 + x)).sum();
+    assert_eq!(sum(&g, (3, 1), (9, 4)), expected);
+    assert_eq!(sum(&g, (3, 1), (3, 4)), 0);
+    assert_eq!(Grid::row_monoid(&g, 2, 0, Rc::new(|x, y| if x > y { x } else { y })), 29);
+}
+
+#[test]
+fn test_reductions_dcg() {
+    manage::init_dcg();
+    let g = grid(100, 10, 8);
+    let total: usize = (0..1000).sum();
+    assert_eq!(sum(&g, (0, 0), (100, 10)), total);
+    // Changes the chunk holding column 20 of row 3, in place.
+    let chunk_nm = name_pair(name_of_str("grid"),
+                             name_pair(name_of_usize(3), name_of_usize(20 / 8)));
+    let g = Grid::set(chunk_nm, g, 20, 3, 1000);
+    assert_eq!(Grid::get(&g, 20, 3), Some(1000));
+    assert_eq!(sum(&g, (0, 0), (100, 10)), total - 320 + 1000);
+    assert_eq!(sum(&g, (16, 3), (24, 4)), (316..324).sum::<usize>() - 320 + 1000);
+}
@@ -0,0 +1,61 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+
+fn drain(h: Heap<usize>) -> Vec<usize> {
+    let mut h = h;
+    let mut out = vec![];
+    while let Some(x) = Heap::find_min(&h) {
+        out.push(x);
+        h = Heap::delete_min(h);
+    }
+    out
+}
+
+/// Inserts `xs`, naming the heap after every fourth insertion, with
+/// names counted from `base`.
+fn heap_of_vec(xs: &[usize], base: usize) -> Heap<usize> {
+    xs.iter().enumerate().fold(Heap::empty(), |h, (i, &x)| {
+        let h = Heap::insert(h, x);
+        if i % 4 == 3 {
+            let nm = name_of_usize(base + i);
+            Heap::name(nm.clone(), Heap::art(cell(nm, h)))
+        } else {
+            h
+        }
+    })
+}
+
+#[test]
+fn test_empty_heap() {
+    let h = Heap::<usize>::empty();
+    assert!(Heap::is_empty(&h));
+    assert_eq!(Heap::find_min(&h), None);
+    assert!(Heap::is_empty(&Heap::delete_min(h)));
+}
+
+#[test]
+fn test_heap_sort() {
+    let xs = [5, 3, 9, 1, 7, 3, 8, 2, 6, 0, 4];
+    let mut sorted = xs.to_vec();
+    sorted.sort();
+    assert_eq!(drain(heap_of_vec(&xs, 0)), sorted);
+}
+
+#[test]
+fn test_meld() {
+    let h1 = heap_of_vec(&[10, 4, 12, 8, 6], 0);
+    let h2 = heap_of_vec(&[5, 11, 1, 9], 100);
+    let h = Heap::meld(h1, h2);
+    assert_eq!(Heap::find_min(&h), Some(1));
+    assert_eq!(drain(h), vec![1, 4, 5, 6, 8, 9, 10, 11, 12]);
+}
+
+#[test]
+fn test_heap_sort_dcg() {
+    manage::init_dcg();
+    let xs = [5, 3, 9, 1, 7, 3, 8, 2, 6, 0, 4];
+    let h = Heap::meld(heap_of_vec(&xs[..6], 0), heap_of_vec(&xs[6..], 100));
+    assert_eq!(drain(h), vec![0, 1, 2, 3, 3, 4, 5, 6, 7, 8, 9]);
+}
@@ -0,0 +1,60 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+
+fn indexed(len: usize) -> IndexedList<usize> {
+    IndexedList::of_vec((0..len).collect(), name_of_str("l"))
+}
+
+fn height(l: &IndexedList<usize>) -> usize {
+    match *l {
+        IndexedList::Nil | IndexedList::Leaf(_, _) => 0,
+        IndexedList::Bin(_, _, ref l, ref r) => 1 + std::cmp::max(height(l), height(r)),
+        IndexedList::Art(_, ref a) => height(&force(a)),
+    }
+}
+
+#[test]
+fn test_nth() {
+    let l = indexed(200);
+    assert_eq!(IndexedList::len(&l), 200);
+    for i in 0..200 {
+        assert_eq!(IndexedList::nth(&l, i), Some(i));
+    }
+    assert_eq!(IndexedList::nth(&l, 200), None);
+    assert_eq!(IndexedList::nth(&indexed(0), 0), None);
+    // Far shallower than a list of the same length.
+    assert!(height(&l) < 40);
+}
+
+#[test]
+fn test_update_at() {
+    let l = indexed(100);
+    let l2 = IndexedList::update_at(l.clone(), 42, 1000);
+    assert_eq!(IndexedList::nth(&l2, 42), Some(1000));
+    assert_eq!(IndexedList::nth(&l2, 41), Some(41));
+    assert_eq!(IndexedList::len(&l2), 100);
+    let l3 = IndexedList::update_at(l2, 42, 42);
+    assert_eq!(l3, l);
+}
+
+#[test]
+fn test_shape_is_history_independent() {
+    let mut v: Vec<usize> = (0..50).collect();
+    v[10] = 99;
+    let l = IndexedList::update_at(indexed(50), 10, 99);
+    assert_eq!(l, IndexedList::of_vec(v, name_of_str("l")));
+}
+
+#[test]
+fn test_update_at_dcg() {
+    manage::init_dcg();
+    let l = indexed(100);
+    let sum = |l: &IndexedList<usize>| -> usize {
+        (0..100).map(|i| IndexedList::nth(l, i).unwrap()).sum()
+    };
+    assert_eq!(sum(&l), 4950);
+    let l = IndexedList::update_at(l, 0, 100);
+    assert_eq!(sum(&l), 5050);
+}
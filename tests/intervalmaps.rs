@@ -0,0 +1,56 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+
+fn values(l: List<&'static str>) -> Vec<&'static str> {
+    vec_of_list_elms(l)
+}
+
+/// Inserts `intervals`, naming the map after every other insertion.
+fn map_of_intervals(intervals: &[(usize, usize, &'static str)])
+                    -> IntervalMap<usize, &'static str> {
+    intervals.iter().enumerate().fold(IntervalMap::empty(), |m, (i, &(lo, hi, v))| {
+        let m = IntervalMap::insert(m, (lo, hi), v);
+        if i % 2 == 1 {
+            let nm = name_of_usize(i);
+            IntervalMap::name(nm.clone(), IntervalMap::art(cell(nm, m)))
+        } else {
+            m
+        }
+    })
+}
+
+#[test]
+fn test_query_point() {
+    let m = map_of_intervals(&[(0, 10, "a"), (5, 7, "b"), (8, 20, "c"), (12, 15, "d"),
+                               (3, 6, "e"), (5, 7, "f")]);
+    assert_eq!(values(IntervalMap::query_point(m.clone(), 0)), vec!["a"]);
+    let mut at_5 = values(IntervalMap::query_point(m.clone(), 5));
+    at_5.sort();
+    assert_eq!(at_5, vec!["a", "b", "e", "f"]);
+    assert_eq!(values(IntervalMap::query_point(m.clone(), 7)), vec!["a"]);
+    assert_eq!(values(IntervalMap::query_point(m.clone(), 10)), vec!["c"]);
+    assert_eq!(values(IntervalMap::query_point(m.clone(), 13)), vec!["c", "d"]);
+    assert_eq!(values(IntervalMap::query_point(m, 20)), Vec::<&str>::new());
+}
+
+#[test]
+fn test_query_point_dcg() {
+    manage::init_dcg();
+    let m = map_of_intervals(&[(0, 4, "a"), (2, 9, "b"), (6, 8, "c"), (1, 3, "d")]);
+    assert_eq!(values(IntervalMap::query_point(m.clone(), 2)), vec!["a", "d", "b"]);
+    let m = IntervalMap::insert(m, (2, 3), "e");
+    assert_eq!(values(IntervalMap::query_point(m, 2)), vec!["a", "d", "e", "b"]);
+}
+
+// Queries at different points keep their own results under the DCG.
+#[test]
+fn test_query_points_dcg() {
+    manage::init_dcg();
+    let m = map_of_intervals(&[(0, 4, "a"), (2, 9, "b"), (6, 8, "c"), (1, 3, "d")]);
+    let at_2 = IntervalMap::query_point(m.clone(), 2);
+    let at_7 = IntervalMap::query_point(m, 7);
+    assert_eq!(values(at_2), vec!["a", "d", "b"]);
+    assert_eq!(values(at_7), vec!["b", "c"]);
+}
@@ -0,0 +1,49 @@
+#[macro_use]
+extern crate adapton;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+
+// External state, changed out of band; the cells hold handles to it.
+thread_local!(static FILES: RefCell<Vec<usize>> = RefCell::new(vec![1, 2, 3]));
+
+fn read(handle: Art<usize>) -> usize {
+    let h = force(&handle);
+    FILES.with(|fs| fs.borrow()[h])
+}
+
+fn plus_one(a: Art<usize>) -> usize {
+    force(&a) + 1
+}
+
+#[test]
+fn test_invalidate() {
+    init_dcg();
+    let handle = cell(name_of_str("handle"), 1);
+    let (contents, x) = eager!(name_of_str("read") =>> read, handle:handle.clone());
+    let (plus, y) = eager!(name_of_str("plus_one") =>> plus_one, a:contents.clone());
+    assert_eq!((x, y), (2, 3));
+    FILES.with(|fs| fs.borrow_mut()[1] = 20);
+    // The handle did not change; so, the results are stale.
+    assert_eq!(force(&plus), 3);
+    assert!(invalidate(&name_of_str("handle")));
+    reset_stats();
+    assert_eq!(force(&plus), 21);
+    assert_eq!(force(&contents), 20);
+    assert_eq!(stats().eval, 2);
+    // Invalidating re-evaluates even when nothing changed.
+    assert!(invalidate(&name_of_str("read")));
+    reset_stats();
+    assert_eq!(force(&plus), 21);
+    assert_eq!(stats().eval, 2);
+    assert!(!invalidate(&name_of_str("no such name")));
+}
+
+#[test]
+fn test_invalidate_naive() {
+    init_naive();
+    assert!(!invalidate(&name_of_str("handle")));
+}
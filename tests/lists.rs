@@ -0,0 +1,331 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+
+fn elems(l: List<usize>) -> Vec<usize> {
+    vec_of_list_elms(l)
+}
+
+fn names(l: List<usize>) -> Vec<Name> {
+    vec_of_list(l, None)
+        .into_iter()
+        .filter_map(|x| match x {
+            NameElse::Else(_) => None,
+            NameElse::Name(n) => Some(n),
+        })
+        .collect()
+}
+
+/// The list `0, 1, ..., len - 1`, with the name `i` before every
+/// element `i` divisible by `stride`.
+fn named_list(len: usize, stride: usize) -> List<usize> {
+    (0..len).rev().fold(List::nil(), |l, i| {
+        let l = List::cons(i, l);
+        if i % stride == 0 {
+            List::name_art(Some(name_of_usize(i)), l)
+        } else {
+            l
+        }
+    })
+}
+
+mod split {
+    use super::*;
+
+    #[test]
+    fn test_split_at() {
+        let (pre, suf) = list_split_at(named_list(10, 3), 4);
+        assert_eq!(elems(pre.clone()), vec![0, 1, 2, 3]);
+        assert_eq!(elems(suf.clone()), vec![4, 5, 6, 7, 8, 9]);
+        assert_eq!(names(pre), vec![name_of_usize(0), name_of_usize(3)]);
+        assert_eq!(names(suf), vec![name_of_usize(6), name_of_usize(9)]);
+        let (pre, suf) = list_split_at(named_list(10, 3), 20);
+        assert_eq!(elems(pre), (0..10).collect::<Vec<_>>());
+        assert_eq!(elems(suf), vec![]);
+    }
+
+    #[test]
+    fn test_take_drop() {
+        assert_eq!(elems(list_take(named_list(10, 3), 6)), vec![0, 1, 2, 3, 4, 5]);
+        let rest = list_drop(named_list(10, 3), 6);
+        assert_eq!(elems(rest.clone()), vec![6, 7, 8, 9]);
+        assert_eq!(names(rest), vec![name_of_usize(6), name_of_usize(9)]);
+        assert_eq!(elems(list_drop(named_list(10, 3), 0)), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_split_at_name() {
+        let (pre, suf) = list_split_at_name(named_list(10, 3), name_of_usize(6));
+        assert_eq!(elems(pre.clone()), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(names(pre), vec![name_of_usize(0), name_of_usize(3)]);
+        assert_eq!(names(suf.clone()), vec![name_of_usize(6), name_of_usize(9)]);
+        assert_eq!(elems(suf), vec![6, 7, 8, 9]);
+        let (pre, suf) = list_split_at_name(named_list(10, 3), name_of_usize(4));
+        assert_eq!(elems(pre), (0..10).collect::<Vec<_>>());
+        assert_eq!(elems(suf), vec![]);
+    }
+
+    // Splits of one list at different points keep their own prefixes
+    // under the DCG.
+    #[test]
+    fn test_splits_dcg() {
+        manage::init_dcg();
+        let l = named_list(10, 3);
+        let (pre_4, _) = list_split_at(l.clone(), 4);
+        let (pre_8, _) = list_split_at(l.clone(), 8);
+        assert_eq!(elems(pre_4), vec![0, 1, 2, 3]);
+        assert_eq!(elems(pre_8), (0..8).collect::<Vec<_>>());
+        let (pre_6, _) = list_split_at_name(l.clone(), name_of_usize(6));
+        let (pre_9, _) = list_split_at_name(l, name_of_usize(9));
+        assert_eq!(elems(pre_6), (0..6).collect::<Vec<_>>());
+        assert_eq!(elems(pre_9), (0..9).collect::<Vec<_>>());
+    }
+}
+
+mod flat_map {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_flat_map() {
+        let l = named_list(6, 2);
+        let out: List<usize> = list_flat_map(l, Rc::new(|x: usize| {
+            let ys: List<usize> = (0..x % 3).fold(List::nil(), |ys, _| List::cons(x, ys));
+            List::name_art(Some(name_of_str("inner")), ys)
+        }));
+        assert_eq!(elems(out.clone()), vec![1, 2, 2, 4, 5, 5]);
+        assert_eq!(names(out), vec![name_of_usize(0), name_of_usize(2), name_of_usize(4)]);
+    }
+}
+
+mod dedup {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_dedup_by_key() {
+        let l = named_list(10, 3);
+        let out = list_dedup_by_key(l, Rc::new(|x: &usize| x % 4));
+        assert_eq!(elems(out.clone()), vec![0, 1, 2, 3]);
+        assert_eq!(names(out), vec![name_of_usize(0), name_of_usize(3),
+                                    name_of_usize(6), name_of_usize(9)]);
+    }
+
+    #[test]
+    fn test_dedup_by_key_dcg() {
+        manage::init_dcg();
+        let l = named_list(100, 10);
+        let out = list_dedup_by_key(l, Rc::new(|x: &usize| x / 3));
+        let expected: Vec<usize> = (0..100).filter(|x| x % 3 == 0).collect();
+        assert_eq!(elems(out), expected);
+    }
+}
+
+mod partition {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_partition() {
+        let l = named_list(10, 4);
+        let (evens, odds): (List<usize>, List<usize>) =
+            list_partition(l, Rc::new(|x: &usize| x % 2 == 0));
+        assert_eq!(elems(evens.clone()), vec![0, 2, 4, 6, 8]);
+        assert_eq!(elems(odds.clone()), vec![1, 3, 5, 7, 9]);
+        let expected = vec![name_of_usize(0), name_of_usize(4), name_of_usize(8)];
+        assert_eq!(names(evens), expected);
+        assert_eq!(names(odds), expected);
+    }
+
+    #[test]
+    fn test_partition_dcg() {
+        manage::init_dcg();
+        let l = named_list(100, 10);
+        let (small, large): (List<usize>, List<usize>) =
+            list_partition(l, Rc::new(|x: &usize| x % 10 < 3));
+        assert_eq!(elems(small).len(), 30);
+        assert_eq!(elems(large).len(), 70);
+    }
+}
+
+mod windows {
+    use super::*;
+
+    fn lists(l: List<List<usize>>) -> Vec<Vec<usize>> {
+        vec_of_list_elms(l).into_iter().map(elems).collect()
+    }
+
+    fn outer_names(l: List<List<usize>>) -> Vec<Name> {
+        vec_of_list(l, None)
+            .into_iter()
+            .filter_map(|x| match x {
+                NameElse::Else(_) => None,
+                NameElse::Name(n) => Some(n),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunks() {
+        let l = named_list(10, 5);
+        let chunks: List<List<usize>> = list_chunks(l, 3);
+        assert_eq!(lists(chunks.clone()),
+                   vec![vec![0, 1, 2], vec![3, 4], vec![5, 6, 7], vec![8, 9]]);
+        assert_eq!(outer_names(chunks), vec![name_of_usize(0), name_of_usize(5)]);
+    }
+
+    #[test]
+    fn test_windows() {
+        let l = named_list(6, 3);
+        let windows: List<List<usize>> = list_windows(l, 3);
+        assert_eq!(lists(windows.clone()),
+                   vec![vec![0, 1, 2], vec![1, 2, 3], vec![2, 3, 4], vec![3, 4, 5]]);
+        assert_eq!(outer_names(windows), vec![name_of_usize(0), name_of_usize(3)]);
+        let short: List<List<usize>> = list_windows(named_list(2, 3), 3);
+        assert_eq!(lists(short), Vec::<Vec<usize>>::new());
+    }
+
+    #[test]
+    fn test_windows_dcg() {
+        manage::init_dcg();
+        let windows: List<List<usize>> = list_windows(named_list(100, 10), 4);
+        let sums: Vec<usize> = lists(windows).into_iter().map(|w| w.iter().sum()).collect();
+        assert_eq!(sums, (0..97).map(|i| 4 * i + 6).collect::<Vec<_>>());
+    }
+}
+
+mod reduce {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_max_min() {
+        let l: List<usize> = (0..20).fold(List::nil(), |l, i| List::cons((i * 7) % 20, l));
+        assert_eq!(list_max(l.clone()), Some(19));
+        assert_eq!(list_min(l), Some(0));
+        assert_eq!(list_max(List::<usize>::nil()), None);
+        assert_eq!(list_min(List::<usize>::nil()), None);
+    }
+
+    #[test]
+    fn test_monoid_of_list() {
+        let sum = monoid_of_list(named_list(100, 10), 0, Rc::new(|x, y| x + y));
+        assert_eq!(sum, 4950);
+    }
+
+    #[test]
+    fn test_reductions_dcg() {
+        manage::init_dcg();
+        let l = named_list(100, 10);
+        assert_eq!(list_max(l.clone()), Some(99));
+        assert_eq!(list_min(l.clone()), Some(0));
+        assert_eq!(monoid_of_list(l, 0, Rc::new(|x, y| x + y)), 4950);
+        let l = List::cons(500, named_list(100, 10));
+        assert_eq!(list_max(l.clone()), Some(500));
+        assert_eq!(list_min(l.clone()), Some(0));
+        assert_eq!(monoid_of_list(l, 0, Rc::new(|x, y| x + y)), 5450);
+    }
+}
+
+mod scan {
+    use super::*;
+    use std::rc::Rc;
+
+    fn sums(len: usize) -> Vec<usize> {
+        (0..len).map(|i| i * (i + 1) / 2).collect()
+    }
+
+    #[test]
+    fn test_scan() {
+        let l: List<usize> = list_scan(named_list(100, 10), 0, Rc::new(|acc, x| acc + x));
+        assert_eq!(elems(l.clone()), sums(100));
+        assert_eq!(names(l), names(named_list(100, 10)));
+    }
+
+    #[test]
+    fn test_scan_dcg() {
+        manage::init_dcg();
+        let scan = |l| -> List<usize> {
+            ns(name_of_str("scan"), || list_scan(l, 0, Rc::new(|acc, x| acc + x)))
+        };
+        assert_eq!(elems(scan(named_list(100, 10))), sums(100));
+        assert_eq!(elems(scan(named_list(100, 10))), sums(100));
+        let l = List::cons(0, named_list(100, 10));
+        assert_eq!(elems(scan(l)), [0].iter().cloned().chain(sums(100)).collect::<Vec<_>>());
+    }
+}
+
+mod convert {
+    use super::*;
+
+    #[test]
+    fn test_list_of_vec_named() {
+        let l: List<usize> = list_of_vec_named((0..25).collect(), 10, name_of_str("v"));
+        assert_eq!(vec_of_list_elms(l.clone()), (0..25).collect::<Vec<_>>());
+        let expected: Vec<Name> =
+            (0..3).map(|i| name_pair(name_of_str("v"), name_of_usize(i))).collect();
+        assert_eq!(names(l), expected);
+        let l: List<usize> = list_of_vec_named((0..5).collect(), 1, name_of_str("v"));
+        assert_eq!(names(l).len(), 5);
+        let l: List<usize> = list_of_vec_named(vec![], 4, name_of_str("v"));
+        assert_eq!(l, List::nil());
+    }
+
+    #[test]
+    fn test_list_of_vec_named_dcg() {
+        manage::init_dcg();
+        let l: List<usize> = list_of_vec_named((0..100).collect(), 10, name_of_str("v"));
+        let tree = ns(name_of_str("tree_of_list"),
+                      || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, l));
+        let l: List<usize> = list_of_tree(tree, Dir2::Left);
+        assert_eq!(vec_of_list_elms(l), (0..100).collect::<Vec<_>>());
+    }
+}
+
+mod insert_sorted {
+    use super::*;
+
+    #[test]
+    fn test_insert_sorted() {
+        let l = named_list(10, 4);
+        let l2: List<usize> = list_insert_sorted(l.clone(), 5);
+        assert_eq!(elems(l2.clone()), vec![0, 1, 2, 3, 4, 5, 5, 6, 7, 8, 9]);
+        assert_eq!(names(l2), names(l.clone()));
+        let expected: Vec<usize> = (0..10).chain(Some(20)).collect();
+        assert_eq!(elems(list_insert_sorted(l.clone(), 20)), expected);
+        assert_eq!(elems(list_insert_sorted(List::nil(), 3)), vec![3]);
+    }
+
+    #[test]
+    fn test_insert_sorted_dcg() {
+        manage::init_dcg();
+        let l = ns(name_of_str("input"), || named_list(100, 10));
+        let mut out = l.clone();
+        let mut expected: Vec<usize> = (0..100).collect();
+        // Each insertion consumes the output of the last one, and so
+        // occurs in a namespace of its own.
+        for (i, &x) in [50, 7, 99, 0, 50].iter().enumerate() {
+            out = ns(name_of_usize(i), || list_insert_sorted(out.clone(), x));
+            expected.push(x);
+            expected.sort();
+            assert_eq!(elems(out.clone()), expected);
+        }
+    }
+
+    // Insertions of different elements into the same list, in the
+    // same namespace, do not overwrite each other.
+    #[test]
+    fn test_insert_sorted_same_list_dcg() {
+        manage::init_dcg();
+        let l = ns(name_of_str("input"), || named_list(100, 10));
+        let l5: List<usize> = list_insert_sorted(l.clone(), 5);
+        let l55: List<usize> = list_insert_sorted(l.clone(), 55);
+        let mut expected: Vec<usize> = (0..100).chain(Some(5)).collect();
+        expected.sort();
+        assert_eq!(elems(l5), expected);
+        let mut expected: Vec<usize> = (0..100).chain(Some(55)).collect();
+        expected.sort();
+        assert_eq!(elems(l55), expected);
+    }
+}
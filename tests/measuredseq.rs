@@ -0,0 +1,59 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+
+/// A list of `xs`, with a name before every fourth element.
+fn list_of_elems(xs: &[usize]) -> List<usize> {
+    xs.iter().enumerate().rev().fold(List::nil(), |l, (i, &x)| {
+        let l = List::cons(x, l);
+        if i % 4 == 0 {
+            List::name_art(Some(name_of_usize(i)), l)
+        } else {
+            l
+        }
+    })
+}
+
+#[test]
+fn test_measure() {
+    let xs = [4, 8, 15, 16, 23, 42, 7, 1, 9, 3];
+    let s: MeasuredSeq<usize, Stats<usize>> = MeasuredSeq::of_list(list_of_elems(&xs));
+    assert_eq!(MeasuredSeq::elems(&s), xs.to_vec());
+    assert_eq!(MeasuredSeq::measure(&s), Stats { size: 10, sum: 128, max: Some(42) });
+    let empty: MeasuredSeq<usize, Size> = MeasuredSeq::of_list(List::nil());
+    assert_eq!(MeasuredSeq::measure(&empty), Size(0));
+}
+
+#[test]
+fn test_split_at_measure() {
+    let xs: Vec<usize> = (0..50).collect();
+    let s: MeasuredSeq<usize, Size> = MeasuredSeq::of_list(list_of_elems(&xs));
+    let (before, x, after) = MeasuredSeq::split_at_measure(s.clone(), &|m: &Size| m.0 > 20);
+    assert_eq!(x, Some(20));
+    assert_eq!(MeasuredSeq::elems(&before), (0..20).collect::<Vec<_>>());
+    assert_eq!(MeasuredSeq::measure(&after), Size(29));
+    assert_eq!(MeasuredSeq::elems(&after), (21..50).collect::<Vec<_>>());
+    let (all, x, rest) = MeasuredSeq::split_at_measure(s, &|m: &Size| m.0 > 50);
+    assert_eq!(x, None);
+    assert_eq!(MeasuredSeq::measure(&all), Size(50));
+    assert_eq!(rest, MeasuredSeq::Nil);
+}
+
+#[test]
+fn test_split_at_sum() {
+    let xs = [4, 8, 15, 16, 23, 42, 7, 1, 9, 3];
+    let s: MeasuredSeq<usize, Stats<usize>> = MeasuredSeq::of_list(list_of_elems(&xs));
+    let (before, x, _) = MeasuredSeq::split_at_measure(s, &|m: &Stats<usize>| m.sum >= 50);
+    assert_eq!(x, Some(23));
+    assert_eq!(MeasuredSeq::measure(&before).sum, 43);
+}
+
+#[test]
+fn test_measure_dcg() {
+    manage::init_dcg();
+    let xs: Vec<usize> = (0..50).collect();
+    let s: MeasuredSeq<usize, Stats<usize>> = MeasuredSeq::of_list(list_of_elems(&xs));
+    assert_eq!(MeasuredSeq::measure(&s), Stats { size: 50, sum: 1225, max: Some(49) });
+    assert_eq!(MeasuredSeq::elems(&s), xs);
+}
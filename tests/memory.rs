@@ -0,0 +1,45 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+#[test]
+fn test_memory_stats() {
+    init_dcg();
+    assert_eq!(memory_stats(), MemoryStats::default());
+    let a = chain_in("a", 2);
+    let b = chain_in("b", 4);
+    let stats = memory_stats();
+    assert_eq!(stats.total.cells, 2);
+    assert_eq!(stats.total.thunks, 6);
+    assert_eq!(stats.total.cached, 0);
+    assert_eq!(stats.namespaces[&name_of_str("a")].thunks, 2);
+    assert_eq!(stats.namespaces[&name_of_str("b")].thunks, 4);
+    assert_eq!(stats.prog_pts.len(), 1);
+    let double_pp = stats.prog_pts.keys().next().unwrap().clone();
+    assert_eq!(double_pp.symbol, "double");
+    assert_eq!(stats.prog_pts[&double_pp].thunks, 6);
+    assert!(stats.namespaces[&name_of_str("b")].bytes > stats.namespaces[&name_of_str("a")].bytes);
+
+    force(&a);
+    force(&b);
+    let forced = memory_stats();
+    assert_eq!(forced.total.cached, 6);
+    assert_eq!(forced.total.edges, 6);
+    assert_eq!(forced.prog_pts[&double_pp].cached, 6);
+    assert!(forced.total.bytes > stats.total.bytes);
+    let sum = forced.namespaces.values().fold(MemoryUse::default(), |s, u| s + *u);
+    assert_eq!(sum, forced.total);
+}
+
+#[test]
+fn test_memory_stats_naive() {
+    init_naive();
+    let a = chain_in("a", 2);
+    assert_eq!(force(&a), 4);
+    assert_eq!(memory_stats(), MemoryStats::default());
+}
@@ -0,0 +1,53 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::collections::trie::*;
+use adapton::engine::*;
+
+fn elems<X: Clone + std::fmt::Debug + std::hash::Hash + Eq + 'static>(l: List<X>) -> Vec<X> {
+    vec_of_list_elms(l)
+}
+
+fn multimap_of_vec(pairs: &[(usize, &'static str)]) -> MultiMap<usize, &'static str> {
+    pairs.iter().enumerate().fold(MapIntro::empty(), |m, (i, &(k, v))| {
+        MultiMap::insert(name_of_usize(i), m, k, v)
+    })
+}
+
+#[test]
+fn test_insert_get_all() {
+    let m = multimap_of_vec(&[(1, "a"), (2, "b"), (1, "c"), (1, "d")]);
+    assert_eq!(elems(MultiMap::get_all(&m, &1)), vec!["d", "c", "a"]);
+    assert_eq!(elems(MultiMap::get_all(&m, &2)), vec!["b"]);
+    assert_eq!(elems(MultiMap::get_all(&m, &3)), Vec::<&str>::new());
+}
+
+#[test]
+fn test_remove_one() {
+    let m = multimap_of_vec(&[(1, "a"), (1, "b"), (1, "a"), (2, "c")]);
+    let m = MultiMap::remove_one(name_of_str("r1"), m, &1, &"a");
+    assert_eq!(elems(MultiMap::get_all(&m, &1)), vec!["b", "a"]);
+    let m = MultiMap::remove_one(name_of_str("r2"), m, &2, &"x");
+    assert_eq!(elems(MultiMap::get_all(&m, &2)), vec!["c"]);
+    let m = MultiMap::remove_one(name_of_str("r3"), m, &2, &"c");
+    assert_eq!(elems(MultiMap::get_all(&m, &2)), Vec::<&str>::new());
+}
+
+#[test]
+fn test_flatten() {
+    let m = multimap_of_vec(&[(1, "a"), (2, "b"), (1, "c")]);
+    let mut pairs = elems(MultiMap::flatten(m));
+    pairs.sort();
+    assert_eq!(pairs, vec![(1, "a"), (1, "c"), (2, "b")]);
+}
+
+#[test]
+fn test_flatten_dcg() {
+    manage::init_dcg();
+    let pairs: Vec<(usize, &'static str)> =
+        (0..50).map(|i| (i % 7, if i % 2 == 0 { "even" } else { "odd" })).collect();
+    let m = multimap_of_vec(&pairs);
+    assert_eq!(elems(MultiMap::flatten(m.clone())).len(), 50);
+    let m = MultiMap::remove_one(name_of_str("r"), m, &3, &"odd");
+    assert_eq!(elems(MultiMap::flatten(m)).len(), 49);
+}
@@ -0,0 +1,47 @@
+extern crate adapton;
+
+use std::collections::HashSet;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+
+#[test]
+fn test_name_gen_fresh() {
+    let mut g = NameGen::of_str("g");
+    let mut h = g.fork();
+    let mut names = HashSet::new();
+    for _ in 0..10 {
+        assert!(names.insert(g.fresh()));
+        assert!(names.insert(h.fresh()));
+    }
+    let mut h2 = g.fork();
+    for _ in 0..10 {
+        assert!(names.insert(h2.fresh()));
+    }
+    assert_eq!(g.count(), 12);
+    assert_eq!(h2.count(), 10);
+    // Generators with the same seed generate the same names.
+    let mut g2 = NameGen::of_str("g");
+    assert_eq!(g2.fresh(), name_pair(name_of_str("g"), name_of_usize(0)));
+}
+
+#[test]
+fn test_name_gen_cells() {
+    init_dcg();
+    let mut g = NameGen::of_str("inputs");
+    let (mut a, mut b) = (g.fork(), g.fork());
+    // Cells of two structures, named without colliding.
+    let xs: Vec<Art<usize>> = (0..5).map(|i| cell(a.fresh(), i)).collect();
+    let ys: Vec<Art<usize>> = (0..5).map(|i| cell(b.fresh(), i * 10)).collect();
+    let sx: usize = xs.iter().map(|x| force(x)).sum();
+    let sy: usize = ys.iter().map(|y| force(y)).sum();
+    assert_eq!((sx, sy), (10, 100));
+}
+
+#[test]
+fn test_name_of_path() {
+    assert_eq!(name_of_path(&[]), name_unit());
+    assert_eq!(name_of_path(&["a"]), name_of_str("a"));
+    assert_eq!(name_of_path(&["a", "b", "c"]),
+               name_pair(name_pair(name_of_str("a"), name_of_str("b")), name_of_str("c")));
+    assert!(name_of_path(&["a", "b"]) != name_of_path(&["b", "a"]));
+}
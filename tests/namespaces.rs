@@ -0,0 +1,41 @@
+#[macro_use]
+extern crate adapton;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+
+fn current_ns(_x: usize) -> Name {
+    current_namespace()
+}
+
+#[test]
+fn test_current_namespace() {
+    init_dcg();
+    assert_eq!(current_namespace(), name_unit());
+    ns(name_of_str("a"), || {
+        assert_eq!(current_namespace(), name_of_str("a"));
+        ns(name_of_str("b"), || {
+            assert_eq!(current_namespace(), name_of_path(&["a", "b"]));
+            assert_eq!(name_under_current_ns(name_of_str("c")),
+                       name_pair(name_of_path(&["a", "b"]), name_of_str("c")));
+        });
+        assert_eq!(current_namespace(), name_of_str("a"));
+    });
+    assert_eq!(current_namespace(), name_unit());
+}
+
+#[test]
+fn test_current_namespace_of_thunk() {
+    init_dcg();
+    // A thunk runs in the namespace where it was allocated.
+    let t = ns(name_of_str("a"), || thunk!(name_of_str("t") =>> current_ns, x:0));
+    ns(name_of_str("b"), || assert_eq!(force(&t), name_of_str("a")));
+}
+
+#[test]
+fn test_current_namespace_naive() {
+    init_naive();
+    ns(name_of_str("a"), || assert_eq!(current_namespace(), name_unit()));
+}
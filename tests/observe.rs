@@ -0,0 +1,93 @@
+#[macro_use]
+extern crate adapton;
+
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+
+fn parity(a: Art<usize>) -> usize {
+    force(&a) % 2
+}
+
+#[test]
+fn test_observe() {
+    init_dcg();
+    let input = cell(name_of_str("input"), 1);
+    let (out, _) = eager!(name_of_str("parity") =>> parity, a:input.clone());
+    let seen_input: Rc<RefCell<Vec<usize>>> = Rc::new(RefCell::new(vec![]));
+    let seen_out: Rc<RefCell<Vec<usize>>> = Rc::new(RefCell::new(vec![]));
+    {
+        let seen = seen_input.clone();
+        assert!(observe(&name_of_str("input"), move |x: &usize| seen.borrow_mut().push(*x)));
+        let seen = seen_out.clone();
+        assert!(observe(&name_of_str("parity"), move |x: &usize| seen.borrow_mut().push(*x)));
+    }
+    assert!(!observe(&name_of_str("no such name"), |_: &usize| ()));
+    set(&input, 3);
+    set(&input, 3);
+    assert_eq!(*seen_input.borrow(), vec![3]);
+    // Thunks notify as change propagation re-evaluates them, and only
+    // when their values change.
+    assert_eq!(force(&out), 1);
+    assert_eq!(*seen_out.borrow(), Vec::<usize>::new());
+    set(&input, 4);
+    assert_eq!(force(&out), 0);
+    set(&input, 6);
+    assert_eq!(force(&out), 0);
+    assert_eq!(*seen_input.borrow(), vec![3, 4, 6]);
+    assert_eq!(*seen_out.borrow(), vec![0]);
+    // Reallocating the cell with `cell` also notifies.
+    let _ = cell(name_of_str("input"), 7usize);
+    assert_eq!(*seen_input.borrow(), vec![3, 4, 6, 7]);
+}
+
+// Callbacks may use the engine, e.g., to push changes to a view.
+#[test]
+fn test_observe_push() {
+    init_dcg();
+    let input = cell(name_of_str("input"), 1);
+    let (out, _) = eager!(name_of_str("parity") =>> parity, a:input.clone());
+    let view = Rc::new(RefCell::new(force(&out)));
+    {
+        let (out, view) = (out.clone(), view.clone());
+        observe(&name_of_str("input"), move |_: &usize| *view.borrow_mut() = force(&out));
+    }
+    set(&input, 2);
+    assert_eq!(*view.borrow(), 0);
+    set(&input, 5);
+    assert_eq!(*view.borrow(), 1);
+}
+
+// Callbacks run once the engine releases its state; so, they may also
+// switch engines, e.g., to mirror a cell in another one.
+#[test]
+fn test_observe_other_engine() {
+    init_dcg();
+    let input = cell(name_of_str("input"), 1usize);
+    let other = EngineHandle::new_dcg();
+    let mirror = other.cell(name_of_str("mirror"), 1usize);
+    {
+        let (other, mirror) = (other.clone(), mirror.clone());
+        observe(&name_of_str("input"), move |x: &usize| other.set(&mirror, *x));
+    }
+    set(&input, 2);
+    assert_eq!(other.force(&mirror), 2);
+}
+
+fn set_all<T: Eq + Debug + Clone>(cells: &[Art<T>], val: T) {
+    for c in cells {
+        set(c, val.clone())
+    }
+}
+
+// `set` does not require `T: 'static` of generic callers.
+#[test]
+fn test_set_generic() {
+    init_dcg();
+    let cells = vec![cell(name_of_str("a"), 1usize), cell(name_of_str("b"), 2)];
+    set_all(&cells, 3);
+    assert_eq!(cells.iter().map(|c| force(c)).collect::<Vec<_>>(), vec![3, 3]);
+}
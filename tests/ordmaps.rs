@@ -0,0 +1,99 @@
+extern crate adapton;
+
+use std::rc::Rc;
+
+use adapton::collections::*;
+use adapton::engine::*;
+
+fn ordmap_of_keys(keys: &[usize]) -> OrdMap<usize, usize> {
+    keys.iter().fold(MapIntro::empty(), |m, &k| MapIntro::update(m, k, k * 10))
+}
+
+fn entries(m: OrdMap<usize, usize>) -> Vec<(usize, usize)> {
+    let entries = MapElim::fold(m, vec![], Rc::new(|k, v, mut es: Vec<(usize, usize)>| {
+        es.push((k, v));
+        es
+    }));
+    entries
+}
+
+#[test]
+fn test_update_find_remove() {
+    let m = ordmap_of_keys(&[5, 3, 9, 1, 7]);
+    assert_eq!(MapElim::find(&m, &3), Some(30));
+    assert_eq!(MapElim::find(&m, &4), None);
+    let m = MapIntro::update(m, 3, 33);
+    assert_eq!(MapElim::find(&m, &3), Some(33));
+    let (m, v) = MapElim::remove(m, &9);
+    assert_eq!(v, Some(90));
+    assert_eq!(MapElim::find(&m, &9), None);
+    assert_eq!(entries(m), vec![(1, 10), (3, 33), (5, 50), (7, 70)]);
+}
+
+#[test]
+fn test_canonical() {
+    let m1 = ordmap_of_keys(&[5, 3, 9, 1, 7, 2, 8]);
+    let m2 = ordmap_of_keys(&[1, 2, 3, 5, 7, 8, 9]);
+    assert_eq!(m1, m2);
+    let (m3, _) = MapElim::remove(ordmap_of_keys(&[4, 1, 2, 3, 5, 7, 8, 9]), &4);
+    assert_eq!(m1, m3);
+}
+
+#[test]
+fn test_order_queries() {
+    let m = ordmap_of_keys(&(0..40).map(|k| k * 3).collect::<Vec<_>>());
+    assert_eq!(OrdMap::min_key(&m), Some(0));
+    assert_eq!(OrdMap::max_key(&m), Some(117));
+    let empty: OrdMap<usize, usize> = MapIntro::empty();
+    assert_eq!(OrdMap::min_key(&empty), None);
+    let range = OrdMap::range(m.clone(), &10, &22);
+    assert_eq!(entries(range), vec![(12, 120), (15, 150), (18, 180), (21, 210)]);
+    let range = OrdMap::range(m, &9, &12);
+    assert_eq!(entries(range), vec![(9, 90)]);
+}
+
+#[test]
+fn test_names_and_append() {
+    let m = ordmap_of_keys(&[1, 2, 3]);
+    let nm = name_of_str("m");
+    let m = OrdMap::name(nm.clone(), OrdMap::art(cell(nm, m)));
+    let m = MapIntro::update(m, 4, 40);
+    assert_eq!(MapElim::find(&m, &2), Some(20));
+    let m = MapElim::append(m, ordmap_of_keys(&[3, 5]));
+    assert_eq!(entries(m), vec![(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]);
+}
+
+#[test]
+fn test_read_apis() {
+    let m = ordmap_of_keys(&[5, 3, 9, 1, 7]);
+    assert_eq!(MapElim::len(&m), 5);
+    assert!(MapElim::contains_key(&m, &9));
+    assert!(!MapElim::contains_key(&m, &4));
+    let keys: Vec<usize> = vec_of_list_elms(MapElim::keys(&m));
+    assert_eq!(keys, vec![9, 7, 5, 3, 1]);
+    let values: Vec<NameElse<usize>> = vec_of_list(MapElim::values(&m), None);
+    assert_eq!(values.len(), 5);
+    assert!(values.contains(&NameElse::Else(90)));
+}
+
+#[test]
+fn test_merge_with() {
+    let a = ordmap_of_keys(&[1, 3, 5, 7]);
+    let b = ordmap_of_keys(&[3, 4, 7, 8]);
+    let m = MapElim::merge_with(a, b, Rc::new(|k: &usize, c1, c2| k + c1 + c2));
+    let mut es = entries(m);
+    es.sort();
+    assert_eq!(es, vec![(1, 10), (3, 63), (4, 40), (5, 50), (7, 147), (8, 80)]);
+}
+
+#[test]
+fn test_update_with() {
+    let count = |m, k| map_update_with(m, name_of_usize(k), k, Rc::new(|c: Option<usize>| {
+        c.unwrap_or(0) + 1
+    }));
+    let m: OrdMap<usize, usize> =
+        [3, 1, 3, 3, 1].iter().fold(MapIntro::empty(), |m, &k| count(m, k));
+    let mut es = entries(m);
+    es.sort();
+    assert_eq!(es, vec![(1, 2), (3, 3)]);
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "persist")]
+
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::env;
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+fn types() -> PersistTypes {
+    let mut types = PersistTypes::new();
+    types.register::<usize>();
+    types
+}
+
+fn quadruple(a: Art<usize>) -> usize {
+    let d = thunk!(name_of_str("double") =>> double, a:a);
+    force(&d) * 2
+}
+
+/// The program: its input cell, and its output thunk.
+fn program(x: usize) -> Art<usize> {
+    let c = cell(name_of_str("input"), x);
+    thunk!(name_of_str("quadruple") =>> quadruple, a:c)
+}
+
+/// Runs the program on `x`, and saves its DCG to `file`; restarts
+/// with a fresh engine, and restores the DCG.
+fn save_and_restart(x: usize, file: &str) {
+    init_dcg();
+    assert_eq!(force(&program(x)), 4 * x);
+    let path = env::temp_dir().join(file);
+    save(&path, &types()).unwrap();
+    init_dcg();
+    load(&path, &types()).unwrap();
+}
+
+#[test]
+fn test_resume() {
+    save_and_restart(3, "adapton_test_resume.bin");
+    let evals = stats().eval;
+    assert_eq!(force(&program(3)), 12);
+    assert_eq!(stats().eval, evals);
+}
+
+#[test]
+fn test_resume_changed_input() {
+    save_and_restart(3, "adapton_test_resume_changed_input.bin");
+    let evals = stats().eval;
+    assert_eq!(force(&program(4)), 16);
+    assert_eq!(stats().eval, evals + 2);
+    // The restored thunks have their code again: they re-evaluate after a change.
+    assert_eq!(force(&program(5)), 20);
+    assert_eq!(stats().eval, evals + 4);
+}
+
+#[test]
+fn test_load_errors() {
+    init_dcg();
+    force(&program(3));
+    let path = env::temp_dir().join("adapton_test_load_errors.bin");
+    save(&path, &types()).unwrap();
+    // The DCG is not fresh.
+    assert!(load(&path, &types()).is_err());
+    // The type of the values is not registered.
+    init_dcg();
+    assert!(load(&path, &PersistTypes::new()).is_err());
+    assert!(load(env::temp_dir().join("adapton_test_no_such_file.bin"), &types()).is_err());
+    init_naive();
+    assert!(save(&path, &types()).is_err());
+    assert!(load(&path, &types()).is_err());
+}
+
+#[test]
+fn test_save_registered_types() {
+    init_dcg();
+    let c = cell(name_of_str("input"), 3usize);
+    cell(name_of_str("label"), String::from("three"));
+    assert_eq!(force(&thunk!(name_of_str("double") =>> double, a:c)), 6);
+    let path = env::temp_dir().join("adapton_test_save_registered_types.bin");
+    save(&path, &types()).unwrap();
+    init_dcg();
+    load(&path, &types()).unwrap();
+    // The string cell was not saved; so, it is created afresh.
+    let creates = stats().create;
+    cell(name_of_str("label"), String::from("four"));
+    assert_eq!(stats().create, creates + 1);
+    let (evals, creates) = (stats().eval, stats().create);
+    let c = cell(name_of_str("input"), 3usize);
+    assert_eq!(force(&thunk!(name_of_str("double") =>> double, a:c)), 6);
+    assert_eq!((stats().eval, stats().create), (evals, creates));
+}
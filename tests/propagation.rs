@@ -0,0 +1,80 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+fn sum(a: Art<usize>, b: Art<usize>) -> usize {
+    force(&a) + force(&b)
+}
+
+/// Two cells, `a` and `b`, and the thunk `sum` of their doubles.
+fn setup() -> (Art<usize>, Art<usize>, Art<usize>) {
+    let a = cell(name_of_str("a"), 1);
+    let b = cell(name_of_str("b"), 2);
+    let (da, _) = eager!(name_of_str("double_a") =>> double, a:a.clone());
+    let (db, _) = eager!(name_of_str("double_b") =>> double, a:b.clone());
+    let (s, _) = eager!(name_of_str("sum") =>> sum, a:da, b:db);
+    (a, b, s)
+}
+
+#[test]
+fn test_lazy_propagation() {
+    init_dcg();
+    let (a, _, s) = setup();
+    reset_stats();
+    set(&a, 10);
+    assert_eq!(stats().eval, 0);
+    assert_eq!(force(&s), 24);
+    assert_eq!(stats().eval, 2);
+}
+
+#[test]
+fn test_eager_propagation() {
+    init_dcg();
+    assert_eq!(set_propagation(Propagation::Eager), Propagation::Lazy);
+    let (a, b, s) = setup();
+    let seen = Rc::new(RefCell::new(vec![]));
+    {
+        let seen = seen.clone();
+        observe(&name_of_str("sum"), move |x: &usize| seen.borrow_mut().push(*x));
+    }
+    reset_stats();
+    set(&a, 10);
+    assert_eq!(stats().eval, 2);
+    assert_eq!(*seen.borrow(), vec![24]);
+    // Nothing is left to re-evaluate on demand.
+    reset_stats();
+    assert_eq!(force(&s), 24);
+    assert_eq!(stats().eval, 0);
+    // Setting a cell to its current value changes nothing.
+    set(&b, 2);
+    assert_eq!(*seen.borrow(), vec![24]);
+}
+
+#[test]
+fn test_cell_propagation() {
+    init_dcg();
+    let (a, b, s) = setup();
+    set_cell_propagation(&a, Some(Propagation::Eager));
+    reset_stats();
+    set(&b, 3);
+    assert_eq!(stats().eval, 0);
+    set(&a, 10);
+    // Eagerly propagating `a` also cleans up after `b`.
+    assert_eq!(stats().eval, 3);
+    reset_stats();
+    assert_eq!(force(&s), 26);
+    assert_eq!(stats().eval, 0);
+    // Removing the override restores the engine's policy.
+    set_cell_propagation(&a, None);
+    set(&a, 1);
+    assert_eq!(stats().eval, 0);
+    assert_eq!(force(&s), 8);
+}
@@ -0,0 +1,67 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+
+fn sketch_of_range(width: usize, depth: usize, xs: std::ops::Range<usize>) -> QuantileSketch {
+    xs.fold(QuantileSketch::empty(width, depth), |s, x| {
+        QuantileSketch::insert(name_of_usize(x), s, x)
+    })
+}
+
+/// The list `0, 1, ..., len - 1`, with the name `i` before every
+/// element `i` divisible by `stride`.
+fn named_list(len: usize, stride: usize) -> List<usize> {
+    (0..len).rev().fold(List::nil(), |l, i| {
+        let l = List::cons(i, l);
+        if i % stride == 0 {
+            List::name_art(Some(name_of_usize(i)), l)
+        } else {
+            l
+        }
+    })
+}
+
+#[test]
+fn test_quantiles() {
+    let s = QuantileSketch::empty(1, 7);
+    assert_eq!(QuantileSketch::median(&s), None);
+    let s = sketch_of_range(1, 7, 1..101);
+    assert_eq!(QuantileSketch::count(&s), 100);
+    assert_eq!(QuantileSketch::median(&s), Some(50));
+    assert_eq!(QuantileSketch::quantile(&s, 0.0), Some(1));
+    assert_eq!(QuantileSketch::quantile(&s, 0.9), Some(90));
+    assert_eq!(QuantileSketch::quantile(&s, 1.0), Some(100));
+}
+
+#[test]
+fn test_buckets() {
+    let s = sketch_of_range(10, 3, 0..100);
+    assert_eq!(QuantileSketch::median(&s), Some(40));
+    assert_eq!(QuantileSketch::quantile(&s, 0.05), Some(0));
+    // Values beyond the last bucket count in it.
+    assert_eq!(QuantileSketch::quantile(&s, 1.0), Some(70));
+}
+
+#[test]
+fn test_merge() {
+    let a = sketch_of_range(1, 7, 0..30);
+    let b = sketch_of_range(1, 7, 30..100);
+    let s = QuantileSketch::merge(a, b);
+    assert_eq!(QuantileSketch::count(&s), 100);
+    assert_eq!(QuantileSketch::median(&s), Some(49));
+    assert_eq!(QuantileSketch::quantile(&s, 0.25), Some(24));
+}
+
+#[test]
+fn test_of_list_dcg() {
+    manage::init_dcg();
+    let s = QuantileSketch::of_list(1, 8, named_list(100, 10));
+    assert_eq!(QuantileSketch::count(&s), 100);
+    assert_eq!(QuantileSketch::median(&s), Some(49));
+    let l = List::cons(200, List::cons(150, named_list(100, 10)));
+    let s = QuantileSketch::of_list(1, 8, l);
+    assert_eq!(QuantileSketch::count(&s), 102);
+    assert_eq!(QuantileSketch::median(&s), Some(50));
+    assert_eq!(QuantileSketch::quantile(&s, 1.0), Some(200));
+}
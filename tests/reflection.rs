@@ -0,0 +1,56 @@
+#[macro_use]
+extern crate adapton;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use adapton::engine::reflect;
+
+fn plus(a: Art<usize>, b: Art<usize>) -> usize {
+    force(&a) + force(&b)
+}
+
+fn loc(name: &'static str) -> reflect::Loc {
+    reflect::Loc { path: vec![], name: name_of_str(name) }
+}
+
+#[test]
+fn test_reflect_nodes_and_edges() {
+    init_dcg();
+    let a = cell(name_of_str("a"), 1usize);
+    let b = cell(name_of_str("b"), 2usize);
+    let (_, v) = eager!(name_of_str("plus") =>> plus, a:a.clone(), b:b.clone());
+    assert_eq!(v, 3);
+
+    let dcg = reflect::dcg_reflect_now().unwrap();
+    assert_eq!(dcg.nodes().count(), 3);
+    match dcg.node(&loc("plus")) {
+        Some(&reflect::Node::Comp(ref nd)) => assert_eq!(nd.prog_pt, prog_pt!("plus")),
+        _ => panic!("expected a thunk"),
+    }
+    match dcg.node(&loc("a")) {
+        Some(&reflect::Node::Ref(ref nd)) => assert_eq!(nd.preds.len(), 1),
+        _ => panic!("expected a cell"),
+    }
+    assert!(dcg.node(&loc("c")).is_none());
+
+    // The thunk forces `a`, then `b`; cells have no outgoing edges.
+    let targets: Vec<&reflect::Loc> = dcg.edges_from(&loc("plus")).map(|e| &e.succ.loc).collect();
+    assert_eq!(targets, vec![&loc("a"), &loc("b")]);
+    assert_eq!(dcg.edges_from(&loc("a")).count(), 0);
+    assert_eq!(dcg.edges().count(), 2);
+    for e in dcg.edges() {
+        assert_eq!(e.succ.effect, reflect::Effect::Force);
+        assert!(!e.succ.dirty);
+        // Each edge is mirrored by a predecessor of its target.
+        let preds = reflect::preds_of_node(dcg.node(&e.succ.loc).unwrap()).unwrap();
+        assert!(preds.iter().any(|p| &p.loc == e.src && p.effect == e.succ.effect));
+    }
+
+    set(&b, 5);
+    let dcg = reflect::dcg_reflect_now().unwrap();
+    let dirty: Vec<&reflect::Loc> =
+        dcg.edges().filter(|e| e.succ.dirty).map(|e| &e.succ.loc).collect();
+    assert_eq!(dirty, vec![&loc("b")]);
+}
@@ -0,0 +1,113 @@
+#[macro_use]
+extern crate adapton;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use adapton::engine::reflect::{Val, Const};
+use adapton::engine::replay::*;
+
+fn plus(a: Art<usize>, b: Art<usize>) -> usize {
+    force(&a) + force(&b)
+}
+
+fn minus(a: Art<usize>, b: Art<usize>) -> usize {
+    force(&a) - force(&b)
+}
+
+/// Records a small program, which allocates and forces `plus`.
+fn record() -> Vec<Step> {
+    with_engine(EngineKind::DCG, || {
+        record_begin();
+        let a = cell(name_of_str("a"), 5usize);
+        let b = ns(name_of_str("inputs"), || cell(name_of_str("b"), 3usize));
+        let t = thunk!(name_of_str("t") =>> plus, a:a.clone(), b:b.clone());
+        assert_eq!(force(&t), 8);
+        set(&a, 10);
+        assert_eq!(force(&t), 13);
+        record_end()
+    })
+}
+
+/// A replayer, which allocates `f` for the recorded thunks at `plus`.
+fn replayer(f: fn(Art<usize>, Art<usize>) -> usize) -> Replayer {
+    let mut r = Replayer::new();
+    r.register_type::<usize, _>(|v| match *v {
+        Val::Const(Const::Nat(n)) => n,
+        _ => panic!("expected a usize"),
+    });
+    r.register_thunk(prog_pt!("plus"), move |n, arg| match *arg {
+        Val::Tuple(ref args) => {
+            let a: Art<usize> = art_of_val(&args[0]).unwrap();
+            let b: Art<usize> = art_of_val(&args[1]).unwrap();
+            let _ = thunk(ArtIdChoice::Nominal(n), prog_pt!("plus"),
+                          Rc::new(Box::new(move |(a, b), ()| f(a, b))), (a, b), ());
+        }
+        _ => panic!("expected a tuple"),
+    });
+    r
+}
+
+#[test]
+fn test_record() {
+    let steps = record();
+    let outer: Vec<&Op> = steps.iter().filter(|s| s.outer).map(|s| &s.op).collect();
+    assert_eq!(outer.len(), 6);
+    match *outer[1] {
+        Op::Cell { ref loc, ref val, .. } => {
+            assert_eq!(loc.path, vec![name_of_str("inputs")]);
+            assert_eq!(*val, Val::Const(Const::Nat(3)));
+        }
+        _ => panic!("expected a cell"),
+    }
+    // The thunk's forces of `a` and `b` are recorded, within it.
+    let inner = steps.iter().filter(|s| !s.outer).count();
+    assert_eq!(inner, 4);
+    assert!(steps.iter().all(|s| match s.op {
+        Op::Cell { ref ty, .. } | Op::Set { ref ty, .. } |
+        Op::Thunk { ref ty, .. } | Op::Force { ref ty, .. } => ty == "usize",
+    }));
+}
+
+#[test]
+fn test_replay() {
+    let steps = record();
+    with_engine(EngineKind::DCG, || {
+        assert_eq!(replayer(plus).replay(&steps), Ok(()));
+        // The replay reproduces the engine state, too.
+        let t = thunk!(name_of_str("t") =>> plus,
+                       a:cell(name_of_str("a"), 10usize),
+                       b:ns(name_of_str("inputs"), || cell(name_of_str("b"), 3usize)));
+        reset_stats();
+        assert_eq!(force(&t), 13);
+        assert_eq!(stats().eval, 0);
+    });
+}
+
+#[test]
+fn test_replay_divergence() {
+    let steps = record();
+    let d = with_engine(EngineKind::DCG, || replayer(minus).replay(&steps)).unwrap_err();
+    assert_eq!(d.step, 5);
+    assert_eq!(d.recorded, Val::Const(Const::Nat(8)));
+    assert_eq!(d.replayed, Val::Const(Const::Nat(2)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_replay_serialized() {
+    let json = serde_json::to_string(&record()).unwrap();
+    let steps: Vec<Step> = serde_json::from_str(&json).unwrap();
+    assert_eq!(steps, record());
+    assert_eq!(with_engine(EngineKind::DCG, || replayer(plus).replay(&steps)), Ok(()));
+}
+
+#[test]
+fn test_record_nothing() {
+    init_dcg();
+    record_begin();
+    assert_eq!(record_end(), vec![]);
+}
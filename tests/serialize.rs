@@ -0,0 +1,67 @@
+#![cfg(feature = "serde")]
+
+#[macro_use]
+extern crate adapton;
+extern crate serde_json;
+
+use adapton::collections::*;
+use adapton::engine::*;
+use adapton::macros::*;
+
+fn named_list(len: usize) -> List<usize> {
+    (0..len).rev().fold(List::nil(), |l, i| {
+        let l = List::cons(i, l);
+        if i % 3 == 0 {
+            List::name_art(Some(name_pair(name_of_str("l"), name_of_usize(i))), l)
+        } else {
+            l
+        }
+    })
+}
+
+#[test]
+fn test_name_round_trip() {
+    let (nm, _) = name_fork(name_pair(name_of_str("a"), name_of_isize(-3)));
+    let json = serde_json::to_string(&nm).unwrap();
+    let nm2: Name = serde_json::from_str(&json).unwrap();
+    assert_eq!(nm, nm2);
+}
+
+// Deserialized program points share their symbols.
+#[test]
+fn test_prog_pt_round_trip() {
+    let json = serde_json::to_string(&prog_pt!("f")).unwrap();
+    let p1: ProgPt = serde_json::from_str(&json).unwrap();
+    let p2: ProgPt = serde_json::from_str(&json).unwrap();
+    assert_eq!(p1, prog_pt!("f"));
+    assert_eq!(p1.symbol.as_ptr(), p2.symbol.as_ptr());
+}
+
+#[test]
+fn test_list_round_trip() {
+    let l = named_list(10);
+    let json = serde_json::to_string(&l).unwrap();
+    let l2: List<usize> = serde_json::from_str(&json).unwrap();
+    assert_eq!(vec_of_list(l, None), vec_of_list(l2, None));
+}
+
+#[test]
+fn test_tree_round_trip() {
+    let t = ns(name_of_str("tree_of_list"),
+               || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, named_list(20)));
+    let json = serde_json::to_string(&t).unwrap();
+    let t2: Tree<usize> = serde_json::from_str(&json).unwrap();
+    assert_eq!(json, serde_json::to_string(&t2).unwrap());
+    let l2: List<usize> = list_of_tree(t2, Dir2::Left);
+    let elms: Vec<usize> = vec_of_list_elms(l2);
+    assert_eq!(elms, (0..20).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_list_round_trip_dcg() {
+    manage::init_dcg();
+    let l = named_list(30);
+    let json = serde_json::to_string(&l).unwrap();
+    let l2: List<usize> = serde_json::from_str(&json).unwrap();
+    assert_eq!(vec_of_list(l, None), vec_of_list(l2, None));
+}
@@ -0,0 +1,50 @@
+#[macro_use]
+extern crate adapton;
+
+mod common;
+
+use std::rc::Rc;
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+#[test]
+fn test_stats() {
+    init_dcg();
+    assert_eq!(stats().create, 0);
+    let c = cell(name_of_str("input"), 3);
+    let (t, v) = eager!(name_of_str("double") =>> double, a:c.clone());
+    assert_eq!(v, 6);
+    let s = stats();
+    assert_eq!((s.create, s.eval, s.hit), (2, 1, 0));
+
+    // Forcing again reuses the cached result.
+    reset_stats();
+    assert_eq!(force(&t), 6);
+    let s = stats();
+    assert_eq!((s.create, s.eval, s.hit, s.dirty), (0, 0, 1, 0));
+
+    // Changing the input dirties the thunk, which re-evaluates.
+    reset_stats();
+    set(&c, 4);
+    assert!(stats().dirty > 0);
+    assert_eq!(force(&t), 8);
+    let s = stats();
+    assert_eq!((s.create, s.eval, s.hit), (0, 1, 1));
+
+    // Setting the input back to its value does not re-evaluate.
+    reset_stats();
+    set(&c, 4);
+    assert_eq!(force(&t), 8);
+    assert_eq!(stats().eval, 0);
+}
+
+#[test]
+fn test_stats_naive() {
+    init_naive();
+    reset_stats();
+    let (_, v) = eager!(name_of_str("double") =>> double, a:put(3));
+    assert_eq!(v, 6);
+    assert_eq!(stats().eval, 0);
+}
@@ -0,0 +1,52 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+fn elems(l: List<usize>) -> Vec<usize> {
+    vec_of_list_elms(l)
+}
+
+fn naturals() -> Stream<usize> {
+    Stream::unfold(name_of_str("naturals"), 0, Rc::new(|i| Some((i, i + 1))))
+}
+
+#[test]
+fn test_take() {
+    assert_eq!(elems(Stream::take(naturals(), 5)), vec![0, 1, 2, 3, 4]);
+    let s = Stream::unfold(name_of_str("short"), 0,
+                           Rc::new(|i| if i < 3 { Some((i, i + 1)) } else { None }));
+    assert_eq!(elems(Stream::take(s, 10)), vec![0, 1, 2]);
+    assert_eq!(elems(Stream::take(Stream::<usize>::nil(), 10)), vec![]);
+}
+
+#[test]
+fn test_take_is_lazy() {
+    let steps = Rc::new(Cell::new(0));
+    let counter = steps.clone();
+    let s = Stream::unfold(name_of_str("counted"), 0, Rc::new(move |i| {
+        counter.set(counter.get() + 1);
+        Some((i, i + 1))
+    }));
+    assert_eq!(steps.get(), 1);
+    assert_eq!(elems(Stream::take(s, 4)), vec![0, 1, 2, 3]);
+    assert_eq!(steps.get(), 4);
+}
+
+#[test]
+fn test_map_filter() {
+    let squares = Stream::map(naturals(), Rc::new(|i| i * i));
+    let odd_squares = Stream::filter(squares, Rc::new(|x: &usize| x % 2 == 1));
+    assert_eq!(elems(Stream::take(odd_squares, 4)), vec![1, 9, 25, 49]);
+}
+
+#[test]
+fn test_map_filter_dcg() {
+    manage::init_dcg();
+    let evens = Stream::filter(naturals(), Rc::new(|x: &usize| x % 2 == 0));
+    let halves = Stream::map(evens, Rc::new(|x| x / 2));
+    assert_eq!(elems(Stream::take(halves.clone(), 50)), (0..50).collect::<Vec<_>>());
+    assert_eq!(elems(Stream::take(halves, 60)), (0..60).collect::<Vec<_>>());
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "tracing")]
+#[macro_use]
+extern crate adapton;
+extern crate tracing;
+
+mod common;
+
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use adapton::macros::*;
+use adapton::engine::*;
+use adapton::engine::manage::*;
+use common::*;
+
+/// Records the spans of the engine, as their names and fields.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &Debug) {
+        self.0.push_str(&format!("{}={:?} ", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata) -> bool { true }
+    fn new_span(&self, span: &Attributes) -> Id {
+        let mut fields = Fields(String::new());
+        span.record(&mut fields);
+        let mut spans = self.spans.lock().unwrap();
+        spans.push((span.metadata().name().to_string(), fields.0));
+        Id::from_u64(spans.len() as u64)
+    }
+    fn record(&self, _: &Id, _: &Record) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, _: &Event) {}
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn test_engine_spans() {
+    let recorder = Recorder::default();
+    let spans = recorder.spans.clone();
+    tracing::subscriber::with_default(recorder, || {
+        init_dcg();
+        let c = cell(name_of_str("c"), 1usize);
+        let t = thunk!(name_of_str("t") =>> double, a:c.clone());
+        assert_eq!(force(&t), 2);
+        set(&c, 2);
+        assert_eq!(force(&t), 4);
+    });
+    let spans = spans.lock().unwrap();
+    let has = |op: &str, name: &str, prog_pt: &str| {
+        spans.iter().any(|&(ref o, ref fields)| {
+            o == op && fields.contains(name) && fields.contains(&format!("prog_pt={:?}", prog_pt))
+        })
+    };
+    assert!(has("alloc", "\"c\"", ""));
+    assert!(has("alloc", "\"t\"", "double"));
+    assert!(has("force", "\"t\"", "double"));
+    assert!(has("eval", "\"t\"", "double"));
+    assert!(has("force", "\"c\"", ""));
+    assert!(has("dirty", "\"c\"", ""));
+    assert!(has("clean", "\"t\"", "double"));
+}
@@ -0,0 +1,193 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+use std::rc::Rc;
+
+/// The list `0, 1, ..., len - 1`, with the name `i` before every
+/// element `i` divisible by `stride`.
+fn named_list(len: usize, stride: usize) -> List<usize> {
+    (0..len).rev().fold(List::nil(), |l, i| {
+        let l = List::cons(i, l);
+        if i % stride == 0 {
+            List::name_art(Some(name_of_usize(i)), l)
+        } else {
+            l
+        }
+    })
+}
+
+fn plain_list(len: usize) -> List<usize> {
+    (0..len).rev().fold(List::nil(), |l, i| List::cons(i, l))
+}
+
+fn leaf(x: usize) -> Tree<usize> {
+    Tree::leaf(x)
+}
+
+#[test]
+fn test_tree_stats_shape() {
+    let t: Tree<usize> = Tree::name(name_of_str("t"), 2,
+                                    Tree::bin(1, leaf(0), leaf(1)),
+                                    Tree::art(cell(name_of_str("r"), leaf(2))));
+    assert_eq!(tree_stats(&t),
+               TreeStats { size: 3, height: 3, name_count: 1, art_count: 1 });
+    assert_eq!(tree_stats(&Tree::<usize>::nil()), TreeStats::default());
+}
+
+#[test]
+fn test_tree_stats_of_list() {
+    let l = named_list(100, 10);
+    let t = ns(name_of_str("tree_of_list"),
+               || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, l));
+    let stats = tree_stats(&t);
+    assert_eq!(stats.size, 100);
+    assert_eq!(stats.name_count, 10);
+    assert!(stats.height <= 100);
+}
+
+#[test]
+fn test_tree_stats_dcg() {
+    manage::init_dcg();
+    let l = named_list(100, 10);
+    let t = ns(name_of_str("tree_of_list"),
+               || tree_of_list::<_, _, Tree<_>, _>(Dir2::Left, l));
+    let stats = tree_stats(&t);
+    assert_eq!(stats.size, 100);
+    assert_eq!(stats.name_count, 10);
+    assert_eq!(tree_stats(&t), stats);
+}
+
+fn elems(t: Tree<usize>) -> Vec<usize> {
+    let l: List<usize> = list_of_tree(t, Dir2::Left);
+    vec_of_list_elms(l)
+}
+
+#[test]
+fn test_tree_of_list_default_levels() {
+    let t1: Tree<usize> = tree_of_list(Dir2::Left, named_list(50, 10));
+    let t2: Tree<usize> = tree_of_list_with(Dir2::Left, named_list(50, 10),
+                                            Rc::new(level_of_elm::<usize, usize>),
+                                            Rc::new(level_of_name::<usize>));
+    assert_eq!(t1, t2);
+}
+
+#[test]
+fn test_tree_of_list_with_levels() {
+    // With every element at the same level, the tree is a spine.
+    let t: Tree<usize> = tree_of_list_with(Dir2::Left, plain_list(50),
+                                           Rc::new(|_: &usize| 1),
+                                           Rc::new(level_of_name::<usize>));
+    assert_eq!(tree_stats(&t).height, 51);
+    assert_eq!(elems(t), (0..50).collect::<Vec<_>>());
+
+    // With levels from the trailing zeros of the elements themselves,
+    // the tree is perfectly balanced.
+    let lev_elm = |x: &usize| (x + 1).trailing_zeros() as usize + 1;
+    let t: Tree<usize> = tree_of_list_with(Dir2::Left, plain_list(63),
+                                           Rc::new(lev_elm),
+                                           Rc::new(level_of_name::<usize>));
+    assert_eq!(tree_stats(&t).height, 7);
+    assert_eq!(elems(t), (0..63).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_tree_scan() {
+    let sums: Vec<usize> = (0..100).map(|i| i * (i + 1) / 2).collect();
+    let t: Tree<usize> = tree_of_list(Dir2::Left, named_list(100, 10));
+    let s: Tree<usize> = tree_scan(t.clone(), 0, Rc::new(|acc, x| acc + x));
+    assert_eq!(elems(s.clone()), sums);
+    let (ts, ss) = (tree_stats(&t), tree_stats(&s));
+    assert_eq!((ss.size, ss.height, ss.name_count), (ts.size, ts.height, ts.name_count));
+}
+
+#[test]
+fn test_tree_scan_dcg() {
+    manage::init_dcg();
+    let sums: Vec<usize> = (0..100).map(|i| i * (i + 1) / 2).collect();
+    let l = named_list(100, 10);
+    let t: Tree<usize> = ns(name_of_str("tree_of_list"),
+                            || tree_of_list(Dir2::Left, l));
+    let s: Tree<usize> = ns(name_of_str("tree_scan"),
+                            || tree_scan(t.clone(), 0, Rc::new(|acc, x| acc + x)));
+    assert_eq!(elems(s), sums);
+    let s: Tree<usize> = ns(name_of_str("tree_scan"),
+                            || tree_scan(t, 0, Rc::new(|acc, x| acc + x)));
+    assert_eq!(elems(s), sums);
+}
+
+#[test]
+fn test_tree_map() {
+    let t: Tree<usize> = Tree::name(name_of_str("t"), 2,
+                                    Tree::bin(1, leaf(0), leaf(1)),
+                                    Tree::art(cell(name_of_str("r"), leaf(2))));
+    let m: Tree<usize> = tree_map(t.clone(), Rc::new(|x| x * 10));
+    assert_eq!(elems(m.clone()), vec![0, 10, 20]);
+    assert_eq!(tree_stats(&m), tree_stats(&t));
+
+    let t: Tree<usize> = tree_of_list(Dir2::Left, named_list(100, 10));
+    let m: Tree<usize> = tree_map(t.clone(), Rc::new(|x| x + 1));
+    assert_eq!(elems(m.clone()), (1..101).collect::<Vec<_>>());
+    assert_eq!(tree_stats(&m), tree_stats(&t));
+}
+
+#[test]
+fn test_tree_map_dcg() {
+    manage::init_dcg();
+    let l = named_list(100, 10);
+    let t: Tree<usize> = ns(name_of_str("tree_of_list"),
+                            || tree_of_list(Dir2::Left, l));
+    let map = |t: Tree<usize>| -> Tree<usize> {
+        ns(name_of_str("tree_map"), || tree_map(t, Rc::new(|x| x * 2)))
+    };
+    let m = map(t.clone());
+    assert_eq!(elems(m.clone()), (0..100).map(|x| x * 2).collect::<Vec<_>>());
+    assert_eq!(tree_stats(&m), tree_stats(&t));
+    assert_eq!(map(t), m);
+}
+
+#[test]
+fn test_tree_filter_map() {
+    let t: Tree<usize> = tree_of_list(Dir2::Left, named_list(100, 10));
+    let evens: Tree<usize> =
+        tree_filter_map(t.clone(), Rc::new(|x| if x % 2 == 0 { Some(x / 2) } else { None }));
+    assert_eq!(elems(evens), (0..50).collect::<Vec<_>>());
+    // Emptying the tree collapses it to `Nil`.
+    let none: Tree<usize> = tree_filter_map(t.clone(), Rc::new(|_| None::<usize>));
+    assert_eq!(none, Tree::nil());
+    let all: Tree<usize> = tree_filter_map(t.clone(), Rc::new(Some));
+    assert_eq!(elems(all.clone()), (0..100).collect::<Vec<_>>());
+    assert!(tree_stats(&all).name_count <= tree_stats(&t).name_count);
+}
+
+#[test]
+fn test_tree_filter_map_dcg() {
+    manage::init_dcg();
+    let l = named_list(100, 10);
+    let t: Tree<usize> = ns(name_of_str("tree_of_list"),
+                            || tree_of_list(Dir2::Left, l));
+    let filter_map = |t: Tree<usize>| -> Tree<usize> {
+        ns(name_of_str("filter_map"), || {
+            tree_filter_map(t, Rc::new(|x| if x % 10 < 3 { Some(x * 2) } else { None }))
+        })
+    };
+    let expected: Vec<usize> = (0..100).filter(|x| x % 10 < 3).map(|x| x * 2).collect();
+    assert_eq!(elems(filter_map(t.clone())), expected);
+    assert_eq!(elems(filter_map(t)), expected);
+}
+
+// Two filters over the same tree, in the same namespace, do not
+// collide: each allocates in its own namespaces.
+#[test]
+fn test_tree_filters_share_ns() {
+    manage::init_dcg();
+    let l = named_list(100, 10);
+    let t: Tree<usize> = ns(name_of_str("tree_of_list"),
+                            || tree_of_list(Dir2::Left, l));
+    let (evens, odds): (Tree<usize>, Tree<usize>) = ns(name_of_str("filters"), || {
+        (filter_tree_of_tree(t.clone(), Box::new(|x| x % 2 == 0)),
+         tree_filter_map(t.clone(), Rc::new(|x| if x % 2 == 1 { Some(x * 10) } else { None })))
+    });
+    assert_eq!(elems(evens), (0..50).map(|x| x * 2).collect::<Vec<_>>());
+    assert_eq!(elems(odds), (0..50).map(|x| (x * 2 + 1) * 10).collect::<Vec<_>>());
+}
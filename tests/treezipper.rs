@@ -0,0 +1,89 @@
+extern crate adapton;
+
+use adapton::collections::*;
+use adapton::engine::*;
+use std::rc::Rc;
+
+fn elems(t: Tree<usize>) -> Vec<usize> {
+    let l: List<usize> = list_of_tree(t, Dir2::Left);
+    vec_of_list_elms(l)
+}
+
+fn small_tree() -> Tree<usize> {
+    Tree::name(name_of_str("t"), 2,
+               Tree::bin(1, Tree::leaf(0), Tree::leaf(1)),
+               Tree::art(cell(name_of_str("r"), Tree::leaf(2))))
+}
+
+/// Moves the focus down along `dirs`.
+fn descend(z: TreeZipper<usize>, dirs: &[Dir2]) -> TreeZipper<usize> {
+    dirs.iter().fold(z, |z, &dir| {
+        let (z, moved) = TreeZipper::descend(z, dir);
+        assert!(moved);
+        z
+    })
+}
+
+#[test]
+fn test_navigate() {
+    let z = TreeZipper::of_tree(small_tree());
+    assert!(!TreeZipper::ascend(z.clone()).1);
+    let z = descend(z, &[Dir2::Left, Dir2::Right]);
+    assert_eq!(TreeZipper::depth(&z), 2);
+    assert_eq!(z.focus, Tree::leaf(1));
+    assert!(!TreeZipper::descend(z.clone(), Dir2::Left).1);
+    let (z, moved) = TreeZipper::ascend(z);
+    assert!(moved);
+    assert_eq!(z.focus, Tree::bin(1, Tree::leaf(0), Tree::leaf(1)));
+    // Without edits, the tree is unchanged.
+    assert_eq!(TreeZipper::unfocus(z), small_tree());
+}
+
+#[test]
+fn test_replace() {
+    let z = descend(TreeZipper::of_tree(small_tree()), &[Dir2::Left, Dir2::Right]);
+    let z = TreeZipper::replace(z, Tree::bin(0, Tree::leaf(10), Tree::leaf(11)));
+    let t = TreeZipper::unfocus(z);
+    assert_eq!(elems(t.clone()), vec![0, 10, 11, 2]);
+    match t {
+        Tree::Name(nm, lev, _, r) => {
+            assert_eq!((nm, lev), (name_of_str("t"), 2));
+            assert_eq!(*r, Tree::art(cell(name_of_str("r"), Tree::leaf(2))));
+        }
+        t => panic!("expected a named node, found {:?}", t),
+    }
+    // Descends through the articulation of the right subtree.
+    let z = descend(TreeZipper::of_tree(small_tree()), &[Dir2::Right]);
+    let z = TreeZipper::replace(z, Tree::leaf(20));
+    assert_eq!(elems(TreeZipper::unfocus(z)), vec![0, 1, 20]);
+}
+
+#[test]
+fn test_replace_dcg() {
+    manage::init_dcg();
+    let l = (0..100).rev().fold(List::nil(), |l, i| {
+        let l = List::cons(i, l);
+        if i % 10 == 0 { List::name_art(Some(name_of_usize(i)), l) } else { l }
+    });
+    let t: Tree<usize> = ns(name_of_str("tree_of_list"), || tree_of_list(Dir2::Left, l));
+    let sum = |t: Tree<usize>| {
+        ns(name_of_str("sum"), || monoid_of_tree(t, 0, Rc::new(|x, y| x + y)))
+    };
+    assert_eq!(sum(t.clone()), 4950);
+    // Replaces the leftmost leaf, re-setting the cells of the named
+    // nodes above it in the namespace of `tree_of_list`.
+    let mut z = TreeZipper::of_tree(t);
+    loop {
+        let (z2, moved) = TreeZipper::descend(z, Dir2::Left);
+        z = z2;
+        if !moved { break }
+        if <Tree<usize> as TreeElim<usize, usize>>::is_empty(&z.focus) {
+            z = TreeZipper::descend(TreeZipper::ascend(z).0, Dir2::Right).0;
+        }
+    }
+    assert_eq!(elems(z.focus.clone()), vec![0]);
+    let z = TreeZipper::replace(z, Tree::leaf(1000));
+    let t = ns(name_of_str("tree_of_list"), || TreeZipper::unfocus(z));
+    assert_eq!(elems(t.clone()), (1..100).fold(vec![1000], |mut v, i| { v.push(i); v }));
+    assert_eq!(sum(t), 5950);
+}
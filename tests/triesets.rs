@@ -1,9 +1,10 @@
 extern crate adapton;
 
+use std::cell::Cell;
 use std::rc::Rc;
 use adapton::engine::*;
 use adapton::engine::manage::*;
-use adapton::collections::{SetIntro, SetElim};
+use adapton::collections::{MapIntro, MapElim, SetIntro, SetElim};
 use adapton::collections::trie::*;
 
 #[test]
@@ -106,3 +107,389 @@ fn test_set_fold() {
         dcg = init_naive();
     }
 }
+
+// Compressing unary spines preserves membership and still admits additions.
+#[test]
+fn test_compress() {
+    init_naive();
+    fn bins(t: Set<usize>) -> usize {
+        trie_fold_up(t,
+                     Rc::new(|_| 0),
+                     Rc::new(|_, _| 0),
+                     Rc::new(|_, l, r| 1 + l + r),
+                     Rc::new(|_, n| n),
+                     Rc::new(|_, n| n))
+    }
+    let mut s: Set<usize> = TrieIntro::empty(Meta { min_depth: 8 });
+    for i in 0..10 {
+        s = SetIntro::add(s, i);
+    }
+    let c = trie_compress(s.clone());
+    assert!(bins(c.clone()) < bins(s));
+    for i in 0..10 {
+        assert!(Set::is_mem(&c, &i));
+    }
+    assert!(!Set::is_mem(&c, &10));
+    let c = SetIntro::add(c, 10);
+    for i in 0..11 {
+        assert!(Set::is_mem(&c, &i));
+    }
+}
+
+// Compaction keeps the elements and leaves an extensible trie.
+#[test]
+fn test_compact() {
+    init_naive();
+    let mut s: Set<usize> = TrieIntro::empty(Meta { min_depth: 2 });
+    for i in 0..50 {
+        s = push_input(i, s);
+    }
+    let c = trie_compact(s.clone(), name_of_str("compact"), 4);
+    let sum = |t: Set<usize>| trie_fold(t, 0, Rc::new(|(i, ()), acc| i + acc));
+    assert_eq!(sum(s.clone()), sum(c.clone()));
+    for i in 0..50 {
+        assert!(Set::is_mem(&c, &i));
+    }
+    let c = SetIntro::add(c, 50);
+    assert!(Set::is_mem(&c, &50));
+    assert_eq!(sum(s) + 50, sum(c));
+}
+
+// Map lookups follow the key, and updates replace earlier bindings.
+#[test]
+fn test_map_update_find() {
+    use adapton::collections::{MapIntro, MapElim};
+    init_naive();
+    let mut m: Trie<(usize, usize)> = MapIntro::empty();
+    for i in 0..20 {
+        m = MapIntro::update(m, i, i);
+    }
+    for i in 0..20 {
+        assert_eq!(MapElim::find(&m, &i), Some(i));
+    }
+    let m = MapIntro::update(m, 3, 30);
+    assert_eq!(MapElim::find(&m, &3), Some(30));
+    assert_eq!(MapElim::find(&m, &20), None);
+    assert_eq!(m.into_hashmap().len(), 20);
+}
+
+// Maps round-trip through `std::collections::HashMap`.
+#[test]
+fn test_hashmap_conversion() {
+    use std::collections::HashMap;
+    init_naive();
+    let mut h = HashMap::new();
+    for i in 0..20usize {
+        h.insert(i, i * i);
+    }
+    let t: Trie<(usize, usize)> = Trie::from(h.clone());
+    assert_eq!(t.into_hashmap(), h);
+    let e: Trie<(usize, usize)> = Trie::from(HashMap::new());
+    assert!(e.into_hashmap().is_empty());
+}
+
+// Retaining keeps exactly the elements satisfying the predicate.
+#[test]
+fn test_retain() {
+    init_naive();
+    let mut s: Set<usize> = SetIntro::empty();
+    for i in 0..30 {
+        s = push_input(i, s);
+    }
+    let evens = ns(name_of_str("retain"),
+                   || trie_retain(s.clone(), Rc::new(|&(i, ()): &(usize, ())| i % 2 == 0)));
+    for i in 0..30 {
+        assert_eq!(Set::is_mem(&evens, &i), i % 2 == 0);
+    }
+    let evens = SetIntro::add(evens, 31);
+    assert!(Set::is_mem(&evens, &31));
+}
+
+// `Meta::auto` grows with the expected size; tries built with a
+// too-small `min_depth` rebalance without losing elements.
+#[test]
+fn test_meta_auto_and_rebalance() {
+    init_naive();
+    assert_eq!(Meta::auto(0).min_depth, 1);
+    assert_eq!(Meta::auto(1000).min_depth, 10);
+    let mut s: Set<usize> = TrieIntro::empty(Meta { min_depth: 1 });
+    for i in 0..500 {
+        s = SetIntro::add(s, i);
+    }
+    for i in 0..500 {
+        assert!(Set::is_mem(&s, &i));
+    }
+    let depth = trie_fold_up(s,
+                             Rc::new(|_| 0),
+                             Rc::new(|_, _| 0),
+                             Rc::new(|_, l: usize, r: usize| 1 + std::cmp::max(l, r)),
+                             Rc::new(|meta: Meta, d| {
+                                 assert!(meta.min_depth > 1);
+                                 d
+                             }),
+                             Rc::new(|_, d| d));
+    assert!(depth > 1);
+}
+
+// Partitioning splits the elements between the two outputs.
+#[test]
+fn test_partition() {
+    init_naive();
+    let mut s: Set<usize> = SetIntro::empty();
+    for i in 0..30 {
+        s = push_input(i, s);
+    }
+    let (small, large) = ns(name_of_str("partition"), || {
+        trie_partition(s.clone(), Rc::new(|&(i, ()): &(usize, ())| i < 10))
+    });
+    for i in 0..30 {
+        assert_eq!(Set::is_mem(&small, &i), i < 10);
+        assert_eq!(Set::is_mem(&large, &i), i >= 10);
+    }
+}
+
+// Short-circuiting queries agree with the set's contents, with each
+// predicate in a namespace of its own.
+#[test]
+fn test_any_all_find_first() {
+    init_dcg();
+    let mut s: Set<usize> = SetIntro::empty();
+    for i in 0..30 {
+        s = push_input(i, s);
+    }
+    assert!(ns(name_of_str("any_17"),
+               || trie_any(s.clone(), Rc::new(|&(i, ()): &(usize, ())| i == 17))));
+    assert!(!ns(name_of_str("any_30"),
+                || trie_any(s.clone(), Rc::new(|&(i, ()): &(usize, ())| i == 30))));
+    assert!(ns(name_of_str("all_lt_30"),
+               || trie_all(s.clone(), Rc::new(|&(i, ()): &(usize, ())| i < 30))));
+    assert!(!ns(name_of_str("all_lt_29"),
+                || trie_all(s.clone(), Rc::new(|&(i, ()): &(usize, ())| i < 29))));
+    let found = ns(name_of_str("find_first"), || {
+        trie_find_first(s.clone(), Rc::new(|&(i, ()): &(usize, ())| i > 25))
+    });
+    match found {
+        Some((i, ())) => assert!(i > 25),
+        None => panic!("expected an element greater than 25"),
+    }
+    // Re-asking in the same namespace reuses the memoized verdicts.
+    let calls = Rc::new(Cell::new(0));
+    let c = calls.clone();
+    assert!(ns(name_of_str("any_17"), || {
+        trie_any(s.clone(), Rc::new(move |&(i, ()): &(usize, ())| {
+            c.set(c.get() + 1);
+            i == 17
+        }))
+    }));
+    assert_eq!(calls.get(), 0);
+}
+
+// `Display` lists the elements, hiding articulations unless asked.
+#[test]
+fn test_display() {
+    init_naive();
+    let e: Set<usize> = SetIntro::empty();
+    assert_eq!(format!("{}", e), "{}");
+    let s = push_input(7, e);
+    assert_eq!(format!("{}", s), "{7}");
+    let s = push_input(8, s);
+    let shown = format!("{}", s);
+    assert!(shown == "{7, 8}" || shown == "{8, 7}");
+    assert!(format!("{:#}", s).contains("@"));
+}
+
+// Map bindings display as `k: v`.
+#[test]
+fn test_display_map() {
+    init_naive();
+    let m: Trie<(usize, String)> = MapIntro::empty();
+    let m = MapIntro::update(m, 3, "three".to_string());
+    assert_eq!(format!("{}", m), "{3: three}");
+}
+
+// Unions merge the two tries, combining the values of shared keys.
+#[test]
+fn test_union() {
+    use adapton::collections::{MapIntro, MapElim};
+    init_naive();
+    let meta = Meta { min_depth: 1 };
+    let mut a: Trie<usize> = TrieIntro::empty(meta.clone());
+    let mut b: Trie<usize> = TrieIntro::empty(meta);
+    for i in 0..20 {
+        a = TrieIntro::extend(name_of_usize(i), a, i);
+        b = TrieIntro::extend(name_of_usize(i), b, i + 10);
+    }
+    let u = trie_union(a, b);
+    let mut elts = trie_fold(u, vec![], Rc::new(|x, mut v: Vec<usize>| {
+        v.push(x);
+        v
+    }));
+    elts.sort();
+    assert_eq!(elts, (0..30).collect::<Vec<_>>());
+
+    let mut m: Trie<(usize, usize)> = MapIntro::empty();
+    let mut n: Trie<(usize, usize)> = MapIntro::empty();
+    for i in 0..20 {
+        m = MapIntro::update(m, i, 1);
+        n = MapIntro::update(n, i + 10, 2);
+    }
+    let sum = Trie::map_union(m.clone(), n.clone(), Rc::new(|x, y| x + y));
+    assert_eq!(MapElim::find(&sum, &0), Some(1));
+    assert_eq!(MapElim::find(&sum, &15), Some(3));
+    assert_eq!(MapElim::find(&sum, &25), Some(2));
+    assert_eq!(sum.into_hashmap().len(), 30);
+
+    let s: Set<usize> = SetIntro::add(SetIntro::add(SetIntro::empty(), 1), 2);
+    let t: Set<usize> = SetIntro::add(SetIntro::add(SetIntro::empty(), 2), 3);
+    let st = SetIntro::union(s, t);
+    assert!(SetElim::is_mem(&st, &1) && SetElim::is_mem(&st, &2) && SetElim::is_mem(&st, &3));
+    assert!(!SetElim::is_mem(&st, &4));
+    let st = SetIntro::add(st, 4);
+    assert!(SetElim::is_mem(&st, &4));
+}
+
+#[test]
+fn test_inter_diff() {
+    init_naive();
+    let mut s: Set<usize> = SetIntro::empty();
+    let mut t: Set<usize> = SetIntro::empty();
+    for i in 0..10 {
+        s = SetIntro::add(s, i);
+        t = SetIntro::add(t, i + 5);
+    }
+    let both = SetIntro::inter(s.clone(), t.clone());
+    let only_s = SetIntro::diff(s, t);
+    for i in 0..15 {
+        assert_eq!(SetElim::is_mem(&both, &i), i >= 5 && i < 10);
+        assert_eq!(SetElim::is_mem(&only_s, &i), i < 5);
+    }
+}
+
+fn elts(l: adapton::collections::List<usize>) -> Vec<usize> {
+    adapton::collections::vec_of_list_elms(l)
+}
+
+#[test]
+fn test_set_to_sorted_list() {
+    init_naive();
+    let s: Set<usize> = (0..20).fold(SetIntro::empty(), |s, i| SetIntro::add(s, (i * 7) % 20));
+    assert_eq!(elts(set_to_sorted_list(s.clone(), Rc::new(|x: &usize| *x))),
+               (0..20).collect::<Vec<_>>());
+    assert_eq!(elts(set_to_sorted_list(s.clone(), Rc::new(|x: &usize| 20 - *x))),
+               (0..20).rev().collect::<Vec<_>>());
+
+    let t: Set<usize> = (0..20).rev().fold(SetIntro::empty(), |t, i| SetIntro::add(t, i));
+    let by_hash = elts(set_to_hash_sorted_list(s));
+    assert_eq!(by_hash, elts(set_to_hash_sorted_list(t)));
+    let mut sorted = by_hash.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_set_to_sorted_list_dcg() {
+    init_dcg();
+    let s = (0..30).fold(SetIntro::empty(), |s, i| push_input(i, s));
+    assert_eq!(elts(set_to_sorted_list(s.clone(), Rc::new(|x: &usize| *x))),
+               (0..30).collect::<Vec<_>>());
+    let s = push_input(30, s);
+    assert_eq!(elts(set_to_sorted_list(s, Rc::new(|x: &usize| *x))),
+               (0..31).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_map_read_apis() {
+    init_dcg();
+    let m: Trie<(usize, usize)> =
+        (0..30).fold(MapIntro::empty(), |m, i| Trie::map_extend(name_of_usize(i), m, i % 20, i));
+    assert_eq!(MapElim::len(&m), 20);
+    assert!(MapElim::contains_key(&m, &19));
+    assert!(!MapElim::contains_key(&m, &20));
+    let mut keys = elts(MapElim::keys(&m));
+    keys.sort();
+    assert_eq!(keys, (0..20).collect::<Vec<_>>());
+    let mut values = elts(MapElim::values(&m));
+    values.sort();
+    assert_eq!(values, (10..30).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_map_merge_with() {
+    init_dcg();
+    let a: Trie<(usize, usize)> =
+        (0..20).fold(MapIntro::empty(), |m, i| Trie::map_extend(name_of_usize(i), m, i, 1));
+    let b: Trie<(usize, usize)> = (10..30).fold(MapIntro::empty(), |m, i| {
+        Trie::map_extend(name_pair(name_of_str("b"), name_of_usize(i)), m, i, 100)
+    });
+    let m = MapElim::merge_with(a, b, Rc::new(|k: &usize, c1, c2| k + c1 + c2));
+    assert_eq!(MapElim::len(&m), 30);
+    assert_eq!(MapElim::find(&m, &5), Some(1));
+    assert_eq!(MapElim::find(&m, &15), Some(116));
+    assert_eq!(MapElim::find(&m, &25), Some(100));
+}
+
+#[test]
+fn test_map_update_with() {
+    init_dcg();
+    let push = |m: Trie<(usize, Vec<usize>)>, i: usize| {
+        MapIntro::update_with(m, name_of_usize(i), i % 3, Rc::new(move |vs: Option<Vec<usize>>| {
+            let mut vs = vs.unwrap_or(vec![]);
+            vs.push(i);
+            vs
+        }))
+    };
+    let m = ns(name_of_str("push"), || (0..10).fold(MapIntro::empty(), &push));
+    assert_eq!(MapElim::len(&m), 3);
+    assert_eq!(MapElim::find(&m, &0), Some(vec![0, 3, 6, 9]));
+    assert_eq!(MapElim::find(&m, &2), Some(vec![2, 5, 8]));
+    let m2 = ns(name_of_str("push"), || (0..10).fold(MapIntro::empty(), &push));
+    assert_eq!(MapElim::find(&m2, &1), MapElim::find(&m, &1));
+    // Updates memoize the calls to their closures, at their names; so,
+    // different closures have different names.
+    let calls = Rc::new(Cell::new(0));
+    let add = |m: Trie<(usize, Vec<usize>)>, x: usize| {
+        let calls = calls.clone();
+        let nm = name_pair(name_of_str("add"), name_of_usize(x));
+        MapIntro::update_with(m, nm, 0, Rc::new(move |vs: Option<Vec<usize>>| {
+            calls.set(calls.get() + 1);
+            let mut vs = vs.unwrap_or(vec![]);
+            vs.push(x);
+            vs
+        }))
+    };
+    assert_eq!(MapElim::find(&add(m.clone(), 10), &0), Some(vec![0, 3, 6, 9, 10]));
+    assert_eq!(MapElim::find(&add(m.clone(), 20), &0), Some(vec![0, 3, 6, 9, 20]));
+    assert_eq!(MapElim::find(&add(m, 10), &0), Some(vec![0, 3, 6, 9, 10]));
+    assert_eq!(calls.get(), 2);
+}
+
+// Sets with the same elements, but built with different names and
+// articulations, hash alike and are logically equal.
+#[test]
+fn test_set_logical_eq() {
+    init_dcg();
+    let s = (0..20).fold(SetIntro::empty(), |s, i| push_input(i, s));
+    let t: Set<usize> = (0..20).rev().fold(SetIntro::empty(), |t, i| SetIntro::add(t, i));
+    let u: Set<usize> = (1..21).fold(SetIntro::empty(), |u, i| SetIntro::add(u, i));
+    assert_eq!(set_hash_commutative(&s), set_hash_commutative(&t));
+    assert!(set_logical_eq(&s, &t));
+    assert!(set_logical_eq(&t, &s));
+    assert!(!set_logical_eq(&s, &u));
+    assert!(!set_logical_eq(&u, &t));
+}
+
+#[test]
+fn test_map_logical_eq() {
+    init_dcg();
+    let a: Trie<(usize, usize)> =
+        (0..20).fold(MapIntro::empty(), |m, i| Trie::map_extend(name_of_usize(i), m, i, i * 2));
+    let b: Trie<(usize, usize)> = (0..20).rev().fold(MapIntro::empty(), |m, i| {
+        Trie::map_extend(name_pair(name_of_str("b"), name_of_usize(i)), m, i, i * 2)
+    });
+    let c = Trie::map_extend(name_of_str("c"), a.clone(), 7, 0);
+    assert_eq!(map_hash_commutative(&a), map_hash_commutative(&b));
+    assert!(map_logical_eq(&a, &b));
+    assert!(map_logical_eq(&b, &a));
+    assert!(!map_logical_eq(&a, &c));
+    assert!(!map_logical_eq(&c, &b));
+}